@@ -6,58 +6,74 @@ use sc_client_api::backend::Backend as BackendT;
 use sp_api::{BlockT, ProvideRuntimeApi};
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_runtime::traits::{NumberFor, UniqueSaturatedInto};
 use std::collections::HashSet;
+use std::iter;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::sync::Arc;
-use tracing::{error, info};
+use tokio::{net::UdpSocket, sync::RwLock};
+use tracing::{debug, error, info, trace};
 use trust_dns_server::{
     authority::{
-        AnyRecords, AuthLookup, Authority, LookupError, LookupOptions, LookupRecords, LookupResult,
-        MessageRequest, UpdateResult, ZoneType,
+        AnyRecords, AuthLookup, Authority, AuthorityObject, Catalog, LookupError, LookupOptions,
+        LookupRecords, LookupResult, MessageRequest, MessageResponseBuilder, UpdateResult,
+        ZoneType,
     },
     client::{
-        client::{Client, SyncClient},
-        op::DnsResponse,
+        client::{AsyncClient, ClientHandle},
         rr::LowerName,
-        udp::UdpClientConnection,
+        udp::UdpClientStream,
     },
+    proto::serialize::binary::BinEncodable,
     proto::{
-        op::ResponseCode,
-        rr::{DNSClass, RData, Record, RecordSet, RecordType},
+        error::ProtoError,
+        op::{Header, ResponseCode},
+        rr::{rdata::SOA, DNSClass, RData, Record, RecordSet, RecordType},
     },
     resolver::Name,
-    server::RequestInfo,
+    server::{Protocol, Request, RequestHandler, RequestInfo, ResponseHandler, ResponseInfo},
 };
 
-use crate::ServerDeps;
+use crate::{
+    zone_transfer::{diff_records, full_transfer, incremental_transfer, soa_record},
+    DnsConfig, ResponseLimits, ServerDeps,
+};
 
-pub struct BlockChainAuthority<Client, Backend, Block, Config>
-where
-    Block: BlockT,
-    Backend: BackendT<Block>,
-{
+/// What a [`BlockChainAuthority`] needs from the node, [`ServerDeps`] outside of tests.
+pub trait ZoneSource: Send + Sync + 'static {
+    fn dns_config(&self) -> &DnsConfig;
+
+    /// The zone serial, which is the best block number.
+    fn serial(&self) -> u32;
+
+    /// The records of `name` which can answer a `query_type` query, with their ttls.
+    fn lookup_typed(
+        &self,
+        name: &Name,
+        query_type: RecordType,
+    ) -> Result<Vec<(RecordType, RData, u32)>, LookupError>;
+
+    /// Every record of `name`, for `ANY` queries.
+    fn lookup_all(&self, name: &Name) -> Result<Vec<(RecordType, RData)>, LookupError>;
+
+    /// Remembers `name` so it is included in zone transfers.
+    fn learn_name(&self, name: &Name);
+
+    /// Every record of the zone `origin` at the best block, for AXFR.
+    fn zone_records(&self, origin: &LowerName) -> Vec<Record>;
+
+    /// Answers an IXFR query of the zone `origin` from a secondary at `client_serial`.
+    fn ixfr(&self, origin: &LowerName, client_serial: Option<u32>) -> Vec<Record>;
+}
+
+pub struct BlockChainAuthority<Z> {
     pub origin: LowerName,
     pub root: LowerName,
     pub zone_type: ZoneType,
-    pub inner: ServerDeps<Client, Backend, Block, Config>,
+    pub inner: Z,
 }
 
-impl<Client, Backend, Block, Config> BlockChainAuthority<Client, Backend, Block, Config>
-where
-    Client: ProvideRuntimeApi<Block>,
-    Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
-    Client: Send + Sync + 'static,
-    Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
-    Client::Api: PnsStorageApi<
-        Block,
-        Config::Moment,
-        BalanceOf<Config>,
-        Config::Signature,
-        Config::AccountId,
-    >,
-    Client::Api: BlockBuilder<Block>,
-    Block: BlockT,
-    Backend: BackendT<Block> + 'static,
-{
+impl<Z: ZoneSource> BlockChainAuthority<Z> {
     fn inner_lookup(
         &self,
         name: &LowerName,
@@ -65,43 +81,32 @@ where
         lookup_options: LookupOptions,
     ) -> Option<Arc<RecordSet>> {
         info!("in inner lookup. {name} {record_type:?}");
-        let all_res = if !self.origin.zone_of(name) && !name.is_root() && !name.is_wildcard() {
-            let name = name.clone();
-            error!("lookup udp server. {name}");
-            let res = std::thread::spawn(move || {
-                let address = "8.8.8.8:53".parse().ok()?;
-                let conn = UdpClientConnection::new(address).ok()?;
-
-                // and then create the Client
-                let client = SyncClient::new(conn);
-                let mut response: DnsResponse = client
-                    .query(name.borrow(), DNSClass::IN, RecordType::ANY)
-                    .ok()?;
-
-                let ans = response.take_answers();
-                return Some(
-                    ans.into_iter()
-                        .filter_map(|record| record.into_data())
-                        .map(|data| (data.to_record_type(), data))
-                        .collect::<Vec<_>>(),
-                );
-            });
-            error!("lookup udp server.");
-            let all_res = res.join().ok()??;
-            all_res
-        } else {
-            let inner = &self.inner;
-            let all_res = inner.inner_lookup(name.borrow()).ok()?;
-            all_res
+        let upstream = self.inner.dns_config().forward_upstream;
+        let all_res = match route(&self.origin, name, upstream) {
+            LookupRoute::Local => {
+                let inner = &self.inner;
+                let all_res = inner.lookup_typed(name.borrow(), record_type).ok()?;
+                if !all_res.is_empty() {
+                    inner.learn_name(name.borrow());
+                }
+                all_res
+            }
+            // queried names outside of the zone are forwarded as a whole by `lookup`,
+            // the client follows e.g. a CNAME pointing out of the zone on its own.
+            LookupRoute::Forward(_) => return None,
+            LookupRoute::Refuse => {
+                info!("refuse to lookup {name}, forwarding is disabled.");
+                return None;
+            }
         };
 
         info!("all_res: {all_res:?}");
         let lookup = all_res
             .into_iter()
-            .find(|(key_type, _)| is_need_type(*key_type, record_type))
-            .map(|(key_type, rdata)| {
+            .find(|(key_type, ..)| is_need_type(*key_type, record_type))
+            .map(|(key_type, rdata, ttl)| {
                 Arc::new({
-                    let mut set = RecordSet::new(name.borrow(), key_type, 0);
+                    let mut set = RecordSet::new(name.borrow(), key_type, ttl);
                     if !set.add_rdata(rdata) {
                         // TODO:
                         error!("insert rdata failed.");
@@ -112,12 +117,32 @@ where
 
         info!("lookup res {lookup:?}");
         // TODO: maybe unwrap this recursion.
+        let dns64_prefix = self.inner.dns_config().dns64_prefix;
         match lookup {
-            None => self.inner_lookup_wildcard(name, record_type, lookup_options),
+            None => self
+                .inner_lookup_wildcard(name, record_type, lookup_options)
+                .or_else(|| match (record_type, dns64_prefix) {
+                    (RecordType::AAAA, Some(prefix)) => {
+                        self.dns64_lookup(name, prefix, lookup_options)
+                    }
+                    _ => None,
+                }),
             l => l,
         }
     }
 
+    /// There is no `AAAA` record for `name`, try to synthesize one from its `A` record.
+    fn dns64_lookup(
+        &self,
+        name: &LowerName,
+        prefix: Ipv6Addr,
+        lookup_options: LookupOptions,
+    ) -> Option<Arc<RecordSet>> {
+        info!("in dns64 lookup. {name}");
+        let a_set = self.inner_lookup(name, RecordType::A, lookup_options)?;
+        dns64_record_set(&a_set, prefix).map(Arc::new)
+    }
+
     fn inner_lookup_wildcard(
         &self,
         name: &LowerName,
@@ -221,27 +246,20 @@ where
             None
         }
     }
+
+    /// The zone serial, which is the best block number.
+    fn serial(&self) -> u32 {
+        self.inner.serial()
+    }
+
+    /// Answers an IXFR query of a secondary at `client_serial`.
+    pub(crate) fn ixfr(&self, client_serial: Option<u32>) -> Vec<Record> {
+        self.inner.ixfr(&self.origin, client_serial)
+    }
 }
 
 #[async_trait::async_trait]
-impl<Client, Backend, Block, Config> Authority
-    for BlockChainAuthority<Client, Backend, Block, Config>
-where
-    Client: ProvideRuntimeApi<Block>,
-    Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
-    Client: Send + Sync + 'static,
-    Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
-    Client::Api: PnsStorageApi<
-        Block,
-        Config::Moment,
-        BalanceOf<Config>,
-        Config::Signature,
-        Config::AccountId,
-    >,
-    Client::Api: BlockBuilder<Block>,
-    Block: BlockT,
-    Backend: BackendT<Block> + 'static,
-{
+impl<Z: ZoneSource> Authority for BlockChainAuthority<Z> {
     type Lookup = AuthLookup;
 
     fn zone_type(&self) -> ZoneType {
@@ -249,7 +267,7 @@ where
     }
 
     fn is_axfr_allowed(&self) -> bool {
-        false
+        self.inner.dns_config().zone_transfer
     }
 
     async fn update(&self, _update: &MessageRequest) -> UpdateResult<bool> {
@@ -267,11 +285,29 @@ where
         lookup_options: LookupOptions,
     ) -> Result<Self::Lookup, LookupError> {
         info!("in lookup. {name} {rtype:?}");
+        let upstream = self.inner.dns_config().forward_upstream;
+        if let LookupRoute::Forward(upstream) = route(&self.origin, name, upstream) {
+            return forward_lookup(upstream, name, rtype, lookup_options).await;
+        }
         let (result, additionals): (LookupResult<LookupRecords>, Option<LookupRecords>) =
             match rtype {
-                RecordType::AXFR | RecordType::ANY => {
+                RecordType::SOA if name == &self.origin => {
+                    let soa = soa_record(&Name::from(&self.origin), self.serial());
+                    let soa = LookupRecords::new(lookup_options, Arc::new(RecordSet::from(soa)));
+                    (Ok(soa), None)
+                }
+                RecordType::AXFR => {
+                    let rrset = self
+                        .inner
+                        .zone_records(&self.origin)
+                        .into_iter()
+                        .map(|record| Arc::new(RecordSet::from(record)))
+                        .collect();
+                    (Ok(LookupRecords::many(lookup_options, rrset)), None)
+                }
+                RecordType::ANY => {
                     let inner = &self.inner;
-                    let res = inner.inner_lookup(name.borrow())?;
+                    let res = inner.lookup_all(name.borrow())?;
                     info!("any res: {res:?}");
                     let rrset = res
                         .into_iter()
@@ -402,17 +438,17 @@ where
         request_info: RequestInfo<'_>,
         lookup_options: LookupOptions,
     ) -> Result<Self::Lookup, LookupError> {
-        error!("searching BlockChainAuthority for: {}", request_info.query);
+        debug!("searching BlockChainAuthority for: {}", request_info.query);
         let name = request_info.query.name();
         let rtype: RecordType = request_info.query.query_type();
-        error!("{name:?} {rtype:?}");
+        debug!("{name:?} {rtype:?}");
 
         // if this is an AXFR zone transfer, verify that this is either the Secondary or Primary
         //  for AXFR the first and last record must be the SOA
         if RecordType::AXFR == rtype {
             // TODO: support more advanced AXFR options
             if !self.is_axfr_allowed() {
-                error!("refused");
+                info!("refused");
                 return Err(LookupError::from(ResponseCode::Refused));
             }
 
@@ -464,6 +500,504 @@ where
     }
 }
 
+impl<Client, Backend, Block, Config> ServerDeps<Client, Backend, Block, Config>
+where
+    Client: ProvideRuntimeApi<Block>,
+    Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
+    Client: Send + Sync + 'static,
+    Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
+    Client::Api: PnsStorageApi<
+        Block,
+        Config::Moment,
+        BalanceOf<Config>,
+        Config::Signature,
+        Config::AccountId,
+    >,
+    Client::Api: BlockBuilder<Block>,
+    Block: BlockT,
+    Backend: BackendT<Block> + 'static,
+{
+    /// Every on-chain record of the zone as of block `at`.
+    ///
+    /// The nodes are walked down from the zone apex on chain, but the chain only
+    /// keeps their hashes, so nodes whose name this node never learned are skipped.
+    fn zone_records_at(&self, zone: &LowerName, at: Block::Hash) -> Vec<Record> {
+        let origin = Name::from(zone);
+        let Some(apex) = crate::name_hash(&origin) else {
+            return Vec::new();
+        };
+
+        let api = self.client.runtime_api();
+        let (mut nodes, mut pending) = (vec![apex], vec![apex]);
+        while let Some(parent) = pending.pop() {
+            let mut start_after = None;
+            loop {
+                let (subnodes, next) = match api.subnodes_of(at, parent, start_after) {
+                    Ok(page) => page,
+                    Err(e) => {
+                        error!("subnodes of {parent:?} at {at:?} failed: {e:?}");
+                        return Vec::new();
+                    }
+                };
+                pending.extend(subnodes.iter().copied());
+                nodes.extend(subnodes);
+                match next {
+                    Some(next) => start_after = Some(next),
+                    None => break,
+                }
+            }
+        }
+
+        let names = match self.zone_journal.lock() {
+            Ok(journal) => nodes
+                .iter()
+                .filter_map(|node| match journal.name_of(node) {
+                    Some(name) => Some(name.clone()),
+                    None if *node == apex => Some(origin.clone()),
+                    None => None,
+                })
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                error!("zone journal lock poisoned: {e:?}");
+                return Vec::new();
+            }
+        };
+        if names.len() < nodes.len() {
+            info!(
+                "{} of {} nodes of {origin} have no known name and are not transferred.",
+                nodes.len() - names.len(),
+                nodes.len()
+            );
+        }
+
+        let mut records = Vec::new();
+        for name in names {
+            for (tp, rdata) in self.onchain_records_at(at, &name).unwrap_or_default() {
+                let ttl = self.record_ttl(&name, tp);
+                records.push(Record::from_rdata(name.clone(), ttl, rdata));
+            }
+        }
+        records
+    }
+}
+
+impl<Client, Backend, Block, Config> ZoneSource for ServerDeps<Client, Backend, Block, Config>
+where
+    Client: ProvideRuntimeApi<Block>,
+    Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
+    Client: Send + Sync + 'static,
+    Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
+    Client::Api: PnsStorageApi<
+        Block,
+        Config::Moment,
+        BalanceOf<Config>,
+        Config::Signature,
+        Config::AccountId,
+    >,
+    Client::Api: BlockBuilder<Block>,
+    Block: BlockT,
+    Backend: BackendT<Block> + 'static,
+{
+    fn dns_config(&self) -> &DnsConfig {
+        &self.dns_config
+    }
+
+    fn serial(&self) -> u32 {
+        self.client.info().best_number.unique_saturated_into()
+    }
+
+    fn lookup_typed(
+        &self,
+        name: &Name,
+        query_type: RecordType,
+    ) -> Result<Vec<(RecordType, RData, u32)>, LookupError> {
+        self.inner_lookup_typed(name, query_type)
+    }
+
+    fn lookup_all(&self, name: &Name) -> Result<Vec<(RecordType, RData)>, LookupError> {
+        self.inner_lookup(name)
+    }
+
+    fn learn_name(&self, name: &Name) {
+        ServerDeps::learn_name(self, name)
+    }
+
+    fn zone_records(&self, origin: &LowerName) -> Vec<Record> {
+        self.zone_records_at(origin, self.client.info().best_hash)
+    }
+
+    /// Changed names are diffed between the block of `client_serial` and the best block,
+    /// a full transfer is sent when the journal or the state doesn't reach back that far.
+    fn ixfr(&self, zone: &LowerName, client_serial: Option<u32>) -> Vec<Record> {
+        let origin = Name::from(zone);
+        let info = self.client.info();
+        let serial: u32 = info.best_number.unique_saturated_into();
+        let full = || full_transfer(&origin, serial, self.zone_records_at(zone, info.best_hash));
+
+        let Some(from) = client_serial else {
+            return full();
+        };
+        if from >= serial {
+            return incremental_transfer(&origin, from, serial, Vec::new(), Vec::new());
+        }
+
+        let changed = match self.zone_journal.lock() {
+            Ok(journal) => journal.changed_since(from).map(|nodes| {
+                nodes
+                    .iter()
+                    .filter_map(|node| journal.name_of(node))
+                    .filter(|name| zone.zone_of(&LowerName::from(*name)))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            }),
+            Err(e) => {
+                error!("zone journal lock poisoned: {e:?}");
+                None
+            }
+        };
+        let Some(names) = changed else {
+            info!("ixfr from {from} is older than the journal, sending the full zone.");
+            return full();
+        };
+        let Ok(Some(from_hash)) = self.client.hash(NumberFor::<Block>::from(from)) else {
+            return full();
+        };
+
+        let (mut deleted, mut added) = (Vec::new(), Vec::new());
+        for name in names {
+            let (Ok(old), Ok(new)) = (
+                self.onchain_records_at(from_hash, &name),
+                self.onchain_records_at(info.best_hash, &name),
+            ) else {
+                info!("state of {from} is not available, sending the full zone.");
+                return full();
+            };
+            let (removed, inserted) = diff_records(old, new);
+            let to_record = |(tp, rdata): (RecordType, RData)| {
+                Record::from_rdata(name.clone(), self.record_ttl(&name, tp), rdata)
+            };
+            deleted.extend(removed.into_iter().map(to_record));
+            added.extend(inserted.into_iter().map(to_record));
+        }
+
+        incremental_transfer(&origin, from, serial, deleted, added)
+    }
+}
+
+/// Sits in front of the catalog for what an [`Authority`] can't do on its own.
+///
+/// - IXFR: the serial of the secondary comes in the authority section of the query,
+///   which an authority never gets to see.
+/// - Udp answers beyond [`ResponseLimits`] are truncated, which needs the TC bit of the header.
+///
+/// The zones are those of [`ServerDeps::zones`], the ones created on chain
+/// after the server started are added before answering the next request.
+pub struct PnsRequestHandler<Client, Backend, Block, Config>
+where
+    Block: BlockT,
+    Backend: BackendT<Block>,
+{
+    inner: ServerDeps<Client, Backend, Block, Config>,
+    served: RwLock<ServedZones<Client, Backend, Block, Config>>,
+}
+
+/// The zones a [`PnsRequestHandler`] answers for.
+struct ServedZones<Client, Backend, Block, Config>
+where
+    Block: BlockT,
+    Backend: BackendT<Block>,
+{
+    catalog: Catalog,
+    /// One per zone, the first one also handles names outside of every zone.
+    authorities: Vec<Arc<BlockChainAuthority<ServerDeps<Client, Backend, Block, Config>>>>,
+    /// How many of the zones of [`ServerDeps::zones`] were looked at, valid or not.
+    zones: usize,
+}
+
+impl<Client, Backend, Block, Config> ServedZones<Client, Backend, Block, Config>
+where
+    Client: ProvideRuntimeApi<Block>,
+    Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
+    Client: Send + Sync + 'static,
+    Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
+    Client::Api: PnsStorageApi<
+        Block,
+        Config::Moment,
+        BalanceOf<Config>,
+        Config::Signature,
+        Config::AccountId,
+    >,
+    Client::Api: BlockBuilder<Block>,
+    Block: BlockT,
+    Backend: BackendT<Block> + 'static,
+{
+    /// Serves the zones of `zones` which are not served yet.
+    fn extend(&mut self, zones: &[String], inner: &ServerDeps<Client, Backend, Block, Config>) {
+        let forwarding = inner.dns_config.forward_upstream.is_some();
+        for zone in zones.iter().skip(self.zones) {
+            let zone_name = match zone.parse::<Name>() {
+                Ok(zone_name) => zone_name,
+                Err(e) => {
+                    error!("invalid dns zone {zone:?}: {e:?}");
+                    continue;
+                }
+            };
+            let authority = Arc::new(BlockChainAuthority {
+                origin: LowerName::from(&zone_name),
+                root: Name::root().into(),
+                zone_type: inner.dns_config.zone_type,
+                inner: inner.clone(),
+            });
+            self.catalog.upsert(
+                authority.origin.clone(),
+                Box::new(authority.clone()) as Box<dyn AuthorityObject>,
+            );
+            // names outside of the pns zones end up here and are forwarded upstream.
+            if self.authorities.is_empty() && forwarding {
+                self.catalog.upsert(
+                    LowerName::from(Name::root()),
+                    Box::new(authority.clone()) as Box<dyn AuthorityObject>,
+                );
+            }
+            info!("serving dns zone {zone}");
+            self.authorities.push(authority);
+        }
+        self.zones = zones.len();
+    }
+
+    /// The authority of the zone `name` is in.
+    fn authority_of(
+        &self,
+        name: &LowerName,
+    ) -> Option<&Arc<BlockChainAuthority<ServerDeps<Client, Backend, Block, Config>>>> {
+        self.authorities
+            .iter()
+            .find(|authority| authority.origin.zone_of(name))
+            .or_else(|| self.authorities.first())
+    }
+}
+
+impl<Client, Backend, Block, Config> PnsRequestHandler<Client, Backend, Block, Config>
+where
+    Client: ProvideRuntimeApi<Block>,
+    Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
+    Client: Send + Sync + 'static,
+    Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
+    Client::Api: PnsStorageApi<
+        Block,
+        Config::Moment,
+        BalanceOf<Config>,
+        Config::Signature,
+        Config::AccountId,
+    >,
+    Client::Api: BlockBuilder<Block>,
+    Block: BlockT,
+    Backend: BackendT<Block> + 'static,
+{
+    pub fn new(inner: ServerDeps<Client, Backend, Block, Config>) -> Self {
+        let mut served = ServedZones {
+            catalog: Catalog::new(),
+            authorities: Vec::new(),
+            zones: 0,
+        };
+        served.extend(&inner.zones(), &inner);
+        Self {
+            inner,
+            served: RwLock::new(served),
+        }
+    }
+
+    /// Serves the zones created on chain since the last request.
+    async fn refresh_zones(&self) {
+        let zones = self.inner.zones();
+        if self.served.read().await.zones >= zones.len() {
+            return;
+        }
+        self.served.write().await.extend(&zones, &self.inner);
+    }
+}
+
+#[async_trait::async_trait]
+impl<Client, Backend, Block, Config> RequestHandler
+    for PnsRequestHandler<Client, Backend, Block, Config>
+where
+    Client: ProvideRuntimeApi<Block>,
+    Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
+    Client: Send + Sync + 'static,
+    Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
+    Client::Api: PnsStorageApi<
+        Block,
+        Config::Moment,
+        BalanceOf<Config>,
+        Config::Signature,
+        Config::AccountId,
+    >,
+    Client::Api: BlockBuilder<Block>,
+    Block: BlockT,
+    Backend: BackendT<Block> + 'static,
+{
+    async fn handle_request<R: ResponseHandler>(
+        &self,
+        request: &Request,
+        mut response_handle: R,
+    ) -> ResponseInfo {
+        self.refresh_zones().await;
+        let served = self.served.read().await;
+        let request_info = request.request_info();
+        let query = request_info.query;
+        let protocol = request_info.protocol;
+        let Some(authority) = served.authority_of(query.name()) else {
+            return served
+                .catalog
+                .handle_request(request, response_handle)
+                .await;
+        };
+
+        // names outside of the zone are forwarded, the upstream answers for them.
+        let authoritative = authority.origin().zone_of(query.name());
+        let (answers, additionals, response_code) = if query.query_type() == RecordType::IXFR
+            && authority.is_axfr_allowed()
+            && authoritative
+        {
+            let client_serial = request
+                .name_servers()
+                .iter()
+                .filter_map(Record::data)
+                .find_map(RData::as_soa)
+                .map(SOA::serial);
+            info!("ixfr from serial {client_serial:?}");
+            (
+                authority.ixfr(client_serial),
+                Vec::new(),
+                ResponseCode::NoError,
+            )
+        } else if protocol == Protocol::Udp {
+            // only udp answers need limiting, failed lookups are answered here
+            // as well so the catalog doesn't look the name up again.
+            match authority
+                .search(request_info, LookupOptions::default())
+                .await
+            {
+                Ok(lookup) => (
+                    lookup.iter().cloned().collect(),
+                    lookup
+                        .additionals()
+                        .map(|additionals| additionals.iter().cloned().collect())
+                        .unwrap_or_default(),
+                    ResponseCode::NoError,
+                ),
+                Err(LookupError::NameExists) => (Vec::new(), Vec::new(), ResponseCode::NoError),
+                Err(LookupError::ResponseCode(code)) => (Vec::new(), Vec::new(), code),
+                Err(e) => {
+                    error!("lookup {} failed: {e:?}", query.name());
+                    (Vec::new(), Vec::new(), ResponseCode::ServFail)
+                }
+            }
+        } else {
+            return served
+                .catalog
+                .handle_request(request, response_handle)
+                .await;
+        };
+
+        // negative answers of the zone carry its soa (RFC 2308).
+        let soa = (authoritative && answers.is_empty())
+            .then(|| soa_record(&Name::from(authority.origin()), authority.serial()));
+        let answer = Answer {
+            response_code,
+            authoritative,
+            answers,
+            additionals,
+            soa,
+        };
+        send_answer(
+            request,
+            response_handle,
+            answer,
+            authority.inner.dns_config.response_limits,
+        )
+        .await
+    }
+}
+
+/// What [`PnsRequestHandler`] answers a request with.
+struct Answer {
+    response_code: ResponseCode,
+    authoritative: bool,
+    answers: Vec<Record>,
+    additionals: Vec<Record>,
+    /// The `SOA` of the zone, for negative answers.
+    soa: Option<Record>,
+}
+
+/// Sends `answer`, truncated beyond `limits` if the request came over udp.
+async fn send_answer<R: ResponseHandler>(
+    request: &Request,
+    mut response_handle: R,
+    answer: Answer,
+    limits: ResponseLimits,
+) -> ResponseInfo {
+    let (answers, additionals, truncated) = limit_response(
+        answer.answers,
+        answer.additionals,
+        request.request_info().protocol,
+        limits,
+    );
+    if truncated {
+        info!(
+            "truncated udp answer to {}",
+            request.request_info().query.name()
+        );
+    }
+
+    let mut header = Header::response_from_request(request.header());
+    header.set_response_code(answer.response_code);
+    header.set_authoritative(answer.authoritative);
+    header.set_truncated(truncated);
+    let response = MessageResponseBuilder::from_message_request(request).build(
+        header,
+        answers.iter(),
+        iter::empty(),
+        answer.soa.iter(),
+        additionals.iter(),
+    );
+    match response_handle.send_response(response).await {
+        Ok(info) => info,
+        Err(e) => {
+            error!("failed to send response: {e:?}");
+            let mut header = Header::new();
+            header.set_response_code(ResponseCode::ServFail);
+            header.into()
+        }
+    }
+}
+
+/// Drops every record of a udp answer beyond `limits` so the client retries over tcp,
+/// returns whether the answer was truncated. Tcp answers are never limited.
+fn limit_response(
+    answers: Vec<Record>,
+    additionals: Vec<Record>,
+    protocol: Protocol,
+    limits: ResponseLimits,
+) -> (Vec<Record>, Vec<Record>, bool) {
+    if protocol != Protocol::Udp {
+        return (answers, additionals, false);
+    }
+
+    let records = answers.len() + additionals.len();
+    // names are counted uncompressed, so this errs on the safe side.
+    let bytes: usize = answers
+        .iter()
+        .chain(additionals.iter())
+        .map(|record| record.to_bytes().map_or(0, |bytes| bytes.len()))
+        .sum();
+    if records > limits.max_records || bytes > limits.max_bytes {
+        (Vec::new(), Vec::new(), true)
+    } else {
+        (answers, additionals, false)
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn name() {
@@ -501,6 +1035,11 @@ fn name() {
 fn client_search() {
     use core::str::FromStr;
 
+    use trust_dns_server::client::{
+        client::{Client, SyncClient},
+        op::DnsResponse,
+        udp::UdpClientConnection,
+    };
     use trust_dns_server::proto::rr::Name;
     let name = Name::from_str("www.baidu.com").unwrap();
     let address = "8.8.8.8:53".parse().unwrap();
@@ -525,6 +1064,391 @@ fn client_search() {
     println!("{res:?}");
 }
 
+#[cfg(test)]
+#[test]
+fn dns64_synthesizes_aaaa_from_a() {
+    use core::str::FromStr;
+
+    let name = Name::from_str("ipv4-only.dot.").unwrap();
+    let prefix = Ipv6Addr::from_str("64:ff9b::").unwrap();
+
+    let mut a_set = RecordSet::new(&name, RecordType::A, 0);
+    a_set.add_rdata(RData::A(Ipv4Addr::new(192, 0, 2, 33)));
+
+    let aaaa_set = dns64_record_set(&a_set, prefix).unwrap();
+    assert_eq!(aaaa_set.record_type(), RecordType::AAAA);
+    assert_eq!(aaaa_set.name(), &name);
+
+    let rdatas = aaaa_set
+        .records_without_rrsigs()
+        .filter_map(Record::data)
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rdatas,
+        vec![RData::AAAA(Ipv6Addr::from_str("64:ff9b::c000:221").unwrap())]
+    );
+
+    // only `A` record sets are synthesized.
+    let mut cname_set = RecordSet::new(&name, RecordType::CNAME, 0);
+    cname_set.add_rdata(RData::CNAME(Name::from_str("other.dot.").unwrap()));
+    assert!(dns64_record_set(&cname_set, prefix).is_none());
+}
+
+#[cfg(test)]
+#[test]
+fn only_names_outside_the_zone_are_forwarded() {
+    use core::str::FromStr;
+
+    let origin = LowerName::from(Name::from_str("dot").unwrap());
+    let upstream: SocketAddr = "8.8.8.8:53".parse().unwrap();
+    let pns_name = LowerName::from(Name::from_str("cupnfish.dot").unwrap());
+    let other_name = LowerName::from(Name::from_str("www.baidu.com").unwrap());
+
+    assert_eq!(route(&origin, &pns_name, Some(upstream)), LookupRoute::Local);
+    assert_eq!(
+        route(&origin, &other_name, Some(upstream)),
+        LookupRoute::Forward(upstream)
+    );
+
+    // forwarding is disabled by default.
+    assert_eq!(route(&origin, &pns_name, None), LookupRoute::Local);
+    assert_eq!(route(&origin, &other_name, None), LookupRoute::Refuse);
+}
+
+/// A zone without a chain, every name has the same `records`.
+#[cfg(test)]
+struct MockZone {
+    dns_config: DnsConfig,
+    records: Vec<(RecordType, RData)>,
+}
+
+#[cfg(test)]
+impl MockZone {
+    fn authority(
+        dns_config: DnsConfig,
+        records: Vec<(RecordType, RData)>,
+    ) -> BlockChainAuthority<Self> {
+        use core::str::FromStr;
+
+        BlockChainAuthority {
+            origin: LowerName::from(Name::from_str("dot.").unwrap()),
+            root: Name::root().into(),
+            zone_type: dns_config.zone_type,
+            inner: MockZone {
+                dns_config,
+                records,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+impl ZoneSource for MockZone {
+    fn dns_config(&self) -> &DnsConfig {
+        &self.dns_config
+    }
+
+    fn serial(&self) -> u32 {
+        1
+    }
+
+    fn lookup_typed(
+        &self,
+        _name: &Name,
+        query_type: RecordType,
+    ) -> Result<Vec<(RecordType, RData, u32)>, LookupError> {
+        Ok(self
+            .records
+            .iter()
+            .filter(|(tp, _)| is_need_type(*tp, query_type))
+            .map(|(tp, rdata)| (*tp, rdata.clone(), 300))
+            .collect())
+    }
+
+    fn lookup_all(&self, _name: &Name) -> Result<Vec<(RecordType, RData)>, LookupError> {
+        Ok(self.records.clone())
+    }
+
+    fn learn_name(&self, _name: &Name) {}
+
+    fn zone_records(&self, _origin: &LowerName) -> Vec<Record> {
+        Vec::new()
+    }
+
+    fn ixfr(&self, _origin: &LowerName, _client_serial: Option<u32>) -> Vec<Record> {
+        Vec::new()
+    }
+}
+
+/// The answers of `authority` to a `rtype` query of `name`.
+#[cfg(test)]
+async fn lookup_rdatas<Z: ZoneSource>(
+    authority: &BlockChainAuthority<Z>,
+    name: &str,
+    rtype: RecordType,
+) -> Result<Vec<RData>, LookupError> {
+    use core::str::FromStr;
+
+    let name = LowerName::from(Name::from_str(name).unwrap());
+    let lookup = authority
+        .lookup(&name, rtype, LookupOptions::default())
+        .await?;
+    Ok(lookup.iter().filter_map(Record::data).cloned().collect())
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn outside_names_are_answered_by_the_upstream() {
+    use trust_dns_server::proto::{
+        op::{Message, MessageType},
+        serialize::binary::BinDecodable,
+    };
+
+    // answers `A` queries with `192.0.2.1`, and nothing else.
+    let stub = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let upstream = stub.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut buf = [0; 512];
+        while let Ok((len, from)) = stub.recv_from(&mut buf).await {
+            let query = Message::from_bytes(&buf[..len]).unwrap();
+            let mut response = Message::new();
+            response
+                .set_id(query.id())
+                .set_message_type(MessageType::Response)
+                .add_queries(query.queries().to_vec());
+            for query in query.queries() {
+                if query.query_type() == RecordType::A {
+                    let rdata = RData::A(Ipv4Addr::new(192, 0, 2, 1));
+                    response.add_answer(Record::from_rdata(query.name().clone(), 60, rdata));
+                }
+            }
+            stub.send_to(&response.to_bytes().unwrap(), from)
+                .await
+                .unwrap();
+        }
+    });
+
+    let local = RData::A(Ipv4Addr::new(127, 0, 0, 1));
+    let dns_config = DnsConfig {
+        forward_upstream: Some(upstream),
+        ..Default::default()
+    };
+    let authority = MockZone::authority(dns_config, vec![(RecordType::A, local.clone())]);
+
+    // the queried type is forwarded, the stub has nothing for `ANY`.
+    assert_eq!(
+        lookup_rdatas(&authority, "www.example.com.", RecordType::A)
+            .await
+            .unwrap(),
+        vec![RData::A(Ipv4Addr::new(192, 0, 2, 1))]
+    );
+    assert_eq!(
+        lookup_rdatas(&authority, "cupnfish.dot.", RecordType::A)
+            .await
+            .unwrap(),
+        vec![local.clone()]
+    );
+
+    // without an upstream outside names are not answered at all.
+    let authority = MockZone::authority(DnsConfig::default(), vec![(RecordType::A, local)]);
+    assert!(lookup_rdatas(&authority, "www.example.com.", RecordType::A)
+        .await
+        .is_err());
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn dns64_answers_aaaa_queries_of_ipv4_only_names() {
+    use core::str::FromStr;
+
+    let v4 = RData::A(Ipv4Addr::new(192, 0, 2, 33));
+    let synthesized = RData::AAAA(Ipv6Addr::from_str("64:ff9b::c000:221").unwrap());
+    let dns64_config = DnsConfig {
+        dns64_prefix: Some(Ipv6Addr::from_str("64:ff9b::").unwrap()),
+        ..Default::default()
+    };
+
+    // DNS64 is off by default.
+    let authority = MockZone::authority(DnsConfig::default(), vec![(RecordType::A, v4.clone())]);
+    assert!(
+        lookup_rdatas(&authority, "ipv4-only.dot.", RecordType::AAAA)
+            .await
+            .is_err()
+    );
+
+    let authority = MockZone::authority(dns64_config.clone(), vec![(RecordType::A, v4.clone())]);
+    assert_eq!(
+        lookup_rdatas(&authority, "ipv4-only.dot.", RecordType::AAAA)
+            .await
+            .unwrap(),
+        vec![synthesized]
+    );
+    assert_eq!(
+        lookup_rdatas(&authority, "ipv4-only.dot.", RecordType::A)
+            .await
+            .unwrap(),
+        vec![v4.clone()]
+    );
+
+    // a name with its own `AAAA` record keeps it.
+    let v6 = RData::AAAA(Ipv6Addr::from_str("2001:db8::1").unwrap());
+    let authority = MockZone::authority(
+        dns64_config,
+        vec![(RecordType::A, v4), (RecordType::AAAA, v6.clone())],
+    );
+    assert_eq!(
+        lookup_rdatas(&authority, "dual-stack.dot.", RecordType::AAAA)
+            .await
+            .unwrap(),
+        vec![v6]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn oversized_udp_answers_are_truncated() {
+    use core::str::FromStr;
+
+    let name = Name::from_str("cupnfish.dot.").unwrap();
+    let records = (1..=3)
+        .map(|i| Record::from_rdata(name.clone(), 0, RData::A(Ipv4Addr::new(127, 0, 0, i))))
+        .collect::<Vec<_>>();
+
+    let limits = ResponseLimits {
+        max_records: 2,
+        ..Default::default()
+    };
+    let (answers, _, truncated) =
+        limit_response(records.clone(), Vec::new(), Protocol::Udp, limits);
+    assert!(truncated);
+    assert!(answers.is_empty());
+
+    // the client retries over tcp and gets everything.
+    let (answers, _, truncated) =
+        limit_response(records.clone(), Vec::new(), Protocol::Tcp, limits);
+    assert!(!truncated);
+    assert_eq!(answers, records);
+
+    // additionals count towards the limits as well.
+    let (answers, additionals, truncated) = limit_response(
+        records[..1].to_vec(),
+        records[1..].to_vec(),
+        Protocol::Udp,
+        limits,
+    );
+    assert!(truncated);
+    assert!(answers.is_empty() && additionals.is_empty());
+
+    // the byte limit applies on its own.
+    let size = records[0].to_bytes().unwrap().len();
+    let limits = ResponseLimits {
+        max_records: 64,
+        max_bytes: size * 2,
+    };
+    let (answers, _, truncated) =
+        limit_response(records[..2].to_vec(), Vec::new(), Protocol::Udp, limits);
+    assert!(!truncated);
+    assert_eq!(answers.len(), 2);
+    let (_, _, truncated) = limit_response(records, Vec::new(), Protocol::Udp, limits);
+    assert!(truncated);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn truncated_udp_answers_are_complete_over_tcp() {
+    use core::str::FromStr;
+    use std::sync::Mutex;
+    use trust_dns_server::{
+        authority::MessageResponse,
+        proto::{
+            op::{Message, Query},
+            serialize::binary::{BinDecodable, BinEncoder},
+        },
+    };
+
+    /// Keeps the encoded response.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    #[async_trait::async_trait]
+    impl ResponseHandler for Captured {
+        async fn send_response<'a>(
+            &mut self,
+            response: MessageResponse<
+                '_,
+                'a,
+                impl Iterator<Item = &'a Record> + Send + 'a,
+                impl Iterator<Item = &'a Record> + Send + 'a,
+                impl Iterator<Item = &'a Record> + Send + 'a,
+                impl Iterator<Item = &'a Record> + Send + 'a,
+            >,
+        ) -> std::io::Result<ResponseInfo> {
+            let mut buf = self.0.lock().unwrap();
+            let mut encoder = BinEncoder::new(&mut *buf);
+            response
+                .destructive_emit(&mut encoder)
+                .map(Into::into)
+                .map_err(Into::into)
+        }
+    }
+
+    async fn respond(
+        query: &[u8],
+        protocol: Protocol,
+        answer: Answer,
+        limits: ResponseLimits,
+    ) -> Message {
+        let message = MessageRequest::from_bytes(query).unwrap();
+        let request = Request::new(message, "127.0.0.1:5353".parse().unwrap(), protocol);
+        let captured = Captured::default();
+        send_answer(&request, captured.clone(), answer, limits).await;
+        let response = captured.0.lock().unwrap().clone();
+        Message::from_bytes(&response).unwrap()
+    }
+
+    let origin = Name::from_str("dot.").unwrap();
+    let name = Name::from_str("cupnfish.dot.").unwrap();
+    let records = (1..=3)
+        .map(|i| Record::from_rdata(name.clone(), 0, RData::A(Ipv4Addr::new(127, 0, 0, i))))
+        .collect::<Vec<_>>();
+    let answer = |answers: Vec<Record>| Answer {
+        response_code: ResponseCode::NoError,
+        authoritative: true,
+        answers,
+        additionals: Vec::new(),
+        soa: None,
+    };
+    let limits = ResponseLimits {
+        max_records: 2,
+        ..Default::default()
+    };
+    let mut query = Message::new();
+    query.add_query(Query::query(name, RecordType::A));
+    let query = query.to_bytes().unwrap();
+
+    let udp = respond(&query, Protocol::Udp, answer(records.clone()), limits).await;
+    assert!(udp.truncated());
+    assert!(udp.answers().is_empty());
+
+    // the client retries over tcp and gets every record.
+    let tcp = respond(&query, Protocol::Tcp, answer(records.clone()), limits).await;
+    assert!(!tcp.truncated());
+    assert!(tcp.authoritative());
+    assert_eq!(tcp.answers(), &records[..]);
+
+    // a name the zone doesn't have is answered with its soa.
+    let soa = soa_record(&origin, 7);
+    let nx_domain = Answer {
+        response_code: ResponseCode::NXDomain,
+        soa: Some(soa.clone()),
+        ..answer(Vec::new())
+    };
+    let udp = respond(&query, Protocol::Udp, nx_domain, limits).await;
+    assert_eq!(udp.response_code(), ResponseCode::NXDomain);
+    assert_eq!(udp.name_servers(), &[soa]);
+}
+
 // #[cfg(test)]
 // #[tokio::test]
 // async fn test_query() {
@@ -541,7 +1465,91 @@ fn client_search() {
 //     server.init_dns_server_test().await;
 // }
 
-fn is_need_type(key_type: RecordType, query_type: RecordType) -> bool {
+#[derive(Debug, PartialEq, Eq)]
+enum LookupRoute {
+    /// The name is served from chain (and offchain) data.
+    Local,
+    /// The name is outside of the pns zone and forwarded to the upstream resolver.
+    Forward(SocketAddr),
+    /// The name is outside of the pns zone and forwarding is disabled.
+    Refuse,
+}
+
+fn route(origin: &LowerName, name: &LowerName, upstream: Option<SocketAddr>) -> LookupRoute {
+    if origin.zone_of(name) || name.is_root() || name.is_wildcard() {
+        LookupRoute::Local
+    } else {
+        upstream.map_or(LookupRoute::Refuse, LookupRoute::Forward)
+    }
+}
+
+/// Asks `upstream` for the `record_type` records of `name`, which is outside of every zone.
+async fn forward_lookup(
+    upstream: SocketAddr,
+    name: &LowerName,
+    record_type: RecordType,
+    lookup_options: LookupOptions,
+) -> Result<AuthLookup, LookupError> {
+    debug!("forward {name} {record_type:?} to {upstream}.");
+    let failed = |e: ProtoError| {
+        info!("forward {name} to {upstream} failed: {e:?}");
+        LookupError::from(ResponseCode::ServFail)
+    };
+
+    let stream = UdpClientStream::<UdpSocket>::new(upstream);
+    let (mut client, background) = AsyncClient::connect(stream).await.map_err(failed)?;
+    tokio::spawn(background);
+    let response = client
+        .query(Name::from(name), DNSClass::IN, record_type)
+        .await
+        .map_err(failed)?;
+    trace!("upstream answer {response:?}");
+
+    let answers = response
+        .answers()
+        .iter()
+        .cloned()
+        .map(|record| Arc::new(RecordSet::from(record)))
+        .collect::<Vec<_>>();
+    if answers.is_empty() {
+        return Err(match response.response_code() {
+            ResponseCode::NoError => LookupError::NameExists,
+            code => LookupError::from(code),
+        });
+    }
+    Ok(AuthLookup::answers(
+        LookupRecords::many(lookup_options, answers),
+        None,
+    ))
+}
+
+/// Embeds `v4` into the low 32 bits of a `/96` NAT64 prefix (RFC 6052 section 2.2).
+///
+/// The low 32 bits of `prefix` are overwritten, the shorter prefixes of RFC 6052
+/// lay the address out differently and are not supported.
+pub fn synthesize_aaaa(prefix: Ipv6Addr, v4: Ipv4Addr) -> Ipv6Addr {
+    let mut octets = prefix.octets();
+    octets[12..].copy_from_slice(&v4.octets());
+    Ipv6Addr::from(octets)
+}
+
+/// Converts an `A` record set to the `AAAA` record set a DNS64 server would answer with.
+fn dns64_record_set(a_set: &RecordSet, prefix: Ipv6Addr) -> Option<RecordSet> {
+    if a_set.record_type() != RecordType::A {
+        return None;
+    }
+
+    let mut set = RecordSet::with_ttl(a_set.name().clone(), RecordType::AAAA, a_set.ttl());
+    for rdata in a_set.records_without_rrsigs().filter_map(Record::data) {
+        if let RData::A(v4) = rdata {
+            set.add_rdata(RData::AAAA(synthesize_aaaa(prefix, *v4)));
+        }
+    }
+
+    (!set.is_empty()).then_some(set)
+}
+
+pub(crate) fn is_need_type(key_type: RecordType, query_type: RecordType) -> bool {
     key_type == query_type
         || key_type == RecordType::CNAME
         || (query_type == RecordType::A || query_type == RecordType::AAAA)
@@ -0,0 +1,243 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tracing::{error, warn};
+
+#[derive(Debug, Clone)]
+pub struct BreakerConfig {
+    /// How many times a failed call is retried before it counts as a failure.
+    pub retries: u32,
+    /// Consecutive failed calls after which the breaker opens.
+    pub failure_threshold: u32,
+    /// How long an open breaker fast-fails before letting a probe call through.
+    pub cooldown: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Calls go through normally.
+    Closed,
+    /// Calls fail immediately without touching the runtime api.
+    Open,
+    /// The cooldown is over, a single probe call decides whether to close again,
+    /// other calls fail immediately until it returns.
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerStatus {
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug)]
+pub enum BreakerError<E> {
+    /// The breaker is open, the call was not attempted.
+    Open,
+    /// Every attempt failed, this is the last error.
+    Failed(E),
+}
+
+#[derive(Default)]
+struct Inner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// Start of the probe call in flight while half open.
+    probing_since: Option<Instant>,
+}
+
+/// Guards the runtime api against being hammered while it is unavailable.
+///
+/// Transient errors are retried a few times, but once the api keeps failing
+/// the breaker opens and every call fails fast until `cooldown` elapses.
+pub struct CircuitBreaker {
+    config: BreakerConfig,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        let inner = self.inner.lock().expect("breaker lock poisoned");
+        self.state_of(&inner)
+    }
+
+    pub fn status(&self) -> BreakerStatus {
+        let inner = self.inner.lock().expect("breaker lock poisoned");
+        BreakerStatus {
+            state: self.state_of(&inner),
+            consecutive_failures: inner.consecutive_failures,
+        }
+    }
+
+    fn state_of(&self, inner: &Inner) -> BreakerState {
+        match inner.opened_at {
+            None => BreakerState::Closed,
+            Some(at) if at.elapsed() < self.config.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    pub fn call<T, E: core::fmt::Debug>(
+        &self,
+        mut f: impl FnMut() -> Result<T, E>,
+    ) -> Result<T, BreakerError<E>> {
+        // the lock is not held while calling `f`, lookups should not wait on each other.
+        let state = {
+            let mut inner = self.inner.lock().expect("breaker lock poisoned");
+            let state = self.state_of(&inner);
+            if state == BreakerState::HalfOpen {
+                // a probe which takes longer than the cooldown is given up on.
+                match inner.probing_since {
+                    Some(at) if at.elapsed() < self.config.cooldown => {
+                        return Err(BreakerError::Open)
+                    }
+                    _ => inner.probing_since = Some(Instant::now()),
+                }
+            }
+            state
+        };
+        let attempts = match state {
+            BreakerState::Open => return Err(BreakerError::Open),
+            // a single probe is enough to know if the api is back.
+            BreakerState::HalfOpen => 1,
+            BreakerState::Closed => self.config.retries + 1,
+        };
+
+        let mut last_err = None;
+        for attempt in 0..attempts {
+            match f() {
+                Ok(res) => {
+                    let mut inner = self.inner.lock().expect("breaker lock poisoned");
+                    *inner = Inner::default();
+                    return Ok(res);
+                }
+                Err(e) => {
+                    warn!("runtime api call failed (attempt {}): {e:?}", attempt + 1);
+                    last_err = Some(e);
+                }
+            }
+        }
+
+        let mut inner = self.inner.lock().expect("breaker lock poisoned");
+        inner.probing_since = None;
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        if state == BreakerState::HalfOpen
+            || inner.consecutive_failures >= self.config.failure_threshold
+        {
+            error!(
+                "runtime api keeps failing ({} calls in a row), circuit breaker opened for {:?}",
+                inner.consecutive_failures, self.config.cooldown
+            );
+            inner.opened_at = Some(Instant::now());
+        }
+
+        Err(BreakerError::Failed(
+            last_err.expect("attempts is at least one; qed"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intermittent_errors_are_retried() {
+        let breaker = CircuitBreaker::new(BreakerConfig::default());
+        let mut calls = 0;
+
+        let res = breaker.call(|| {
+            calls += 1;
+            if calls < 3 {
+                Err("runtime api unavailable")
+            } else {
+                Ok(calls)
+            }
+        });
+
+        assert_eq!(res.unwrap(), 3);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert_eq!(breaker.status().consecutive_failures, 0);
+    }
+
+    #[test]
+    fn consistent_errors_open_the_breaker() {
+        let breaker = CircuitBreaker::new(BreakerConfig {
+            retries: 1,
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(60),
+        });
+        let mut calls = 0;
+        let mut failing = || -> Result<(), &str> {
+            calls += 1;
+            Err("runtime api unavailable")
+        };
+
+        assert!(matches!(breaker.call(&mut failing), Err(BreakerError::Failed(_))));
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(matches!(breaker.call(&mut failing), Err(BreakerError::Failed(_))));
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        // open breaker fails fast without calling the api.
+        assert!(matches!(breaker.call(&mut failing), Err(BreakerError::Open)));
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn half_open_breaker_closes_after_success() {
+        let breaker = CircuitBreaker::new(BreakerConfig {
+            retries: 0,
+            failure_threshold: 1,
+            cooldown: Duration::ZERO,
+        });
+
+        assert!(breaker.call(|| Err::<(), _>("down")).is_err());
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+
+        assert_eq!(breaker.call(|| Ok::<_, &str>(7)).unwrap(), 7);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+
+    #[test]
+    fn half_open_breaker_lets_a_single_probe_through() {
+        let breaker = CircuitBreaker::new(BreakerConfig {
+            retries: 0,
+            failure_threshold: 1,
+            cooldown: Duration::from_millis(50),
+        });
+
+        assert!(breaker.call(|| Err::<(), _>("down")).is_err());
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+
+        // calls made while the probe is in flight fail fast.
+        let res = breaker.call(|| {
+            let concurrent = breaker.call(|| Ok::<_, &str>(()));
+            assert!(matches!(concurrent, Err(BreakerError::Open)));
+            Err::<(), _>("still down")
+        });
+        assert!(matches!(res, Err(BreakerError::Failed(_))));
+        assert_eq!(breaker.state(), BreakerState::Open);
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    net::{Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use sc_network::IfDisconnected;
+use trust_dns_server::authority::ZoneType;
+
+use crate::breaker::BreakerConfig;
+
+/// Options of the dns server which are not provided by the chain.
+///
+/// Everything defaults to the most conservative behaviour, so
+/// `DnsConfig::default()` serves exactly what is stored on chain.
+#[derive(Debug, Clone)]
+pub struct DnsConfig {
+    /// Top level domains served from the chain, each one a base node of the registrar.
+    ///
+    /// The ones created on chain are added when the dns server starts.
+    /// Names outside of every zone are handled by the first one.
+    pub zones: Vec<String>,
+    /// Zone type announced for the PNS zones.
+    ///
+    /// A `Secondary` node is expected to be kept in sync through AXFR or IXFR from a primary.
+    pub zone_type: ZoneType,
+    /// Upstream resolver queries outside of the PNS zone are forwarded to.
+    ///
+    /// `None` refuses to answer names PNS is not authoritative for.
+    pub forward_upstream: Option<SocketAddr>,
+    /// NAT64 prefix (a `/96`, e.g. `64:ff9b::`) used to synthesize `AAAA`
+    /// answers from `A` records for IPv6-only clients (DNS64, RFC 6147).
+    ///
+    /// Only `/96` prefixes are supported: the low 32 bits are replaced by the
+    /// IPv4 address, so they should be zero.
+    ///
+    /// `None` disables DNS64.
+    pub dns64_prefix: Option<Ipv6Addr>,
+    /// Retry and circuit breaker settings for runtime api lookups.
+    pub runtime_api_breaker: BreakerConfig,
+    /// How record updates are gossiped to the other ddns peers.
+    pub gossip: GossipConfig,
+    /// How long a lookup which found no record is remembered.
+    ///
+    /// Records set through this node invalidate it immediately, records coming
+    /// from the chain or other peers show up after at most this long.
+    /// `Duration::ZERO` disables negative caching.
+    pub negative_cache_ttl: Duration,
+    /// Answer AXFR and IXFR queries for the PNS zones, also over TCP.
+    ///
+    /// Records are read from chain, but the chain only keeps name hashes,
+    /// so only names this node has seen registered or looked up are transferred.
+    pub zone_transfer: bool,
+    /// How many blocks with record changes are kept for IXFR,
+    /// older serials get a full transfer instead.
+    pub zone_journal_len: usize,
+    /// How many learned names are kept for zone transfers, the oldest are forgotten first.
+    pub zone_names_len: usize,
+    /// Udp responses beyond these limits are sent empty with the TC bit set,
+    /// so the client retries over tcp. This keeps the server from being used
+    /// for DNS amplification.
+    pub response_limits: ResponseLimits,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseLimits {
+    /// Maximum number of answer and additional records.
+    pub max_records: usize,
+    /// Maximum size of the answer and additional records in bytes.
+    ///
+    /// Defaults to 1232, the EDNS buffer size recommended to avoid fragmentation.
+    pub max_bytes: usize,
+}
+
+impl Default for ResponseLimits {
+    fn default() -> Self {
+        Self {
+            max_records: 64,
+            max_bytes: 1232,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GossipConfig {
+    /// What to do when a peer is not connected at the time of sending.
+    ///
+    /// `TryConnect` delivers to briefly disconnected peers at the cost of latency.
+    pub if_disconnected: IfDisconnected,
+    /// Gives up on a single request after this long, `None` waits for the network.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for GossipConfig {
+    fn default() -> Self {
+        Self {
+            if_disconnected: IfDisconnected::ImmediateError,
+            timeout: None,
+        }
+    }
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self {
+            zones: vec![String::from("dot")],
+            zone_type: ZoneType::Primary,
+            forward_upstream: None,
+            dns64_prefix: None,
+            runtime_api_breaker: BreakerConfig::default(),
+            gossip: GossipConfig::default(),
+            negative_cache_ttl: Duration::ZERO,
+            zone_transfer: false,
+            zone_journal_len: 1024,
+            zone_names_len: 100_000,
+            response_limits: ResponseLimits::default(),
+        }
+    }
+}
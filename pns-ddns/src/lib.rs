@@ -1,16 +1,28 @@
 mod block_chain;
+mod breaker;
 mod builder;
+mod config;
+mod negative_cache;
 mod network;
 mod offchain;
+mod zone_transfer;
 
 use core::{marker::PhantomData, str::FromStr};
-use std::{net::SocketAddr, sync::Mutex, time::Duration};
+use std::{
+    net::SocketAddr,
+    sync::{Mutex, RwLock},
+    time::Duration,
+};
 
 use std::sync::Arc;
 
+pub use crate::breaker::{BreakerConfig, BreakerState, BreakerStatus, CircuitBreaker};
 pub use crate::builder::{build_network, DdnsNetworkParams};
+pub use crate::config::{DnsConfig, GossipConfig, ResponseLimits};
+pub use crate::negative_cache::NegativeCache;
 pub use crate::network::{DdnsNetworkManager, DdnsReuqestHandler};
 pub use crate::offchain::{from_backend, OffChain};
+pub use crate::zone_transfer::ZoneJournal;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -18,30 +30,38 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use block_chain::BlockChainAuthority;
+use block_chain::PnsRequestHandler;
+use breaker::BreakerError;
+use codec::Decode;
+use futures::StreamExt;
 use libp2p::PeerId;
 use network::Message;
 use pns_registrar::{registrar::BalanceOf, traits::Label};
 use pns_runtime_api::PnsStorageApi;
-use pns_types::DomainHash;
-use sc_client_api::backend::Backend as BackendT;
+use pns_types::{ddns::codec_type::RecordType as CodecRecordType, DomainHash};
+use sc_client_api::{
+    backend::Backend as BackendT, BlockchainEvents, ProofProvider, StorageProof, StorageProvider,
+};
 use sc_network::NetworkRequest;
 use sc_service::SpawnTaskHandle;
 use sp_api::{BlockT, ProvideRuntimeApi};
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
-use sp_core::Pair;
-use tokio::net::UdpSocket;
+use sp_core::{hashing::twox_128, storage::StorageKey, Pair};
+use sp_runtime::traits::{Header as HeaderT, UniqueSaturatedInto};
+use tokio::net::{TcpListener, UdpSocket};
 use tracing::{error, info, warn};
 
 pub use trust_dns_server::proto::rr::{Name, RData};
 use trust_dns_server::{
-    authority::{AuthorityObject, Catalog, LookupError},
-    client::rr::LowerName,
+    authority::LookupError,
     proto::{op::ResponseCode, rr::RecordType},
     ServerFuture,
 };
 
+/// How long a tcp connection, e.g. of a zone transfer, may stay idle.
+const TCP_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub struct ServerDeps<Client, Backend, Block, Config>
 where
     Block: BlockT,
@@ -53,6 +73,12 @@ where
     pub manager: DdnsNetworkManager,
     pub network: Arc<sc_network::NetworkService<Block, <Block as BlockT>::Hash>>,
     pub spawn_handle: SpawnTaskHandle,
+    pub dns_config: DnsConfig,
+    pub breaker: Arc<CircuitBreaker>,
+    pub negative_cache: Arc<NegativeCache>,
+    pub zone_journal: Arc<Mutex<ZoneJournal>>,
+    /// The zones served, loaded from chain on first use and extended by `run_zone_journal`.
+    zones: Arc<RwLock<Vec<String>>>,
     _block: PhantomData<(Block, Config)>,
 }
 
@@ -68,6 +94,11 @@ where
             manager: self.manager.clone(),
             network: self.network.clone(),
             spawn_handle: self.spawn_handle.clone(),
+            dns_config: self.dns_config.clone(),
+            breaker: self.breaker.clone(),
+            negative_cache: self.negative_cache.clone(),
+            zone_journal: self.zone_journal.clone(),
+            zones: self.zones.clone(),
             _block: PhantomData::default(),
             offchain_db: self.offchain_db.clone(),
         }
@@ -109,15 +140,110 @@ where
             manager,
             spawn_handle,
             network,
+            dns_config: DnsConfig::default(),
+            breaker: Arc::new(CircuitBreaker::new(Default::default())),
+            negative_cache: Arc::new(NegativeCache::new(Duration::ZERO)),
+            zone_journal: Arc::new(Mutex::new(ZoneJournal::new(
+                DnsConfig::default().zone_journal_len,
+                DnsConfig::default().zone_names_len,
+            ))),
+            zones: Default::default(),
             _block: PhantomData::default(),
         }
     }
+
+    pub fn with_dns_config(mut self, dns_config: DnsConfig) -> Self {
+        self.breaker = Arc::new(CircuitBreaker::new(dns_config.runtime_api_breaker.clone()));
+        self.negative_cache = Arc::new(NegativeCache::new(dns_config.negative_cache_ttl));
+        self.zone_journal = Arc::new(Mutex::new(ZoneJournal::new(
+            dns_config.zone_journal_len,
+            dns_config.zone_names_len,
+        )));
+        self.zones = Default::default();
+        self.dns_config = dns_config;
+        self
+    }
+
+    /// Remembers `name` so it is included in zone transfers.
+    pub(crate) fn learn_name(&self, name: &Name) {
+        let Some(node) = name_hash(name) else {
+            return;
+        };
+        match self.zone_journal.lock() {
+            Ok(mut journal) => journal.learn_name(node, name.clone()),
+            Err(e) => error!("zone journal lock poisoned: {e:?}"),
+        }
+    }
+}
+
+impl<Client, Backend, Block, Config> ServerDeps<Client, Backend, Block, Config>
+where
+    Client: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
+    Client::Api: PnsStorageApi<
+        Block,
+        Config::Moment,
+        BalanceOf<Config>,
+        Config::Signature,
+        Config::AccountId,
+    >,
+    Block: BlockT,
+    Backend: BackendT<Block>,
+{
+    /// The zones of `DnsConfig` followed by the top level domains created on chain,
+    /// new zones are only ever appended.
+    pub(crate) fn zones(&self) -> Vec<String> {
+        match self.zones.read() {
+            Ok(zones) if !zones.is_empty() => return zones.clone(),
+            Ok(_) => {}
+            Err(e) => error!("zones lock poisoned: {e:?}"),
+        }
+        let zones = self.load_zones();
+        match self.zones.write() {
+            Ok(mut loaded) if loaded.is_empty() => *loaded = zones.clone(),
+            Ok(loaded) => return loaded.clone(),
+            Err(e) => error!("zones lock poisoned: {e:?}"),
+        }
+        zones
+    }
+
+    /// Serves `zone` from now on, e.g. a top level domain just created on chain.
+    fn add_zone(&self, zone: String) {
+        // the zones on chain are loaded first, so the new one comes after them.
+        if self.zones().contains(&zone) {
+            return;
+        }
+        match self.zones.write() {
+            Ok(mut zones) if !zones.contains(&zone) => zones.push(zone),
+            Ok(_) => {}
+            Err(e) => error!("zones lock poisoned: {e:?}"),
+        }
+    }
+
+    fn load_zones(&self) -> Vec<String> {
+        let mut zones = self.dns_config.zones.clone();
+        let at = self.client.info().best_hash;
+        match self.client.runtime_api().tlds(at) {
+            Ok(tlds) => {
+                for tld in tlds {
+                    match String::from_utf8(tld) {
+                        Ok(tld) if !zones.contains(&tld) => zones.push(tld),
+                        Ok(_) => {}
+                        Err(e) => error!("invalid tld on chain: {e:?}"),
+                    }
+                }
+            }
+            Err(e) => error!("get tlds error: {e:?}"),
+        }
+        zones
+    }
 }
 
 impl<Client, Backend, Block, Config> ServerDeps<Client, Backend, Block, Config>
 where
     Client: ProvideRuntimeApi<Block>,
     Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
+    Client: ProofProvider<Block>,
     Client: Send + Sync + 'static,
     Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
     Client::Api: PnsStorageApi<
@@ -140,6 +266,11 @@ where
             .route("/set_record/:data", post(Self::set_record))
             .route("/all", get(Self::all))
             .route("/ddns/state", get(Self::ddns_state))
+            .route("/health", get(Self::health))
+            .route(
+                "/record_proof/:name/:kind",
+                get(Self::record_proof_from_name),
+            )
             .with_state(self);
 
         axum::Server::bind(&socket)
@@ -149,26 +280,17 @@ where
     }
 
     pub async fn init_dns_server(self, port: u16) {
-        let zone_name = Name::from_str("dot").unwrap();
-        let authority = BlockChainAuthority {
-            origin: LowerName::from(&zone_name),
-            root: Name::root().into(),
-            zone_type: trust_dns_server::authority::ZoneType::Primary,
-            inner: self,
-        };
-
-        let mut catalog: Catalog = Catalog::new();
-        catalog.upsert(
-            LowerName::from(&zone_name),
-            Box::new(Arc::new(authority)) as Box<dyn AuthorityObject>,
-        );
-
-        let mut server = ServerFuture::new(catalog);
+        let mut server = ServerFuture::new(PnsRequestHandler::new(self));
 
         let udp_socket = UdpSocket::bind(("127.0.0.1", port))
             .await
             .expect("bind udp socket failed.");
         server.register_socket(udp_socket);
+        // truncated udp answers and zone transfers are retried over tcp.
+        let tcp_listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .expect("bind tcp listener failed.");
+        server.register_listener(tcp_listener, TCP_TIMEOUT);
         match server.block_until_done().await {
             Ok(()) => {
                 // we're exiting for some reason...
@@ -221,6 +343,7 @@ where
         if let Some((k, v)) =
             guard.set_with_signature::<Config, _>(who, code, id, tp, content, checker)
         {
+            state.negative_cache.invalidate(id);
             if let Ok(peers) = state.manager.peers.lock() {
                 let msg = Message::Set {
                     k,
@@ -230,12 +353,13 @@ where
                 if let Ok(request) = msg.encode() {
                     let spawn_handle = state.spawn_handle;
                     let network = state.network;
+                    let gossip = state.dns_config.gossip;
 
                     for peer in peers.iter().cloned() {
                         spawn_handle.spawn(
                             "ddns_handle_peer",
                             Some("ddns"),
-                            gen_task(network.clone(), request.clone(), peer),
+                            gen_task(network.clone(), request.clone(), peer, gossip.clone()),
                         );
                     }
                 } else {
@@ -270,6 +394,7 @@ where
         Json(res)
     }
 
+    /// Gets every record of `name`, this is only needed to answer `ANY` queries.
     pub(crate) fn inner_lookup(
         &self,
         name: &Name,
@@ -278,30 +403,126 @@ where
         let api = self.client.runtime_api();
         let id = name_hash(name).ok_or(LookupError::ResponseCode(ResponseCode::NoError))?;
         info!("namehash: {id:?}");
-        match api.lookup(at, id) {
-            Ok(mut onchain) => {
-                // offchain:
-                let mut guard = self.offchain_db.lock().expect("db lock error");
-                let mut offchain = guard.get::<Config>(id);
-
-                onchain.append(&mut offchain);
-                let mut records = Vec::new();
-                for (raw_tp, v) in onchain.into_iter() {
-                    let rt = RecordType::from(raw_tp);
-                    info!("will serde rdata");
-                    let rdata = bincode::serde::decode_from_slice::<RData, _>(
-                        &v,
-                        bincode::config::legacy(),
-                    )
-                    .map_err(|_| LookupError::ResponseCode(ResponseCode::FormErr))?
-                    .0;
-                    info!("serde rdata well");
-                    records.push((rt, rdata));
+        let mut onchain = self.runtime_api_call(name, || api.lookup(at, id))?;
+
+        // offchain:
+        let mut guard = self.offchain_db.lock().expect("db lock error");
+        let mut offchain = guard.get::<Config>(id);
+
+        onchain.append(&mut offchain);
+        let records = decode_records(onchain)?;
+        info!("inner inner_lookup res: {records:?}");
+        Ok(records)
+    }
+
+    /// Gets the records of `name` which can answer a `query_type` query with their ttls,
+    /// reading each candidate type directly instead of iterating all of them.
+    pub(crate) fn inner_lookup_typed(
+        &self,
+        name: &Name,
+        query_type: RecordType,
+    ) -> Result<Vec<(RecordType, RData, u32)>, LookupError> {
+        let at = self.client.info().best_hash;
+        let api = self.client.runtime_api();
+        let id = name_hash(name).ok_or(LookupError::ResponseCode(ResponseCode::NoError))?;
+        info!("namehash: {id:?} query type: {query_type:?}");
+
+        let raw = self.negative_cache.get_or_fetch(
+            id,
+            query_type.into(),
+            || -> Result<_, LookupError> {
+                let mut candidates = vec![query_type, RecordType::CNAME];
+                if query_type == RecordType::A || query_type == RecordType::AAAA {
+                    candidates.push(RecordType::ANAME);
+                }
+                candidates.dedup();
+
+                let mut onchain = Vec::with_capacity(candidates.len());
+                for tp in candidates {
+                    let tp: CodecRecordType = tp.into();
+                    if let Some((content, ttl)) =
+                        self.runtime_api_call(name, || api.lookup_one_with_ttl(at, id, tp))?
+                    {
+                        onchain.push((tp, content, ttl));
+                    }
                 }
-                info!("inner inner_lookup res: {records:?}");
-                Ok(records)
+
+                // offchain:
+                let offchain = {
+                    let mut guard = self.offchain_db.lock().expect("db lock error");
+                    guard
+                        .get::<Config>(id)
+                        .into_iter()
+                        .filter(|(tp, _)| {
+                            block_chain::is_need_type(RecordType::from(*tp), query_type)
+                        })
+                        .collect::<Vec<_>>()
+                };
+                onchain.extend(offchain.into_iter().map(|(tp, content)| {
+                    let ttl = self.record_ttl(name, RecordType::from(tp));
+                    (tp, content, ttl)
+                }));
+                Ok(onchain)
+            },
+        )?;
+        let (raw, ttls): (Vec<_>, Vec<_>) = raw
+            .into_iter()
+            .map(|(tp, content, ttl)| ((tp, content), ttl))
+            .unzip();
+        let records = decode_records(raw)?
+            .into_iter()
+            .zip(ttls)
+            .map(|((tp, rdata), ttl)| (tp, rdata, ttl))
+            .collect::<Vec<_>>();
+        info!("inner inner_lookup_typed res: {records:?}");
+        Ok(records)
+    }
+
+    /// Ttl of the record of `rtype` of `name`, `0` if it can't be determined.
+    ///
+    /// The ttl set on the node itself wins over the defaults of the resolver.
+    pub(crate) fn record_ttl(&self, name: &Name, rtype: RecordType) -> u32 {
+        let at = self.client.info().best_hash;
+        let api = self.client.runtime_api();
+        let Some(id) = name_hash(name) else {
+            return 0;
+        };
+        self.runtime_api_call(name, || match api.ttl(at, id)? {
+            Some(ttl) => Ok(ttl),
+            None => api.record_ttl(at, id, rtype.into()),
+        })
+        .unwrap_or(0)
+    }
+
+    /// Every on-chain record of `name` as of block `at`, used for zone transfers.
+    ///
+    /// Old states are often pruned, so failures here don't count against the breaker.
+    pub(crate) fn onchain_records_at(
+        &self,
+        at: Block::Hash,
+        name: &Name,
+    ) -> Result<Vec<(RecordType, RData)>, LookupError> {
+        let api = self.client.runtime_api();
+        let id = name_hash(name).ok_or(LookupError::ResponseCode(ResponseCode::NoError))?;
+        let onchain = api.lookup(at, id).map_err(|e| {
+            info!("lookup {name} at {at:?} failed: {e:?}");
+            LookupError::ResponseCode(ResponseCode::ServFail)
+        })?;
+        decode_records(onchain)
+    }
+
+    fn runtime_api_call<R, E: core::fmt::Debug + Into<Box<dyn std::error::Error + Send + Sync>>>(
+        &self,
+        name: &Name,
+        f: impl FnMut() -> Result<R, E>,
+    ) -> Result<R, LookupError> {
+        match self.breaker.call(f) {
+            Ok(res) => Ok(res),
+            Err(BreakerError::Open) => {
+                error!("lookup {name} refused: runtime api circuit breaker is open.");
+                Err(LookupError::ResponseCode(ResponseCode::ServFail))
             }
-            Err(err) => {
+            Err(BreakerError::Failed(err)) => {
                 error!("lookup {name} failed: {err:?}");
                 Err(LookupError::Io(std::io::Error::new(
                     std::io::ErrorKind::Other,
@@ -318,12 +539,17 @@ where
         let client = state.client;
         let at = client.info().best_hash;
         let api = client.runtime_api();
-        let res = Label::new_with_len(name.as_bytes())
-            .map(|(label, _)| {
-                use sp_core::Get;
-                let basenode = <Config as pns_registrar::registrar::Config>::BaseNode::get();
-                label.encode_with_node(&basenode)
-            })
+        // `name` without a top level domain is looked up in the first zone.
+        let (label, zone) = match name.split_once('.') {
+            Some((label, zone)) => (label, zone),
+            None => (
+                name.as_str(),
+                state.dns_config.zones.first().map_or("", String::as_str),
+            ),
+        };
+        let res = Label::new_with_len(label.as_bytes())
+            .zip(Label::new_basenode(zone.as_bytes()))
+            .map(|((label, _), basenode)| label.encode_with_node(&basenode.node))
             .and_then(|id| match api.get_info(at, id) {
                 Ok(res) => {
                     if res.is_none() {
@@ -340,6 +566,66 @@ where
         Json(res)
     }
 
+    /// Gets a record of `node` at the best block together with a storage proof
+    /// against that block's state root, so light clients can verify it with
+    /// nothing but the block header.
+    pub fn record_proof(
+        &self,
+        node: DomainHash,
+        kind: CodecRecordType,
+    ) -> Option<(Block::Hash, Vec<u8>, StorageProof)> {
+        let at = self.client.info().best_hash;
+        let api = self.client.runtime_api();
+        let key = match api.record_key(at, node, kind) {
+            Ok(key) => key,
+            Err(e) => {
+                tracing::error!("get record key error: {e:?}");
+                return None;
+            }
+        };
+        let value = match api.lookup_one(at, node, kind) {
+            Ok(value) => value?,
+            Err(e) => {
+                tracing::error!("lookup record error: {e:?}");
+                return None;
+            }
+        };
+        match self
+            .client
+            .read_proof(at, &mut core::iter::once(key.as_slice()))
+        {
+            Ok(proof) => Some((at, value, proof)),
+            Err(e) => {
+                tracing::error!("read proof error: {e:?}");
+                None
+            }
+        }
+    }
+
+    async fn record_proof_from_name(
+        State(state): State<Self>,
+        Path((name, kind)): Path<(String, String)>,
+    ) -> impl IntoResponse {
+        let Some(id) = name_hash_str(&name) else {
+            error!("invalid name: {name:?}");
+            return (StatusCode::BAD_REQUEST, Json(None));
+        };
+        let Ok(kind) = RecordType::from_str(&kind) else {
+            error!("invalid record type: {kind:?}");
+            return (StatusCode::BAD_REQUEST, Json(None));
+        };
+
+        let res = state
+            .record_proof(id, kind.into())
+            .map(|(at, value, proof)| RecordProof {
+                at: hex::encode(at.as_ref()),
+                value: hex::encode(value),
+                proof: proof.into_iter_nodes().map(hex::encode).collect(),
+            });
+
+        (StatusCode::OK, Json(res))
+    }
+
     async fn all(State(state): State<Self>) -> impl IntoResponse {
         let client = state.client;
         let at = client.info().best_hash;
@@ -355,6 +641,10 @@ where
         Json(res)
     }
 
+    async fn health(State(state): State<Self>) -> impl IntoResponse {
+        Json(state.breaker.status())
+    }
+
     async fn ddns_state(State(state): State<Self>) -> impl IntoResponse {
         let peers = state.manager.peers;
         let lock = peers.lock().expect("failed to lock peers");
@@ -363,11 +653,141 @@ where
     }
 }
 
+impl<Client, Backend, Block, Config> ServerDeps<Client, Backend, Block, Config>
+where
+    Client: HeaderBackend<Block> + BlockchainEvents<Block> + StorageProvider<Block, Backend>,
+    Client: ProvideRuntimeApi<Block>,
+    Config: pns_registrar::registrar::Config + pns_resolvers::resolvers::Config,
+    Client::Api: PnsStorageApi<
+        Block,
+        Config::Moment,
+        BalanceOf<Config>,
+        Config::Signature,
+        Config::AccountId,
+    >,
+    <Config as frame_system::Config>::RuntimeEvent: TryInto<pns_registrar::registrar::Event<Config>>
+        + TryInto<pns_resolvers::resolvers::Event<Config>>,
+    Block: BlockT,
+    Backend: BackendT<Block> + 'static,
+{
+    /// Follows the best chain and journals the nodes whose records changed in each block,
+    /// so secondaries can catch up through IXFR. Spawn it next to `init_dns_server`.
+    pub async fn run_zone_journal(self) {
+        use pns_registrar::registrar::Event as RegistrarEvent;
+        use pns_resolvers::resolvers::Event as ResolversEvent;
+
+        let zone_of = |zone: &str| {
+            let base_node = Label::new_basenode(zone.as_bytes())?.node;
+            Some((base_node, Name::from_str(zone).ok()?))
+        };
+        let mut zones = self
+            .zones()
+            .iter()
+            .filter_map(|zone| zone_of(zone))
+            .collect::<Vec<_>>();
+        let mut imports = self.client.import_notification_stream();
+        while let Some(notification) = imports.next().await {
+            if !notification.is_new_best {
+                continue;
+            }
+
+            let mut changed = Vec::new();
+            for event in self.events_at(notification.hash) {
+                match TryInto::<ResolversEvent<Config>>::try_into(event.clone()) {
+                    Ok(ResolversEvent::RecordsChanged { node, .. })
+                    | Ok(ResolversEvent::ProfileCleared { node }) => changed.push(node),
+                    _ => {}
+                }
+                match TryInto::<RegistrarEvent<Config>>::try_into(event) {
+                    Ok(RegistrarEvent::NameRegistered {
+                        name, base_node, ..
+                    }) => {
+                        let zone = zones.iter().find(|(node, _)| *node == base_node);
+                        let name = core::str::from_utf8(&name)
+                            .ok()
+                            .zip(zone)
+                            .and_then(|(label, (_, zone))| Name::parse(label, Some(zone)).ok());
+                        if let Some(name) = name {
+                            self.learn_name(&name);
+                        }
+                    }
+                    // the dns server picks the new zone up on its next request.
+                    Ok(RegistrarEvent::TldCreated { label, .. }) => {
+                        let Ok(label) = String::from_utf8(label) else {
+                            continue;
+                        };
+                        if let Some(zone) = zone_of(&label) {
+                            zones.push(zone);
+                            self.add_zone(label);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let serial: u32 = (*notification.header.number()).unique_saturated_into();
+            match self.zone_journal.lock() {
+                Ok(mut journal) => journal.record(serial, changed),
+                Err(e) => error!("zone journal lock poisoned: {e:?}"),
+            }
+        }
+    }
+
+    fn events_at(&self, at: Block::Hash) -> Vec<<Config as frame_system::Config>::RuntimeEvent> {
+        type EventRecordOf<T> = frame_system::EventRecord<
+            <T as frame_system::Config>::RuntimeEvent,
+            <T as frame_system::Config>::Hash,
+        >;
+
+        // `frame_system::Events` is not public, its key is `twox128("System") ++ twox128("Events")`.
+        let key = [twox_128(b"System"), twox_128(b"Events")].concat();
+        match self.client.storage(at, &StorageKey(key)) {
+            Ok(Some(data)) => match Vec::<EventRecordOf<Config>>::decode(&mut &data.0[..]) {
+                Ok(records) => records.into_iter().map(|record| record.event).collect(),
+                Err(e) => {
+                    error!("decode events at {at:?} failed: {e:?}");
+                    Vec::new()
+                }
+            },
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                error!("read events at {at:?} failed: {e:?}");
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// A record with its storage proof, everything hex encoded.
+#[derive(serde::Serialize)]
+pub struct RecordProof {
+    /// Hash of the block whose state root the proof is checked against.
+    pub at: String,
+    pub value: String,
+    pub proof: Vec<String>,
+}
+
 pub fn name_hash_str(name: &str) -> Option<DomainHash> {
     let name = Name::from_str(name).ok()?;
     name_hash(&name)
 }
 
+fn decode_records(
+    raw: Vec<(CodecRecordType, Vec<u8>)>,
+) -> Result<Vec<(RecordType, RData)>, LookupError> {
+    let mut records = Vec::with_capacity(raw.len());
+    for (raw_tp, v) in raw.into_iter() {
+        let rt = RecordType::from(raw_tp);
+        info!("will serde rdata");
+        let rdata = bincode::serde::decode_from_slice::<RData, _>(&v, bincode::config::legacy())
+            .map_err(|_| LookupError::ResponseCode(ResponseCode::FormErr))?
+            .0;
+        info!("serde rdata well");
+        records.push((rt, rdata));
+    }
+    Ok(records)
+}
+
 fn name_hash(name: &Name) -> Option<DomainHash> {
     error!("name_hash {name:?}");
     let mut iter = name.iter();
@@ -450,17 +870,43 @@ async fn gen_task<Block: BlockT>(
     network: Arc<sc_network::NetworkService<Block, <Block as BlockT>::Hash>>,
     request: Vec<u8>,
     peer: libp2p::PeerId,
+    gossip: GossipConfig,
 ) {
-    if let Err(e) = network
-        .request(
+    let res = send_gossip(&gossip, |if_disconnected| {
+        network.request(
             peer,
             sc_network::ProtocolName::from(network::PROTOCOL_NAME),
             request,
-            sc_network::IfDisconnected::ImmediateError,
+            if_disconnected,
         )
-        .await
-    {
-        error!("{e:?}");
+    })
+    .await;
+    if let Err(e) = res {
+        error!("gossip to {peer} failed: {e:?}");
+    }
+}
+
+#[derive(Debug)]
+enum GossipError<E> {
+    /// The request did not finish within `GossipConfig::timeout`.
+    Timeout,
+    Failed(E),
+}
+
+async fn send_gossip<R, E, Fut>(
+    gossip: &GossipConfig,
+    send: impl FnOnce(sc_network::IfDisconnected) -> Fut,
+) -> Result<R, GossipError<E>>
+where
+    Fut: core::future::Future<Output = Result<R, E>>,
+{
+    let request = send(gossip.if_disconnected);
+    match gossip.timeout {
+        Some(timeout) => tokio::time::timeout(timeout, request)
+            .await
+            .map_err(|_| GossipError::Timeout)?
+            .map_err(GossipError::Failed),
+        None => request.await.map_err(GossipError::Failed),
     }
 }
 
@@ -511,3 +957,43 @@ pub async fn init_ddns<TBl>(
         warn!("get connected_peers falied")
     }
 }
+
+#[cfg(test)]
+#[tokio::test]
+async fn try_connect_reaches_briefly_disconnected_peer() {
+    use sc_network::IfDisconnected;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    // a peer which is only reachable after being dialed again.
+    let connected = AtomicBool::new(false);
+    let send = |if_disconnected: IfDisconnected| {
+        let connected = &connected;
+        async move {
+            if !connected.load(Ordering::SeqCst) {
+                if matches!(if_disconnected, IfDisconnected::ImmediateError) {
+                    return Err("peer not connected");
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                connected.store(true, Ordering::SeqCst);
+            }
+            Ok(b"update".to_vec())
+        }
+    };
+
+    let res = send_gossip(&GossipConfig::default(), send).await;
+    assert!(matches!(res, Err(GossipError::Failed("peer not connected"))));
+
+    let impatient = GossipConfig {
+        if_disconnected: IfDisconnected::TryConnect,
+        timeout: Some(Duration::from_millis(1)),
+    };
+    let res = send_gossip(&impatient, send).await;
+    assert!(matches!(res, Err(GossipError::Timeout)));
+
+    let try_connect = GossipConfig {
+        if_disconnected: IfDisconnected::TryConnect,
+        timeout: Some(Duration::from_secs(5)),
+    };
+    let res = send_gossip(&try_connect, send).await;
+    assert_eq!(res.unwrap(), b"update".to_vec());
+}
@@ -0,0 +1,133 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use pns_types::{ddns::codec_type::RecordType, DomainHash};
+
+/// Upper bound of cached misses, so querying random names can't grow the cache forever.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Remembers `(node, type)` lookups which found no record for a short while,
+/// so repeated misses don't hit the runtime api and the offchain store again.
+///
+/// A zero `ttl` disables the cache.
+pub struct NegativeCache {
+    ttl: Duration,
+    misses: Mutex<HashMap<(DomainHash, RecordType), Instant>>,
+}
+
+impl NegativeCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            misses: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns an empty result if `(node, tp)` missed within the ttl,
+    /// otherwise calls `fetch` and remembers the result if it is empty.
+    pub fn get_or_fetch<T, E>(
+        &self,
+        node: DomainHash,
+        tp: RecordType,
+        fetch: impl FnOnce() -> Result<Vec<T>, E>,
+    ) -> Result<Vec<T>, E> {
+        if self.ttl.is_zero() {
+            return fetch();
+        }
+
+        if self.is_miss(node, tp) {
+            return Ok(Vec::new());
+        }
+
+        let res = fetch()?;
+        if res.is_empty() {
+            self.insert(node, tp);
+        }
+        Ok(res)
+    }
+
+    /// Forgets every cached miss of `node`, called when one of its records is set.
+    pub fn invalidate(&self, node: DomainHash) {
+        let mut misses = self.misses.lock().expect("negative cache lock poisoned");
+        misses.retain(|(cached, _), _| *cached != node);
+    }
+
+    fn is_miss(&self, node: DomainHash, tp: RecordType) -> bool {
+        let mut misses = self.misses.lock().expect("negative cache lock poisoned");
+        match misses.get(&(node, tp)) {
+            Some(at) if at.elapsed() < self.ttl => true,
+            Some(_) => {
+                misses.remove(&(node, tp));
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&self, node: DomainHash, tp: RecordType) {
+        let mut misses = self.misses.lock().expect("negative cache lock poisoned");
+        if misses.len() >= MAX_ENTRIES {
+            let ttl = self.ttl;
+            misses.retain(|_, at| at.elapsed() < ttl);
+            if misses.len() >= MAX_ENTRIES {
+                return;
+            }
+        }
+        misses.insert((node, tp), Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_miss_within_ttl_is_cached() {
+        let cache = NegativeCache::new(Duration::from_secs(60));
+        let node = DomainHash::repeat_byte(1);
+        let mut fetched = 0;
+
+        for _ in 0..2 {
+            let res = cache.get_or_fetch(node, RecordType::A, || {
+                fetched += 1;
+                Ok::<Vec<()>, ()>(Vec::new())
+            });
+            assert_eq!(res, Ok(Vec::new()));
+        }
+        assert_eq!(fetched, 1);
+
+        // other types of the same node are not affected.
+        let _ = cache.get_or_fetch(node, RecordType::TXT, || {
+            fetched += 1;
+            Ok::<Vec<()>, ()>(Vec::new())
+        });
+        assert_eq!(fetched, 2);
+
+        // setting a record of the node invalidates its misses.
+        cache.invalidate(node);
+        let res = cache.get_or_fetch(node, RecordType::A, || {
+            fetched += 1;
+            Ok::<_, ()>(vec![()])
+        });
+        assert_eq!(res, Ok(vec![()]));
+        assert_eq!(fetched, 3);
+    }
+
+    #[test]
+    fn zero_ttl_disables_the_cache() {
+        let cache = NegativeCache::new(Duration::ZERO);
+        let node = DomainHash::repeat_byte(1);
+        let mut fetched = 0;
+
+        for _ in 0..2 {
+            let _ = cache.get_or_fetch(node, RecordType::A, || {
+                fetched += 1;
+                Ok::<Vec<()>, ()>(Vec::new())
+            });
+        }
+        assert_eq!(fetched, 2);
+    }
+}
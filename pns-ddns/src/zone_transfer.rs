@@ -0,0 +1,259 @@
+use std::collections::{HashMap, VecDeque};
+
+use pns_types::DomainHash;
+use trust_dns_server::proto::rr::{rdata::SOA, Name, RData, Record, RecordType};
+
+/// Ttl of the synthesized `SOA` record, also used as its negative caching ttl.
+const SOA_TTL: u32 = 300;
+
+/// Remembers which nodes had their records changed in which block,
+/// so secondaries can pull only what changed since their serial (IXFR, RFC 1995).
+///
+/// The chain only stores name hashes, names are learned from registrations
+/// and lookups. A node whose name was never seen can't be transferred.
+pub struct ZoneJournal {
+    capacity: usize,
+    /// The journal knows every change made after this serial.
+    since: Option<u32>,
+    changes: VecDeque<(u32, Vec<DomainHash>)>,
+    max_names: usize,
+    names: HashMap<DomainHash, Name>,
+    /// Learned nodes, oldest first, so the oldest name is forgotten first.
+    learned: VecDeque<DomainHash>,
+}
+
+impl ZoneJournal {
+    /// Keeps the changes of at most `capacity` blocks and at most `max_names` names.
+    pub fn new(capacity: usize, max_names: usize) -> Self {
+        Self {
+            capacity,
+            since: None,
+            changes: VecDeque::new(),
+            max_names,
+            names: HashMap::new(),
+            learned: VecDeque::new(),
+        }
+    }
+
+    pub fn learn_name(&mut self, node: DomainHash, name: Name) {
+        if self.max_names == 0 || self.names.contains_key(&node) {
+            return;
+        }
+        while self.names.len() >= self.max_names {
+            let Some(oldest) = self.learned.pop_front() else {
+                break;
+            };
+            self.names.remove(&oldest);
+        }
+        self.names.insert(node, name);
+        self.learned.push_back(node);
+    }
+
+    pub fn name_of(&self, node: &DomainHash) -> Option<&Name> {
+        self.names.get(node)
+    }
+
+    /// Records the nodes changed by the block `serial`.
+    ///
+    /// A serial not above the last one is a reorg, changes of retracted blocks are dropped.
+    pub fn record(&mut self, serial: u32, nodes: Vec<DomainHash>) {
+        let since = *self.since.get_or_insert(serial.saturating_sub(1));
+        if serial <= since {
+            // reorged below what we know, start over.
+            self.changes.clear();
+            self.since = Some(serial.saturating_sub(1));
+        }
+        while matches!(self.changes.back(), Some((last, _)) if *last >= serial) {
+            self.changes.pop_back();
+        }
+
+        if nodes.is_empty() {
+            return;
+        }
+        self.changes.push_back((serial, nodes));
+        while self.changes.len() > self.capacity {
+            if let Some((evicted, _)) = self.changes.pop_front() {
+                self.since = Some(evicted);
+            }
+        }
+    }
+
+    /// Nodes changed after `serial`, `None` if the journal doesn't reach back that far.
+    pub fn changed_since(&self, serial: u32) -> Option<Vec<DomainHash>> {
+        if serial < self.since? {
+            return None;
+        }
+
+        let mut nodes = Vec::new();
+        for (_, changed) in self.changes.iter().filter(|(at, _)| *at > serial) {
+            for node in changed {
+                if !nodes.contains(node) {
+                    nodes.push(*node);
+                }
+            }
+        }
+        Some(nodes)
+    }
+}
+
+/// The `SOA` record of `origin`, the block number is used as the zone serial.
+pub fn soa_record(origin: &Name, serial: u32) -> Record {
+    let rname = Name::parse("hostmaster", Some(origin)).unwrap_or_else(|_| origin.clone());
+    let soa = SOA::new(origin.clone(), rname, serial, 3600, 600, 604_800, SOA_TTL);
+    Record::from_rdata(origin.clone(), SOA_TTL, RData::SOA(soa))
+}
+
+/// Splits the records of a name at two serials into the removed and the added ones.
+pub fn diff_records(
+    old: Vec<(RecordType, RData)>,
+    new: Vec<(RecordType, RData)>,
+) -> (Vec<(RecordType, RData)>, Vec<(RecordType, RData)>) {
+    let deleted = old.iter().filter(|r| !new.contains(r)).cloned().collect();
+    let added = new.into_iter().filter(|r| !old.contains(r)).collect();
+    (deleted, added)
+}
+
+/// Builds an incremental transfer answer (RFC 1995 section 4) from `from` to `to`.
+///
+/// All deltas are condensed into a single difference sequence,
+/// a client which is up to date only gets the current `SOA`.
+pub fn incremental_transfer(
+    origin: &Name,
+    from: u32,
+    to: u32,
+    deleted: Vec<Record>,
+    added: Vec<Record>,
+) -> Vec<Record> {
+    let current = soa_record(origin, to);
+    if from >= to || (deleted.is_empty() && added.is_empty()) {
+        return vec![current];
+    }
+
+    let mut records = Vec::with_capacity(deleted.len() + added.len() + 4);
+    records.push(current.clone());
+    records.push(soa_record(origin, from));
+    records.extend(deleted);
+    records.push(current.clone());
+    records.extend(added);
+    records.push(current);
+    records
+}
+
+/// Builds a full transfer answer, which is also a valid answer to an IXFR query.
+pub fn full_transfer(origin: &Name, serial: u32, records: Vec<Record>) -> Vec<Record> {
+    let soa = soa_record(origin, serial);
+    let mut all = Vec::with_capacity(records.len() + 2);
+    all.push(soa.clone());
+    all.extend(records);
+    all.push(soa);
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::str::FromStr;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn ixfr_returns_only_the_delta() {
+        let origin = Name::from_str("dot.").unwrap();
+        let changed = Name::from_str("cupnfish.dot.").unwrap();
+        let untouched = Name::from_str("other.dot.").unwrap();
+        let (changed_node, untouched_node) =
+            (DomainHash::repeat_byte(1), DomainHash::repeat_byte(2));
+
+        let mut journal = ZoneJournal::new(16, 16);
+        journal.learn_name(changed_node, changed.clone());
+        journal.learn_name(untouched_node, untouched);
+        journal.record(3, vec![untouched_node]);
+        journal.record(4, vec![]);
+        journal.record(5, vec![changed_node]);
+
+        // the secondary is at serial 4, only the change of block 5 is new to it.
+        let nodes = journal.changed_since(4).unwrap();
+        assert_eq!(nodes, vec![changed_node]);
+        assert_eq!(journal.changed_since(2).unwrap().len(), 2);
+        // the journal starts at block 3, anything older needs a full transfer.
+        assert_eq!(journal.changed_since(1), None);
+
+        let old_a = RData::A(Ipv4Addr::new(127, 0, 0, 1));
+        let new_a = RData::A(Ipv4Addr::new(127, 0, 0, 2));
+        let txt = RData::TXT(trust_dns_server::proto::rr::rdata::TXT::new(vec![
+            "hello pns".to_string(),
+        ]));
+        let (deleted, added) = diff_records(
+            vec![
+                (RecordType::A, old_a.clone()),
+                (RecordType::TXT, txt.clone()),
+            ],
+            vec![(RecordType::A, new_a.clone()), (RecordType::TXT, txt)],
+        );
+        assert_eq!(deleted, vec![(RecordType::A, old_a.clone())]);
+        assert_eq!(added, vec![(RecordType::A, new_a.clone())]);
+
+        let name = journal.name_of(&nodes[0]).unwrap().clone();
+        let to_record =
+            |(_, rdata): (RecordType, RData)| Record::from_rdata(name.clone(), 0, rdata);
+        let answer = incremental_transfer(
+            &origin,
+            4,
+            5,
+            deleted.into_iter().map(to_record).collect(),
+            added.into_iter().map(to_record).collect(),
+        );
+
+        let rdatas = answer
+            .iter()
+            .filter_map(Record::data)
+            .cloned()
+            .collect::<Vec<_>>();
+        let serial = |rdata: &RData| rdata.as_soa().map(SOA::serial);
+        assert_eq!(rdatas.len(), 6);
+        assert_eq!(serial(&rdatas[0]), Some(5));
+        assert_eq!(serial(&rdatas[1]), Some(4));
+        assert_eq!(rdatas[2], old_a);
+        assert_eq!(serial(&rdatas[3]), Some(5));
+        assert_eq!(rdatas[4], new_a);
+        assert_eq!(serial(&rdatas[5]), Some(5));
+
+        // an up to date secondary only gets the current soa.
+        assert_eq!(
+            incremental_transfer(&origin, 5, 5, Vec::new(), Vec::new()).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn reorged_blocks_are_dropped_from_the_journal() {
+        let node = DomainHash::repeat_byte(1);
+        let mut journal = ZoneJournal::new(16, 16);
+        journal.record(10, vec![]);
+        journal.record(11, vec![node]);
+
+        // block 11 is retracted by another block 11 without changes.
+        journal.record(11, vec![]);
+        assert_eq!(journal.changed_since(10), Some(Vec::new()));
+    }
+
+    #[test]
+    fn oldest_names_are_forgotten_first() {
+        let name = |label: &str| Name::from_str(&format!("{label}.dot.")).unwrap();
+        let mut journal = ZoneJournal::new(16, 2);
+        journal.learn_name(DomainHash::repeat_byte(1), name("first"));
+        journal.learn_name(DomainHash::repeat_byte(2), name("second"));
+        // learning a known name again doesn't make it younger.
+        journal.learn_name(DomainHash::repeat_byte(1), name("first"));
+        journal.learn_name(DomainHash::repeat_byte(3), name("third"));
+
+        assert_eq!(journal.name_of(&DomainHash::repeat_byte(1)), None);
+        assert_eq!(
+            journal.name_of(&DomainHash::repeat_byte(2)),
+            Some(&name("second"))
+        );
+        assert_eq!(
+            journal.name_of(&DomainHash::repeat_byte(3)),
+            Some(&name("third"))
+        );
+    }
+}
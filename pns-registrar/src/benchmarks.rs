@@ -46,10 +46,10 @@ mod registry {
     use crate::mock::Test;
     use crate::{
         registry::{Call, Config, Pallet},
-        traits::{Label, Registrar},
+        traits::{Arbitration, Label, NftBackend, Registrar},
     };
-    use frame_benchmarking::Zero;
     use frame_benchmarking::{account, benchmarks};
+    use frame_support::traits::{Currency, EnsureOrigin, Get};
     use frame_system::RawOrigin;
     use pns_types::DomainHash;
     use sp_runtime::DispatchError;
@@ -64,21 +64,15 @@ mod registry {
         let label = Label::new(alloc::format!("{name}{index}").as_bytes())
             .unwrap()
             .0;
-        let class_id = T::ClassId::zero();
         let node = label.encode_with_node(&T::Registrar::basenode());
 
-        crate::nft::Pallet::<T>::mint(
-            &owner,
-            (class_id, node),
-            Default::default(),
-            Default::default(),
-        )?;
+        T::Nft::mint(&owner, node, Default::default())?;
         use crate::registry::RuntimeOrigin;
         use pns_types::DomainTracing;
         if RuntimeOrigin::<T>::get(T::Registrar::basenode()).is_some() {
             panic!("Unexpected arm");
         } else {
-            Pallet::<T>::add_children(T::Registrar::basenode(), class_id)?;
+            Pallet::<T>::add_children(T::Registrar::basenode())?;
 
             RuntimeOrigin::<T>::insert(node, DomainTracing::Root);
         }
@@ -90,6 +84,7 @@ mod registry {
         where_clause {
             where
             T: crate::origin::Config,
+            <T::Registrar as Registrar>::Moment: From<u32>,
         }
         approval_for_all_true {
             let caller = account::<T::AccountId>("caller",0,SEED);
@@ -118,7 +113,7 @@ mod registry {
             let (owner,node) = get_account_and_node::<T>("caller",3)?;
         }: _(RawOrigin::Signed(owner), node)
         verify {
-            assert!(!crate::nft::Tokens::<T>::contains_key(T::ClassId::zero(),node));
+            assert!(T::Nft::owner(node).is_none());
         }
         set_official {
             let official = account::<T::AccountId>("official",567,SEED);
@@ -141,6 +136,57 @@ mod registry {
         verify {
             assert!(!crate::registry::TokenApprovals::<T>::contains_key(node,to));
         }
+        transfer_all {
+            // l is the number of domains transferred.
+            let l in 1..T::MaxTransferAll::get();
+            let owner = account::<T::AccountId>("owner",0,SEED);
+            for i in 0..l {
+                let name = alloc::format!("owner{i}").into_bytes();
+                let label = Label::new(&name).unwrap().0;
+                T::Registrar::for_redeem_code(name, owner.clone(), (28 * 24 * 60 * 60).into(), label)?;
+            }
+            let to = account::<T::AccountId>("to",996,SEED);
+        }: _(RawOrigin::Signed(owner), account_to_source::<T>(to.clone()), l, None)
+        verify {
+            assert_eq!(crate::registry::OwnedTokenCount::<T>::get(to), l);
+        }
+        emergency_freeze {
+            let (_owner,node) = get_account_and_node::<T>("owner",0)?;
+            let origin = T::GovernanceOrigin::successful_origin();
+        }: _<T::RuntimeOrigin>(origin, node, false)
+        verify {
+            assert!(crate::registry::EmergencyFreezes::<T>::contains_key(node));
+        }
+        appeal_freeze {
+            let (owner,node) = get_account_and_node::<T>("owner",0)?;
+            T::Currency::make_free_balance_be(&owner, T::AppealBond::get() + T::Currency::minimum_balance());
+            Pallet::<T>::emergency_freeze(T::GovernanceOrigin::successful_origin(), node, false)?;
+        }: _(RawOrigin::Signed(owner), node)
+        verify {
+            assert!(crate::registry::EmergencyFreezes::<T>::get(node).unwrap().appeal.is_some());
+        }
+        confirm_freeze {
+            let (owner,node) = get_account_and_node::<T>("owner",0)?;
+            T::Currency::make_free_balance_be(&owner, T::AppealBond::get() + T::Currency::minimum_balance());
+            Pallet::<T>::emergency_freeze(T::GovernanceOrigin::successful_origin(), node, false)?;
+            Pallet::<T>::appeal_freeze(RawOrigin::Signed(owner).into(), node)?;
+            // the arbitration upheld the freeze.
+            T::Arbitration::drop_appeal(node);
+            let origin = T::GovernanceOrigin::successful_origin();
+        }: _<T::RuntimeOrigin>(origin, node, true)
+        verify {
+            assert!(!crate::registry::EmergencyFreezes::<T>::contains_key(node));
+        }
+        release_freeze {
+            let (owner,node) = get_account_and_node::<T>("owner",0)?;
+            T::Currency::make_free_balance_be(&owner, T::AppealBond::get() + T::Currency::minimum_balance());
+            Pallet::<T>::emergency_freeze(T::GovernanceOrigin::successful_origin(), node, false)?;
+            Pallet::<T>::appeal_freeze(RawOrigin::Signed(owner.clone()).into(), node)?;
+            frame_system::Pallet::<T>::set_block_number(crate::registry::EmergencyFreezes::<T>::get(node).unwrap().expires_at);
+        }: _(RawOrigin::Signed(owner), node)
+        verify {
+            assert!(!crate::registry::FrozenNodes::<T>::contains_key(node));
+        }
         impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), Test);
     }
 }
@@ -153,14 +199,18 @@ mod registrar {
     #[cfg(test)]
     use crate::mock::Test;
     use crate::{
-        registrar::{Call, Config, Pallet},
-        traits::{Label, Registrar, MIN_REGISTRABLE_LEN},
+        registrar::{BaseNodes, Call, Config, DefaultBaseNode, Pallet},
+        traits::{Label, Registrar, LABEL_MAX_LEN, MIN_REGISTRABLE_LEN},
     };
     use frame_benchmarking::{account, benchmarks};
-    use frame_support::traits::{Currency, Get};
+    use frame_support::traits::{Currency, EnsureOrigin, Get, ReservableCurrency};
     use frame_system::RawOrigin;
     use pns_types::DomainHash;
-    use sp_runtime::SaturatedConversion;
+    use sp_runtime::{
+        traits::{One, Zero},
+        Perbill, SaturatedConversion,
+    };
+    use sp_std::vec::Vec;
 
     pub fn create_caller<T>(idx: u32) -> T::AccountId
     where
@@ -188,6 +238,23 @@ mod registrar {
         label.encode_with_node(&node)
     }
 
+    /// `n` different names, all of them as long as a label can be.
+    fn get_batch_names(n: u32) -> Vec<Vec<u8>> {
+        (0..n)
+            .map(|i| {
+                let mut name = get_name(LABEL_MAX_LEN - 8);
+                name.extend_from_slice(alloc::format!("{i:08}").as_bytes());
+                name
+            })
+            .collect()
+    }
+
+    pub(super) fn min_duration<T: Config>() -> T::Moment {
+        BaseNodes::<T>::get(DefaultBaseNode::<T>::get())
+            .unwrap()
+            .min_duration
+    }
+
     benchmarks! {
         where_clause {
             where
@@ -209,15 +276,64 @@ mod registrar {
         verify {
             assert!(!crate::registrar::ReservedList::<T>::contains_key(node));
         }
+        reserve_for {
+            let node = get_rand_node(567);
+            let manager = get_manager::<T>();
+            let holder = create_caller::<T>(8);
+            let source = account_to_source::<T>(holder.clone());
+        }:_(RawOrigin::Signed(manager), node, source)
+        verify {
+            assert_eq!(crate::registrar::ReservedFor::<T>::get(node), Some(holder));
+        }
+        clear_reserved_for {
+            let node = get_rand_node(567);
+            let manager = get_manager::<T>();
+            let source = account_to_source::<T>(create_caller::<T>(8));
+
+            Pallet::<T>::reserve_for(RawOrigin::Signed(get_manager::<T>()).into(), node, source)?;
+        }:_(RawOrigin::Signed(manager), node)
+        verify {
+            assert!(!crate::registrar::ReservedFor::<T>::contains_key(node));
+        }
         register {
             // l is length of name.
             let l in U32_MIN_REGISTRABLE_LEN..U32_LABEL_MAX_LEN;
             let name = get_name(l as usize);
             let rich_account = create_caller::<T>(8);
             let source = account_to_source::<T>(rich_account.clone());
-        }:_(RawOrigin::Signed(rich_account), name.clone(),source,T::MinRegistrationDuration::get())
+        }:_(RawOrigin::Signed(rich_account), DefaultBaseNode::<T>::get(), name.clone(),source,min_duration::<T>(),None)
         verify {
-            assert!(Pallet::<T>::check_expires_renewable(name_to_node(name,T::BaseNode::get())).is_ok());
+            assert!(Pallet::<T>::check_expires_renewable(name_to_node(name,DefaultBaseNode::<T>::get())).is_ok());
+        }
+
+        register_batch {
+            // n is the number of names.
+            let n in 1..T::MaxBatchSize::get();
+            let names = get_batch_names(n);
+            let rich_account = create_caller::<T>(8);
+            let source = account_to_source::<T>(rich_account.clone());
+        }:_(RawOrigin::Signed(rich_account), DefaultBaseNode::<T>::get(), names.clone().try_into().unwrap(), source, min_duration::<T>())
+        verify {
+            for name in names {
+                assert!(Pallet::<T>::check_expires_renewable(name_to_node(name,DefaultBaseNode::<T>::get())).is_ok());
+            }
+        }
+
+        commit {
+            let caller = create_caller::<T>(8);
+            let commitment = Pallet::<T>::make_commitment(DefaultBaseNode::<T>::get(), &get_name(MIN_REGISTRABLE_LEN), &caller, &[7; 32]);
+        }:_(RawOrigin::Signed(caller), commitment)
+        verify {
+            assert!(crate::registrar::Commitments::<T>::contains_key(commitment));
+        }
+
+        remove_commitment {
+            let caller = create_caller::<T>(8);
+            let commitment = Pallet::<T>::make_commitment(DefaultBaseNode::<T>::get(), &get_name(MIN_REGISTRABLE_LEN), &caller, &[7; 32]);
+            Pallet::<T>::commit(RawOrigin::Signed(caller.clone()).into(), commitment)?;
+        }:_(RawOrigin::Signed(caller), commitment)
+        verify {
+            assert!(!crate::registrar::Commitments::<T>::contains_key(commitment));
         }
 
         renew {
@@ -227,32 +343,114 @@ mod registrar {
             let rich_account = create_caller::<T>(8);
             let clone_rich = rich_account.clone();
             T::Currency::deposit_creating(&clone_rich,u32::MAX.into());
-            Pallet::<T>::register(RawOrigin::Signed(clone_rich).into(), name.clone(),account_to_source::<T>(rich_account.clone()),T::MinRegistrationDuration::get())?;
-        }:_(RawOrigin::Signed(rich_account),name,T::MinRegistrationDuration::get())
+            Pallet::<T>::register(RawOrigin::Signed(clone_rich).into(), DefaultBaseNode::<T>::get(), name.clone(),account_to_source::<T>(rich_account.clone()),min_duration::<T>(),None)?;
+        }:_(RawOrigin::Signed(rich_account),DefaultBaseNode::<T>::get(),name,min_duration::<T>(),None)
 
+        renew_batch {
+            // n is the number of names.
+            let n in 1..T::MaxBatchSize::get();
+            let names = get_batch_names(n);
+            let rich_account = create_caller::<T>(8);
+            Pallet::<T>::register_batch(RawOrigin::Signed(rich_account.clone()).into(), DefaultBaseNode::<T>::get(), names.clone().try_into().unwrap(), account_to_source::<T>(rich_account.clone()), min_duration::<T>())?;
+        }:_(RawOrigin::Signed(rich_account), DefaultBaseNode::<T>::get(), names.try_into().unwrap(), min_duration::<T>())
 
         transfer {
             let name = get_name(MIN_REGISTRABLE_LEN);
-            let hash = name_to_node(name.clone(),T::BaseNode::get());
+            let hash = name_to_node(name.clone(),DefaultBaseNode::<T>::get());
             let rich_account = create_caller::<T>(8);
             let clone_rich = rich_account.clone();
             let to_account = create_caller::<T>(2);
-            Pallet::<T>::register(RawOrigin::Signed(clone_rich).into(), name,account_to_source::<T>(rich_account.clone()),T::MinRegistrationDuration::get())?;
+            Pallet::<T>::register(RawOrigin::Signed(clone_rich).into(), DefaultBaseNode::<T>::get(), name,account_to_source::<T>(rich_account.clone()),min_duration::<T>(),None)?;
         }:_(RawOrigin::Signed(rich_account),account_to_source::<T>(to_account),hash)
 
+        transfer_batch {
+            // n is the number of names.
+            let n in 1..T::MaxBatchSize::get();
+            let names = get_batch_names(n);
+            let rich_account = create_caller::<T>(8);
+            let to_account = create_caller::<T>(2);
+            Pallet::<T>::register_batch(RawOrigin::Signed(rich_account.clone()).into(), DefaultBaseNode::<T>::get(), names.clone().try_into().unwrap(), account_to_source::<T>(rich_account.clone()), min_duration::<T>())?;
+            let nodes = names.into_iter().map(|name| name_to_node(name,DefaultBaseNode::<T>::get())).collect::<Vec<_>>();
+        }:_(RawOrigin::Signed(rich_account), account_to_source::<T>(to_account.clone()), nodes.try_into().unwrap())
+        verify {
+            assert_eq!(crate::registry::OwnedTokenCount::<T>::get(to_account), n);
+        }
+
 
         mint_subname {
             let l in  U32_LABEL_MIN_LEN..U32_LABEL_MAX_LEN;
             let name = get_name(MIN_REGISTRABLE_LEN);
-            let hash = name_to_node(name.clone(),T::BaseNode::get());
+            let hash = name_to_node(name.clone(),DefaultBaseNode::<T>::get());
             let rich_account = create_caller::<T>(8);
             let clone_rich = rich_account.clone();
-            Pallet::<T>::register(RawOrigin::Signed(clone_rich).into(), name,account_to_source::<T>(rich_account.clone()),T::MinRegistrationDuration::get())?;
+            Pallet::<T>::register(RawOrigin::Signed(clone_rich).into(), DefaultBaseNode::<T>::get(), name,account_to_source::<T>(rich_account.clone()),min_duration::<T>(),None)?;
             let subname = get_name(l as usize);
             let subhash = get_subhash(&subname,hash);
             let clone_rich = rich_account.clone();
         }:_(RawOrigin::Signed(clone_rich),hash,subname,account_to_source::<T>(rich_account))
 
+        bid {
+            // l is length of name, s the samples the outbid leader led.
+            let l in 1..(U32_MIN_REGISTRABLE_LEN - 1);
+            let s in 1..Pallet::<T>::candle().samples();
+            let name = get_name(l as usize);
+            let node = Label::from_normalized(&name).encode_with_node(&DefaultBaseNode::<T>::get());
+            let leader = create_caller::<T>(2);
+            Pallet::<T>::bid(RawOrigin::Signed(leader.clone()).into(), DefaultBaseNode::<T>::get(), name.clone(), T::MinAuctionBid::get())?;
+            for sample in 1..s {
+                crate::registrar::AuctionSamples::<T>::insert(node, sample, (leader.clone(), T::MinAuctionBid::get()));
+            }
+            let caller = create_caller::<T>(8);
+            let amount = T::MinAuctionBid::get() + T::MinAuctionBid::get();
+        }:_(RawOrigin::Signed(caller.clone()), DefaultBaseNode::<T>::get(), name, amount)
+        verify {
+            assert_eq!(crate::registrar::AuctionReserves::<T>::get(node, caller), amount);
+        }
+
+        settle_auction {
+            // s is the samples with a bid.
+            let s in 1..Pallet::<T>::candle().samples();
+            let name = get_name(1);
+            let node = Label::from_normalized(&name).encode_with_node(&DefaultBaseNode::<T>::get());
+            let winner = create_caller::<T>(8);
+            Pallet::<T>::bid(RawOrigin::Signed(winner.clone()).into(), DefaultBaseNode::<T>::get(), name, T::MinAuctionBid::get())?;
+            for sample in 1..s {
+                crate::registrar::AuctionSamples::<T>::insert(node, sample, (winner.clone(), T::MinAuctionBid::get()));
+            }
+            // the auction is over, it was closed before the randomness was known.
+            crate::registrar::Auctions::<T>::mutate(node, |auction| {
+                auction.as_mut().unwrap().end = Zero::zero();
+            });
+            crate::registrar::AuctionClosings::<T>::insert(node, T::BlockNumber::zero());
+            frame_system::Pallet::<T>::set_block_number(One::one());
+        }:_(RawOrigin::Signed(create_caller::<T>(2)), node)
+        verify {
+            assert!(!crate::registrar::Auctions::<T>::contains_key(node));
+        }
+
+        slash_deposit {
+            let name = get_name(MIN_REGISTRABLE_LEN);
+            let node = name_to_node(name.clone(),DefaultBaseNode::<T>::get());
+            let rich_account = create_caller::<T>(8);
+            Pallet::<T>::register(RawOrigin::Signed(rich_account.clone()).into(), DefaultBaseNode::<T>::get(), name,account_to_source::<T>(rich_account),min_duration::<T>(),None)?;
+            let origin = T::JudicialOrigin::successful_origin();
+        }:_<T::RuntimeOrigin>(origin, node, Perbill::from_percent(50), true)
+        verify {
+            assert!(!crate::registrar::RegistrarInfos::<T>::contains_key(node));
+        }
+
+        claim_bid {
+            let node = get_rand_node(567);
+            let bidder = create_caller::<T>(8);
+            let amount = T::MinAuctionBid::get();
+            T::Currency::reserve(&bidder, amount)?;
+            crate::registrar::AuctionReserves::<T>::insert(node, &bidder, amount);
+            crate::registrar::AuctionBidders::<T>::insert(node, 1);
+        }:_(RawOrigin::Signed(create_caller::<T>(2)), node, bidder.clone())
+        verify {
+            assert!(!crate::registrar::AuctionReserves::<T>::contains_key(node, bidder));
+        }
+
         impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), Test);
     }
 }
@@ -266,6 +464,7 @@ mod redeem_code {
     };
     use codec::Decode;
     use frame_benchmarking::benchmarks;
+    use frame_support::traits::Get;
     use frame_system::RawOrigin;
 
     benchmarks! {
@@ -276,6 +475,8 @@ mod redeem_code {
             T::AccountId: Decode,
         }
 
+        // TODO: the signatures below were made over the payloads without `owner`,
+        // `deadline` and `length` and must be signed again by the official key.
         name_redeem_min {
             let name = sp_std::vec![104, 120, 120];
             let duration = T::Moment::from(31536000_u32);
@@ -284,11 +485,12 @@ mod redeem_code {
             let official = T::AccountId::decode(&mut &sp_std::vec![13, 213, 60, 222, 83, 155, 9, 162, 203, 198, 116, 100, 154, 230, 209, 84, 224, 76, 72, 25, 6, 39, 161, 214, 157, 32, 78, 221, 137, 199, 207, 162][..]).unwrap();
 
             crate::registry::Pallet::<T>::set_official(RawOrigin::Signed(get_manager::<T>()).into(),official)?;
-            Pallet::<T>::mint_redeem(RawOrigin::Signed(get_manager::<T>()).into(),0,10)?;
+            Pallet::<T>::mint_redeem(RawOrigin::Signed(get_manager::<T>()).into(),0,10,None,1,None)?;
             let hash = name_to_node(name.clone(),<T as Config>::Registrar::basenode());
             let poor_account7 = poor_account::<T>(7);
             let poor_account77 = poor_account::<T>(77);
-        }:name_redeem(RawOrigin::Signed(poor_account7),name,duration,nouce,signature,poor_account77)
+            let deadline = T::BlockNumber::from(100_u32);
+        }:name_redeem(RawOrigin::Signed(poor_account7),name,duration,nouce,signature,poor_account77,deadline)
 
         name_redeem_any_min {
             let name = sp_std::vec![99, 117, 112, 110, 102, 105, 115, 104, 120, 120];
@@ -298,11 +500,25 @@ mod redeem_code {
             let official = T::AccountId::decode(&mut &sp_std::vec![13, 213, 60, 222, 83, 155, 9, 162, 203, 198, 116, 100, 154, 230, 209, 84, 224, 76, 72, 25, 6, 39, 161, 214, 157, 32, 78, 221, 137, 199, 207, 162][..]).unwrap();
 
             crate::registry::Pallet::<T>::set_official(RawOrigin::Signed(get_manager::<T>()).into(),official)?;
-            Pallet::<T>::mint_redeem(RawOrigin::Signed(get_manager::<T>()).into(),0,10)?;
+            Pallet::<T>::mint_redeem(RawOrigin::Signed(get_manager::<T>()).into(),0,10,None,1,None)?;
             let hash = name_to_node(name.clone(),<T as Config>::Registrar::basenode());
             let poor_account7 = poor_account::<T>(7);
             let poor_account77 = poor_account::<T>(77);
-        }:name_redeem_any(RawOrigin::Signed(poor_account7),name,duration,nouce,signature,poor_account77)
+            let deadline = T::BlockNumber::from(100_u32);
+        }:name_redeem_any(RawOrigin::Signed(poor_account7),name,duration,nouce,signature,poor_account77,deadline,crate::redeem_code::LengthClass::AtLeast(10))
+
+        commit_redeem_batch {
+        }:_(RawOrigin::Signed(get_manager::<T>()),Default::default(),1000,100_u32.into())
+
+        name_redeem_batch_min {
+            let name = sp_std::vec![99, 117, 112, 110, 102, 105, 115, 104, 120, 120];
+            let duration = T::Moment::from(31536000_u32);
+            let poor_account7 = poor_account::<T>(7);
+            let poor_account77 = poor_account::<T>(77);
+            // a batch of one code, its leaf is the root
+            let root = Pallet::<T>::leaf_of(0, duration, &poor_account77);
+            Pallet::<T>::commit_redeem_batch(RawOrigin::Signed(get_manager::<T>()).into(),root,1,100_u32.into())?;
+        }:name_redeem_batch(RawOrigin::Signed(poor_account7),name,duration,0,0,Default::default(),poor_account77)
 
         create_label {
             let l in U32_LABEL_MIN_LEN..U32_LABEL_MAX_LEN;
@@ -332,8 +548,34 @@ mod redeem_code {
 
         mint_redeem {
             let l in 1..10_000;
+            Pallet::<T>::create_campaign(RawOrigin::Signed(get_manager::<T>()).into(),Default::default(),None,None)?;
+        }:_(RawOrigin::Signed(get_manager::<T>()),0,l,None,2,Some(0))
+
+        sweep_redeems {
+            let l in 1..10_000;
+            Pallet::<T>::create_campaign(RawOrigin::Signed(get_manager::<T>()).into(),Default::default(),None,None)?;
+            Pallet::<T>::mint_redeem(RawOrigin::Signed(get_manager::<T>()).into(),0,l,Some(0_u32.into()),2,Some(0))?;
+            frame_system::Pallet::<T>::set_block_number(1_u32.into());
+        }:_(RawOrigin::Signed(poor_account::<T>(7)),0,l)
+
+        revoke_redeem {
+            let l in 1..10_000;
+            Pallet::<T>::create_campaign(RawOrigin::Signed(get_manager::<T>()).into(),Default::default(),None,None)?;
+            Pallet::<T>::mint_redeem(RawOrigin::Signed(get_manager::<T>()).into(),0,l,None,2,Some(0))?;
         }:_(RawOrigin::Signed(get_manager::<T>()),0,l)
 
+        create_campaign {
+            let name = sp_std::vec![b'p'; T::MaxCampaignNameLength::get() as usize].try_into().unwrap();
+        }:_(RawOrigin::Signed(get_manager::<T>()),name,Some(100),Some(T::Moment::from(31536000_u32)))
+
+        pause_campaign {
+            Pallet::<T>::create_campaign(RawOrigin::Signed(get_manager::<T>()).into(),Default::default(),None,None)?;
+        }:_(RawOrigin::Signed(get_manager::<T>()),0,true)
+
+        close_campaign {
+            Pallet::<T>::create_campaign(RawOrigin::Signed(get_manager::<T>()).into(),Default::default(),None,None)?;
+        }:_(RawOrigin::Signed(get_manager::<T>()),0)
+
     }
 }
 
@@ -341,10 +583,18 @@ mod price_oracle {
     use super::get_manager;
     #[cfg(test)]
     use crate::mock::Test;
-    use crate::price_oracle::{Call, Config, Pallet};
+    use crate::price_oracle::{Call, Config, Pallet, PricesOf};
+    use codec::Decode;
     use frame_benchmarking::benchmarks;
+    use frame_support::traits::Get;
     use frame_system::RawOrigin;
 
+    fn prices<T: Config>() -> PricesOf<T> {
+        sp_std::vec![996_u32.into(); T::MaxPriceTiers::get() as usize]
+            .try_into()
+            .unwrap()
+    }
+
     benchmarks! {
         where_clause {
             where
@@ -352,17 +602,66 @@ mod price_oracle {
         }
 
         set_exchange_rate {
+            crate::price_oracle::ExchangeRate::<T>::set(1000_u32.into());
         }:_(RawOrigin::Signed(get_manager::<T>()),1000_u32.into())
 
+        force_set_exchange_rate {
+        }:_(RawOrigin::Root,1000_u32.into())
+
 
         set_base_price {
-        }:_(RawOrigin::Signed(get_manager::<T>()),[996_u32.into();11])
+            let prices = prices::<T>();
+        }:_(RawOrigin::Signed(get_manager::<T>()),prices)
 
         set_rent_price {
-        }:_(RawOrigin::Signed(get_manager::<T>()),[996_u32.into();11])
+            let prices = prices::<T>();
+        }:_(RawOrigin::Signed(get_manager::<T>()),prices)
 
         set_deposit_price{
-        }:_(RawOrigin::Signed(get_manager::<T>()),[996_u32.into();11])
+            let prices = prices::<T>();
+        }:_(RawOrigin::Signed(get_manager::<T>()),prices)
+
+        set_capacity_price{
+        }:_(RawOrigin::Signed(get_manager::<T>()),996_u32.into())
+
+        schedule_price_update{
+            let prices = crate::price_oracle::PriceUpdate {
+                base_prices: Some(prices::<T>()),
+                rent_prices: Some(prices::<T>()),
+                deposit_prices: Some(prices::<T>()),
+            };
+            let effective_at = T::MinPriceNotice::get() + 1_u32.into();
+        }:_(RawOrigin::Signed(get_manager::<T>()),prices,effective_at)
+
+        set_oracle_endpoints{
+            let url = b"https://oracle.pns.link/rate".to_vec().try_into().unwrap();
+            let endpoints = sp_std::vec![url; T::MaxEndpoints::get() as usize].try_into().unwrap();
+        }:_(RawOrigin::Signed(get_manager::<T>()),endpoints)
+
+        set_duration_discounts{
+            let discounts = (1..=T::MaxDurationDiscounts::get())
+                .map(|years| crate::price_oracle::DurationDiscount {
+                    min_duration: (years * 365 * 24 * 60 * 60).into(),
+                    discount_bps: (years * 100) as u16,
+                })
+                .collect::<sp_std::vec::Vec<_>>()
+                .try_into()
+                .unwrap();
+        }:_(RawOrigin::Signed(get_manager::<T>()),discounts)
+
+        set_target_registrations{
+        }:_(RawOrigin::Signed(get_manager::<T>()),100)
+
+        set_promotion{
+        }:_(RawOrigin::Signed(get_manager::<T>()),1_u32.into(),100_u32.into(),2_000,3)
+
+        set_asset_rate{
+            let asset = T::AssetId::decode(&mut sp_runtime::traits::TrailingZeroInput::zeroes()).unwrap();
+        }:_(RawOrigin::Signed(get_manager::<T>()),asset,Some(1000_u32.into()))
+
+        submit_exchange_rate{
+            crate::price_oracle::ExchangeRate::<T>::set(1000_u32.into());
+        }:_(RawOrigin::None,1000_u32.into())
 
         impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), Test);
     }
@@ -372,7 +671,8 @@ mod origin {
     use super::{account_to_source, get_manager, poor_account};
     #[cfg(test)]
     use crate::mock::Test;
-    use crate::origin::{Call, Config, Pallet};
+    use crate::origin::{Call, Config, Pallet, Role};
+    use crate::traits::Operation;
     use frame_benchmarking::benchmarks;
     use frame_system::RawOrigin;
 
@@ -402,6 +702,114 @@ mod origin {
         set_registrar_open {
         }:_(RawOrigin::Signed(get_manager::<T>()),false)
 
+        set_role {
+            let account = poor_account::<T>(7);
+        }:_(RawOrigin::Signed(get_manager::<T>()),account_to_source::<T>(account),Role::PriceAdmin,true)
+
+        set_paused {
+        }:_(RawOrigin::Signed(get_manager::<T>()),Operation::Register,true)
+
+        propose_origin {
+            let account = poor_account::<T>(7);
+        }:_(RawOrigin::Signed(get_manager::<T>()),account_to_source::<T>(account))
+
+        accept_origin {
+            let account = poor_account::<T>(7);
+            Pallet::<T>::propose_origin(RawOrigin::Signed(get_manager::<T>()).into(),account_to_source::<T>(account.clone()))?;
+        }:_(RawOrigin::Signed(account))
+
+        impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), Test);
+    }
+}
+
+mod dispute {
+    use super::{
+        account_to_source, get_name, name_to_node,
+        registrar::{create_caller, min_duration},
+    };
+    #[cfg(test)]
+    use crate::mock::Test;
+    use crate::{
+        dispute::{Call, Config, EvidenceOf, Pallet, Ruling},
+        registrar::DefaultBaseNode,
+        traits::{Registry, MIN_REGISTRABLE_LEN},
+    };
+    use frame_benchmarking::benchmarks;
+    use frame_support::traits::{EnsureOrigin, Get};
+    use frame_system::RawOrigin;
+    use pns_types::DomainHash;
+    use sp_runtime::DispatchError;
+
+    fn get_holder_and_node<T>() -> Result<(T::AccountId, DomainHash), DispatchError>
+    where
+        T: crate::registrar::Config + pallet_balances::Config,
+    {
+        let name = get_name(MIN_REGISTRABLE_LEN);
+        let node = name_to_node(name.clone(), DefaultBaseNode::<T>::get());
+        let holder = create_caller::<T>(8);
+        crate::registrar::Pallet::<T>::register(
+            RawOrigin::Signed(holder.clone()).into(),
+            DefaultBaseNode::<T>::get(),
+            name,
+            account_to_source::<T>(holder.clone()),
+            min_duration::<T>(),
+            None,
+        )?;
+        Ok((holder, node))
+    }
+
+    fn get_evidence<T: Config>() -> EvidenceOf<T> {
+        EvidenceOf::<T>::truncate_from(sp_std::vec![b'x'; T::MaxEvidenceLength::get() as usize])
+    }
+
+    benchmarks! {
+        where_clause {
+            where
+            T: crate::origin::Config + crate::registrar::Config + crate::registry::Config + pallet_balances::Config,
+        }
+        open_dispute {
+            let (_holder,node) = get_holder_and_node::<T>()?;
+            let challenger = create_caller::<T>(2);
+        }:_(RawOrigin::Signed(challenger), node, get_evidence::<T>())
+        verify {
+            assert!(crate::dispute::Disputes::<T>::contains_key(node));
+        }
+        respond {
+            let (holder,node) = get_holder_and_node::<T>()?;
+            Pallet::<T>::open_dispute(RawOrigin::Signed(create_caller::<T>(2)).into(), node, get_evidence::<T>())?;
+        }:_(RawOrigin::Signed(holder), node, get_evidence::<T>())
+        verify {
+            assert!(crate::dispute::Disputes::<T>::get(node).unwrap().response.is_some());
+        }
+        rule {
+            let (holder,node) = get_holder_and_node::<T>()?;
+            let challenger = create_caller::<T>(2);
+            Pallet::<T>::open_dispute(RawOrigin::Signed(challenger.clone()).into(), node, get_evidence::<T>())?;
+            Pallet::<T>::respond(RawOrigin::Signed(holder).into(), node, get_evidence::<T>())?;
+            let origin = T::ArbitrationOrigin::successful_origin();
+        }:_<T::RuntimeOrigin>(origin, node, Ruling::Transfer)
+        verify {
+            assert_eq!(<T as Config>::Registry::owner_of(node), Some(challenger));
+        }
+        withdraw_dispute {
+            let (holder,node) = get_holder_and_node::<T>()?;
+            let challenger = create_caller::<T>(2);
+            Pallet::<T>::open_dispute(RawOrigin::Signed(challenger.clone()).into(), node, get_evidence::<T>())?;
+            Pallet::<T>::respond(RawOrigin::Signed(holder).into(), node, get_evidence::<T>())?;
+        }:_(RawOrigin::Signed(challenger), node)
+        verify {
+            assert!(!crate::dispute::Disputes::<T>::contains_key(node));
+        }
+        rule_appeal {
+            let (holder,node) = get_holder_and_node::<T>()?;
+            crate::registry::Pallet::<T>::emergency_freeze(<T as crate::registry::Config>::GovernanceOrigin::successful_origin(), node, false)?;
+            crate::registry::Pallet::<T>::appeal_freeze(RawOrigin::Signed(holder).into(), node)?;
+            let origin = T::ArbitrationOrigin::successful_origin();
+        }:_<T::RuntimeOrigin>(origin, node, false)
+        verify {
+            assert!(!crate::registry::FrozenNodes::<T>::contains_key(node));
+        }
+
         impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), Test);
     }
 }
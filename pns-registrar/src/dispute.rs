@@ -0,0 +1,428 @@
+//! # Dispute
+//!
+//! This module lets third parties dispute the holder of a domain,
+//! e.g. for a trademark, and an arbitration body rule on it.
+//!
+//! ## Introduction
+//!
+//! A challenger opens a dispute against a domain with its claim, e.g. the
+//! link to its evidence, reserving `ChallengeBond`. The holder has
+//! `ResponsePeriod` to answer it, reserving `ResponseBond`. Once the
+//! holder answered or the period is over, the `ArbitrationOrigin` rules:
+//!
+//! - [`Ruling::Transfer`] moves the domain to the challenger, whatever its
+//!   fuses or freezes, and the bond of the holder goes to the challenger.
+//! - [`Ruling::Retain`] leaves the domain to its holder, and the bond of the
+//!   challenger goes to the holder.
+//!
+//! The bond of the winner is returned. A domain has a single open dispute,
+//! it stays open if the holder transfers the domain, and the ruling applies
+//! to whoever owns it then.
+//!
+//! The owner appealing an emergency freeze of the registry opens a case as
+//! well, see `registry::appeal_freeze`. The `ArbitrationOrigin` either
+//! lifts the freeze or lets governance confirm it.
+//!
+//! ### Module functions
+//!
+//! - `open_dispute` - disputes a domain the caller does not own
+//! - `respond` - answers the dispute of a domain, only its holder within `ResponsePeriod`
+//! - `rule` - decides a dispute, only the `ArbitrationOrigin`
+//! - `withdraw_dispute` - drops a dispute before the ruling, only its challenger
+//! - `rule_appeal` - decides the appeal of an emergency freeze, only the `ArbitrationOrigin`
+
+pub use pallet::*;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// What the arbitration decided for a disputed domain.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum Ruling {
+    /// The domain goes to the challenger.
+    Transfer,
+    /// The domain stays with its holder.
+    Retain,
+}
+
+/// An open dispute of a domain, see `open_dispute`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Dispute<AccountId, Balance, BlockNumber, Evidence> {
+    pub challenger: AccountId,
+    /// Reserved from the challenger.
+    pub challenger_bond: Balance,
+    pub claim: Evidence,
+    /// The owner of the domain when the dispute was opened or answered.
+    pub holder: AccountId,
+    /// The answer of the holder and the bond reserved for it.
+    pub response: Option<(Evidence, Balance)>,
+    /// The holder can answer until this block.
+    pub respond_by: BlockNumber,
+}
+
+pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<
+    <T as frame_system::Config>::AccountId,
+>>::Balance;
+
+pub type EvidenceOf<T> = frame_support::BoundedVec<u8, <T as Config>::MaxEvidenceLength>;
+
+pub type DisputeOf<T> = Dispute<
+    <T as frame_system::Config>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Config>::BlockNumber,
+    EvidenceOf<T>,
+>;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use crate::traits::{Arbitration, AuditLog, Registry};
+    use frame_support::pallet_prelude::*;
+    use frame_support::traits::{BalanceStatus, EnsureOrigin, ReservableCurrency};
+    use frame_system::pallet_prelude::*;
+    use pns_types::DomainHash;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        type WeightInfo: WeightInfo;
+
+        type Registry: Registry<AccountId = Self::AccountId>;
+
+        /// Rules on the disputes, e.g. an arbitration council.
+        type ArbitrationOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+        /// Records every ruling on a dispute or an appeal, along with who ruled.
+        type AuditLog: AuditLog<Self::AccountId>;
+
+        /// Reserves the bonds of the challengers and holders.
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// Reserved from the challenger opening a dispute.
+        #[pallet::constant]
+        type ChallengeBond: Get<BalanceOf<Self>>;
+
+        /// Reserved from the holder answering a dispute.
+        #[pallet::constant]
+        type ResponseBond: Get<BalanceOf<Self>>;
+
+        /// How long the holder can answer a dispute.
+        #[pallet::constant]
+        type ResponsePeriod: Get<Self::BlockNumber>;
+
+        /// The maximum length of a claim or an answer.
+        #[pallet::constant]
+        type MaxEvidenceLength: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    /// `name_hash` -> its open dispute
+    #[pallet::storage]
+    pub type Disputes<T: Config> = StorageMap<_, Twox64Concat, DomainHash, DisputeOf<T>>;
+
+    /// `name_hash` -> the owner who appealed its emergency freeze, until it is ruled on
+    #[pallet::storage]
+    pub type Appeals<T: Config> = StorageMap<_, Twox64Concat, DomainHash, T::AccountId>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// `challenger` disputed `node` of `holder`, who can answer until `respond_by`.
+        DisputeOpened {
+            node: DomainHash,
+            challenger: T::AccountId,
+            holder: T::AccountId,
+            bond: BalanceOf<T>,
+            respond_by: T::BlockNumber,
+        },
+        /// `holder` answered the dispute of `node`.
+        DisputeAnswered {
+            node: DomainHash,
+            holder: T::AccountId,
+            bond: BalanceOf<T>,
+        },
+        /// The dispute of `node` was decided, `winner` got the bond of `loser`.
+        DisputeRuled {
+            node: DomainHash,
+            ruling: Ruling,
+            winner: T::AccountId,
+            loser: T::AccountId,
+        },
+        /// The challenger dropped the dispute of `node`.
+        DisputeWithdrawn { node: DomainHash },
+        /// `appellant` appealed the emergency freeze of `node`.
+        AppealOpened {
+            node: DomainHash,
+            appellant: T::AccountId,
+        },
+        /// The appeal of `node` was decided, its freeze was lifted unless `upheld`.
+        AppealRuled {
+            node: DomainHash,
+            appellant: T::AccountId,
+            upheld: bool,
+        },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The domain does not exist.
+        NotExist,
+        /// The domain is already disputed.
+        AlreadyDisputed,
+        /// The domain is not disputed.
+        NotDisputed,
+        /// The holder can't dispute its own domain.
+        OwnDomain,
+        /// Only the holder of the domain can answer the dispute.
+        NotHolder,
+        /// Only the challenger can withdraw the dispute.
+        NotChallenger,
+        /// The dispute is already answered.
+        AlreadyAnswered,
+        /// The response period of the dispute is over.
+        ResponseClosed,
+        /// The holder can still answer the dispute.
+        AwaitingResponse,
+        /// The emergency freeze of the domain is not appealed.
+        NotAppealed,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Dispute `node` with `claim`, reserving `ChallengeBond` from the caller.
+        ///
+        /// Ensure: The caller does not own `node` and it is not disputed yet.
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::open_dispute())]
+        pub fn open_dispute(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            claim: EvidenceOf<T>,
+        ) -> DispatchResult {
+            let challenger = ensure_signed(origin)?;
+
+            let holder = T::Registry::owner_of(node).ok_or(Error::<T>::NotExist)?;
+            ensure!(holder != challenger, Error::<T>::OwnDomain);
+            ensure!(
+                !Disputes::<T>::contains_key(node),
+                Error::<T>::AlreadyDisputed
+            );
+
+            let bond = T::ChallengeBond::get();
+            T::Currency::reserve(&challenger, bond)?;
+
+            let respond_by = frame_system::Pallet::<T>::block_number() + T::ResponsePeriod::get();
+            Disputes::<T>::insert(
+                node,
+                Dispute {
+                    challenger: challenger.clone(),
+                    challenger_bond: bond,
+                    claim,
+                    holder: holder.clone(),
+                    response: None,
+                    respond_by,
+                },
+            );
+
+            Self::deposit_event(Event::<T>::DisputeOpened {
+                node,
+                challenger,
+                holder,
+                bond,
+                respond_by,
+            });
+            Ok(())
+        }
+        /// Answer the dispute of `node` with `response`, reserving `ResponseBond` from the caller.
+        ///
+        /// Ensure: The caller owns `node` and the response period is not over.
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::respond())]
+        pub fn respond(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            response: EvidenceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Disputes::<T>::try_mutate(node, |maybe_dispute| -> DispatchResult {
+                let dispute = maybe_dispute.as_mut().ok_or(Error::<T>::NotDisputed)?;
+                ensure!(
+                    T::Registry::owner_of(node) == Some(who.clone()),
+                    Error::<T>::NotHolder
+                );
+                ensure!(dispute.response.is_none(), Error::<T>::AlreadyAnswered);
+                ensure!(
+                    frame_system::Pallet::<T>::block_number() < dispute.respond_by,
+                    Error::<T>::ResponseClosed
+                );
+
+                let bond = T::ResponseBond::get();
+                T::Currency::reserve(&who, bond)?;
+                dispute.holder = who.clone();
+                dispute.response = Some((response, bond));
+
+                Self::deposit_event(Event::<T>::DisputeAnswered {
+                    node,
+                    holder: who,
+                    bond,
+                });
+                Ok(())
+            })
+        }
+        /// Decide the dispute of `node`, once the holder answered it or the
+        /// response period is over. The bond of the loser goes to the winner.
+        /// Only the arbitration
+        #[pallet::call_index(2)]
+        #[pallet::weight(T::WeightInfo::rule())]
+        pub fn rule(origin: OriginFor<T>, node: DomainHash, ruling: Ruling) -> DispatchResult {
+            let arbitrator = T::ArbitrationOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&arbitrator, &Call::<T>::rule { node, ruling });
+
+            let dispute = Disputes::<T>::get(node).ok_or(Error::<T>::NotDisputed)?;
+            ensure!(
+                dispute.response.is_some()
+                    || frame_system::Pallet::<T>::block_number() >= dispute.respond_by,
+                Error::<T>::AwaitingResponse
+            );
+            Disputes::<T>::remove(node);
+
+            let response_bond = dispute.response.map(|(_, bond)| bond).unwrap_or_default();
+            let (winner, loser) = match ruling {
+                Ruling::Transfer => {
+                    T::Registry::thaw_unchecked(node);
+                    T::Registry::transfer_unchecked(&dispute.challenger, node)?;
+                    T::Registry::clear_approvals(node);
+
+                    T::Currency::unreserve(&dispute.challenger, dispute.challenger_bond);
+                    T::Currency::repatriate_reserved(
+                        &dispute.holder,
+                        &dispute.challenger,
+                        response_bond,
+                        BalanceStatus::Free,
+                    )?;
+                    (dispute.challenger, dispute.holder)
+                }
+                Ruling::Retain => {
+                    T::Currency::unreserve(&dispute.holder, response_bond);
+                    T::Currency::repatriate_reserved(
+                        &dispute.challenger,
+                        &dispute.holder,
+                        dispute.challenger_bond,
+                        BalanceStatus::Free,
+                    )?;
+                    (dispute.holder, dispute.challenger)
+                }
+            };
+
+            Self::deposit_event(Event::<T>::DisputeRuled {
+                node,
+                ruling,
+                winner,
+                loser,
+            });
+            Ok(())
+        }
+        /// Drop the dispute of `node` before it is decided, both bonds are returned.
+        ///
+        /// Ensure: The caller opened the dispute.
+        #[pallet::call_index(3)]
+        #[pallet::weight(T::WeightInfo::withdraw_dispute())]
+        pub fn withdraw_dispute(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let dispute = Disputes::<T>::get(node).ok_or(Error::<T>::NotDisputed)?;
+            ensure!(dispute.challenger == who, Error::<T>::NotChallenger);
+            Disputes::<T>::remove(node);
+
+            T::Currency::unreserve(&dispute.challenger, dispute.challenger_bond);
+            if let Some((_, bond)) = dispute.response {
+                T::Currency::unreserve(&dispute.holder, bond);
+            }
+
+            Self::deposit_event(Event::<T>::DisputeWithdrawn { node });
+            Ok(())
+        }
+        /// Decide the appeal of the emergency freeze of `node`: `uphold` leaves the
+        /// freeze to be confirmed by governance, otherwise it is lifted and the
+        /// bond of the appeal returned.
+        /// Only the arbitration
+        #[pallet::call_index(4)]
+        #[pallet::weight(T::WeightInfo::rule_appeal())]
+        pub fn rule_appeal(origin: OriginFor<T>, node: DomainHash, uphold: bool) -> DispatchResult {
+            let arbitrator = T::ArbitrationOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &arbitrator,
+                &Call::<T>::rule_appeal { node, uphold },
+            );
+
+            let appellant = Appeals::<T>::take(node).ok_or(Error::<T>::NotAppealed)?;
+            if !uphold {
+                T::Registry::thaw_unchecked(node);
+            }
+
+            Self::deposit_event(Event::<T>::AppealRuled {
+                node,
+                appellant,
+                upheld: uphold,
+            });
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Arbitration<T::AccountId> for Pallet<T> {
+        fn open_appeal(node: DomainHash, appellant: &T::AccountId) -> DispatchResult {
+            Appeals::<T>::insert(node, appellant);
+
+            Self::deposit_event(Event::<T>::AppealOpened {
+                node,
+                appellant: appellant.clone(),
+            });
+            Ok(())
+        }
+
+        fn appeal_pending(node: DomainHash) -> bool {
+            Appeals::<T>::contains_key(node)
+        }
+
+        fn drop_appeal(node: DomainHash) {
+            Appeals::<T>::remove(node);
+        }
+    }
+}
+
+use frame_support::dispatch::Weight;
+
+pub trait WeightInfo {
+    fn open_dispute() -> Weight;
+    fn respond() -> Weight;
+    fn rule() -> Weight;
+    fn withdraw_dispute() -> Weight;
+    fn rule_appeal() -> Weight;
+}
+
+impl WeightInfo for () {
+    fn open_dispute() -> Weight {
+        Weight::zero()
+    }
+
+    fn respond() -> Weight {
+        Weight::zero()
+    }
+
+    fn rule() -> Weight {
+        Weight::zero()
+    }
+
+    fn withdraw_dispute() -> Weight {
+        Weight::zero()
+    }
+
+    fn rule_appeal() -> Weight {
+        Weight::zero()
+    }
+}
@@ -0,0 +1,273 @@
+//! # Internationalized labels
+//!
+//! A deterministic subset of UTS-46 processing for a single label, used by
+//! [`crate::traits::normalize_label`]:
+//!
+//! * full width ASCII is mapped to ASCII, variation selectors are dropped and
+//!   everything is lower cased,
+//! * only characters of the scripts below, ASCII digits, `-` and emoji are valid.
+//!   Characters which normalize to something else (combining marks, compatibility
+//!   ideographs, conjoining jamo, ...) are rejected instead of mapped, so every
+//!   accepted label already is in NFC,
+//! * scripts may only be mixed the way UTS-39 "highly restrictive" allows it,
+//!   which rejects confusables such as a cyrillic `а` in an otherwise latin name,
+//! * the result is encoded with punycode (RFC 3492) and prefixed with `xn--`.
+//!
+//! Supported scripts are Latin, Greek, Cyrillic, Han, Hiragana, Katakana and Hangul.
+use sp_std::vec::Vec;
+
+pub const ACE_PREFIX: &str = "xn--";
+
+/// Converts a non ASCII label into its `xn--` form, its length is left to the caller.
+///
+/// Returns `None` if the label is not valid, also if it does not contain any
+/// non ASCII character after the mapping. `strict_hyphens` rejects hyphens
+/// at the 3rd and 4th character, like for ASCII labels.
+pub fn to_ascii(label: &str, strict_hyphens: bool) -> Option<Vec<u8>> {
+    encode(&map(label.chars())?, strict_hyphens)
+}
+
+/// Converts a lower cased `xn--` label back into its unicode characters.
+///
+/// Only the canonical encoding of a valid label is accepted, so both forms
+/// always normalize to the same bytes.
+pub fn to_unicode(label: &str, strict_hyphens: bool) -> Option<Vec<char>> {
+    let chars = punycode::decode(label.strip_prefix(ACE_PREFIX)?.as_bytes())?;
+    if map(chars.iter().copied())? != chars {
+        return None;
+    }
+
+    (encode(&chars, strict_hyphens)? == label.as_bytes()).then_some(chars)
+}
+
+fn encode(chars: &[char], strict_hyphens: bool) -> Option<Vec<u8>> {
+    if chars.iter().all(char::is_ascii) {
+        return None;
+    }
+    // same rules as for ASCII labels.
+    if chars.first() == Some(&'-')
+        || chars.last() == Some(&'-')
+        || strict_hyphens && (chars.get(2) == Some(&'-') || chars.get(3) == Some(&'-'))
+    {
+        return None;
+    }
+
+    let mut scripts = 0u8;
+    for &c in chars.iter() {
+        scripts |= script_of(c)?.bit();
+    }
+    if !Script::may_mix(scripts) {
+        return None;
+    }
+
+    let mut encoded = Vec::from(ACE_PREFIX.as_bytes());
+    encoded.extend(punycode::encode(chars)?);
+    Some(encoded)
+}
+
+fn map(label: impl Iterator<Item = char>) -> Option<Vec<char>> {
+    let mut chars = Vec::new();
+    for c in label {
+        let c = match c {
+            // full width forms of ASCII.
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0)?,
+            // variation selectors are ignored.
+            '\u{FE00}'..='\u{FE0F}' => continue,
+            c => c,
+        };
+        chars.extend(c.to_lowercase());
+    }
+    Some(chars)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Script {
+    /// Digits, `-` and emoji, these go with every script.
+    Common,
+    Latin,
+    Greek,
+    Cyrillic,
+    Han,
+    Hiragana,
+    Katakana,
+    Hangul,
+}
+
+impl Script {
+    fn bit(self) -> u8 {
+        match self {
+            Script::Common => 0,
+            script => 1 << (script as u8 - 1),
+        }
+    }
+
+    /// Whether the `scripts` of a label are allowed together, see UTS-39 "highly restrictive".
+    fn may_mix(scripts: u8) -> bool {
+        use Script::*;
+
+        let japanese = Latin.bit() | Han.bit() | Hiragana.bit() | Katakana.bit();
+        let korean = Latin.bit() | Han.bit() | Hangul.bit();
+
+        scripts.count_ones() <= 1 || scripts & !japanese == 0 || scripts & !korean == 0
+    }
+}
+
+fn script_of(c: char) -> Option<Script> {
+    match c {
+        'a'..='z' => Some(Script::Latin),
+        '0'..='9' | '-' => Some(Script::Common),
+        // latin letters UTS-46 maps to something else.
+        '\u{0132}' | '\u{0133}' | '\u{013F}' | '\u{0140}' | '\u{0149}' | '\u{017F}' => None,
+        '\u{01C4}'..='\u{01CC}' | '\u{01F1}'..='\u{01F3}' => None,
+        '\u{00DF}'..='\u{00F6}' | '\u{00F8}'..='\u{024F}' => Some(Script::Latin),
+        '\u{0390}' | '\u{03AC}'..='\u{03CE}' => Some(Script::Greek),
+        '\u{0430}'..='\u{045F}' => Some(Script::Cyrillic),
+        '\u{3041}'..='\u{3096}' | '\u{309D}' | '\u{309E}' => Some(Script::Hiragana),
+        '\u{30A1}'..='\u{30FA}' | '\u{30FD}' | '\u{30FE}' => Some(Script::Katakana),
+        // the prolonged sound mark is used with both kana.
+        '\u{30FC}' => Some(Script::Common),
+        '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{20000}'..='\u{2A6DF}' => {
+            Some(Script::Han)
+        }
+        '\u{AC00}'..='\u{D7A3}' => Some(Script::Hangul),
+        // emoji.
+        '\u{2600}'..='\u{27BF}'
+        | '\u{1F300}'..='\u{1F64F}'
+        | '\u{1F680}'..='\u{1F6FF}'
+        | '\u{1F900}'..='\u{1F9FF}' => Some(Script::Common),
+        _ => None,
+    }
+}
+
+/// Punycode as specified by RFC 3492, every overflow is an invalid label.
+mod punycode {
+    use sp_std::vec::Vec;
+
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+    const SKEW: u32 = 38;
+    const DAMP: u32 = 700;
+    const INITIAL_BIAS: u32 = 72;
+    const INITIAL_N: u32 = 128;
+
+    fn adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+        delta /= if first_time { DAMP } else { 2 };
+        delta += delta / num_points;
+        let mut k = 0;
+        while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+            delta /= BASE - T_MIN;
+            k += BASE;
+        }
+        k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+    }
+
+    fn threshold(k: u32, bias: u32) -> u32 {
+        if k <= bias {
+            T_MIN
+        } else if k >= bias + T_MAX {
+            T_MAX
+        } else {
+            k - bias
+        }
+    }
+
+    fn encode_digit(digit: u32) -> u8 {
+        match digit {
+            0..=25 => b'a' + digit as u8,
+            _ => b'0' + (digit - 26) as u8,
+        }
+    }
+
+    fn decode_digit(c: u8) -> Option<u32> {
+        match c {
+            b'a'..=b'z' => Some((c - b'a') as u32),
+            b'0'..=b'9' => Some((c - b'0') as u32 + 26),
+            _ => None,
+        }
+    }
+
+    pub fn encode(input: &[char]) -> Option<Vec<u8>> {
+        let mut output = input
+            .iter()
+            .filter(|c| c.is_ascii())
+            .map(|&c| c as u8)
+            .collect::<Vec<_>>();
+        let basic = output.len() as u32;
+        if basic > 0 {
+            output.push(b'-');
+        }
+
+        let mut handled = basic;
+        let mut n = INITIAL_N;
+        let mut delta = 0u32;
+        let mut bias = INITIAL_BIAS;
+        while (handled as usize) < input.len() {
+            let m = input.iter().map(|&c| c as u32).filter(|&c| c >= n).min()?;
+            delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+            n = m;
+            for &c in input {
+                let c = c as u32;
+                if c < n {
+                    delta = delta.checked_add(1)?;
+                }
+                if c == n {
+                    let mut q = delta;
+                    let mut k = BASE;
+                    loop {
+                        let t = threshold(k, bias);
+                        if q < t {
+                            break;
+                        }
+                        output.push(encode_digit(t + (q - t) % (BASE - t)));
+                        q = (q - t) / (BASE - t);
+                        k += BASE;
+                    }
+                    output.push(encode_digit(q));
+                    bias = adapt(delta, handled + 1, handled == basic);
+                    delta = 0;
+                    handled += 1;
+                }
+            }
+            delta = delta.checked_add(1)?;
+            n = n.checked_add(1)?;
+        }
+        Some(output)
+    }
+
+    pub fn decode(input: &[u8]) -> Option<Vec<char>> {
+        let (basic, mut extended) = match input.iter().rposition(|&c| c == b'-') {
+            Some(delimiter) => (&input[..delimiter], &input[delimiter + 1..]),
+            None => (&[][..], input),
+        };
+        let mut output = basic.iter().map(|&c| c as char).collect::<Vec<_>>();
+
+        let mut n = INITIAL_N;
+        let mut i = 0u32;
+        let mut bias = INITIAL_BIAS;
+        while !extended.is_empty() {
+            let old_i = i;
+            let mut w = 1u32;
+            let mut k = BASE;
+            loop {
+                let (&c, rest) = extended.split_first()?;
+                extended = rest;
+                let digit = decode_digit(c)?;
+                i = i.checked_add(digit.checked_mul(w)?)?;
+                let t = threshold(k, bias);
+                if digit < t {
+                    break;
+                }
+                w = w.checked_mul(BASE - t)?;
+                k += BASE;
+            }
+            let len = output.len() as u32 + 1;
+            bias = adapt(i - old_i, len, old_i == 0);
+            n = n.checked_add(i / len)?;
+            i %= len;
+            output.insert(i as usize, char::from_u32(n)?);
+            i += 1;
+        }
+        Some(output)
+    }
+}
@@ -3,9 +3,12 @@
 #[cfg(feature = "runtime-benchmarks")]
 extern crate alloc;
 
-//pub mod auction;
+pub mod auction;
+pub mod dispute;
+pub mod idn;
 pub mod migration;
 pub mod nft;
+pub mod nft_adapter;
 pub mod origin;
 pub mod price_oracle;
 pub mod redeem_code;
@@ -16,6 +19,10 @@ pub mod traits;
 #[cfg(test)]
 pub mod mock;
 
+// the benchmark helpers of the nft pallets need item ids `From<u16>`, which domain hashes are not.
+#[cfg(all(test, not(feature = "runtime-benchmarks")))]
+pub mod mock_nfts;
+
 #[cfg(test)]
 pub(crate) mod tests;
 
@@ -2,14 +2,18 @@ use core::marker::PhantomData;
 
 use frame_support::dispatch::Weight;
 use frame_support::traits::Get;
-use pns_types::DomainHash;
+use pns_types::{DomainHash, Record};
 use sp_std::vec::Vec;
 
-use crate::{nft, origin, price_oracle, registry};
+use crate::{nft, origin, price_oracle, redeem_code, registrar, registry};
 
 pub struct Initialize<T>(PhantomData<T>);
 
-impl<T: registry::Config> Initialize<T> {
+/// For runtimes which mint the domains in the bundled [`nft`] pallet.
+impl<
+        T: registry::Config + nft::Config<ClassData = (), TokenData = Record, TokenId = DomainHash>,
+    > Initialize<T>
+{
     pub fn initial_registry(official: T::AccountId, root_domain: DomainHash) -> Weight {
         // writes 1
         registry::Official::<T>::put(&official);
@@ -26,8 +30,59 @@ impl<T: registry::Config> Initialize<T> {
             Default::default(),
         )
         .expect("Token mint cannot fail during initialize");
+        registry::Pallet::<T>::index_owned(&official, root_domain);
+
+        <T as frame_system::Config>::DbWeight::get().writes(8)
+    }
+
+    /// Indexes the domains minted before `OwnedNodes` existed.
+    pub fn initial_owned_nodes() -> Weight {
+        use sp_runtime::traits::Zero;
 
-        <T as frame_system::Config>::DbWeight::get().writes(6)
+        let mut count = 0;
+        for (node, token) in nft::Tokens::<T>::iter_prefix(T::ClassId::zero()) {
+            registry::Pallet::<T>::index_owned(&token.owner, node);
+            count += 1;
+        }
+        <T as frame_system::Config>::DbWeight::get().reads_writes(count * 2, count * 2)
+    }
+
+    /// Counts the domains indexed before `OwnedTokenCount` existed.
+    pub fn initial_owned_token_count() -> Weight {
+        let _ = registry::OwnedTokenCount::<T>::clear(u32::MAX, None);
+        let mut count = 0;
+        for (owner, _) in registry::OwnedNodes::<T>::iter_keys() {
+            registry::OwnedTokenCount::<T>::mutate(owner, |owned| *owned += 1);
+            count += 1;
+        }
+        <T as frame_system::Config>::DbWeight::get().reads_writes(count, count)
+    }
+
+    /// Moves the subdomain counters, which used to be the nft token data, to `Children`.
+    pub fn initial_children() -> Weight {
+        use sp_runtime::traits::Zero;
+
+        let mut reads = 0;
+        let mut writes = 0;
+        for (node, token) in nft::Tokens::<T>::iter_prefix(T::ClassId::zero()) {
+            reads += 1;
+            if token.data.children != 0 {
+                registry::Children::<T>::insert(node, token.data.children);
+                writes += 1;
+            }
+        }
+        <T as frame_system::Config>::DbWeight::get().reads_writes(reads, writes)
+    }
+}
+
+impl<T: registry::Config> Initialize<T> {
+    /// Removes every token approval, which used to survive transfers and burns.
+    ///
+    /// Approvals given before and after a transfer can't be told apart,
+    /// so owners have to approve again.
+    pub fn clear_token_approvals() -> Weight {
+        let count = registry::TokenApprovals::<T>::clear(u32::MAX, None).unique as u64;
+        <T as frame_system::Config>::DbWeight::get().writes(count)
     }
 }
 
@@ -42,6 +97,36 @@ impl<T: origin::Config> Initialize<T> {
     }
 }
 
+impl<T: registrar::Config> Initialize<T> {
+    /// Counts the domains registered before `ActiveDomains` existed.
+    pub fn initial_stats() -> Weight {
+        let active = registrar::RegistrarInfos::<T>::iter_keys().count() as u64;
+        registrar::ActiveDomains::<T>::put(active);
+        <T as frame_system::Config>::DbWeight::get().reads_writes(active, 1)
+    }
+
+    /// Moves the registered domains under `base_node`, which used to be the
+    /// `BaseNode` constant, and gives it the settings that used to be constants.
+    pub fn initial_base_nodes(base_node: DomainHash, config: registrar::TldConfigOf<T>) -> Weight {
+        registrar::BaseNodes::<T>::insert(base_node, config);
+        registrar::DefaultBaseNode::<T>::put(base_node);
+        let mut count = 0;
+        for node in registrar::RegistrarInfos::<T>::iter_keys() {
+            registrar::BaseNodeOf::<T>::insert(node, base_node);
+            count += 1;
+        }
+        <T as frame_system::Config>::DbWeight::get().reads_writes(count, count + 2)
+    }
+
+    /// Removes the pending transfer offers, which have no price yet.
+    ///
+    /// They expire after `TransferOfferExpiry` blocks anyway, so they are made again.
+    pub fn clear_transfer_offers() -> Weight {
+        let count = registrar::TransferOffers::<T>::clear(u32::MAX, None).unique as u64;
+        <T as frame_system::Config>::DbWeight::get().writes(count)
+    }
+}
+
 type BalanceOf<T> = <<T as price_oracle::Config>::Currency as frame_support::traits::Currency<
     <T as frame_system::Config>::AccountId,
 >>::Balance;
@@ -49,9 +134,9 @@ type BalanceOf<T> = <<T as price_oracle::Config>::Currency as frame_support::tra
 impl<T: price_oracle::Config> Initialize<T> {
     /// (`BasePrice` or `RentPrice`) is dollar * exchange rate -> finale value
     pub fn initial_price_oracle(
-        base_prices: [BalanceOf<T>; 11],
-        rent_prices: [BalanceOf<T>; 11],
-        deposit_prices: [BalanceOf<T>; 11],
+        base_prices: price_oracle::PricesOf<T>,
+        rent_prices: price_oracle::PricesOf<T>,
+        deposit_prices: price_oracle::PricesOf<T>,
         init_rate: BalanceOf<T>,
     ) -> Weight {
         <price_oracle::BasePrice<T>>::put(base_prices);
@@ -60,4 +145,32 @@ impl<T: price_oracle::Config> Initialize<T> {
         <price_oracle::ExchangeRate<T>>::put(init_rate);
         <T as frame_system::Config>::DbWeight::get().writes(3)
     }
+
+    /// Moves the prices from the former eleven fixed tiers to `MaxPriceTiers`
+    /// tiers, dropping the tiers which don't fit.
+    pub fn migrate_price_tiers() -> Weight {
+        let tiers = |old: Option<[BalanceOf<T>; 11]>| {
+            old.map(|prices| {
+                let max = <T as price_oracle::Config>::MaxPriceTiers::get() as usize;
+                let prices = prices.into_iter().take(max).collect::<Vec<_>>();
+                price_oracle::PricesOf::<T>::try_from(prices).expect("truncated to the bound")
+            })
+        };
+        let _ = <price_oracle::BasePrice<T>>::translate(tiers);
+        let _ = <price_oracle::RentPrice<T>>::translate(tiers);
+        let _ = <price_oracle::DepositPrice<T>>::translate(tiers);
+        <T as frame_system::Config>::DbWeight::get().reads_writes(3, 3)
+    }
+}
+
+impl<T: redeem_code::Config> Initialize<T> {
+    /// Marks the codes minted before they could expire as never expiring.
+    pub fn migrate_redeem_expiries() -> Weight {
+        let mut count = 0_u64;
+        <redeem_code::Redeems<T>>::translate::<(), _>(|_, _| {
+            count += 1;
+            Some(None)
+        });
+        <T as frame_system::Config>::DbWeight::get().reads_writes(count, count)
+    }
 }
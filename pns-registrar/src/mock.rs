@@ -5,6 +5,7 @@ use sp_core::H256;
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
+    FixedPointNumber, FixedU128, Perbill,
 };
 
 pub type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -42,10 +43,15 @@ frame_support::construct_runtime!(
         Balances: pallet_balances,
         Timestamp: pallet_timestamp,
         Aura: pallet_aura,
+        Assets: pallet_assets,
+        Council: pallet_collective::<Instance1>,
+        Dispute: crate::dispute,
     }
 );
 
 impl pns_resolvers::resolvers::Config for Test {
+    const OFFCHAIN_PREFIX: &'static [u8] = b"pns";
+
     type RuntimeEvent = RuntimeEvent;
 
     type WeightInfo = ();
@@ -53,12 +59,24 @@ impl pns_resolvers::resolvers::Config for Test {
     type AccountIndex = u32;
 
     type RegistryChecker = TestChecker;
+
+    type ManagerOrigin = ManagerOrigin;
+
+    type Public = sp_runtime::testing::UintAuthorityId;
+
+    type Signature = sp_runtime::testing::TestSignature;
 }
 
 impl crate::origin::Config for Test {
     type RuntimeEvent = RuntimeEvent;
 
     type WeightInfo = ();
+
+    type MaxAuditEntries = MaxAuditEntries;
+}
+
+parameter_types! {
+    pub const MaxAuditEntries: u32 = 4;
 }
 
 pub struct TestChecker;
@@ -68,8 +86,37 @@ impl pns_resolvers::resolvers::RegistryChecker for TestChecker {
 
     fn check_node_useable(node: DomainHash, owner: &Self::AccountId) -> bool {
         use crate::traits::Registrar as _;
-        crate::nft::TokensByOwner::<Test>::contains_key((owner, 0, node))
+        Registry::controls(owner, node)
             && Registrar::check_expires_useable(node).is_ok()
+            && Registry::check_frozen(node).is_ok()
+    }
+
+    fn record_version(node: DomainHash) -> u32 {
+        Registry::record_version(node)
+    }
+
+    fn records_served(node: DomainHash) -> bool {
+        Registry::records_served(node)
+    }
+
+    fn records_writable() -> bool {
+        use crate::traits::IsRegistrarOpen as _;
+        ManagerOrigin::is_enabled(crate::traits::Operation::ResolverWrites)
+    }
+}
+
+pub struct TestRecords;
+
+impl crate::traits::ResolverRecords for TestRecords {
+    type Record = pns_resolvers::resolvers::InitialRecord<AccountId>;
+
+    fn set_records(node: DomainHash, records: Vec<Self::Record>) -> sp_runtime::DispatchResult {
+        Resolvers::set_initial_records(node, records);
+        Ok(())
+    }
+
+    fn clear_records(node: DomainHash) {
+        Resolvers::clear_records(node);
     }
 }
 
@@ -111,6 +158,8 @@ pub const MANAGER_ACCOUNT: AccountId = 1;
 pub const POOR_ACCOUNT: AccountId = 2;
 pub const RICH_ACCOUNT: AccountId = 4;
 pub const MONEY_ACCOUNT: AccountId = 5;
+pub const TREASURY_ACCOUNT: AccountId = 6;
+pub const COUNCIL_ACCOUNT: AccountId = 7;
 
 pub const BASE: Balance = 1_000_000_000_000;
 
@@ -128,6 +177,7 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 
     let origin_genesis = crate::origin::GenesisConfig::<Test> {
         origins: vec![OFFICIAL_ACCOUNT, MANAGER_ACCOUNT],
+        ..Default::default()
     };
 
     <crate::origin::GenesisConfig<Test> as frame_support::traits::GenesisBuild<Test>>::assimilate_storage(&origin_genesis,&mut genesis_storage).unwrap();
@@ -158,15 +208,25 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     <pallet_balances::GenesisConfig<Test> as frame_support::traits::GenesisBuild<Test>>::assimilate_storage(&balances_genesis,&mut genesis_storage).unwrap();
 
     let price_oracle_genesis = crate::price_oracle::GenesisConfig::<Test> {
-        base_prices: [11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1],
-        rent_prices: [11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1],
-        deposit_prices: [11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1],
+        base_prices: vec![11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1],
+        rent_prices: vec![11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1],
+        deposit_prices: vec![11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1],
+        capacity_price: 1,
         init_rate: BASE,
     };
 
     <crate::price_oracle::GenesisConfig<Test> as frame_support::traits::GenesisBuild<Test>>::assimilate_storage(&price_oracle_genesis,&mut genesis_storage).unwrap();
 
     let registrar_genesis = crate::registrar::GenesisConfig::<Test> {
+        base_nodes: vec![(
+            DOT_BASENODE,
+            pns_types::TldConfig {
+                grace_period: GracePeriod::get(),
+                capacity: DefaultCapacity::get(),
+                min_duration: MinRegistrationDuration::get(),
+                open: true,
+            },
+        )],
         infos: Default::default(),
         reserved_list: Default::default(),
     };
@@ -205,14 +265,110 @@ impl crate::registry::Config for Test {
 
     type ResolverId = u32;
 
-    type ManagerOrigin = ManagerOrigin;
+    type ManagerOrigin = crate::origin::EnsureRegistrarAdmin<Test>;
+
+    type AuditLog = ManagerOrigin;
+
+    type Nft = Nft;
+
+    type MaxUriLength = MaxMetadata;
+
+    type GovernanceOrigin = GovernanceOrigin;
+
+    type Currency = Balances;
+
+    type AppealPeriod = AppealPeriod;
+
+    type FreezeConfirmPeriod = FreezeConfirmPeriod;
+
+    type AppealBond = AppealBond;
+
+    type Arbitration = Dispute;
+
+    type MaxTransferAll = MaxTransferAll;
+}
+
+impl crate::dispute::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+
+    type WeightInfo = ();
+
+    type Registry = crate::registry::Pallet<Test>;
+
+    type ArbitrationOrigin = GovernanceOrigin;
+
+    type AuditLog = ManagerOrigin;
+
+    type Currency = Balances;
+
+    type ChallengeBond = ChallengeBond;
+
+    type ResponseBond = ResponseBond;
+
+    type ResponsePeriod = ResponsePeriod;
+
+    type MaxEvidenceLength = MaxEvidenceLength;
+}
+
+parameter_types! {
+    pub const ChallengeBond: Balance = 20 * BASE;
+    pub const ResponseBond: Balance = 20 * BASE;
+    pub const ResponsePeriod: u64 = 5;
+    pub const MaxEvidenceLength: u32 = 64;
+}
+
+parameter_types! {
+    pub const AppealPeriod: u64 = 5;
+    pub const FreezeConfirmPeriod: u64 = 10;
+    pub const AppealBond: Balance = 10 * BASE;
+    pub const MaxTransferAll: u32 = 2;
 }
 
 parameter_types! {
     pub const GracePeriod: BlockNumber = 90 * 24 * 60 * 60;
+    pub const ReRegistrationCooldown: Moment = 7 * 24 * 60 * 60;
+    pub const MinCommitmentAge: Moment = 60;
+    pub const MaxCommitmentAge: Moment = 24 * 60 * 60;
+    pub const CommitmentDeposit: Balance = 10;
     pub const MinRegistrationDuration: Moment = 28 * 24 * 60 * 60;
     pub const DefaultCapacity: u32 = 20;
-    pub const BaseNode: Hash = DOT_BASENODE;
+    pub const MaxCapacity: u32 = 30;
+    pub const MaxRegistrationsPerBlock: u32 = 2;
+    pub const RegistrationQuota: u32 = 10;
+    pub const RegistrationQuotaWindow: Moment = 24 * 60 * 60;
+    pub const MaxBatchSize: u32 = 3;
+    pub const ReserveBatchThreshold: u32 = 2;
+    pub const MaxInitialRecords: u32 = 4;
+    pub const MaxRegistrationDuration: Moment = 365 * 24 * 60 * 60;
+    pub const PremiumMultiplier: u32 = 100;
+    pub const PremiumDuration: Moment = 21 * 24 * 60 * 60;
+    pub const GraceRenewalSurcharge: Perbill = Perbill::from_percent(10);
+    pub const AutoRenewWindow: Moment = 7 * 24 * 60 * 60;
+    pub const MaxAutoRenewalsPerBlock: u32 = 2;
+    pub const ExpiryWarningPeriod: Moment = 7 * 24 * 60 * 60;
+    pub const ExpiryBucket: Moment = 24 * 60 * 60;
+    pub const MaxExpiryWarningsPerBlock: u32 = 2;
+    pub const ReapReward: Perbill = Perbill::from_percent(10);
+    pub const MaxReapsPerBlock: u32 = 2;
+    pub const MaxReservationRules: u32 = 4;
+    pub const TransferOfferExpiry: u64 = 10;
+    pub const AuctionPeriod: Moment = 3 * 24 * 60 * 60;
+    pub const MinAuctionBid: Balance = 100;
+    pub const MaxAuctionBidders: u32 = 2;
+    pub const AuctionEndingPeriod: Moment = 24 * 60 * 60;
+    pub const AuctionSampleLength: Moment = 60 * 60;
+    pub static RandomSeed: u32 = 0;
+    pub const DefaultResolver: u32 = 1;
+    pub static FeeBurnShare: Perbill = Perbill::zero();
+    pub static FeeTreasuryShare: Perbill = Perbill::zero();
+    pub static DefaultLabelPolicy: bool = false;
+    pub static LabelMinLen: u32 = 3;
+    pub static RequireCommitment: bool = false;
+    pub static MaxExpiryBucketsPerBlock: u32 = 64;
+    pub static LabelMaxLen: u32 = 63;
+    pub static StrictHyphens: bool = true;
+    pub static Internationalized: bool = true;
+    pub static LifecycleLog: Vec<DomainLifecycle> = Vec::new();
 }
 
 pub type Moment = u64;
@@ -226,27 +382,157 @@ impl crate::registrar::Config for Test {
 
     type Currency = pallet_balances::Pallet<Test>;
 
-    type GracePeriod = GracePeriod;
+    type GraceRenewalSurcharge = GraceRenewalSurcharge;
+
+    type ReRegistrationCooldown = ReRegistrationCooldown;
+
+    type MinCommitmentAge = MinCommitmentAge;
+
+    type MaxCommitmentAge = MaxCommitmentAge;
 
-    type DefaultCapacity = DefaultCapacity;
+    type CommitmentDeposit = CommitmentDeposit;
 
-    type BaseNode = BaseNode;
+    type RequireCommitment = RequireCommitment;
+
+    type MaxCapacity = MaxCapacity;
 
     type WeightInfo = ();
 
-    type MinRegistrationDuration = MinRegistrationDuration;
+    type MaxRegistrationsPerBlock = MaxRegistrationsPerBlock;
+    type RegistrationQuota = RegistrationQuota;
+    type RegistrationQuotaWindow = RegistrationQuotaWindow;
+    type MaxBatchSize = MaxBatchSize;
+    type MaxInitialRecords = MaxInitialRecords;
+    type MaxRegistrationDuration = MaxRegistrationDuration;
+    type ResolverRecords = TestRecords;
+    type AutoRenewWindow = AutoRenewWindow;
+    type MaxAutoRenewalsPerBlock = MaxAutoRenewalsPerBlock;
+    type ExpiryWarningPeriod = ExpiryWarningPeriod;
+    type ExpiryBucket = ExpiryBucket;
+    type MaxExpiryWarningsPerBlock = MaxExpiryWarningsPerBlock;
+    type MaxExpiryBucketsPerBlock = MaxExpiryBucketsPerBlock;
+    type ReapReward = ReapReward;
+    type MaxReapsPerBlock = MaxReapsPerBlock;
+    type MaxReservationRules = MaxReservationRules;
+    type TransferOfferExpiry = TransferOfferExpiry;
+    type AuctionPeriod = AuctionPeriod;
+    type MinAuctionBid = MinAuctionBid;
+    type MaxAuctionBidders = MaxAuctionBidders;
+    type AuctionEndingPeriod = AuctionEndingPeriod;
+    type AuctionSampleLength = AuctionSampleLength;
+    type Randomness = TestRandomness;
+
+    type DefaultResolver = DefaultResolver;
 
     type PriceOracle = crate::price_oracle::Pallet<Test>;
 
+    type Assets = Assets;
+
     type Moment = Moment;
 
     type NowProvider = pallet_timestamp::Pallet<Test>;
 
     type Official = crate::registry::Pallet<Test>;
 
-    type ManagerOrigin = ManagerOrigin;
+    type ManagerOrigin = crate::origin::EnsureRegistrarAdmin<Test>;
+    type ReserveOrigin = crate::origin::EnsureReserveAdmin<Test>;
+
+    type GovernanceOrigin = GovernanceOrigin;
+
+    type ReserveBatchThreshold = ReserveBatchThreshold;
+
+    type JudicialOrigin = GovernanceOrigin;
+
+    type AuditLog = ManagerOrigin;
 
     type IsOpen = crate::origin::Pallet<Test>;
+
+    type LabelPolicy = TestLabelPolicy;
+
+    type Lifecycle = (TestLifecycle, ());
+
+    type VoucherSigner = sp_runtime::testing::UintAuthorityId;
+
+    type VoucherSignature = sp_runtime::testing::TestSignature;
+
+    type FeeBurnShare = FeeBurnShare;
+
+    type FeeTreasuryShare = FeeTreasuryShare;
+
+    type Treasury = ToTreasury;
+}
+
+type TestRules =
+    crate::traits::RulesLabelPolicy<LabelMinLen, LabelMaxLen, StrictHyphens, Internationalized>;
+
+/// The rules of the statics above, or the ones of `()` with `DefaultLabelPolicy`.
+pub struct TestLabelPolicy;
+
+impl crate::traits::LabelPolicy for TestLabelPolicy {
+    fn normalize(label: &[u8]) -> Option<Vec<u8>> {
+        if DefaultLabelPolicy::get() {
+            <() as crate::traits::LabelPolicy>::normalize(label)
+        } else {
+            <TestRules as crate::traits::LabelPolicy>::normalize(label)
+        }
+    }
+
+    fn normalize_auctionable(label: &[u8]) -> Option<Vec<u8>> {
+        if DefaultLabelPolicy::get() {
+            <() as crate::traits::LabelPolicy>::normalize_auctionable(label)
+        } else {
+            <TestRules as crate::traits::LabelPolicy>::normalize_auctionable(label)
+        }
+    }
+}
+
+/// Draws `RandomSeed`, known since the current block.
+pub struct TestRandomness;
+
+impl frame_support::traits::Randomness<H256, u64> for TestRandomness {
+    fn random(_subject: &[u8]) -> (H256, u64) {
+        let mut seed = H256::zero();
+        seed.0[..4].copy_from_slice(&RandomSeed::get().to_le_bytes());
+        (seed, System::block_number())
+    }
+}
+
+pub struct ToTreasury;
+
+impl frame_support::traits::OnUnbalanced<pallet_balances::NegativeImbalance<Test>> for ToTreasury {
+    fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Test>) {
+        use frame_support::traits::Currency;
+        Balances::resolve_creating(&TREASURY_ACCOUNT, amount);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainLifecycle {
+    Registered(DomainHash, AccountId, Moment),
+    Renewed(DomainHash, Moment),
+    Transferred(DomainHash, AccountId, AccountId),
+    Expired(DomainHash, AccountId),
+}
+
+/// Records every call into `LifecycleLog`.
+pub struct TestLifecycle;
+
+impl crate::traits::OnDomainLifecycle<AccountId, Moment> for TestLifecycle {
+    fn on_registered(node: DomainHash, owner: &AccountId, expire: Moment) {
+        LifecycleLog::mutate(|log| log.push(DomainLifecycle::Registered(node, *owner, expire)));
+    }
+
+    fn on_renewed(node: DomainHash, expire: Moment) {
+        LifecycleLog::mutate(|log| log.push(DomainLifecycle::Renewed(node, expire)));
+    }
+
+    fn on_transferred(node: DomainHash, from: &AccountId, to: &AccountId) {
+        LifecycleLog::mutate(|log| log.push(DomainLifecycle::Transferred(node, *from, *to)));
+    }
+
+    fn on_expired(node: DomainHash, owner: &AccountId) {
+        LifecycleLog::mutate(|log| log.push(DomainLifecycle::Expired(node, *owner)));
+    }
 }
 
 impl crate::price_oracle::Config for Test {
@@ -260,7 +546,84 @@ impl crate::price_oracle::Config for Test {
 
     type ExchangeRate = TestRate;
 
-    type ManagerOrigin = ManagerOrigin;
+    type AssetId = u32;
+
+    type MaxPriceTiers = MaxPriceTiers;
+
+    type PremiumMultiplier = PremiumMultiplier;
+
+    type PremiumDuration = PremiumDuration;
+
+    type MinPriceNotice = MinPriceNotice;
+
+    type MaxDurationDiscounts = MaxDurationDiscounts;
+
+    type OracleInterval = OracleInterval;
+
+    type MaxRateChange = MaxRateChange;
+
+    type RateChangeCooldown = RateChangeCooldown;
+
+    type MaxEndpoints = MaxEndpoints;
+
+    type MaxEndpointLength = MaxEndpointLength;
+
+    type UnsignedPriority = UnsignedPriority;
+
+    type DemandEpoch = DemandEpoch;
+
+    type MaxDemandAdjustment = MaxDemandAdjustment;
+
+    type MinDemandMultiplier = MinDemandMultiplier;
+
+    type MaxDemandMultiplier = MaxDemandMultiplier;
+
+    type ManagerOrigin = frame_support::traits::EitherOf<
+        crate::origin::EnsurePriceAdmin<Test>,
+        crate::origin::EnsureCollectiveMajority<AccountId, CouncilCollective, CouncilAccount>,
+    >;
+
+    type AuditLog = ManagerOrigin;
+}
+
+parameter_types! {
+    pub const MaxPriceTiers: u32 = 11;
+    pub const MinPriceNotice: u64 = 5;
+    pub const MaxDurationDiscounts: u32 = 5;
+    pub const OracleInterval: u64 = 10;
+    pub const MaxRateChange: Perbill = Perbill::from_percent(10);
+    pub const RateChangeCooldown: u64 = 10;
+    pub const MaxEndpoints: u32 = 3;
+    pub const MaxEndpointLength: u32 = 64;
+    pub const UnsignedPriority: u64 = 1 << 20;
+    pub const DemandEpoch: u64 = 100;
+    pub const MaxDemandAdjustment: Perbill = Perbill::from_percent(10);
+    pub MinDemandMultiplier: FixedU128 = FixedU128::saturating_from_rational(1, 2);
+    pub MaxDemandMultiplier: FixedU128 = FixedU128::saturating_from_integer(2);
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+    RuntimeCall: From<C>,
+{
+    type OverarchingCall = RuntimeCall;
+    type Extrinsic = UncheckedExtrinsic;
+}
+
+parameter_types! {
+    pub static FeedRate: Option<(Balance, u64)> = None;
+    pub const MaxFeedAge: u64 = 60_000;
+}
+
+pub struct TestFeed;
+
+impl crate::traits::PriceFeed for TestFeed {
+    type Balance = Balance;
+    type Moment = u64;
+
+    fn latest_rate() -> Option<(Balance, u64)> {
+        FeedRate::get()
+    }
 }
 
 pub struct TestRate;
@@ -288,7 +651,21 @@ impl crate::redeem_code::Config for Test {
 
     type Official = crate::registry::Pallet<Test>;
 
-    type ManagerOrigin = ManagerOrigin;
+    type MaxRedeemUses = MaxRedeemUses;
+
+    type MaxCampaignNameLength = MaxCampaignNameLength;
+
+    type MaxBatchDepth = MaxBatchDepth;
+
+    type ManagerOrigin = crate::origin::EnsureRedeemAdmin<Test>;
+
+    type AuditLog = ManagerOrigin;
+}
+
+parameter_types! {
+    pub const MaxRedeemUses: u32 = 100;
+    pub const MaxCampaignNameLength: u32 = 32;
+    pub const MaxBatchDepth: u32 = 20;
 }
 
 parameter_types! {
@@ -313,6 +690,56 @@ parameter_types! {
     pub const MinimumPeriod: u64 = SLOT_DURATION / 2;
 }
 
+pub type CouncilCollective = pallet_collective::Instance1;
+
+pub type GovernanceOrigin = frame_support::traits::EitherOf<
+    frame_system::EnsureRootWithSuccess<AccountId, CouncilAccount>,
+    crate::origin::EnsureCollectiveMajority<AccountId, CouncilCollective, CouncilAccount>,
+>;
+
+parameter_types! {
+    pub const CouncilAccount: AccountId = COUNCIL_ACCOUNT;
+    pub const CouncilMotionDuration: u64 = 10;
+    pub const CouncilMaxProposals: u32 = 10;
+    pub const CouncilMaxMembers: u32 = 10;
+}
+
+impl pallet_collective::Config<CouncilCollective> for Test {
+    type RuntimeOrigin = RuntimeOrigin;
+    type Proposal = RuntimeCall;
+    type RuntimeEvent = RuntimeEvent;
+    type MotionDuration = CouncilMotionDuration;
+    type MaxProposals = CouncilMaxProposals;
+    type MaxMembers = CouncilMaxMembers;
+    type DefaultVote = pallet_collective::PrimeDefaultVote;
+    type WeightInfo = ();
+    type SetMembersOrigin = frame_system::EnsureRoot<AccountId>;
+}
+
+impl pallet_assets::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type Balance = Balance;
+    type RemoveItemsLimit = frame_support::traits::ConstU32<1000>;
+    type AssetId = u32;
+    type AssetIdParameter = codec::Compact<u32>;
+    type Currency = Balances;
+    type CreateOrigin =
+        frame_support::traits::AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+    type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+    type AssetDeposit = frame_support::traits::ConstU128<0>;
+    type AssetAccountDeposit = frame_support::traits::ConstU128<0>;
+    type MetadataDepositBase = frame_support::traits::ConstU128<0>;
+    type MetadataDepositPerByte = frame_support::traits::ConstU128<0>;
+    type ApprovalDeposit = frame_support::traits::ConstU128<0>;
+    type StringLimit = frame_support::traits::ConstU32<50>;
+    type Freezer = ();
+    type Extra = ();
+    type CallbackHandle = ();
+    type WeightInfo = ();
+    #[cfg(feature = "runtime-benchmarks")]
+    type BenchmarkHelper = ();
+}
+
 impl pallet_timestamp::Config for Test {
     /// A timestamp: milliseconds since the unix epoch.
     type Moment = u64;
@@ -0,0 +1,339 @@
+//! A runtime minting the domains through [`crate::nft_adapter`], in `pallet-nfts`
+//! or `pallet-uniques` depending on `UseNftsV2`.
+use frame_support::{
+    parameter_types,
+    traits::{AsEnsureOriginWithArg, ConstU128, ConstU32, ConstU64},
+};
+use pns_types::DomainHash;
+use sp_core::H256;
+use sp_runtime::{
+    testing::{Header, TestSignature, UintAuthorityId},
+    traits::{BlakeTwo256, IdentityLookup},
+    DispatchResult,
+};
+
+use crate::{
+    nft_adapter::{NonfungiblesAdapter, NonfungiblesV2Adapter},
+    traits::{Label, NftBackend},
+};
+
+pub type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+pub type Block = frame_system::mocking::MockBlock<Test>;
+pub type Balance = u128;
+pub type AccountId = u64;
+
+pub const OFFICIAL_ACCOUNT: AccountId = 0;
+pub const MANAGER_ACCOUNT: AccountId = 1;
+pub const ALICE: AccountId = 2;
+pub const BOB: AccountId = 3;
+
+pub const DOT_BASENODE: H256 = crate::mock::DOT_BASENODE;
+
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system,
+        Balances: pallet_balances,
+        Registry: crate::registry,
+        Nfts: pallet_nfts,
+        Uniques: pallet_uniques,
+    }
+);
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type RuntimeEvent = RuntimeEvent;
+    type BlockHashCount = ConstU64<250>;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ();
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = Balance;
+    type RuntimeEvent = RuntimeEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ConstU128<1>;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const ManagerAccount: AccountId = MANAGER_ACCOUNT;
+    pub const PnsCollection: u32 = 0;
+    pub static UseNftsV2: bool = true;
+    pub NftsFeatures: pallet_nfts::PalletFeatures = pallet_nfts::PalletFeatures::all_enabled();
+}
+
+impl crate::registry::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+
+    type WeightInfo = ();
+
+    type Registrar = TestRegistrar;
+
+    type ResolverId = u32;
+
+    type ManagerOrigin = frame_system::EnsureRootWithSuccess<AccountId, ManagerAccount>;
+
+    type AuditLog = ();
+
+    type Nft = TestNft;
+
+    type MaxUriLength = ConstU32<15>;
+
+    type GovernanceOrigin = frame_system::EnsureRootWithSuccess<AccountId, ManagerAccount>;
+
+    type Currency = Balances;
+
+    type AppealPeriod = ConstU64<5>;
+
+    type FreezeConfirmPeriod = ConstU64<10>;
+
+    type AppealBond = ConstU128<0>;
+
+    type Arbitration = ();
+
+    type MaxTransferAll = ConstU32<2>;
+}
+
+impl pallet_nfts::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type CollectionId = u32;
+    type ItemId = DomainHash;
+    type Currency = Balances;
+    type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+    type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+    type Locker = ();
+    type CollectionDeposit = ConstU128<0>;
+    type ItemDeposit = ConstU128<0>;
+    type MetadataDepositBase = ConstU128<0>;
+    type AttributeDepositBase = ConstU128<0>;
+    type DepositPerByte = ConstU128<0>;
+    type StringLimit = ConstU32<50>;
+    type KeyLimit = ConstU32<50>;
+    type ValueLimit = ConstU32<50>;
+    type ApprovalsLimit = ConstU32<10>;
+    type ItemAttributesApprovalsLimit = ConstU32<2>;
+    type MaxTips = ConstU32<10>;
+    type MaxDeadlineDuration = ConstU64<10000>;
+    type MaxAttributesPerCall = ConstU32<2>;
+    type Features = NftsFeatures;
+    type OffchainSignature = TestSignature;
+    type OffchainPublic = UintAuthorityId;
+    type WeightInfo = ();
+}
+
+impl pallet_uniques::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type CollectionId = u32;
+    type ItemId = DomainHash;
+    type Currency = Balances;
+    type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+    type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+    type Locker = ();
+    type CollectionDeposit = ConstU128<0>;
+    type ItemDeposit = ConstU128<0>;
+    type MetadataDepositBase = ConstU128<0>;
+    type AttributeDepositBase = ConstU128<0>;
+    type DepositPerByte = ConstU128<0>;
+    type StringLimit = ConstU32<50>;
+    type KeyLimit = ConstU32<50>;
+    type ValueLimit = ConstU32<50>;
+    type WeightInfo = ();
+}
+
+type V2 = NonfungiblesV2Adapter<Nfts, PnsCollection, pallet_nfts::ItemConfig>;
+type V1 = NonfungiblesAdapter<Uniques, PnsCollection>;
+
+/// [`NonfungiblesV2Adapter`] over `pallet-nfts`, or [`NonfungiblesAdapter`]
+/// over `pallet-uniques` without `UseNftsV2`.
+pub struct TestNft;
+
+impl NftBackend<AccountId> for TestNft {
+    type ClassId = u32;
+
+    fn class_id() -> u32 {
+        PnsCollection::get()
+    }
+
+    fn owner(node: DomainHash) -> Option<AccountId> {
+        if UseNftsV2::get() {
+            V2::owner(node)
+        } else {
+            V1::owner(node)
+        }
+    }
+
+    fn mint(owner: &AccountId, node: DomainHash, metadata: Vec<u8>) -> DispatchResult {
+        if UseNftsV2::get() {
+            V2::mint(owner, node, metadata)
+        } else {
+            V1::mint(owner, node, metadata)
+        }
+    }
+
+    fn transfer(from: &AccountId, to: &AccountId, node: DomainHash) -> DispatchResult {
+        if UseNftsV2::get() {
+            V2::transfer(from, to, node)
+        } else {
+            V1::transfer(from, to, node)
+        }
+    }
+
+    fn burn(owner: &AccountId, node: DomainHash) -> DispatchResult {
+        if UseNftsV2::get() {
+            V2::burn(owner, node)
+        } else {
+            V1::burn(owner, node)
+        }
+    }
+
+    fn set_metadata(node: DomainHash, metadata: Vec<u8>) -> DispatchResult {
+        if UseNftsV2::get() {
+            V2::set_metadata(node, metadata)
+        } else {
+            V1::set_metadata(node, metadata)
+        }
+    }
+
+    fn set_class_owner(owner: &AccountId) -> DispatchResult {
+        if UseNftsV2::get() {
+            V2::set_class_owner(owner)
+        } else {
+            V1::set_class_owner(owner)
+        }
+    }
+}
+
+/// Every domain is registered forever, the registry is what is tested here.
+pub struct TestRegistrar;
+
+impl crate::traits::Registrar for TestRegistrar {
+    type Balance = Balance;
+    type AccountId = AccountId;
+    type Moment = u64;
+
+    fn check_expires_registrable(_node: DomainHash) -> DispatchResult {
+        Ok(())
+    }
+
+    fn check_expires_renewable(_node: DomainHash) -> DispatchResult {
+        Ok(())
+    }
+
+    fn check_expires_useable(_node: DomainHash) -> DispatchResult {
+        Ok(())
+    }
+
+    fn check_unlocked(_node: DomainHash) -> DispatchResult {
+        Ok(())
+    }
+
+    fn clear_registrar_info(_node: DomainHash, _owner: &AccountId) -> DispatchResult {
+        Ok(())
+    }
+
+    fn clear_subname_info(_node: DomainHash) {}
+
+    fn on_transferred(_node: DomainHash, _from: &AccountId, _to: &AccountId) {}
+
+    fn for_redeem_code(
+        _name: Vec<u8>,
+        _to: AccountId,
+        _duration: u64,
+        _label: Label,
+    ) -> DispatchResult {
+        Ok(())
+    }
+
+    fn for_redeem_code_in(
+        _base_node: DomainHash,
+        _name: Vec<u8>,
+        _to: AccountId,
+        _duration: u64,
+        _label: Label,
+    ) -> DispatchResult {
+        Ok(())
+    }
+
+    fn for_renew_code(_caller: &AccountId, _name: Vec<u8>, _duration: u64) -> DispatchResult {
+        Ok(())
+    }
+
+    fn basenode() -> DomainHash {
+        DOT_BASENODE
+    }
+
+    fn base_nodes() -> Vec<DomainHash> {
+        vec![DOT_BASENODE]
+    }
+
+    fn label_of(name: &[u8]) -> Option<(Label, usize)> {
+        Label::new_with_len(name)
+    }
+}
+
+/// The `PnsCollection` of both pallets is created, owned by the official account.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    use frame_support::traits::GenesisBuild;
+
+    let mut genesis_storage = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+
+    let registry_genesis = crate::registry::GenesisConfig::<Test> {
+        official: Some(OFFICIAL_ACCOUNT),
+        ..Default::default()
+    };
+    <crate::registry::GenesisConfig<Test> as GenesisBuild<Test>>::assimilate_storage(
+        &registry_genesis,
+        &mut genesis_storage,
+    )
+    .unwrap();
+
+    let mut ext: sp_io::TestExternalities = genesis_storage.into();
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        frame_support::assert_ok!(Nfts::force_create(
+            RuntimeOrigin::root(),
+            OFFICIAL_ACCOUNT,
+            pallet_nfts::CollectionConfig {
+                settings: pallet_nfts::CollectionSettings::all_enabled(),
+                max_supply: None,
+                mint_settings: Default::default(),
+            },
+        ));
+        frame_support::assert_ok!(Uniques::force_create(
+            RuntimeOrigin::root(),
+            PnsCollection::get(),
+            OFFICIAL_ACCOUNT,
+            true,
+        ));
+    });
+    ext
+}
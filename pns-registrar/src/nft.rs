@@ -38,15 +38,19 @@
 
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::{ensure, pallet_prelude::*, traits::Get, BoundedVec, Parameter};
+use pns_types::DomainHash;
 use scale_info::TypeInfo;
 use sp_runtime::{
     traits::{
-        AtLeast32BitUnsigned, CheckedAdd, CheckedSub, MaybeSerializeDeserialize, Member, One,
+        AtLeast32BitUnsigned, CheckedAdd, CheckedSub, MaybeSerializeDeserialize, Member, One, Zero,
     },
     ArithmeticError, DispatchError, DispatchResult, RuntimeDebug,
 };
 use sp_std::vec::Vec;
 
+/// Upper bound of tokens returned by one [`Pallet::tokens_of_owner`] call.
+pub const MAX_TOKENS_PAGE: u32 = 256;
+
 /// Class info
 #[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
 pub struct ClassInfo<TotalId, AccountId, Data, ClassMetadataOf> {
@@ -80,20 +84,26 @@ pub mod module {
     #[pallet::config]
     pub trait Config: frame_system::Config {
         /// The class ID type
-        type ClassId: Parameter + Member + AtLeast32BitUnsigned + Default + Copy;
+        type ClassId: Parameter + Member + AtLeast32BitUnsigned + Default + Copy + MaxEncodedLen;
         /// The total ID type
         type TotalId: Parameter
             + Member
             + AtLeast32BitUnsigned
             + Default
             + Copy
-            + MaybeSerializeDeserialize;
+            + MaybeSerializeDeserialize
+            + MaxEncodedLen;
         /// The token ID type
-        type TokenId: Parameter + Member + Default + Copy + MaybeSerializeDeserialize;
+        type TokenId: Parameter
+            + Member
+            + Default
+            + Copy
+            + MaybeSerializeDeserialize
+            + MaxEncodedLen;
         /// The class properties type
-        type ClassData: Parameter + Member + MaybeSerializeDeserialize;
+        type ClassData: Parameter + Member + MaybeSerializeDeserialize + MaxEncodedLen;
         /// The token properties type
-        type TokenData: Parameter + Member + MaybeSerializeDeserialize;
+        type TokenData: Parameter + Member + MaybeSerializeDeserialize + MaxEncodedLen;
         /// The maximum size of a class's metadata
         type MaxClassMetadata: Get<u32>;
         /// The maximum size of a token's metadata
@@ -214,7 +224,6 @@ pub mod module {
     }
 
     #[pallet::pallet]
-    #[pallet::without_storage_info]
     pub struct Pallet<T>(_);
 
     #[pallet::hooks]
@@ -277,6 +286,37 @@ impl<T: Config> Pallet<T> {
         })
     }
 
+    /// Transfer several NFTs(non fungible tokens) of `from` to `to`,
+    /// none of them if any is not owned by `from`
+    pub fn transfer_batch(
+        from: &T::AccountId,
+        to: &T::AccountId,
+        tokens: &[(T::ClassId, T::TokenId)],
+    ) -> DispatchResult {
+        for (class_id, token_id) in tokens {
+            let info = Tokens::<T>::get(class_id, token_id).ok_or(Error::<T>::TokenNotFound)?;
+            ensure!(info.owner == *from, Error::<T>::NoPermission);
+        }
+        if from == to {
+            // no change needed
+            return Ok(());
+        }
+
+        for (class_id, token_id) in tokens {
+            Tokens::<T>::mutate(class_id, token_id, |token_info| {
+                if let Some(info) = token_info {
+                    info.owner = to.clone();
+                }
+            });
+        }
+        for (class_id, token_id) in tokens {
+            TokensByOwner::<T>::remove((from, class_id, token_id));
+            TokensByOwner::<T>::insert((to, class_id, token_id), ());
+        }
+
+        Ok(())
+    }
+
     /// Mint NFT(non fungible token) to `owner`
     pub fn mint(
         owner: &T::AccountId,
@@ -310,6 +350,19 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    /// Replace the metadata of NFT(non fungible token)
+    pub fn set_metadata(token: (T::ClassId, T::TokenId), metadata: Vec<u8>) -> DispatchResult {
+        let bounded_metadata: BoundedVec<u8, T::MaxTokenMetadata> = metadata
+            .try_into()
+            .map_err(|_| Error::<T>::MaxMetadataExceeded)?;
+
+        Tokens::<T>::try_mutate(token.0, token.1, |token_info| -> DispatchResult {
+            let info = token_info.as_mut().ok_or(Error::<T>::TokenNotFound)?;
+            info.metadata = bounded_metadata;
+            Ok(())
+        })
+    }
+
     /// Burn NFT(non fungible token) from `owner`
     pub fn burn(owner: &T::AccountId, token: (T::ClassId, T::TokenId)) -> DispatchResult {
         Tokens::<T>::try_mutate_exists(token.0, token.1, |token_info| -> DispatchResult {
@@ -341,9 +394,7 @@ impl<T: Config> Pallet<T> {
     //             Error::<T>::CannotDestroyClass
     //         );
 
-    //         Tokens::<T>::remove_prefix(class_id, None);
-
-    //         let res = Tokens::<T>::clear_prefix(class_id,u32::MAX,None);
+    //         // nothing is left to clear, the total issuance is zero.
 
     //         Ok(())
     //     })
@@ -352,4 +403,95 @@ impl<T: Config> Pallet<T> {
     pub fn is_owner(account: &T::AccountId, token: (T::ClassId, T::TokenId)) -> bool {
         TokensByOwner::<T>::contains_key((account, token.0, token.1))
     }
+
+    /// Up to `limit` tokens of `owner`, at most [`MAX_TOKENS_PAGE`], after `start_key` if given.
+    ///
+    /// Also returns the key to continue with, `None` if nothing is left.
+    #[allow(clippy::type_complexity)]
+    pub fn tokens_of_owner(
+        owner: &T::AccountId,
+        start_key: Option<(T::ClassId, T::TokenId)>,
+        limit: u32,
+    ) -> (
+        Vec<(T::ClassId, T::TokenId)>,
+        Option<(T::ClassId, T::TokenId)>,
+    ) {
+        let limit = limit.min(MAX_TOKENS_PAGE) as usize;
+        let tokens = match start_key {
+            Some((class_id, token_id)) => {
+                let raw_key = TokensByOwner::<T>::hashed_key_for((owner, class_id, token_id));
+                TokensByOwner::<T>::iter_key_prefix_from((owner,), raw_key)
+                    .take(limit)
+                    .collect::<Vec<_>>()
+            }
+            None => TokensByOwner::<T>::iter_key_prefix((owner,))
+                .take(limit)
+                .collect::<Vec<_>>(),
+        };
+
+        let next = if limit != 0 && tokens.len() == limit {
+            tokens.last().cloned()
+        } else {
+            None
+        };
+        (tokens, next)
+    }
+}
+
+impl<T: Config<TokenId = DomainHash>> crate::traits::NftBackend<T::AccountId> for Pallet<T>
+where
+    T::TokenData: Default,
+{
+    type ClassId = T::ClassId;
+
+    fn class_id() -> T::ClassId {
+        Zero::zero()
+    }
+
+    fn owner(node: DomainHash) -> Option<T::AccountId> {
+        Tokens::<T>::get(Self::class_id(), node).map(|token| token.owner)
+    }
+
+    fn mint(owner: &T::AccountId, node: DomainHash, metadata: Vec<u8>) -> DispatchResult {
+        Self::mint(
+            owner,
+            (Self::class_id(), node),
+            metadata,
+            Default::default(),
+        )
+    }
+
+    fn transfer(from: &T::AccountId, to: &T::AccountId, node: DomainHash) -> DispatchResult {
+        Self::transfer(from, to, (Self::class_id(), node))
+    }
+
+    fn transfer_batch(
+        from: &T::AccountId,
+        to: &T::AccountId,
+        nodes: &[DomainHash],
+    ) -> DispatchResult {
+        let class_id = Self::class_id();
+        let tokens = nodes
+            .iter()
+            .map(|node| (class_id, *node))
+            .collect::<Vec<_>>();
+        Self::transfer_batch(from, to, &tokens)
+    }
+
+    fn burn(owner: &T::AccountId, node: DomainHash) -> DispatchResult {
+        Self::burn(owner, (Self::class_id(), node))
+    }
+
+    fn set_metadata(node: DomainHash, metadata: Vec<u8>) -> DispatchResult {
+        Self::set_metadata((Self::class_id(), node), metadata)
+    }
+
+    fn set_class_owner(owner: &T::AccountId) -> DispatchResult {
+        Classes::<T>::mutate(Self::class_id(), |info| {
+            if let Some(info) = info {
+                info.owner = owner.clone();
+            }
+        });
+        Ok(())
+    }
 }
@@ -0,0 +1,152 @@
+//! # Nft adapter
+//!
+//! Lets the registry mint the domains in a standard nft pallet instead of
+//! the bundled [`crate::nft`], through the `nonfungibles` traits of FRAME:
+//!
+//! - [`NonfungiblesAdapter`] - for `pallet-uniques`
+//! - [`NonfungiblesV2Adapter`] - for `pallet-nfts`
+//!
+//! ```ignore
+//! parameter_types! {
+//!     pub const PnsCollection: u32 = 0;
+//! }
+//!
+//! impl pns_registrar::registry::Config for Runtime {
+//!     type Nft = NonfungiblesV2Adapter<Nfts, PnsCollection, pallet_nfts::ItemConfig>;
+//!     // ...
+//! }
+//! ```
+//!
+//! The collection has to be created beforehand, without deposits and with
+//! domain hashes as item ids. Its owner is managed by the nft pallet, so
+//! `set_official` leaves it alone. The token metadata is stored in the
+//! `metadata` attribute of the item, `pallet-uniques` has no attributes in
+//! `nonfungibles::Mutate` so `registry::set_token_metadata` fails with
+//! `TokenError::Unsupported` there.
+use core::marker::PhantomData;
+
+use frame_support::{
+    traits::{
+        tokens::{nonfungibles, nonfungibles_v2},
+        Get,
+    },
+    Parameter,
+};
+use pns_types::DomainHash;
+use sp_runtime::{traits::Member, DispatchResult, TokenError};
+use sp_std::vec::Vec;
+
+use crate::traits::NftBackend;
+
+/// The attribute the token metadata is stored in.
+pub const METADATA_KEY: &[u8] = b"metadata";
+
+/// [`NftBackend`] over the `nonfungibles` traits, minting in the `Collection` of `Nfts`.
+pub struct NonfungiblesAdapter<Nfts, Collection>(PhantomData<(Nfts, Collection)>);
+
+impl<AccountId, Nfts, Collection> NftBackend<AccountId> for NonfungiblesAdapter<Nfts, Collection>
+where
+    AccountId: PartialEq,
+    Nfts: nonfungibles::Mutate<AccountId, ItemId = DomainHash> + nonfungibles::Transfer<AccountId>,
+    Nfts::CollectionId: Parameter + Member + Default + Copy,
+    Collection: Get<Nfts::CollectionId>,
+{
+    type ClassId = Nfts::CollectionId;
+
+    fn class_id() -> Self::ClassId {
+        Collection::get()
+    }
+
+    fn owner(node: DomainHash) -> Option<AccountId> {
+        Nfts::owner(&Collection::get(), &node)
+    }
+
+    fn mint(owner: &AccountId, node: DomainHash, metadata: Vec<u8>) -> DispatchResult {
+        Nfts::mint_into(&Collection::get(), &node, owner)?;
+        if !metadata.is_empty() {
+            Self::set_metadata(node, metadata)?;
+        }
+        Ok(())
+    }
+
+    fn transfer(from: &AccountId, to: &AccountId, node: DomainHash) -> DispatchResult {
+        frame_support::ensure!(
+            Self::owner(node).as_ref() == Some(from),
+            TokenError::UnknownAsset
+        );
+        Nfts::transfer(&Collection::get(), &node, to)
+    }
+
+    fn burn(owner: &AccountId, node: DomainHash) -> DispatchResult {
+        Nfts::burn(&Collection::get(), &node, Some(owner))
+    }
+
+    fn set_metadata(node: DomainHash, metadata: Vec<u8>) -> DispatchResult {
+        Nfts::set_attribute(&Collection::get(), &node, METADATA_KEY, &metadata)
+    }
+
+    fn set_class_owner(_owner: &AccountId) -> DispatchResult {
+        Ok(())
+    }
+}
+
+/// [`NftBackend`] over the `nonfungibles_v2` traits, minting in the `Collection`
+/// of `Nfts` with the default `ItemConfig`.
+pub struct NonfungiblesV2Adapter<Nfts, Collection, ItemConfig>(
+    PhantomData<(Nfts, Collection, ItemConfig)>,
+);
+
+impl<AccountId, Nfts, Collection, ItemConfig> NftBackend<AccountId>
+    for NonfungiblesV2Adapter<Nfts, Collection, ItemConfig>
+where
+    AccountId: PartialEq,
+    Nfts: nonfungibles_v2::Mutate<AccountId, ItemConfig, ItemId = DomainHash>
+        + nonfungibles_v2::Transfer<AccountId>,
+    Nfts::CollectionId: Parameter + Member + Default + Copy,
+    Collection: Get<Nfts::CollectionId>,
+    ItemConfig: Default,
+{
+    type ClassId = Nfts::CollectionId;
+
+    fn class_id() -> Self::ClassId {
+        Collection::get()
+    }
+
+    fn owner(node: DomainHash) -> Option<AccountId> {
+        Nfts::owner(&Collection::get(), &node)
+    }
+
+    fn mint(owner: &AccountId, node: DomainHash, metadata: Vec<u8>) -> DispatchResult {
+        Nfts::mint_into(
+            &Collection::get(),
+            &node,
+            owner,
+            &ItemConfig::default(),
+            false,
+        )?;
+        if !metadata.is_empty() {
+            Self::set_metadata(node, metadata)?;
+        }
+        Ok(())
+    }
+
+    fn transfer(from: &AccountId, to: &AccountId, node: DomainHash) -> DispatchResult {
+        frame_support::ensure!(
+            Self::owner(node).as_ref() == Some(from),
+            TokenError::UnknownAsset
+        );
+        Nfts::transfer(&Collection::get(), &node, to)
+    }
+
+    fn burn(owner: &AccountId, node: DomainHash) -> DispatchResult {
+        Nfts::burn(&Collection::get(), &node, Some(owner))
+    }
+
+    fn set_metadata(node: DomainHash, metadata: Vec<u8>) -> DispatchResult {
+        Nfts::set_attribute(&Collection::get(), &node, METADATA_KEY, &metadata)
+    }
+
+    fn set_class_owner(_owner: &AccountId) -> DispatchResult {
+        Ok(())
+    }
+}
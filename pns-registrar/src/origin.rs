@@ -10,17 +10,35 @@
 //! This module provides storage to mark which account is the manager
 //!  and to enable or disable `pns` domain registration.
 //!
+//! Besides the managers, which hold every power, accounts can be given
+//! a single [`Role`], checked by the [`EnsureRole`] adapters the other
+//! pallets are configured with, e.g. so the price setter key can't also
+//! freeze domains.
+//!
+//! Governance bodies can administer PNS too: [`EnsureCollectiveMajority`]
+//! and [`EnsureCollectiveProportion`] accept the motions of a
+//! `pallet-collective` instance on behalf of a configured account, and
+//! combine with the roles through `frame_support::traits::EitherOf`.
+//!
+//! Every privileged call of the PNS pallets is recorded in a ring buffer
+//! of the last `MaxAuditEntries` calls, served by `audit_log`.
+//!
 //! ### Module functions
 //!
 //! - `set_registrar_open` - sets whether to turn on domain registration
+//! - `set_paused` - pauses or resumes a single [`Operation`](crate::traits::Operation)
 //! - `set_origin` - sets the manager (caller must be an manager)
 //! - `set_origin_for_root` - sets the manager (caller must be `Root`)
+//! - `set_role` - grants or revokes a role (caller must be an manager)
+//! - `propose_origin` - proposes an account to take over the caller's manager seat
+//! - `accept_origin` - accepts a proposal, the proposer stops being a manager
 
 pub use pallet::*;
 
 #[frame_support::pallet]
 pub mod pallet {
-    use super::WeightInfo;
+    use super::{Role, WeightInfo};
+    use crate::traits::{AuditLog, Operation};
     use frame_support::pallet_prelude::*;
     use frame_support::traits::{EnsureOrigin, Get};
     use frame_system::pallet_prelude::*;
@@ -31,17 +49,45 @@ pub mod pallet {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
         type WeightInfo: WeightInfo;
+
+        /// How many privileged calls are kept in `AuditEntries`, the oldest are overwritten.
+        #[pallet::constant]
+        type MaxAuditEntries: Get<u32>;
     }
 
+    pub type AuditEntryOf<T> =
+        pns_types::AuditEntry<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
     #[pallet::pallet]
     pub struct Pallet<T>(_);
 
     #[pallet::storage]
     pub type Origins<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, ()>;
 
+    /// Roles of the accounts which are not managers.
+    #[pallet::storage]
+    pub type Roles<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, Role, ()>;
+
+    /// The proposed manager => the manager handing over to it.
+    #[pallet::storage]
+    pub type ProposedOrigins<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, T::AccountId>;
+
+    /// Ring buffer of the recorded privileged calls, see `crate::traits::AuditLog`.
+    #[pallet::storage]
+    pub type AuditEntries<T: Config> = StorageMap<_, Twox64Concat, u32, AuditEntryOf<T>>;
+
+    /// How many privileged calls were ever recorded.
+    #[pallet::storage]
+    pub type AuditCount<T: Config> = StorageValue<_, u64, ValueQuery>;
+
     #[pallet::storage]
     pub type IsRegistrarOpen<T: Config> = StorageValue<_, bool, ValueQuery, DefaultOpen>;
 
+    /// Bitmask of the paused operations, see `Operation::bit`.
+    #[pallet::storage]
+    pub type PausedOperations<T: Config> = StorageValue<_, u32, ValueQuery>;
+
     pub struct DefaultOpen;
 
     impl Get<bool> for DefaultOpen {
@@ -53,6 +99,7 @@ pub mod pallet {
     #[pallet::genesis_config]
     pub struct GenesisConfig<T: Config> {
         pub origins: sp_std::vec::Vec<T::AccountId>,
+        pub roles: sp_std::vec::Vec<(T::AccountId, Role)>,
     }
 
     #[cfg(feature = "std")]
@@ -60,6 +107,7 @@ pub mod pallet {
         fn default() -> Self {
             Self {
                 origins: sp_std::vec::Vec::with_capacity(0),
+                roles: sp_std::vec::Vec::with_capacity(0),
             }
         }
     }
@@ -70,6 +118,9 @@ pub mod pallet {
             for origin in self.origins.iter() {
                 Origins::<T>::insert(origin, ())
             }
+            for (account, role) in self.roles.iter() {
+                Roles::<T>::insert(account, role, ())
+            }
         }
     }
 
@@ -78,6 +129,26 @@ pub mod pallet {
     pub enum Event<T: Config> {
         AddedOrigin(T::AccountId),
         RemovedOrigin(T::AccountId),
+        /// The registrar was opened (`true`) or closed (`false`).
+        RegistrarOpenChanged(bool),
+        /// `[account, role]`
+        RoleGranted(T::AccountId, Role),
+        /// `[account, role]`
+        RoleRevoked(T::AccountId, Role),
+        /// `[manager, proposed]`
+        OriginProposed(T::AccountId, T::AccountId),
+        /// `[old, new]`
+        OriginAccepted(T::AccountId, T::AccountId),
+        /// `[operation, paused]`
+        PauseChanged(Operation, bool),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The caller has not been proposed as a manager.
+        NotProposed,
+        /// The account which proposed the caller is not a manager anymore.
+        ProposerNotManager,
     }
 
     #[pallet::call]
@@ -85,10 +156,12 @@ pub mod pallet {
         #[pallet::call_index(0)]
         #[pallet::weight(T::WeightInfo::set_registrar_open())]
         pub fn set_registrar_open(origin: OriginFor<T>, is_open: bool) -> DispatchResult {
-            let _who = Self::ensure_origin(origin)?;
+            let manager = Self::ensure_origin(origin)?;
+            Self::record_call::<Self, _>(&manager, &Call::<T>::set_registrar_open { is_open });
 
             IsRegistrarOpen::<T>::put(is_open);
 
+            Self::deposit_event(Event::<T>::RegistrarOpenChanged(is_open));
             Ok(())
         }
         #[pallet::call_index(1)]
@@ -98,7 +171,14 @@ pub mod pallet {
             account: <T::Lookup as StaticLookup>::Source,
             approved: bool,
         ) -> DispatchResult {
-            let _who = Self::ensure_origin(origin)?;
+            let manager = Self::ensure_origin(origin)?;
+            Self::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_origin {
+                    account: account.clone(),
+                    approved,
+                },
+            );
             let account = T::Lookup::lookup(account)?;
 
             if approved {
@@ -131,10 +211,220 @@ pub mod pallet {
 
             Ok(())
         }
+        #[pallet::call_index(3)]
+        #[pallet::weight(T::WeightInfo::set_role())]
+        pub fn set_role(
+            origin: OriginFor<T>,
+            account: <T::Lookup as StaticLookup>::Source,
+            role: Role,
+            approved: bool,
+        ) -> DispatchResult {
+            let manager = Self::ensure_origin(origin)?;
+            Self::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_role {
+                    account: account.clone(),
+                    role,
+                    approved,
+                },
+            );
+            let account = T::Lookup::lookup(account)?;
+
+            if approved {
+                Roles::<T>::insert(&account, role, ());
+                Self::deposit_event(Event::<T>::RoleGranted(account, role));
+            } else {
+                Roles::<T>::remove(&account, role);
+                Self::deposit_event(Event::<T>::RoleRevoked(account, role));
+            }
+
+            Ok(())
+        }
+        #[pallet::call_index(4)]
+        #[pallet::weight(T::WeightInfo::propose_origin())]
+        pub fn propose_origin(
+            origin: OriginFor<T>,
+            account: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            let who = Self::ensure_origin(origin)?;
+            Self::record_call::<Self, _>(
+                &who,
+                &Call::<T>::propose_origin {
+                    account: account.clone(),
+                },
+            );
+            let account = T::Lookup::lookup(account)?;
+
+            ProposedOrigins::<T>::insert(&account, &who);
+
+            Self::deposit_event(Event::<T>::OriginProposed(who, account));
+            Ok(())
+        }
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::accept_origin())]
+        pub fn accept_origin(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let old = ProposedOrigins::<T>::get(&who).ok_or(Error::<T>::NotProposed)?;
+            ensure!(
+                Origins::<T>::contains_key(&old),
+                Error::<T>::ProposerNotManager
+            );
+
+            ProposedOrigins::<T>::remove(&who);
+            Origins::<T>::remove(&old);
+            Origins::<T>::insert(&who, ());
+
+            Self::deposit_event(Event::<T>::OriginAccepted(old, who));
+            Ok(())
+        }
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::set_paused())]
+        pub fn set_paused(origin: OriginFor<T>, op: Operation, paused: bool) -> DispatchResult {
+            let manager = Self::ensure_origin(origin)?;
+            Self::record_call::<Self, _>(&manager, &Call::<T>::set_paused { op, paused });
+
+            PausedOperations::<T>::mutate(|ops| {
+                if paused {
+                    *ops |= op.bit()
+                } else {
+                    *ops &= !op.bit()
+                }
+            });
+
+            Self::deposit_event(Event::<T>::PauseChanged(op, paused));
+            Ok(())
+        }
     }
 }
-use frame_support::{dispatch::Weight, traits::EnsureOrigin};
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::marker::PhantomData;
+use frame_support::{
+    dispatch::Weight,
+    traits::{EnsureOrigin, Get, TypedGet},
+};
 use frame_system::RawOrigin;
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// A part of the powers of the managers.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+    /// Manages the registrar and the registry, e.g. the TLDs or frozen domains.
+    RegistrarAdmin,
+    /// Sets the prices and exchange rates.
+    PriceAdmin,
+    /// Manages the reserved names.
+    ReserveAdmin,
+    /// Mints and manages the redeem codes.
+    RedeemAdmin,
+}
+
+/// Ensures the origin is a manager or an account with the role `R`.
+pub struct EnsureRole<T, R>(PhantomData<(T, R)>);
+
+macro_rules! role {
+    ($name:ident) => {
+        pub struct $name;
+
+        impl Get<Role> for $name {
+            fn get() -> Role {
+                Role::$name
+            }
+        }
+    };
+}
+
+role!(RegistrarAdmin);
+role!(PriceAdmin);
+role!(ReserveAdmin);
+role!(RedeemAdmin);
+
+pub type EnsureRegistrarAdmin<T> = EnsureRole<T, RegistrarAdmin>;
+pub type EnsurePriceAdmin<T> = EnsureRole<T, PriceAdmin>;
+pub type EnsureReserveAdmin<T> = EnsureRole<T, ReserveAdmin>;
+pub type EnsureRedeemAdmin<T> = EnsureRole<T, RedeemAdmin>;
+
+/// Accepts the origins of `O` as the account `A`, for the pallets which
+/// expect their manager origins to succeed with an account.
+pub struct AsAccount<O, A>(PhantomData<(O, A)>);
+
+impl<OuterOrigin, O: EnsureOrigin<OuterOrigin>, A: TypedGet> EnsureOrigin<OuterOrigin>
+    for AsAccount<O, A>
+{
+    type Success = A::Type;
+    fn try_origin(o: OuterOrigin) -> Result<Self::Success, OuterOrigin> {
+        O::try_origin(o).map(|_| A::get())
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> OuterOrigin {
+        O::successful_origin()
+    }
+}
+
+/// More than half of the members of the collective `I`, acting as `A`.
+pub type EnsureCollectiveMajority<AccountId, I, A> =
+    AsAccount<pallet_collective::EnsureProportionMoreThan<AccountId, I, 1, 2>, A>;
+
+/// At least `N / D` of the members of the collective `I`, acting as `A`.
+pub type EnsureCollectiveProportion<AccountId, I, const N: u32, const D: u32, A> =
+    AsAccount<pallet_collective::EnsureProportionAtLeast<AccountId, I, N, D>, A>;
+
+impl<T: Config> crate::traits::AuditLog<T::AccountId> for Pallet<T> {
+    fn record(who: &T::AccountId, pallet: u8, call: u8, args_hash: sp_core::H256) {
+        let max = T::MaxAuditEntries::get();
+        if max == 0 {
+            return;
+        }
+        let count = AuditCount::<T>::mutate(|count| {
+            *count = count.saturating_add(1);
+            *count - 1
+        });
+        let entry = pns_types::AuditEntry {
+            pallet,
+            call,
+            args_hash,
+            who: who.clone(),
+            at: frame_system::Pallet::<T>::block_number(),
+        };
+        AuditEntries::<T>::insert((count % max as u64) as u32, entry);
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// The recorded privileged calls, oldest first.
+    pub fn audit_log() -> sp_std::vec::Vec<AuditEntryOf<T>> {
+        let max = T::MaxAuditEntries::get() as u64;
+        let count = AuditCount::<T>::get();
+        (count.saturating_sub(max)..count)
+            .filter_map(|i| AuditEntries::<T>::get((i % max) as u32))
+            .collect()
+    }
+
+    /// Whether `who` is a manager or holds `role`.
+    pub fn has_role(who: &T::AccountId, role: Role) -> bool {
+        Origins::<T>::contains_key(who) || Roles::<T>::contains_key(who, role)
+    }
+}
+
+impl<T: Config, R: Get<Role>> EnsureOrigin<T::RuntimeOrigin> for EnsureRole<T, R> {
+    type Success = T::AccountId;
+    fn try_origin(o: T::RuntimeOrigin) -> Result<Self::Success, T::RuntimeOrigin> {
+        o.into().and_then(|o| match o {
+            RawOrigin::<T::AccountId>::Signed(who) if Pallet::<T>::has_role(&who, R::get()) => {
+                Ok(who)
+            }
+            r => Err(T::RuntimeOrigin::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> T::RuntimeOrigin {
+        <Pallet<T> as EnsureOrigin<T::RuntimeOrigin>>::successful_origin()
+    }
+}
 
 impl<T: Config> EnsureOrigin<T::RuntimeOrigin> for Pallet<T> {
     type Success = T::AccountId;
@@ -165,6 +455,10 @@ impl<T: Config> crate::traits::IsRegistrarOpen for Pallet<T> {
     fn is_open() -> bool {
         IsRegistrarOpen::<T>::get()
     }
+
+    fn is_enabled(op: crate::traits::Operation) -> bool {
+        PausedOperations::<T>::get() & op.bit() == 0
+    }
 }
 
 pub trait WeightInfo {
@@ -183,6 +477,10 @@ pub trait WeightInfo {
         }
     }
     fn set_registrar_open() -> Weight;
+    fn set_role() -> Weight;
+    fn set_paused() -> Weight;
+    fn propose_origin() -> Weight;
+    fn accept_origin() -> Weight;
     fn set_origin_true() -> Weight;
     fn set_origin_false() -> Weight;
     fn set_origin_for_root_true() -> Weight;
@@ -194,6 +492,22 @@ impl WeightInfo for () {
         Weight::zero()
     }
 
+    fn set_role() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_paused() -> Weight {
+        Weight::zero()
+    }
+
+    fn propose_origin() -> Weight {
+        Weight::zero()
+    }
+
+    fn accept_origin() -> Weight {
+        Weight::zero()
+    }
+
     fn set_origin_true() -> Weight {
         Weight::zero()
     }
@@ -1,10 +1,8 @@
 //! # Price Oracle
 //!
 //! This module is responsible for providing a price list
-//! that can be set dynamically, but it is not intelligent
-//! and the base price can only be set manually by the manager.
-//! (A more intelligent approach, such as an off-chain worker,
-//!  is being considered)
+//! that can be set dynamically. The prices are set manually by the
+//! manager, while the exchange rate can also be fed by an off-chain worker.
 //!
 //! ## Introduction
 //!
@@ -13,15 +11,56 @@
 //!
 //! ### Module functions
 //!
-//! - `set_exchange_rate` - sets the local rate
+//! - `set_exchange_rate` - sets the local rate, at most `MaxRateChange` away from
+//!   the current one and once per `RateChangeCooldown`
+//! - `force_set_exchange_rate` - sets the local rate without bounds, `Root` only
 //! - `set_base_price` - sets the base price
 //! - `set_rent_price` - sets the price used for time growth
+//! - `set_capacity_price` - sets the price of a subdomain slot bought with `buy_capacity`
+//! - `schedule_price_update` - announces new base, rent or deposit prices taking effect at a later block
+//! - `set_target_registrations` - sets the registrations per epoch the demand pricing aims for
+//! - `set_promotion` - schedules a discount of the registration and renewal fees
+//! - `set_duration_discounts` - sets the discounts of the rent of long registrations and renewals
+//! - `set_asset_rate` - sets the rate of an asset the fees can also be paid in
+//! - `set_oracle_endpoints` - sets the HTTP endpoints the off-chain worker reads the rate from
+//! - `submit_exchange_rate` - unsigned, submitted by the off-chain worker
 //!
-//! All the above methods require manager privileges in `pnsOrigin`.
+//! Every `OracleInterval` blocks the off-chain worker queries all the
+//! `OracleEndpoints`, each answering with the rate as a plain decimal
+//! integer, and submits the median of the answers. Only transactions produced
+//! by the local node are accepted into the pool, and a submitted rate may move
+//! at most `MaxRateChange` away from the current one.
+//!
+//! With a non-zero `TargetRegistrations`, set by `set_target_registrations`,
+//! the registration fee also follows the demand: at the end of every
+//! `DemandEpoch` the `DemandMultiplier` moves up when more names than the
+//! target were registered during the epoch and down when fewer were, by at most
+//! `MaxDemandAdjustment`, and stays between `MinDemandMultiplier` and
+//! `MaxDemandMultiplier`.
+//!
+//! Price changes can be announced with `schedule_price_update` instead, at
+//! least `MinPriceNotice` blocks before they take effect, and are applied by
+//! the `on_initialize` hook of their effective block.
+//!
+//! The manager can also schedule a promotion with `set_promotion`: between its
+//! `start` and `end` blocks the registration and rent of names of at least
+//! `min_len` characters are discounted by `discount_bps` basis points.
+//!
+//! Registrations and renewals for at least the `min_duration` of one of the
+//! `DurationDiscounts` get its `discount_bps` off the rent, the largest
+//! `min_duration` not exceeding the duration applies.
+//!
+//! Names re-registered right after their grace period ended also pay a premium,
+//! which starts at `PremiumMultiplier` times the registration fee and
+//! decays linearly to zero over `PremiumDuration`.
+//!
+//! All the above methods except `submit_exchange_rate` and `force_set_exchange_rate`
+//! require manager privileges in `pnsOrigin`.
 //!
 //! Note that the `trait` of `ExchangeRate` is to conveniently follow
 //! if the parallel chain itself provides price oracle related functions,
-//! and can be directly replaced.
+//! and can be directly replaced, e.g. by `traits::FeedExchangeRate` over
+//! an `orml-oracle` feed.
 //!
 pub use pallet::*;
 
@@ -32,15 +71,15 @@ type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
-    use crate::traits::ExchangeRate as ExchangeRateT;
+    use crate::traits::{AuditLog, ExchangeRate as ExchangeRateT};
     use frame_support::traits::{Currency, EnsureOrigin};
     use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
-    use frame_system::pallet_prelude::*;
+    use frame_system::{offchain::SendTransactionTypes, pallet_prelude::*};
     use scale_info::TypeInfo;
-    use sp_runtime::traits::AtLeast32BitUnsigned;
+    use sp_runtime::{traits::AtLeast32BitUnsigned, FixedU128, Perbill};
 
     #[pallet::config]
-    pub trait Config: frame_system::Config {
+    pub trait Config: frame_system::Config + SendTransactionTypes<Call<Self>> {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
         type Currency: Currency<Self::AccountId>;
@@ -55,36 +94,159 @@ pub mod pallet {
             + Default
             + TypeInfo
             + AtLeast32BitUnsigned
+            + MaxEncodedLen
             + MaybeSerializeDeserialize;
 
         type ExchangeRate: ExchangeRateT<Balance = BalanceOf<Self>>;
 
+        /// Identifier of the assets the fees can be paid in, see `set_asset_rate`.
+        type AssetId: Parameter + Member + Copy + MaxEncodedLen;
+
+        /// The maximum number of length-based price tiers, names longer than
+        /// the last tier pay its price.
+        #[pallet::constant]
+        type MaxPriceTiers: Get<u32>;
+
+        /// The maximum number of duration discounts.
+        #[pallet::constant]
+        type MaxDurationDiscounts: Get<u32>;
+
+        /// The premium of a name which just left its grace period,
+        /// as a multiple of its registration fee.
+        #[pallet::constant]
+        type PremiumMultiplier: Get<u32>;
+
+        /// How long it takes the premium to decay to zero.
+        #[pallet::constant]
+        type PremiumDuration: Get<Self::Moment>;
+
+        /// The minimum number of blocks between the announcement of a price
+        /// update and its effective block.
+        #[pallet::constant]
+        type MinPriceNotice: Get<Self::BlockNumber>;
+
+        /// Blocks between two exchange rates submitted by the off-chain worker.
+        #[pallet::constant]
+        type OracleInterval: Get<Self::BlockNumber>;
+
+        /// How far the manager or the off-chain worker may move the exchange rate
+        /// in one update.
+        #[pallet::constant]
+        type MaxRateChange: Get<Perbill>;
+
+        /// Blocks between two exchange rates set by the manager.
+        #[pallet::constant]
+        type RateChangeCooldown: Get<Self::BlockNumber>;
+
+        /// The maximum number of oracle endpoints.
+        #[pallet::constant]
+        type MaxEndpoints: Get<u32>;
+
+        /// The maximum length of an oracle endpoint url.
+        #[pallet::constant]
+        type MaxEndpointLength: Get<u32>;
+
+        /// Priority of the exchange rates submitted by the off-chain worker.
+        #[pallet::constant]
+        type UnsignedPriority: Get<TransactionPriority>;
+
+        /// Blocks between two adjustments of the demand multiplier.
+        #[pallet::constant]
+        type DemandEpoch: Get<Self::BlockNumber>;
+
+        /// How far the demand multiplier moves in one epoch, reached with no
+        /// registrations or twice the target.
+        #[pallet::constant]
+        type MaxDemandAdjustment: Get<Perbill>;
+
+        #[pallet::constant]
+        type MinDemandMultiplier: Get<FixedU128>;
+
+        #[pallet::constant]
+        type MaxDemandMultiplier: Get<FixedU128>;
+
         type WeightInfo: WeightInfo;
 
         type ManagerOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+        /// Records every price, rate and promotion change of the managers.
+        type AuditLog: AuditLog<Self::AccountId>;
     }
 
     #[pallet::pallet]
     pub struct Pallet<T>(_);
 
-    // TODO: 只有11档价格，并且档数的选择与域名长度相关
     #[pallet::storage]
-    pub type BasePrice<T: Config> = StorageValue<_, [BalanceOf<T>; 11], ValueQuery>;
+    pub type BasePrice<T: Config> = StorageValue<_, PricesOf<T>, ValueQuery>;
 
     #[pallet::storage]
-    pub type RentPrice<T: Config> = StorageValue<_, [BalanceOf<T>; 11], ValueQuery>;
+    pub type RentPrice<T: Config> = StorageValue<_, PricesOf<T>, ValueQuery>;
 
     #[pallet::storage]
-    pub type DepositPrice<T: Config> = StorageValue<_, [BalanceOf<T>; 11], ValueQuery>;
+    pub type DepositPrice<T: Config> = StorageValue<_, PricesOf<T>, ValueQuery>;
+
+    /// Price of one extra subdomain slot, before the exchange rate.
+    #[pallet::storage]
+    pub type CapacityPrice<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
 
     #[pallet::storage]
     pub type ExchangeRate<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
 
+    /// HTTP endpoints the off-chain worker reads the exchange rate from.
+    #[pallet::storage]
+    pub type OracleEndpoints<T: Config> = StorageValue<_, EndpointsOf<T>, ValueQuery>;
+
+    /// The block of the last exchange rate submitted by the off-chain worker.
+    #[pallet::storage]
+    pub type LastOracleUpdate<T: Config> = StorageValue<_, T::BlockNumber>;
+
+    /// Registrations per epoch the demand multiplier aims for, zero for flat prices.
+    #[pallet::storage]
+    pub type TargetRegistrations<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Registrations in the current demand epoch.
+    #[pallet::storage]
+    pub type EpochRegistrations<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// The block of the last exchange rate set by the manager.
+    #[pallet::storage]
+    pub type LastRateChange<T: Config> = StorageValue<_, T::BlockNumber>;
+
+    /// 1 USD to units of an asset the fees can be paid in.
+    #[pallet::storage]
+    pub type AssetRates<T: Config> = StorageMap<_, Twox64Concat, T::AssetId, BalanceOf<T>>;
+
+    /// The scheduled or running promotion.
+    #[pallet::storage]
+    pub type CurrentPromotion<T: Config> = StorageValue<_, PromotionOf<T>>;
+
+    /// Discounts of the rent by duration, sorted by `min_duration`.
+    #[pallet::storage]
+    pub type DurationDiscounts<T: Config> = StorageValue<_, DurationDiscountsOf<T>, ValueQuery>;
+
+    /// The announced price update and its effective block.
+    #[pallet::storage]
+    pub type PendingPriceUpdate<T: Config> = StorageValue<_, (PriceUpdateOf<T>, T::BlockNumber)>;
+
+    /// Multiplier of the registration fee, following the demand.
+    #[pallet::storage]
+    pub type DemandMultiplier<T: Config> =
+        StorageValue<_, FixedU128, ValueQuery, DefaultMultiplier>;
+
+    pub struct DefaultMultiplier;
+
+    impl Get<FixedU128> for DefaultMultiplier {
+        fn get() -> FixedU128 {
+            FixedU128::saturating_from_integer(1)
+        }
+    }
+
     #[pallet::genesis_config]
     pub struct GenesisConfig<T: Config> {
-        pub base_prices: [BalanceOf<T>; 11],
-        pub rent_prices: [BalanceOf<T>; 11],
-        pub deposit_prices: [BalanceOf<T>; 11],
+        pub base_prices: Vec<BalanceOf<T>>,
+        pub rent_prices: Vec<BalanceOf<T>>,
+        pub deposit_prices: Vec<BalanceOf<T>>,
+        pub capacity_price: BalanceOf<T>,
         pub init_rate: BalanceOf<T>,
     }
 
@@ -92,9 +254,10 @@ pub mod pallet {
     impl<T: Config> Default for GenesisConfig<T> {
         fn default() -> Self {
             GenesisConfig {
-                base_prices: [Default::default(); 11],
-                rent_prices: [Default::default(); 11],
-                deposit_prices: [Default::default(); 11],
+                base_prices: Default::default(),
+                rent_prices: Default::default(),
+                deposit_prices: Default::default(),
+                capacity_price: Default::default(),
                 init_rate: Default::default(),
             }
         }
@@ -103,9 +266,16 @@ pub mod pallet {
     #[pallet::genesis_build]
     impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
         fn build(&self) {
-            <BasePrice<T>>::put(self.base_prices);
-            <RentPrice<T>>::put(self.rent_prices);
-            <DepositPrice<T>>::put(self.deposit_prices);
+            let tiers = |prices: &Vec<BalanceOf<T>>| -> PricesOf<T> {
+                prices
+                    .clone()
+                    .try_into()
+                    .expect("more price tiers than `MaxPriceTiers`")
+            };
+            <BasePrice<T>>::put(tiers(&self.base_prices));
+            <RentPrice<T>>::put(tiers(&self.rent_prices));
+            <DepositPrice<T>>::put(tiers(&self.deposit_prices));
+            <CapacityPrice<T>>::put(self.capacity_price);
             <ExchangeRate<T>>::put(self.init_rate);
         }
     }
@@ -115,20 +285,123 @@ pub mod pallet {
     pub enum Event<T: Config> {
         /// Base praice changed
         /// `[base_prices]`
-        BasePriceChanged([BalanceOf<T>; 11]),
+        BasePriceChanged(PricesOf<T>),
         /// Rent price changed
         /// `[rent_prices]`
-        RentPriceChanged([BalanceOf<T>; 11]),
+        RentPriceChanged(PricesOf<T>),
         /// Deposit price changed
         /// `[deposit_prices]`
-        DepositPriceChanged([BalanceOf<T>; 11]),
+        DepositPriceChanged(PricesOf<T>),
+        /// Capacity price changed
+        /// `[capacity_price]`
+        CapacityPriceChanged(BalanceOf<T>),
         /// Exchange rate changed
         /// `[who, rate]`
         ExchangeRateChanged(T::AccountId, BalanceOf<T>),
+        /// Oracle endpoints changed
+        /// `[endpoints]`
+        OracleEndpointsChanged(EndpointsOf<T>),
+        /// Exchange rate submitted by the off-chain worker
+        /// `[rate]`
+        OracleRateSubmitted(BalanceOf<T>),
+        /// Target registrations changed
+        /// `[target]`
+        TargetRegistrationsChanged(u32),
+        /// Demand multiplier changed
+        /// `[multiplier]`
+        DemandMultiplierChanged(FixedU128),
+        /// Promotion scheduled
+        /// `[promotion]`
+        PromotionScheduled(PromotionOf<T>),
+        /// Promotion began
+        /// `[promotion]`
+        PromotionStarted(PromotionOf<T>),
+        /// Promotion ended
+        /// `[promotion]`
+        PromotionEnded(PromotionOf<T>),
+        /// Exchange rate forced by `Root`
+        /// `[rate]`
+        ExchangeRateForced(BalanceOf<T>),
+        /// Asset rate changed, `None` if the asset is not accepted anymore
+        /// `[asset, rate]`
+        AssetRateChanged(T::AssetId, Option<BalanceOf<T>>),
+        /// Price update announced
+        /// `[prices, effective_at]`
+        PriceUpdateScheduled(PriceUpdateOf<T>, T::BlockNumber),
+        /// Duration discounts changed
+        /// `[discounts]`
+        DurationDiscountsChanged(DurationDiscountsOf<T>),
     }
 
     #[pallet::error]
-    pub enum Error<T> {}
+    pub enum Error<T> {
+        /// The exchange rate is zero.
+        ZeroRate,
+        /// The exchange rate moved further than `MaxRateChange`.
+        RateOutOfBounds,
+        /// The last oracle rate is more recent than `OracleInterval`.
+        OracleNotDue,
+        /// The last exchange rate set by the manager is more recent than `RateChangeCooldown`.
+        RateChangeTooSoon,
+        /// The promotion does not end after its start and the current block.
+        InvalidPromotion,
+        /// A discount can't be more than `MAX_BASIS_POINTS`.
+        DiscountTooHigh,
+        /// The price update takes effect less than `MinPriceNotice` blocks from now.
+        PriceNoticeTooShort,
+        /// The duration discounts are not sorted by strictly increasing `min_duration`.
+        UnsortedDurationDiscounts,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            let mut weight = Self::update_promotion(now);
+            weight = weight.saturating_add(Self::apply_price_update(now));
+            let epoch = T::DemandEpoch::get();
+            if !epoch.is_zero() && (now % epoch).is_zero() {
+                weight = weight.saturating_add(Self::adjust_demand_multiplier());
+            }
+            weight
+        }
+
+        fn offchain_worker(now: BlockNumberFor<T>) {
+            if Self::oracle_due(now) {
+                let _ = Self::fetch_rate().and_then(Self::submit_rate);
+            }
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            let Call::submit_exchange_rate { rate } = call else {
+                return InvalidTransaction::Call.into();
+            };
+            if !matches!(
+                source,
+                TransactionSource::Local | TransactionSource::InBlock
+            ) {
+                return InvalidTransaction::Call.into();
+            }
+            let now = frame_system::Pallet::<T>::block_number();
+            if !Self::oracle_due(now) {
+                return InvalidTransaction::Stale.into();
+            }
+            if Self::check_rate(*rate).is_err() {
+                return InvalidTransaction::Call.into();
+            }
+
+            ValidTransaction::with_tag_prefix("PnsPriceOracle")
+                .priority(T::UnsignedPriority::get())
+                .and_provides(LastOracleUpdate::<T>::get())
+                .longevity(T::OracleInterval::get().saturated_into::<u64>())
+                .propagate(false)
+                .build()
+        }
+    }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
@@ -139,8 +412,22 @@ pub mod pallet {
             exchange_rate: BalanceOf<T>,
         ) -> DispatchResult {
             let who = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &who,
+                &Call::<T>::set_exchange_rate { exchange_rate },
+            );
+
+            let now = frame_system::Pallet::<T>::block_number();
+            if let Some(last) = <LastRateChange<T>>::get() {
+                ensure!(
+                    now >= last.saturating_add(T::RateChangeCooldown::get()),
+                    Error::<T>::RateChangeTooSoon
+                );
+            }
+            Self::check_rate(exchange_rate)?;
 
             <ExchangeRate<T>>::put(exchange_rate);
+            <LastRateChange<T>>::put(now);
 
             Self::deposit_event(Event::ExchangeRateChanged(who, exchange_rate));
 
@@ -149,10 +436,16 @@ pub mod pallet {
         /// Internal root method.
         #[pallet::call_index(1)]
         #[pallet::weight(T::WeightInfo::set_base_price())]
-        pub fn set_base_price(origin: OriginFor<T>, prices: [BalanceOf<T>; 11]) -> DispatchResult {
-            let _who = T::ManagerOrigin::ensure_origin(origin)?;
+        pub fn set_base_price(origin: OriginFor<T>, prices: PricesOf<T>) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_base_price {
+                    prices: prices.clone(),
+                },
+            );
 
-            <BasePrice<T>>::put(prices);
+            <BasePrice<T>>::put(prices.clone());
 
             Self::deposit_event(Event::BasePriceChanged(prices));
 
@@ -161,10 +454,16 @@ pub mod pallet {
         /// Internal root method.
         #[pallet::call_index(2)]
         #[pallet::weight(T::WeightInfo::set_rent_price())]
-        pub fn set_rent_price(origin: OriginFor<T>, prices: [BalanceOf<T>; 11]) -> DispatchResult {
-            let _who = T::ManagerOrigin::ensure_origin(origin)?;
+        pub fn set_rent_price(origin: OriginFor<T>, prices: PricesOf<T>) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_rent_price {
+                    prices: prices.clone(),
+                },
+            );
 
-            <RentPrice<T>>::put(prices);
+            <RentPrice<T>>::put(prices.clone());
 
             Self::deposit_event(Event::RentPriceChanged(prices));
 
@@ -173,29 +472,503 @@ pub mod pallet {
         /// Internal root method.
         #[pallet::call_index(3)]
         #[pallet::weight(T::WeightInfo::set_deposit_price())]
-        pub fn set_deposit_price(
+        pub fn set_deposit_price(origin: OriginFor<T>, prices: PricesOf<T>) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_deposit_price {
+                    prices: prices.clone(),
+                },
+            );
+
+            <DepositPrice<T>>::put(prices.clone());
+
+            Self::deposit_event(Event::DepositPriceChanged(prices));
+
+            Ok(())
+        }
+        /// Internal root method.
+        #[pallet::call_index(4)]
+        #[pallet::weight(T::WeightInfo::set_capacity_price())]
+        pub fn set_capacity_price(origin: OriginFor<T>, price: BalanceOf<T>) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&manager, &Call::<T>::set_capacity_price { price });
+
+            <CapacityPrice<T>>::put(price);
+
+            Self::deposit_event(Event::CapacityPriceChanged(price));
+
+            Ok(())
+        }
+        /// Internal root method.
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::set_oracle_endpoints())]
+        pub fn set_oracle_endpoints(
             origin: OriginFor<T>,
-            prices: [BalanceOf<T>; 11],
+            endpoints: EndpointsOf<T>,
         ) -> DispatchResult {
-            let _who = T::ManagerOrigin::ensure_origin(origin)?;
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_oracle_endpoints {
+                    endpoints: endpoints.clone(),
+                },
+            );
 
-            <DepositPrice<T>>::put(prices);
+            <OracleEndpoints<T>>::put(endpoints.clone());
 
-            Self::deposit_event(Event::DepositPriceChanged(prices));
+            Self::deposit_event(Event::OracleEndpointsChanged(endpoints));
+
+            Ok(())
+        }
+        /// Submits the exchange rate fetched by the off-chain worker.
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::submit_exchange_rate())]
+        pub fn submit_exchange_rate(origin: OriginFor<T>, rate: BalanceOf<T>) -> DispatchResult {
+            ensure_none(origin)?;
+
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(Self::oracle_due(now), Error::<T>::OracleNotDue);
+            Self::check_rate(rate)?;
+
+            <ExchangeRate<T>>::put(rate);
+            <LastOracleUpdate<T>>::put(now);
+
+            Self::deposit_event(Event::OracleRateSubmitted(rate));
+
+            Ok(())
+        }
+        /// Internal root method.
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::WeightInfo::set_target_registrations())]
+        pub fn set_target_registrations(origin: OriginFor<T>, target: u32) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_target_registrations { target },
+            );
+
+            <TargetRegistrations<T>>::put(target);
+            <EpochRegistrations<T>>::kill();
+            if target == 0 {
+                <DemandMultiplier<T>>::kill();
+            }
+
+            Self::deposit_event(Event::TargetRegistrationsChanged(target));
+
+            Ok(())
+        }
+        /// Internal root method.
+        ///
+        /// Replaces the current promotion, if any.
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::set_promotion())]
+        pub fn set_promotion(
+            origin: OriginFor<T>,
+            start: T::BlockNumber,
+            end: T::BlockNumber,
+            discount_bps: u16,
+            min_len: u32,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_promotion {
+                    start,
+                    end,
+                    discount_bps,
+                    min_len,
+                },
+            );
+
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(start < end && now < end, Error::<T>::InvalidPromotion);
+            ensure!(
+                discount_bps <= MAX_BASIS_POINTS,
+                Error::<T>::DiscountTooHigh
+            );
+
+            let promotion = Promotion {
+                start,
+                end,
+                discount_bps,
+                min_len,
+            };
+            <CurrentPromotion<T>>::put(&promotion);
+
+            Self::deposit_event(Event::PromotionScheduled(promotion.clone()));
+            if start <= now {
+                Self::deposit_event(Event::PromotionStarted(promotion));
+            }
+
+            Ok(())
+        }
+        /// Internal root method.
+        ///
+        /// `rate` is 1 USD to units of `asset`, `None` stops accepting `asset`.
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::set_asset_rate())]
+        pub fn set_asset_rate(
+            origin: OriginFor<T>,
+            asset: T::AssetId,
+            rate: Option<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_asset_rate { asset, rate },
+            );
+
+            <AssetRates<T>>::set(asset, rate);
+
+            Self::deposit_event(Event::AssetRateChanged(asset, rate));
+
+            Ok(())
+        }
+        /// Sets the exchange rate without the bounds of `set_exchange_rate`.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::force_set_exchange_rate())]
+        pub fn force_set_exchange_rate(
+            origin: OriginFor<T>,
+            exchange_rate: BalanceOf<T>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            <ExchangeRate<T>>::put(exchange_rate);
+
+            Self::deposit_event(Event::ExchangeRateForced(exchange_rate));
+
+            Ok(())
+        }
+        /// Internal root method.
+        ///
+        /// Replaces the pending price update, if any.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::schedule_price_update())]
+        pub fn schedule_price_update(
+            origin: OriginFor<T>,
+            prices: PriceUpdateOf<T>,
+            effective_at: T::BlockNumber,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::schedule_price_update {
+                    prices: prices.clone(),
+                    effective_at,
+                },
+            );
+
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(
+                effective_at >= now.saturating_add(T::MinPriceNotice::get()),
+                Error::<T>::PriceNoticeTooShort
+            );
+
+            <PendingPriceUpdate<T>>::put((&prices, effective_at));
+
+            Self::deposit_event(Event::PriceUpdateScheduled(prices, effective_at));
+
+            Ok(())
+        }
+        /// Internal root method.
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::set_duration_discounts())]
+        pub fn set_duration_discounts(
+            origin: OriginFor<T>,
+            discounts: DurationDiscountsOf<T>,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_duration_discounts {
+                    discounts: discounts.clone(),
+                },
+            );
+
+            ensure!(
+                discounts
+                    .windows(2)
+                    .all(|pair| pair[0].min_duration < pair[1].min_duration),
+                Error::<T>::UnsortedDurationDiscounts
+            );
+            ensure!(
+                discounts
+                    .iter()
+                    .all(|discount| discount.discount_bps <= MAX_BASIS_POINTS),
+                Error::<T>::DiscountTooHigh
+            );
+
+            <DurationDiscounts<T>>::put(discounts.clone());
+
+            Self::deposit_event(Event::DurationDiscountsChanged(discounts));
 
             Ok(())
         }
     }
 }
+use crate::registry::MAX_BASIS_POINTS;
 use crate::traits::{ExchangeRate as ExchangeRateT, PriceOracle};
-use frame_support::pallet_prelude::Weight;
-use sp_runtime::{traits::CheckedMul, SaturatedConversion};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+    dispatch::DispatchResult,
+    ensure,
+    pallet_prelude::{BoundedVec, Weight},
+    traits::Get,
+};
+use frame_system::offchain::SubmitTransaction;
+use scale_info::TypeInfo;
+use sp_runtime::{
+    offchain::{http, Duration},
+    traits::{CheckedMul, Saturating, Zero},
+    FixedPointNumber, FixedU128, Perbill, RuntimeDebug, SaturatedConversion,
+};
+use sp_std::vec::Vec;
+
+/// A discount of the registration and renewal fees, see `set_promotion`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Promotion<BlockNumber> {
+    /// The first block of the promotion.
+    pub start: BlockNumber,
+    /// The first block after the promotion.
+    pub end: BlockNumber,
+    /// Hundredths of a percent of the fees, at most [`MAX_BASIS_POINTS`].
+    pub discount_bps: u16,
+    /// Shorter names pay the full fees.
+    pub min_len: u32,
+}
+
+pub type PromotionOf<T> = Promotion<<T as frame_system::Config>::BlockNumber>;
+
+/// New prices announced with `schedule_price_update`, `None` keeps the current ones.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct PriceUpdate<Prices> {
+    pub base_prices: Option<Prices>,
+    pub rent_prices: Option<Prices>,
+    pub deposit_prices: Option<Prices>,
+}
+
+pub type PriceUpdateOf<T> = PriceUpdate<PricesOf<T>>;
+
+/// A discount of the rent of registrations and renewals of at least `min_duration`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct DurationDiscount<Moment> {
+    pub min_duration: Moment,
+    /// Hundredths of a percent of the rent, at most [`MAX_BASIS_POINTS`].
+    pub discount_bps: u16,
+}
+
+pub type DurationDiscountsOf<T> =
+    BoundedVec<DurationDiscount<<T as Config>::Moment>, <T as Config>::MaxDurationDiscounts>;
+
+/// Prices by name length, the first one for names of one character.
+pub type PricesOf<T> = BoundedVec<BalanceOf<T>, <T as Config>::MaxPriceTiers>;
+
+/// The oracle endpoint urls.
+pub type EndpointsOf<T> =
+    BoundedVec<BoundedVec<u8, <T as Config>::MaxEndpointLength>, <T as Config>::MaxEndpoints>;
+
+/// How long the off-chain worker waits for the oracle endpoints, in milliseconds.
+pub const HTTP_TIMEOUT: u64 = 2_000;
+
+impl<T: Config> Pallet<T> {
+    /// The price of the tier of names of `name_len`, `None` without any tier.
+    fn tier_price(prices: &[BalanceOf<T>], name_len: usize) -> Option<BalanceOf<T>> {
+        prices.get(name_len.min(prices.len()).max(1) - 1).copied()
+    }
+
+    /// Whether the off-chain worker should submit a new exchange rate at `now`.
+    pub fn oracle_due(now: T::BlockNumber) -> bool {
+        LastOracleUpdate::<T>::get().map_or(true, |last| {
+            now >= last.saturating_add(T::OracleInterval::get())
+        })
+    }
+
+    /// Checks `rate` is within `MaxRateChange` of the current exchange rate.
+    pub fn check_rate(rate: BalanceOf<T>) -> DispatchResult {
+        ensure!(!rate.is_zero(), Error::<T>::ZeroRate);
+
+        let current = ExchangeRate::<T>::get();
+        if !current.is_zero() {
+            let max_change = T::MaxRateChange::get() * current;
+            ensure!(
+                rate >= current.saturating_sub(max_change)
+                    && rate <= current.saturating_add(max_change),
+                Error::<T>::RateOutOfBounds
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Queries all the oracle endpoints and returns the median of their answers.
+    pub fn fetch_rate() -> Result<BalanceOf<T>, http::Error> {
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(HTTP_TIMEOUT));
+        let pending = OracleEndpoints::<T>::get()
+            .iter()
+            .filter_map(|url| core::str::from_utf8(url).ok())
+            .filter_map(|url| http::Request::get(url).deadline(deadline).send().ok())
+            .collect::<Vec<_>>();
+
+        let mut rates = pending
+            .into_iter()
+            .filter_map(|request| {
+                let response = request.try_wait(deadline).ok()?.ok()?;
+                if response.code != 200 {
+                    return None;
+                }
+                let body = response.body().collect::<Vec<u8>>();
+                let rate = core::str::from_utf8(&body)
+                    .ok()?
+                    .trim()
+                    .parse::<u128>()
+                    .ok()?;
+                Some(rate.saturated_into::<BalanceOf<T>>())
+            })
+            .collect::<Vec<_>>();
+
+        Self::median(&mut rates).ok_or(http::Error::Unknown)
+    }
+
+    fn median(rates: &mut [BalanceOf<T>]) -> Option<BalanceOf<T>> {
+        rates.sort();
+        let mid = rates.len() / 2;
+        let upper = *rates.get(mid)?;
+        if rates.len() % 2 == 1 {
+            return Some(upper);
+        }
+        let lower = rates[mid - 1];
+        Some(lower + (upper - lower) / 2_u32.into())
+    }
+
+    /// Announces the start of the promotion at `now` and removes it at its end.
+    pub(crate) fn update_promotion(now: T::BlockNumber) -> Weight {
+        let Some(promotion) = CurrentPromotion::<T>::get() else {
+            return T::DbWeight::get().reads(1);
+        };
+        if now == promotion.start {
+            Self::deposit_event(Event::PromotionStarted(promotion));
+        } else if now >= promotion.end {
+            CurrentPromotion::<T>::kill();
+            Self::deposit_event(Event::PromotionEnded(promotion));
+            return T::DbWeight::get().reads_writes(1, 1);
+        }
+        T::DbWeight::get().reads(1)
+    }
+
+    /// Applies the pending price update once its effective block is reached.
+    pub(crate) fn apply_price_update(now: T::BlockNumber) -> Weight {
+        let Some((prices, effective_at)) = PendingPriceUpdate::<T>::get() else {
+            return T::DbWeight::get().reads(1);
+        };
+        if now < effective_at {
+            return T::DbWeight::get().reads(1);
+        }
+
+        PendingPriceUpdate::<T>::kill();
+        if let Some(base_prices) = prices.base_prices {
+            BasePrice::<T>::put(&base_prices);
+            Self::deposit_event(Event::BasePriceChanged(base_prices));
+        }
+        if let Some(rent_prices) = prices.rent_prices {
+            RentPrice::<T>::put(&rent_prices);
+            Self::deposit_event(Event::RentPriceChanged(rent_prices));
+        }
+        if let Some(deposit_prices) = prices.deposit_prices {
+            DepositPrice::<T>::put(&deposit_prices);
+            Self::deposit_event(Event::DepositPriceChanged(deposit_prices));
+        }
+        T::DbWeight::get().reads_writes(1, 4)
+    }
+
+    /// `fee` with the discount of the promotion running for names of `name_len`.
+    fn discounted(name_len: usize, fee: BalanceOf<T>) -> BalanceOf<T> {
+        let now = frame_system::Pallet::<T>::block_number();
+        match CurrentPromotion::<T>::get() {
+            Some(promotion)
+                if promotion.start <= now
+                    && now < promotion.end
+                    && name_len >= promotion.min_len as usize =>
+            {
+                let discount = Perbill::from_rational(
+                    u32::from(promotion.discount_bps),
+                    u32::from(MAX_BASIS_POINTS),
+                );
+                fee.saturating_sub(discount * fee)
+            }
+            _ => fee,
+        }
+    }
+
+    /// `rent` with the discount of the longest `DurationDiscounts` entry `duration` reaches.
+    fn duration_discounted(duration: T::Moment, rent: BalanceOf<T>) -> BalanceOf<T> {
+        match DurationDiscounts::<T>::get()
+            .iter()
+            .rev()
+            .find(|discount| discount.min_duration <= duration)
+        {
+            Some(discount) => {
+                let discount = Perbill::from_rational(
+                    u32::from(discount.discount_bps),
+                    u32::from(MAX_BASIS_POINTS),
+                );
+                rent.saturating_sub(discount * rent)
+            }
+            None => rent,
+        }
+    }
+
+    /// Moves the demand multiplier after the registrations of the epoch which just ended.
+    pub(crate) fn adjust_demand_multiplier() -> Weight {
+        let weight = T::DbWeight::get().reads_writes(3, 2);
+        let registrations = EpochRegistrations::<T>::take();
+        let target = TargetRegistrations::<T>::get();
+        if target == 0 {
+            return weight;
+        }
+
+        let multiplier = DemandMultiplier::<T>::get();
+        let max_change = multiplier.saturating_mul(T::MaxDemandAdjustment::get().into());
+        let multiplier = if registrations > target {
+            let excess = (registrations - target).min(target);
+            multiplier.saturating_add(
+                max_change.saturating_mul(FixedU128::saturating_from_rational(excess, target)),
+            )
+        } else {
+            let shortfall = target - registrations;
+            multiplier.saturating_sub(
+                max_change.saturating_mul(FixedU128::saturating_from_rational(shortfall, target)),
+            )
+        }
+        .max(T::MinDemandMultiplier::get())
+        .min(T::MaxDemandMultiplier::get());
+
+        DemandMultiplier::<T>::put(multiplier);
+        Self::deposit_event(Event::DemandMultiplierChanged(multiplier));
+        weight
+    }
+
+    fn submit_rate(rate: BalanceOf<T>) -> Result<(), http::Error> {
+        let call = Call::submit_exchange_rate { rate };
+        SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+            .map_err(|_| http::Error::Unknown)
+    }
+}
 
 pub trait WeightInfo {
     fn set_exchange_rate() -> Weight;
     fn set_base_price() -> Weight;
     fn set_rent_price() -> Weight;
     fn set_deposit_price() -> Weight;
+    fn set_capacity_price() -> Weight;
+    fn set_oracle_endpoints() -> Weight;
+    fn submit_exchange_rate() -> Weight;
+    fn set_target_registrations() -> Weight;
+    fn set_promotion() -> Weight;
+    fn set_asset_rate() -> Weight;
+    fn force_set_exchange_rate() -> Weight;
+    fn schedule_price_update() -> Weight;
+    fn set_duration_discounts() -> Weight;
 }
 
 impl<T: Config> PriceOracle for Pallet<T> {
@@ -203,51 +976,74 @@ impl<T: Config> PriceOracle for Pallet<T> {
 
     type Balance = BalanceOf<T>;
 
+    type AssetId = T::AssetId;
+
     fn deposit_fee(name_len: usize) -> Option<Self::Balance> {
-        let deposit_prices = DepositPrice::<T>::get();
-        let prices_len = deposit_prices.len();
-        let len = if name_len < prices_len {
-            name_len.max(1)
-        } else {
-            prices_len
-        };
         let exchange_rate = T::ExchangeRate::get_exchange_rate();
-        deposit_prices[len - 1].checked_mul(&exchange_rate)
+        Self::tier_price(&DepositPrice::<T>::get(), name_len)?.checked_mul(&exchange_rate)
     }
 
     fn registration_fee(name_len: usize) -> Option<Self::Balance> {
-        let base_prices = BasePrice::<T>::get();
-        let prices_len = base_prices.len();
-        let len = if name_len < prices_len {
-            name_len.max(1)
-        } else {
-            prices_len
-        };
         let exchange_rate = T::ExchangeRate::get_exchange_rate();
+        let fee = Self::tier_price(&BasePrice::<T>::get(), name_len)?
+            .checked_mul(&exchange_rate)?
+            .saturated_into::<u128>();
 
-        base_prices[len - 1].checked_mul(&exchange_rate)
+        Some(
+            DemandMultiplier::<T>::get()
+                .saturating_mul_int(fee)
+                .saturated_into::<Self::Balance>(),
+        )
     }
 
     fn register_fee(name_len: usize, duration: Self::Moment) -> Option<Self::Balance> {
-        let register_price = Self::registration_fee(name_len)?;
+        let register_price = Self::discounted(name_len, Self::registration_fee(name_len)?);
         let rent_price = Self::renew_fee(name_len, duration)?;
 
         Some(register_price + rent_price)
     }
     fn renew_fee(name_len: usize, duration: Self::Moment) -> Option<Self::Balance> {
-        let rent_prices = RentPrice::<T>::get();
-        let prices_len = rent_prices.len();
-        let len = if name_len < prices_len {
-            name_len.max(1)
-        } else {
-            prices_len
-        };
-        let duration = duration.saturated_into::<u128>();
-        let rent_price = (rent_prices[len - 1].checked_mul(&T::ExchangeRate::get_exchange_rate()))?
+        let rent_price = Self::tier_price(&RentPrice::<T>::get(), name_len)?
+            .checked_mul(&T::ExchangeRate::get_exchange_rate())?
             .saturated_into::<u128>();
 
         rent_price
-            .checked_mul(duration)
+            .checked_mul(duration.saturated_into::<u128>())
+            .map(|res| Self::duration_discounted(duration, res.saturated_into::<Self::Balance>()))
+            .map(|res| Self::discounted(name_len, res))
+    }
+    fn premium(name_len: usize, expired_for: Self::Moment) -> Option<Self::Balance> {
+        let premium_duration = T::PremiumDuration::get();
+        if expired_for >= premium_duration {
+            return Some(Zero::zero());
+        }
+        let start = Self::registration_fee(name_len)?
+            .checked_mul(&T::PremiumMultiplier::get().into())?
+            .saturated_into::<u128>();
+
+        start
+            .checked_mul((premium_duration - expired_for).saturated_into::<u128>())?
+            .checked_div(premium_duration.saturated_into::<u128>())
+            .map(|res| res.saturated_into::<Self::Balance>())
+    }
+    fn capacity_fee(extra_slots: u32) -> Option<Self::Balance> {
+        CapacityPrice::<T>::get()
+            .checked_mul(&T::ExchangeRate::get_exchange_rate())?
+            .checked_mul(&extra_slots.into())
+    }
+    fn note_registration() {
+        if TargetRegistrations::<T>::get() != 0 {
+            EpochRegistrations::<T>::mutate(|count| *count = count.saturating_add(1));
+        }
+    }
+    fn asset_amount(asset: Self::AssetId, amount: Self::Balance) -> Option<Self::Balance> {
+        let asset_rate = AssetRates::<T>::get(asset)?.saturated_into::<u128>();
+        let exchange_rate = T::ExchangeRate::get_exchange_rate().saturated_into::<u128>();
+
+        amount
+            .saturated_into::<u128>()
+            .checked_mul(asset_rate)?
+            .checked_div(exchange_rate)
             .map(|res| res.saturated_into::<Self::Balance>())
     }
 }
@@ -276,4 +1072,40 @@ impl WeightInfo for () {
     fn set_deposit_price() -> Weight {
         Weight::zero()
     }
+
+    fn set_capacity_price() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_oracle_endpoints() -> Weight {
+        Weight::zero()
+    }
+
+    fn submit_exchange_rate() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_target_registrations() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_promotion() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_asset_rate() -> Weight {
+        Weight::zero()
+    }
+
+    fn force_set_exchange_rate() -> Weight {
+        Weight::zero()
+    }
+
+    fn schedule_price_update() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_duration_discounts() -> Weight {
+        Weight::zero()
+    }
 }
@@ -3,14 +3,15 @@
 //! This module is an implementation of the functionality
 //! related to redemption codes.
 //!
-//! But the current implementation has a fatal problem.
+//! The official signature covers the `owner` the domain is redeemed for
+//! and a `deadline` block, so a redemption intercepted before it is
+//! included can only be replayed for the same owner, and not at all
+//! after the deadline.
 //!
-//! - When hackers submit transactions with their own accounts
-//! by intercepting transactions submitted by other users before
-//! other users successfully register, they may intercept the
-//! domain name that can be redeemed by obtaining that redemption code.
-//!
-//! Therefore this part of the code should be re-implemented.
+//! Codes signed before the `owner` and `deadline` were part of the
+//! payload are rejected, while their `nouce` stays unused: the central
+//! server only has to sign them again in the new format, no storage
+//! migration is needed.
 //!
 //! ## Introduction
 //!
@@ -19,18 +20,37 @@
 //!
 //! ### Module functions
 //!
-//! - `mint_redeem` - generates `nouce` for the specified range
+//! - `mint_redeem` - generates `nouce` for the specified range, optionally expiring
+//!   and usable by up to `max_uses` accounts
+//! - `sweep_redeems` - removes the expired `nouce` of the specified range, callable by anyone
+//! - `revoke_redeem` - removes the unused `nouce` of the specified range
 //! - `name_redeem` - redeem the specified domain
-//! - `name_redeem_any` - redeem any registrable domain name above a certain length (a certain length currently is 10 digits)
+//! - `name_redeem_any` - redeem any registrable domain name of the `LengthClass` signed with the code
+//! - `name_redeem_exact` - redeem the domain, base node included, signed with the code
+//! - `renew_redeem` - renew the specified domain for free
+//! - `create_campaign` - creates a campaign, optionally limited in redemptions or total duration
+//! - `pause_campaign` - pauses or resumes the redemption of the codes of a campaign
+//! - `close_campaign` - stops the codes of a campaign for good, keeping its statistics
+//! - `commit_redeem_batch` - commits the Merkle root of a batch of codes
+//! - `name_redeem_batch` - redeem any registrable domain name with a code of a batch
+//!
+//! Codes minted for a campaign count against its budget, and can't be
+//! redeemed while it is paused or once it is closed.
+//!
+//! `mint_redeem`, `revoke_redeem`, `commit_redeem_batch` and the campaign methods require manager privileges in `pnsOrigin`.
 //!
-//! All the above methods require manager privileges in `pnsOrigin`.
+//! A batch costs a single storage write however many codes it holds. Its
+//! leaves are `blake2_256((index, duration, owner).encode())`, padded with
+//! zero leaves to a power of two, and each parent is the `blake2_256` of the
+//! encoded pair of its children. A code is redeemed with the Merkle proof of
+//! its leaf, from the bottom, and its index is then marked in a spent bitmap.
 
 pub use pallet::*;
 
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
-    use crate::traits::{Available, Label, Official, Registrar};
+    use crate::traits::{AuditLog, Available, Official, Registrar};
     use codec::EncodeLike;
     use frame_support::{dispatch::DispatchResult, pallet_prelude::*, traits::EnsureOrigin};
     use frame_system::pallet_prelude::*;
@@ -72,15 +92,68 @@ pub mod pallet {
 
         type ManagerOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
 
+        /// Records the campaigns and codes the managers create, close or revoke.
+        type AuditLog: AuditLog<Self::AccountId>;
+
         type Official: Official<AccountId = Self::AccountId>;
+
+        /// The maximum number of accounts a code can be redeemed by.
+        #[pallet::constant]
+        type MaxRedeemUses: Get<u32>;
+
+        /// The maximum length of the name of a campaign.
+        #[pallet::constant]
+        type MaxCampaignNameLength: Get<u32>;
+
+        /// The maximum depth of the Merkle tree of a batch, at most `2^MaxBatchDepth` codes.
+        #[pallet::constant]
+        type MaxBatchDepth: Get<u32>;
     }
 
     #[pallet::pallet]
     pub struct Pallet<T>(_);
 
-    /// redeem code
+    /// redeem code, with the last block it can be redeemed at
+    #[pallet::storage]
+    pub type Redeems<T: Config> = StorageMap<_, Twox64Concat, u32, Option<T::BlockNumber>>;
+
+    /// The remaining uses of the codes usable by more than one account.
+    #[pallet::storage]
+    pub type RedeemUses<T> = StorageMap<_, Twox64Concat, u32, u32>;
+
+    /// The accounts which already redeemed a code usable by more than one account.
+    #[pallet::storage]
+    pub type RedeemedBy<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, u32, Blake2_128Concat, T::AccountId, ()>;
+
+    /// The codes used, by `nouce` and the account they were redeemed for,
+    /// kept to settle disputes about redemptions.
+    #[pallet::storage]
+    pub type SpentCodes<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, u32, Blake2_128Concat, T::AccountId, SpentCodeOf<T>>;
+
+    /// The campaign of the codes minted for one.
+    #[pallet::storage]
+    pub type RedeemCampaign<T> = StorageMap<_, Twox64Concat, u32, u32>;
+
+    /// Campaigns with their budget and statistics.
+    #[pallet::storage]
+    pub type Campaigns<T: Config> = StorageMap<_, Twox64Concat, u32, CampaignOf<T>>;
+
     #[pallet::storage]
-    pub type Redeems<T> = StorageMap<_, Twox64Concat, u32, ()>;
+    pub type NextCampaignId<T> = StorageValue<_, u32, ValueQuery>;
+
+    /// Batches of codes committed by their Merkle root.
+    #[pallet::storage]
+    pub type RedeemBatches<T: Config> = StorageMap<_, Twox64Concat, u32, RedeemBatchOf<T>>;
+
+    #[pallet::storage]
+    pub type NextBatchId<T> = StorageValue<_, u32, ValueQuery>;
+
+    /// Spent codes of a batch, 128 indexes per word.
+    #[pallet::storage]
+    pub type SpentLeaves<T> =
+        StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, u32, u128, ValueQuery>;
 
     #[pallet::genesis_config]
     #[cfg_attr(feature = "std", derive(Default))]
@@ -96,7 +169,7 @@ pub mod pallet {
                 let mut nouce = start;
 
                 while nouce < end {
-                    Redeems::<T>::insert(nouce, ());
+                    Redeems::<T>::insert(nouce, None::<T::BlockNumber>);
                     nouce += 1;
                 }
             }
@@ -109,6 +182,55 @@ pub mod pallet {
         /// When the redemption code is used, it will be logged.
         RedeemCodeUsed {
             code: T::Signature,
+            nouce: u32,
+            node: pns_types::DomainHash,
+            to: T::AccountId,
+            remaining: u32,
+        },
+        /// When a renewal code is used, it will be logged.
+        RenewCodeUsed {
+            code: T::Signature,
+            nouce: u32,
+            node: pns_types::DomainHash,
+            duration: T::Moment,
+            remaining: u32,
+        },
+        /// Logged when the redemption codes from `start` to `end` (inclusive) are minted.
+        RedeemsMinted {
+            start: u32,
+            end: u32,
+            expires_at: Option<T::BlockNumber>,
+            max_uses: u32,
+            campaign: Option<u32>,
+        },
+        /// Logged when the redemption codes from `start` to `end` (inclusive) are revoked.
+        RedeemsRevoked { start: u32, end: u32 },
+        /// Logged when `count` expired redemption codes are removed.
+        RedeemsSwept { count: u32 },
+        /// Logged when a campaign is created.
+        CampaignCreated {
+            campaign: u32,
+            name: CampaignNameOf<T>,
+        },
+        /// Logged when a campaign is paused or resumed.
+        CampaignPaused { campaign: u32, paused: bool },
+        /// Logged when a campaign is closed, with its statistics.
+        CampaignClosed {
+            campaign: u32,
+            redemptions: u32,
+            total_duration: T::Moment,
+        },
+        /// Logged when a batch of `size` codes is committed.
+        RedeemBatchCommitted {
+            batch: u32,
+            root: H256,
+            size: u32,
+            expires_at: T::BlockNumber,
+        },
+        /// Logged when a code of a batch is used.
+        BatchCodeUsed {
+            batch: u32,
+            index: u32,
             node: pns_types::DomainHash,
             to: T::AccountId,
         },
@@ -132,27 +254,91 @@ pub mod pallet {
         /// The length of the domain name you entered does not match the
         /// requirements of this redemption code.
         LabelLenInvalid,
+        /// The deadline of the redemption code has passed.
+        RedeemCodeExpired,
+        /// The batch is empty or holds more than `2^MaxBatchDepth` codes.
+        InvalidBatchSize,
+        /// A code is usable by at least one and at most `MaxRedeemUses` accounts.
+        InvalidMaxUses,
+        /// The owner already redeemed this code.
+        AlreadyRedeemed,
+        /// There is no campaign with this id.
+        UnknownCampaign,
+        /// The campaign is closed.
+        CampaignClosed,
+        /// The campaign is paused or closed.
+        CampaignNotActive,
+        /// The campaign has no budget left for this redemption.
+        CampaignBudgetExceeded,
+        /// There is no batch with this id.
+        UnknownBatch,
+        /// The Merkle proof does not lead to the root of the batch.
+        InvalidProof,
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         /// This is a Root method which is used to create the nouce needed to redeem the code.
         ///
+        /// The codes can't be redeemed after `expires_at`, if any, and each
+        /// of them can be redeemed by up to `max_uses` different owners,
+        /// within the budget of `campaign`, if any.
+        ///
         /// Ensure: start < end
         #[pallet::call_index(0)]
         #[pallet::weight(T::WeightInfo::mint_redeem(end.checked_sub(*start).unwrap_or_default()))]
-        pub fn mint_redeem(origin: OriginFor<T>, start: u32, end: u32) -> DispatchResult {
-            let _who = T::ManagerOrigin::ensure_origin(origin)?;
+        pub fn mint_redeem(
+            origin: OriginFor<T>,
+            start: u32,
+            end: u32,
+            expires_at: Option<T::BlockNumber>,
+            max_uses: u32,
+            campaign: Option<u32>,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::mint_redeem {
+                    start,
+                    end,
+                    expires_at,
+                    max_uses,
+                    campaign,
+                },
+            );
 
             ensure!(start < end, Error::<T>::RangeInvaild);
+            ensure!(
+                (1..=T::MaxRedeemUses::get()).contains(&max_uses),
+                Error::<T>::InvalidMaxUses
+            );
+            if let Some(campaign) = campaign {
+                let status = Campaigns::<T>::get(campaign)
+                    .ok_or(Error::<T>::UnknownCampaign)?
+                    .status;
+                ensure!(status != CampaignStatus::Closed, Error::<T>::CampaignClosed);
+            }
 
             let mut nouce = start;
 
             while nouce <= end {
-                Redeems::<T>::insert(nouce, ());
+                Redeems::<T>::insert(nouce, expires_at);
+                if max_uses > 1 {
+                    RedeemUses::<T>::insert(nouce, max_uses);
+                }
+                if let Some(campaign) = campaign {
+                    RedeemCampaign::<T>::insert(nouce, campaign);
+                }
                 nouce += 1;
             }
 
+            Self::deposit_event(Event::<T>::RedeemsMinted {
+                start,
+                end,
+                expires_at,
+                max_uses,
+                campaign,
+            });
             Ok(())
         }
         /// This is an interface to the PNS front-end.
@@ -164,6 +350,8 @@ pub mod pallet {
         /// our central server through the `redemption code`,
         /// and then calls the interface.
         ///
+        /// The `code` is only valid for `owner` and until the `deadline` block.
+        ///
         /// Ensure: The length of name needs to be greater than 3.
         #[pallet::call_index(1)]
         #[pallet::weight(T::WeightInfo::name_redeem(name.len() as u32))]
@@ -175,18 +363,20 @@ pub mod pallet {
             nouce: u32,
             code: T::Signature,
             owner: T::AccountId,
+            deadline: T::BlockNumber,
         ) -> DispatchResult {
             let _who = ensure_signed(origin)?;
 
             ensure!(
-                Redeems::<T>::contains_key(nouce),
-                Error::<T>::RedeemsHasBeenUsed
+                frame_system::Pallet::<T>::block_number() <= deadline,
+                Error::<T>::RedeemCodeExpired
             );
+            Self::check_code(nouce, &owner)?;
 
-            let (label, _) = Label::new_with_len(&name).ok_or(Error::<T>::ParseLabelFailed)?;
+            let (label, _) = T::Registrar::label_of(&name).ok_or(Error::<T>::ParseLabelFailed)?;
 
             let label_node = label.node;
-            let data = (label_node, duration, nouce).encode();
+            let data = (label_node, duration, nouce, &owner, deadline).encode();
 
             let signer = T::Official::get_official_account()?;
 
@@ -197,14 +387,18 @@ pub mod pallet {
 
             let node = label.encode_with_node(&T::Registrar::basenode());
 
+            Self::charge_campaign(nouce, duration)?;
+
             T::Registrar::for_redeem_code(name, owner.clone(), duration, label)?;
 
-            Redeems::<T>::remove(nouce);
+            let remaining = Self::use_code(nouce, &owner, node);
 
             Self::deposit_event(Event::<T>::RedeemCodeUsed {
                 code,
+                nouce,
                 node,
                 to: owner,
+                remaining,
             });
 
             Ok(())
@@ -216,10 +410,14 @@ pub mod pallet {
         /// from our central server via the redemption code,
         /// and gets `name` from the user, then calls this interface.
         ///
+        /// The `code` is only valid for `owner`, until the `deadline` block and
+        /// for names of the `length` class, so vouchers of short names can't be
+        /// used for ordinary names and vice versa.
+        ///
         /// NOTE: The front-end should check if the name is legal
         /// or occupied when it is called.
         ///
-        /// Ensure: The length of name needs to be greater than 10.
+        /// Ensure: The length of name is in the `length` class.
         #[pallet::call_index(2)]
         #[pallet::weight(T::WeightInfo::name_redeem_any(name.len() as u32))]
         #[frame_support::transactional]
@@ -230,20 +428,26 @@ pub mod pallet {
             nouce: u32,
             code: T::Signature,
             owner: T::AccountId,
+            deadline: T::BlockNumber,
+            length: LengthClass,
         ) -> DispatchResult {
             let _who = ensure_signed(origin)?;
 
             ensure!(
-                Redeems::<T>::contains_key(nouce),
-                Error::<T>::RedeemsHasBeenUsed
+                frame_system::Pallet::<T>::block_number() <= deadline,
+                Error::<T>::RedeemCodeExpired
             );
+            Self::check_code(nouce, &owner)?;
 
             let (label, label_len) =
-                Label::new_with_len(&name).ok_or(Error::<T>::ParseLabelFailed)?;
+                T::Registrar::label_of(&name).ok_or(Error::<T>::ParseLabelFailed)?;
 
-            ensure!(label_len.is_registrable(), Error::<T>::LabelLenInvalid);
+            ensure!(
+                label_len.is_registrable() && length.contains(label_len),
+                Error::<T>::LabelLenInvalid
+            );
 
-            let data = (duration, nouce).encode();
+            let data = (duration, nouce, &owner, deadline, length).encode();
 
             let signer = T::Official::get_official_account()?;
 
@@ -254,18 +458,551 @@ pub mod pallet {
 
             let node = label.encode_with_node(&T::Registrar::basenode());
 
+            Self::charge_campaign(nouce, duration)?;
+
             T::Registrar::for_redeem_code(name, owner.clone(), duration, label)?;
 
-            Redeems::<T>::remove(nouce);
+            let remaining = Self::use_code(nouce, &owner, node);
+
+            Self::deposit_event(Event::<T>::RedeemCodeUsed {
+                code,
+                nouce,
+                node,
+                to: owner,
+                remaining,
+            });
+
+            Ok(())
+        }
+
+        /// Removes the expired codes from `start` to `end` (inclusive).
+        ///
+        /// Anyone can call it to reclaim the storage of the codes of ended campaigns.
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::sweep_redeems(end.checked_sub(*start).unwrap_or_default()))]
+        pub fn sweep_redeems(origin: OriginFor<T>, start: u32, end: u32) -> DispatchResult {
+            let _who = ensure_signed(origin)?;
+
+            ensure!(start < end, Error::<T>::RangeInvaild);
+
+            let mut count = 0_u32;
+            for nouce in start..=end {
+                if let Some(expires_at) = Redeems::<T>::get(nouce) {
+                    if Self::is_expired(expires_at) {
+                        Self::remove_code(nouce);
+                        count += 1;
+                    }
+                }
+            }
+
+            Self::deposit_event(Event::<T>::RedeemsSwept { count });
+            Ok(())
+        }
+
+        /// This is a Root method which invalidates the unused codes from `start`
+        /// to `end` (inclusive), e.g. of a leaked or mis-printed batch.
+        ///
+        /// Ensure: start < end
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::revoke_redeem(end.checked_sub(*start).unwrap_or_default()))]
+        pub fn revoke_redeem(origin: OriginFor<T>, start: u32, end: u32) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&manager, &Call::<T>::revoke_redeem { start, end });
+
+            ensure!(start < end, Error::<T>::RangeInvaild);
+
+            for nouce in start..=end {
+                Self::remove_code(nouce);
+            }
+
+            Self::deposit_event(Event::<T>::RedeemsRevoked { start, end });
+            Ok(())
+        }
+
+        /// Commits a batch of `size` codes by the Merkle root of their leaves,
+        /// redeemable until `expires_at`.
+        #[pallet::call_index(3)]
+        #[pallet::weight(T::WeightInfo::commit_redeem_batch())]
+        pub fn commit_redeem_batch(
+            origin: OriginFor<T>,
+            root: H256,
+            size: u32,
+            expires_at: T::BlockNumber,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::commit_redeem_batch {
+                    root,
+                    size,
+                    expires_at,
+                },
+            );
+
+            ensure!(
+                size > 0 && u64::from(size) <= 1_u64 << T::MaxBatchDepth::get().min(32),
+                Error::<T>::InvalidBatchSize
+            );
+
+            let batch = NextBatchId::<T>::mutate(|id| {
+                let batch = *id;
+                *id = id.saturating_add(1);
+                batch
+            });
+            RedeemBatches::<T>::insert(
+                batch,
+                RedeemBatch {
+                    root,
+                    size,
+                    expires_at,
+                },
+            );
+
+            Self::deposit_event(Event::<T>::RedeemBatchCommitted {
+                batch,
+                root,
+                size,
+                expires_at,
+            });
+            Ok(())
+        }
+
+        /// Redeems the code at `index` of `batch` for `owner`, who is part of its leaf.
+        ///
+        /// Ensure: The length of name needs to be greater than 10.
+        #[pallet::call_index(4)]
+        #[pallet::weight(T::WeightInfo::name_redeem_batch(name.len() as u32))]
+        #[frame_support::transactional]
+        pub fn name_redeem_batch(
+            origin: OriginFor<T>,
+            name: Vec<u8>,
+            duration: T::Moment,
+            batch: u32,
+            index: u32,
+            proof: BoundedVec<H256, T::MaxBatchDepth>,
+            owner: T::AccountId,
+        ) -> DispatchResult {
+            let _who = ensure_signed(origin)?;
+
+            let redeem_batch = RedeemBatches::<T>::get(batch).ok_or(Error::<T>::UnknownBatch)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() <= redeem_batch.expires_at,
+                Error::<T>::RedeemCodeExpired
+            );
+            ensure!(index < redeem_batch.size, Error::<T>::InvalidProof);
+            ensure!(
+                proof.len() as u32 == Self::batch_depth(redeem_batch.size)
+                    && Self::root_of(index, Self::leaf_of(index, duration, &owner), &proof)
+                        == redeem_batch.root,
+                Error::<T>::InvalidProof
+            );
+
+            let (word, bit) = (index / 128, 1_u128 << (index % 128));
+            ensure!(
+                SpentLeaves::<T>::get(batch, word) & bit == 0,
+                Error::<T>::RedeemsHasBeenUsed
+            );
+
+            let (label, label_len) =
+                T::Registrar::label_of(&name).ok_or(Error::<T>::ParseLabelFailed)?;
+
+            ensure!(label_len.is_registrable(), Error::<T>::LabelLenInvalid);
+
+            let node = label.encode_with_node(&T::Registrar::basenode());
+
+            T::Registrar::for_redeem_code(name, owner.clone(), duration, label)?;
+
+            SpentLeaves::<T>::mutate(batch, word, |spent| *spent |= bit);
+
+            Self::deposit_event(Event::<T>::BatchCodeUsed {
+                batch,
+                index,
+                node,
+                to: owner,
+            });
+
+            Ok(())
+        }
+
+        /// This is an interface to the PNS front-end, for names reserved for their `owner`.
+        ///
+        /// Unlike `name_redeem`, the `code` fixes the whole domain, its `base_node`
+        /// included, so it can only mint that domain, whatever its length and
+        /// the current default base node.
+        ///
+        /// The `code` is only valid for `owner` and until the `deadline` block.
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::WeightInfo::name_redeem_exact(name.len() as u32))]
+        #[frame_support::transactional]
+        pub fn name_redeem_exact(
+            origin: OriginFor<T>,
+            base_node: pns_types::DomainHash,
+            name: Vec<u8>,
+            duration: T::Moment,
+            nouce: u32,
+            code: T::Signature,
+            owner: T::AccountId,
+            deadline: T::BlockNumber,
+        ) -> DispatchResult {
+            let _who = ensure_signed(origin)?;
+
+            ensure!(
+                frame_system::Pallet::<T>::block_number() <= deadline,
+                Error::<T>::RedeemCodeExpired
+            );
+            Self::check_code(nouce, &owner)?;
+
+            let (label, _) = T::Registrar::label_of(&name).ok_or(Error::<T>::ParseLabelFailed)?;
+
+            let node = label.encode_with_node(&base_node);
+            let data = (node, duration, nouce, &owner, deadline).encode();
+
+            let signer = T::Official::get_official_account()?;
+
+            ensure!(
+                code.verify(&data[..], &signer),
+                Error::<T>::InvalidSignature
+            );
+
+            Self::charge_campaign(nouce, duration)?;
+
+            T::Registrar::for_redeem_code_in(base_node, name, owner.clone(), duration, label)?;
+
+            let remaining = Self::use_code(nouce, &owner, node);
 
             Self::deposit_event(Event::<T>::RedeemCodeUsed {
                 code,
+                nouce,
                 node,
                 to: owner,
+                remaining,
             });
 
             Ok(())
         }
+
+        /// This is an interface for the PNS support, which compensates users
+        /// by extending the expiry of their domain under the default base node
+        /// by `duration`, without any fee.
+        ///
+        /// The `code` is only valid for `owner` and until the `deadline` block.
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::renew_redeem(name.len() as u32))]
+        #[frame_support::transactional]
+        pub fn renew_redeem(
+            origin: OriginFor<T>,
+            name: Vec<u8>,
+            duration: T::Moment,
+            nouce: u32,
+            code: T::Signature,
+            owner: T::AccountId,
+            deadline: T::BlockNumber,
+        ) -> DispatchResult {
+            let _who = ensure_signed(origin)?;
+
+            ensure!(
+                frame_system::Pallet::<T>::block_number() <= deadline,
+                Error::<T>::RedeemCodeExpired
+            );
+            Self::check_code(nouce, &owner)?;
+
+            let (label, _) = T::Registrar::label_of(&name).ok_or(Error::<T>::ParseLabelFailed)?;
+
+            let node = label.encode_with_node(&T::Registrar::basenode());
+            let data = (node, duration, nouce, &owner, deadline).encode();
+
+            let signer = T::Official::get_official_account()?;
+
+            ensure!(
+                code.verify(&data[..], &signer),
+                Error::<T>::InvalidSignature
+            );
+
+            Self::charge_campaign(nouce, duration)?;
+
+            T::Registrar::for_renew_code(&owner, name, duration)?;
+
+            let remaining = Self::use_code(nouce, &owner, node);
+
+            Self::deposit_event(Event::<T>::RenewCodeUsed {
+                code,
+                nouce,
+                node,
+                duration,
+                remaining,
+            });
+
+            Ok(())
+        }
+
+        /// Creates a campaign, its codes can register or renew at most
+        /// `max_redemptions` names for at most `max_duration` in total.
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::create_campaign())]
+        pub fn create_campaign(
+            origin: OriginFor<T>,
+            name: CampaignNameOf<T>,
+            max_redemptions: Option<u32>,
+            max_duration: Option<T::Moment>,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::create_campaign {
+                    name: name.clone(),
+                    max_redemptions,
+                    max_duration,
+                },
+            );
+
+            let campaign = NextCampaignId::<T>::mutate(|id| {
+                let campaign = *id;
+                *id = id.saturating_add(1);
+                campaign
+            });
+            Campaigns::<T>::insert(
+                campaign,
+                Campaign {
+                    name: name.clone(),
+                    max_redemptions,
+                    max_duration,
+                    status: CampaignStatus::Active,
+                    redemptions: 0,
+                    total_duration: Default::default(),
+                },
+            );
+
+            Self::deposit_event(Event::<T>::CampaignCreated { campaign, name });
+            Ok(())
+        }
+
+        /// Pauses or resumes the redemption of the codes of `campaign`.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::pause_campaign())]
+        pub fn pause_campaign(origin: OriginFor<T>, campaign: u32, paused: bool) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::pause_campaign { campaign, paused },
+            );
+
+            Campaigns::<T>::try_mutate(campaign, |info| -> DispatchResult {
+                let info = info.as_mut().ok_or(Error::<T>::UnknownCampaign)?;
+                ensure!(
+                    info.status != CampaignStatus::Closed,
+                    Error::<T>::CampaignClosed
+                );
+                info.status = if paused {
+                    CampaignStatus::Paused
+                } else {
+                    CampaignStatus::Active
+                };
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::<T>::CampaignPaused { campaign, paused });
+            Ok(())
+        }
+
+        /// Closes `campaign`, its codes can't be redeemed anymore.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::close_campaign())]
+        pub fn close_campaign(origin: OriginFor<T>, campaign: u32) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&manager, &Call::<T>::close_campaign { campaign });
+
+            let info = Campaigns::<T>::try_mutate(campaign, |info| -> Result<_, DispatchError> {
+                let info = info.as_mut().ok_or(Error::<T>::UnknownCampaign)?;
+                ensure!(
+                    info.status != CampaignStatus::Closed,
+                    Error::<T>::CampaignClosed
+                );
+                info.status = CampaignStatus::Closed;
+                Ok(info.clone())
+            })?;
+
+            Self::deposit_event(Event::<T>::CampaignClosed {
+                campaign,
+                redemptions: info.redemptions,
+                total_duration: info.total_duration,
+            });
+            Ok(())
+        }
+    }
+}
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{dispatch::DispatchResult, ensure, pallet_prelude::BoundedVec, traits::Get};
+use pns_types::DomainHash;
+use scale_info::TypeInfo;
+use sp_core::H256;
+use sp_runtime::{traits::Saturating, RuntimeDebug};
+
+/// The label lengths a code of `name_redeem_any` can be used for.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum LengthClass {
+    /// Labels of exactly this many characters.
+    Exactly(u32),
+    /// Labels of at least this many characters.
+    AtLeast(u32),
+}
+
+impl LengthClass {
+    pub fn contains(&self, len: usize) -> bool {
+        match *self {
+            LengthClass::Exactly(exact) => len == exact as usize,
+            LengthClass::AtLeast(min) => len >= min as usize,
+        }
+    }
+}
+
+/// The use of a code, see `SpentCodes`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct SpentCode<BlockNumber> {
+    /// The domain registered or renewed with the code.
+    pub node: DomainHash,
+    /// The block of the redemption.
+    pub at: BlockNumber,
+}
+
+pub type SpentCodeOf<T> = SpentCode<<T as frame_system::Config>::BlockNumber>;
+
+/// Whether the codes of a campaign can be redeemed.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum CampaignStatus {
+    Active,
+    Paused,
+    Closed,
+}
+
+/// A group of codes sharing a budget, see `create_campaign`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Campaign<Name, Moment> {
+    pub name: Name,
+    /// The most names the codes can register or renew, `None` for no limit.
+    pub max_redemptions: Option<u32>,
+    /// The most duration the codes can give in total, `None` for no limit.
+    pub max_duration: Option<Moment>,
+    pub status: CampaignStatus,
+    /// The names registered or renewed so far.
+    pub redemptions: u32,
+    /// The duration given so far.
+    pub total_duration: Moment,
+}
+
+pub type CampaignNameOf<T> = BoundedVec<u8, <T as Config>::MaxCampaignNameLength>;
+
+pub type CampaignOf<T> = Campaign<CampaignNameOf<T>, <T as Config>::Moment>;
+
+/// A batch of codes, see `commit_redeem_batch`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct RedeemBatch<BlockNumber> {
+    /// The Merkle root of the leaves of the codes.
+    pub root: H256,
+    /// The number of codes, the leaves past it are zero.
+    pub size: u32,
+    /// The last block the codes can be redeemed at.
+    pub expires_at: BlockNumber,
+}
+
+pub type RedeemBatchOf<T> = RedeemBatch<<T as frame_system::Config>::BlockNumber>;
+
+impl<T: Config> Pallet<T> {
+    /// The leaf of the code at `index` of a batch.
+    pub fn leaf_of(index: u32, duration: T::Moment, owner: &T::AccountId) -> H256 {
+        H256(sp_io::hashing::blake2_256(
+            &(index, duration, owner).encode(),
+        ))
+    }
+
+    /// The root reached from `leaf` at `index` by the sibling hashes of `proof`.
+    pub fn root_of(mut index: u32, leaf: H256, proof: &[H256]) -> H256 {
+        proof.iter().fold(leaf, |node, sibling| {
+            let pair = if index % 2 == 0 {
+                (node, *sibling)
+            } else {
+                (*sibling, node)
+            };
+            index /= 2;
+            H256(sp_io::hashing::blake2_256(&pair.encode()))
+        })
+    }
+
+    /// Checks the code `nouce` can still be redeemed for `owner`.
+    fn check_code(nouce: u32, owner: &T::AccountId) -> DispatchResult {
+        let expires_at = Redeems::<T>::get(nouce).ok_or(Error::<T>::RedeemsHasBeenUsed)?;
+        ensure!(!Self::is_expired(expires_at), Error::<T>::RedeemCodeExpired);
+        ensure!(
+            !RedeemedBy::<T>::contains_key(nouce, owner),
+            Error::<T>::AlreadyRedeemed
+        );
+        Ok(())
+    }
+
+    /// Counts a redemption of `duration` with the code `nouce` against the
+    /// budget of its campaign, if any.
+    fn charge_campaign(nouce: u32, duration: T::Moment) -> DispatchResult {
+        let Some(campaign) = RedeemCampaign::<T>::get(nouce) else {
+            return Ok(());
+        };
+        Campaigns::<T>::try_mutate(campaign, |info| -> DispatchResult {
+            let info = info.as_mut().ok_or(Error::<T>::UnknownCampaign)?;
+            ensure!(
+                info.status == CampaignStatus::Active,
+                Error::<T>::CampaignNotActive
+            );
+            let redemptions = info.redemptions.saturating_add(1);
+            let total_duration = info.total_duration.saturating_add(duration);
+            ensure!(
+                info.max_redemptions.map_or(true, |max| redemptions <= max)
+                    && info.max_duration.map_or(true, |max| total_duration <= max),
+                Error::<T>::CampaignBudgetExceeded
+            );
+            info.redemptions = redemptions;
+            info.total_duration = total_duration;
+            Ok(())
+        })
+    }
+
+    /// Uses the code `nouce` for `owner` on `node` and returns its remaining uses.
+    fn use_code(nouce: u32, owner: &T::AccountId, node: DomainHash) -> u32 {
+        SpentCodes::<T>::insert(
+            nouce,
+            owner,
+            SpentCode {
+                node,
+                at: frame_system::Pallet::<T>::block_number(),
+            },
+        );
+        let remaining = RedeemUses::<T>::get(nouce).unwrap_or(1).saturating_sub(1);
+        if remaining == 0 {
+            Self::remove_code(nouce);
+        } else {
+            RedeemUses::<T>::insert(nouce, remaining);
+            RedeemedBy::<T>::insert(nouce, owner, ());
+        }
+        remaining
+    }
+
+    /// Removes the code `nouce` and the owners which redeemed it.
+    fn remove_code(nouce: u32) {
+        Redeems::<T>::remove(nouce);
+        RedeemCampaign::<T>::remove(nouce);
+        if RedeemUses::<T>::take(nouce).is_some() {
+            let _ = RedeemedBy::<T>::clear_prefix(nouce, T::MaxRedeemUses::get(), None);
+        }
+    }
+
+    /// Whether a code redeemable until `expires_at` has expired.
+    pub fn is_expired(expires_at: Option<T::BlockNumber>) -> bool {
+        expires_at.map_or(false, |expires_at| {
+            frame_system::Pallet::<T>::block_number() > expires_at
+        })
+    }
+
+    /// The depth of the Merkle tree of a batch of `size` codes.
+    pub fn batch_depth(size: u32) -> u32 {
+        32 - size.saturating_sub(1).leading_zeros()
     }
 }
 
@@ -285,10 +1022,30 @@ pub trait WeightInfo {
             + Self::for_redeem_code(len - MIN_REGISTRABLE_LEN as u32)
             + Self::name_redeem_any_min()
     }
+    fn name_redeem_batch(len: u32) -> Weight {
+        Self::create_label(len - MIN_REGISTRABLE_LEN as u32)
+            + Self::for_redeem_code(len - MIN_REGISTRABLE_LEN as u32)
+            + Self::name_redeem_batch_min()
+    }
+    /// Does the same work as `name_redeem`.
+    fn name_redeem_exact(len: u32) -> Weight {
+        Self::name_redeem(len)
+    }
+    /// Bounded by the work of `name_redeem`.
+    fn renew_redeem(len: u32) -> Weight {
+        Self::name_redeem(len)
+    }
+    fn commit_redeem_batch() -> Weight;
+    fn create_campaign() -> Weight;
+    fn pause_campaign() -> Weight;
+    fn close_campaign() -> Weight;
+    fn sweep_redeems(len: u32) -> Weight;
+    fn revoke_redeem(len: u32) -> Weight;
     fn create_label(len: u32) -> Weight;
     fn for_redeem_code(len: u32) -> Weight;
     fn name_redeem_min() -> Weight;
     fn name_redeem_any_min() -> Weight;
+    fn name_redeem_batch_min() -> Weight;
 }
 
 impl WeightInfo for () {
@@ -311,4 +1068,32 @@ impl WeightInfo for () {
     fn name_redeem_any_min() -> Weight {
         Weight::zero()
     }
+
+    fn commit_redeem_batch() -> Weight {
+        Weight::zero()
+    }
+
+    fn create_campaign() -> Weight {
+        Weight::zero()
+    }
+
+    fn pause_campaign() -> Weight {
+        Weight::zero()
+    }
+
+    fn close_campaign() -> Weight {
+        Weight::zero()
+    }
+
+    fn sweep_redeems(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn revoke_redeem(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn name_redeem_batch_min() -> Weight {
+        Weight::zero()
+    }
 }
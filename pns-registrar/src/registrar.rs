@@ -21,10 +21,75 @@
 //! ### Module functions
 //! - `add_reserved` - adds a pre-reserved domain name (pre-reserved domains cannot be registered), requires manager privileges
 //! - `remove_reserved` - removes a reserved domain name, requires manager privileges
+//! - `add_reserved_batch` - adds several reserved domain names at once, batches larger than `ReserveBatchThreshold` require the governance origin
+//! - `remove_reserved_batch` - removes several reserved domain names at once, same permission as `add_reserved_batch`
+//! - `add_reservation_rule` - reserves every domain name matching a rule (a prefix or a length), requires manager privileges
+//! - `remove_reservation_rule` - removes a reservation rule, requires manager privileges
+//! - `reserve_for` - holds a domain name so that only the given account can register it, requires manager privileges
+//! - `clear_reserved_for` - removes the hold of a domain name, requires manager privileges
+//! - `assign_reserved_claim` - lets an account claim a reserved domain name until a deadline, requires manager privileges
+//! - `claim_reserved` - registers a reserved domain name assigned to the caller, paying the normal fees
 //! - `register` - register a domain name
-//! - `renew` - renew a domain name, requires caller to have permission to operate the domain
+//! - `register_batch` - register several domain names with a single payment
+//! - `register_with_config` - register a domain name and set up its resolver records at once
+//! - `commit` - commits to registering a domain name without revealing it, reserving `CommitmentDeposit`
+//! - `reveal_register` - registers a domain name committed to earlier, so the registration can't be front-run
+//! - `remove_commitment` - returns the deposit of a commitment, its committer at any time and anyone once it is too old
+//! - `renew` - renew a domain name, anyone can pay for it unless the owner has locked renewals
+//! - `renew_batch` - renew several domain names with a single payment
+//! - `enable_auto_renew` - renews a domain name automatically before it expires, paid from a reserved pot, requires the permission of the domain
+//! - `top_up` - adds to the pot of an auto-renewal, only its payer
+//! - `cancel_auto_renew` - stops an auto-renewal and returns what is left in the pot, its payer or the permission of the domain
+//! - `set_renew_lock` - only allow the owner (or its operators) to renew a domain name, requires the permission of the domain
 //! - `transfer` - transfer a domain name, requires the caller to have permission to operate the domain name
+//! - `lock` - blocks transfers and approvals of a domain name until a given time, only its owner
+//! - `unlock` - lifts the lock of a domain name early, only its owner
+//! - `transfer_batch` - transfer several domain names to the same account at once, requires the permission of every domain
+//! - `transfer_and_reset` - transfer a domain name and clear its records, resolver and approvals, same permission as `transfer`
+//! - `offer_transfer` - offers a domain name to an account, which has to accept it within `TransferOfferExpiry` blocks, same permission as `transfer`
+//! - `accept_transfer` - takes over a domain name offered to the caller
+//! - `offer_sale` - same as `offer_transfer`, but the account pays a price when accepting it, of which the royalty of the domain goes to its beneficiary
+//! - `cancel_transfer_offer` - withdraws an offer, its offerer or the permission of the domain
 //! - `mint_subname` - Cast a subdomain, requires the caller to have permission to operate the domain
+//! - `mint_subnames` - casts several subdomains of a domain to the same account at once, same permission as `mint_subname`
+//! - `offer_subname_lease` - offers a subdomain to an account for a limited time and a rent paid to the owner of the domain, requires the permission of the domain
+//! - `accept_subname_lease` - takes a subdomain lease offered to the caller, or extends the one it holds, and pays its rent
+//! - `reclaim_subname` - burns a subdomain whose lease is over, requires the permission of its parent domain
+//! - `set_subname_expiry` - lets a subdomain expire before the domain it belongs to, requires the permission of its parent domain
+//! - `buy_capacity` - buys more subdomain slots for a domain name, requires the caller to have permission to operate the domain
+//! - `set_phase` - moves the registrar between the `Closed`, `Whitelist` and `Open` registration phases, requires manager privileges
+//! - `add_whitelisted` - lets an account register domain names in the whitelist phase, requires manager privileges
+//! - `remove_whitelisted` - removes an account from the whitelist, requires manager privileges
+//! - `redeem_voucher` - whitelists the caller with a voucher signed by the official account
+//! - `create_tld` - creates a new top level domain and mints its base node to the official account, requires manager privileges
+//! - `set_tld_config` - changes the grace period, default capacity, minimum duration or openness of a base node, requires manager privileges
+//! - `bid` - bids on a name shorter than `MIN_REGISTRABLE_LEN`, the first bid starts its auction
+//! - `settle_auction` - registers an auctioned name to the winner once the auction is over, anyone can call it
+//! - `claim_bid` - returns a bid which lost a settled auction to its bidder, anyone can call it
+//! - `reap_expired` - burns a domain name past its grace period and cooldown and clears its records, anyone can call it for a share of the deposit
+//! - `slash_deposit` - slashes part of the deposit of an abusive domain name to the treasury and optionally burns it, requires the judicial origin
+//!
+//! The auctions of short names are candle auctions, see `crate::auction`: the last
+//! `AuctionEndingPeriod` of an auction is cut into samples of `AuctionSampleLength`,
+//! and a sample is drawn with `Randomness` once it is over. Bids which could still
+//! win stay reserved until the auction is settled, the others are returned as
+//! soon as they are outbid. When bids of several samples could win,
+//! `settle_auction` first closes the auction and settles it with randomness
+//! which was only known after that. The bids which lost are returned by `claim_bid`.
+//!
+//! Names are registered under a base node (a top level domain such as `dot`),
+//! each base node in `BaseNodes` has its own grace period, default subdomain
+//! capacity and minimum registration duration.
+//!
+//! Domains entering the last `ExpiryWarningPeriod` before their expiry get a
+//! `NameExpiringSoon` event, so wallets don't need to poll every domain.
+//!
+//! Other pallets can follow registrations, renewals, transfers and expiries
+//! through `Lifecycle`, see `traits::OnDomainLifecycle`.
+//!
+//! Registration, renewal and capacity fees are split: `FeeBurnShare` of them is burned,
+//! `FeeTreasuryShare` goes to `Treasury` and the rest to the official account.
+//! Deposits always go to the official account.
 //!
 //! There is a problem with the part about deposits, first review the process of collecting deposits:
 //! 1. the deposit is the transaction of the registered domain name to the `PnsOfficial` account
@@ -47,19 +112,37 @@ pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Curre
     <T as frame_system::Config>::AccountId,
 >>::Balance;
 
+pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<
+    <T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+pub type AssetIdOf<T> =
+    <<T as Config>::Assets as frame_support::traits::tokens::fungibles::Inspect<
+        <T as frame_system::Config>::AccountId,
+    >>::AssetId;
+
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
-    use crate::traits::{IsRegistrarOpen, Label, Official, PriceOracle, Registry};
+    use crate::traits::{
+        AuditLog, IsRegistrarOpen, Label, LabelPolicy, Official, OnDomainLifecycle, Operation,
+        PriceOracle, Registry, ResolverRecords,
+    };
     use frame_support::{
         pallet_prelude::*,
-        traits::{Currency, EnsureOrigin, ExistenceRequirement, ReservableCurrency, Time},
+        traits::{
+            tokens::fungibles, BalanceStatus, Currency, EnsureOrigin, ExistenceRequirement,
+            Imbalance, OnUnbalanced, Randomness, ReservableCurrency, Time, WithdrawReasons,
+        },
         Twox64Concat,
     };
     use frame_system::{ensure_signed, pallet_prelude::*};
-    use pns_types::{DomainHash, RegistrarInfo};
-    use sp_runtime::traits::{AtLeast32Bit, CheckedAdd, MaybeSerializeDeserialize, StaticLookup};
-    use sp_runtime::ArithmeticError;
+    use pns_types::{DomainHash, RegistrarInfo, TldConfig};
+    use sp_runtime::traits::{
+        AtLeast32Bit, CheckedAdd, IdentifyAccount, MaybeSerializeDeserialize, One, Saturating,
+        StaticLookup, Verify, Zero,
+    };
+    use sp_runtime::{ArithmeticError, Perbill};
     use sp_std::vec::Vec;
 
     #[pallet::config]
@@ -68,7 +151,11 @@ pub mod pallet {
 
         type ResolverId: Parameter + Default;
 
-        type Registry: Registry<AccountId = Self::AccountId, Balance = BalanceOf<Self>>;
+        type Registry: Registry<
+            AccountId = Self::AccountId,
+            Balance = BalanceOf<Self>,
+            ResolverId = Self::ResolverId,
+        >;
 
         type Currency: ReservableCurrency<Self::AccountId>;
 
@@ -81,32 +168,358 @@ pub mod pallet {
             + MaxEncodedLen
             + MaybeSerializeDeserialize;
 
+        /// Added to the price of renewals made after `expire`, within the grace period.
+        #[pallet::constant]
+        type GraceRenewalSurcharge: Get<Perbill>;
+
+        /// Time after the grace period of a domain during which only the previous
+        /// owner can register the name again, zero disables it.
+        #[pallet::constant]
+        type ReRegistrationCooldown: Get<Self::Moment>;
+
+        /// How old a commitment has to be before it can be revealed.
+        #[pallet::constant]
+        type MinCommitmentAge: Get<Self::Moment>;
+
+        /// Commitments older than this can no longer be revealed.
+        #[pallet::constant]
+        type MaxCommitmentAge: Get<Self::Moment>;
+
+        /// Reserved from the committer until its commitment is revealed or removed.
+        #[pallet::constant]
+        type CommitmentDeposit: Get<BalanceOf<Self>>;
+
+        /// Whether names can only be registered with `reveal_register`, a reveal
+        /// can otherwise still be front-run with `register`, `register_batch`
+        /// or `register_with_config`.
+        #[pallet::constant]
+        type RequireCommitment: Get<bool>;
+
+        /// The most subdomains a domain name can have, including bought capacity.
+        #[pallet::constant]
+        type MaxCapacity: Get<u32>;
+
+        /// How far in the future a domain can expire at most,
+        /// enforced on registration and renewal.
+        #[pallet::constant]
+        type MaxRegistrationDuration: Get<Self::Moment>;
+
+        /// Resolver every newly registered domain starts with.
+        #[pallet::constant]
+        type DefaultResolver: Get<Self::ResolverId>;
+
+        /// How many names a single account can register in one block,
+        /// enforced by [`CheckRegistrationLimit`].
+        #[pallet::constant]
+        type MaxRegistrationsPerBlock: Get<u32>;
+
+        /// How many names a single account can register within `RegistrationQuotaWindow`,
+        /// zero disables the quota.
+        #[pallet::constant]
+        type RegistrationQuota: Get<u32>;
+
+        /// The rolling window `RegistrationQuota` is counted over, e.g. a day.
+        #[pallet::constant]
+        type RegistrationQuotaWindow: Get<Self::Moment>;
+
+        /// How many names a single `register_batch`, `renew_batch`
+        /// or `transfer_batch` call can handle.
+        #[pallet::constant]
+        type MaxBatchSize: Get<u32>;
+
+        /// How many records `register_with_config` can set up at once.
+        #[pallet::constant]
+        type MaxInitialRecords: Get<u32>;
+
+        /// Where `register_with_config` writes the initial records to.
+        type ResolverRecords: ResolverRecords;
+
+        /// Auto-renewals are due once their domain expires within this time.
+        #[pallet::constant]
+        type AutoRenewWindow: Get<Self::Moment>;
+
+        /// How many auto-renewals are looked at in one block at most.
+        #[pallet::constant]
+        type MaxAutoRenewalsPerBlock: Get<u32>;
+
+        /// `NameExpiringSoon` is emitted once a domain expires within this time.
+        #[pallet::constant]
+        type ExpiryWarningPeriod: Get<Self::Moment>;
+
+        /// Width of the time buckets expiries are indexed by, e.g. a day.
+        #[pallet::constant]
+        type ExpiryBucket: Get<Self::Moment>;
+
+        /// How many `NameExpiringSoon` events are emitted in one block at most,
+        /// the rest follow in the next blocks.
+        #[pallet::constant]
+        type MaxExpiryWarningsPerBlock: Get<u32>;
+
+        /// How many buckets of `ExpiringAt` are looked at in one block at most,
+        /// so a hook falling behind catches up over several blocks.
+        #[pallet::constant]
+        type MaxExpiryBucketsPerBlock: Get<u32>;
+
+        /// Share of the deposit paid to whoever reaps an expired domain with `reap_expired`,
+        /// the rest goes back to the owner.
+        #[pallet::constant]
+        type ReapReward: Get<Perbill>;
+
+        /// Share of registration, renewal and capacity fees that is burned.
+        #[pallet::constant]
+        type FeeBurnShare: Get<Perbill>;
+
+        /// Share of registration, renewal and capacity fees that goes to `Treasury`,
+        /// the rest of the fees stays with the official account.
+        #[pallet::constant]
+        type FeeTreasuryShare: Get<Perbill>;
+
+        /// Receives the treasury share of the fees.
+        type Treasury: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+        /// How many domains the `on_idle` sweeper looks at in one block at most.
+        #[pallet::constant]
+        type MaxReapsPerBlock: Get<u32>;
+
+        /// How many reservation rules there can be at most,
+        /// every registration is checked against all of them.
+        #[pallet::constant]
+        type MaxReservationRules: Get<u32>;
+
+        /// How long the auction of a short name runs after its first bid.
+        #[pallet::constant]
+        type AuctionPeriod: Get<Self::Moment>;
+
+        /// The lowest bid an auction can start with.
+        #[pallet::constant]
+        type MinAuctionBid: Get<BalanceOf<Self>>;
+
+        /// The most bidders with a reserved bid an auction can have,
+        /// each of them but the winner claims it back with `claim_bid`.
         #[pallet::constant]
-        type GracePeriod: Get<Self::Moment>;
+        type MaxAuctionBidders: Get<u32>;
 
+        /// The end of an auction in which any sample can turn out to be the last one,
+        /// at most `AuctionPeriod`.
         #[pallet::constant]
-        type DefaultCapacity: Get<u32>;
+        type AuctionEndingPeriod: Get<Self::Moment>;
 
+        /// The length of each sample of the `AuctionEndingPeriod`.
         #[pallet::constant]
-        type BaseNode: Get<DomainHash>;
+        type AuctionSampleLength: Get<Self::Moment>;
+
+        /// Draws the sample which decides an auction, e.g. BABE's `RandomnessFromOneEpochAgo`.
+        type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
 
+        /// How many blocks the recipient of an `offer_transfer` has to accept it.
         #[pallet::constant]
-        type MinRegistrationDuration: Get<Self::Moment>;
+        type TransferOfferExpiry: Get<Self::BlockNumber>;
 
         type WeightInfo: WeightInfo;
 
-        type PriceOracle: PriceOracle<Moment = Self::Moment, Balance = BalanceOf<Self>>;
+        type PriceOracle: PriceOracle<
+            Moment = Self::Moment,
+            Balance = BalanceOf<Self>,
+            AssetId = AssetIdOf<Self>,
+        >;
+
+        /// Assets the fees can also be paid in, at the rates of the `PriceOracle`.
+        type Assets: fungibles::Transfer<Self::AccountId, Balance = BalanceOf<Self>>;
 
         type ManagerOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
 
+        /// Records the phase, whitelist, reservation and top level domain changes,
+        /// and every slashed deposit.
+        type AuditLog: AuditLog<Self::AccountId>;
+
+        /// Manages the reserved names and the reservation rules.
+        type ReserveOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+        /// Required instead of the `ReserveOrigin` for reserved list batches
+        /// larger than `ReserveBatchThreshold`, e.g. root or a council.
+        type GovernanceOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+        /// How many names a single `add_reserved_batch` or `remove_reserved_batch`
+        /// call of the `ReserveOrigin` can handle.
+        #[pallet::constant]
+        type ReserveBatchThreshold: Get<u32>;
+
+        /// Slashes the deposits of abusive domain names, see `slash_deposit`.
+        type JudicialOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
         type IsOpen: IsRegistrarOpen;
 
+        /// Which labels can be registered and the form they are stored with,
+        /// `()` for the rules of `traits::check_label`.
+        type LabelPolicy: LabelPolicy;
+
+        /// Notified of registrations, renewals, transfers and expiries, `()` if nobody listens.
+        type Lifecycle: OnDomainLifecycle<Self::AccountId, Self::Moment>;
+
         type Official: Official<AccountId = Self::AccountId>;
+
+        /// Signer of whitelist vouchers, see `redeem_voucher`.
+        type VoucherSigner: IdentifyAccount<AccountId = Self::AccountId>;
+
+        /// Whitelist voucher, a signature of the official account.
+        type VoucherSignature: Parameter + Verify<Signer = Self::VoucherSigner>;
     }
 
     #[pallet::pallet]
     pub struct Pallet<T>(_);
 
+    /// An auto-renewal of a domain name, paid from balance reserved on the `payer`.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct AutoRenewal<AccountId, Moment, Balance> {
+        pub payer: AccountId,
+        pub base_node: DomainHash,
+        pub name: BoundedVec<u8, ConstU32<{ crate::traits::LABEL_MAX_LEN as u32 }>>,
+        /// How long each renewal extends the domain.
+        pub duration: Moment,
+        /// What is left of the reserved balance.
+        pub pot: Balance,
+    }
+
+    pub type AutoRenewalOf<T> =
+        AutoRenewal<<T as frame_system::Config>::AccountId, <T as Config>::Moment, BalanceOf<T>>;
+
+    /// The auction of a name too short to be registered, see `bid`.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct Auction<AccountId, Moment, Balance> {
+        pub base_node: DomainHash,
+        pub name: BoundedVec<u8, ConstU32<{ crate::traits::LABEL_MAX_LEN as u32 }>>,
+        /// The highest bidder, its bid is reserved.
+        pub bidder: AccountId,
+        pub amount: Balance,
+        pub end: Moment,
+    }
+
+    pub type AuctionOf<T> =
+        Auction<<T as frame_system::Config>::AccountId, <T as Config>::Moment, BalanceOf<T>>;
+
+    /// A domain name offered by `from` to `to`, see `offer_transfer` and `offer_sale`.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct TransferOffer<AccountId, BlockNumber, Balance> {
+        pub from: AccountId,
+        pub to: AccountId,
+        /// The last block the offer can be accepted in.
+        pub deadline: BlockNumber,
+        /// What `to` pays for it, zero if it is given away.
+        pub price: Balance,
+    }
+
+    /// A subdomain offered by the owner of `node` to `to` for `duration` and `rent`,
+    /// see `offer_subname_lease`.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct LeaseOffer<AccountId, Moment, Balance> {
+        pub node: DomainHash,
+        pub label: BoundedVec<u8, ConstU32<{ crate::traits::LABEL_MAX_LEN as u32 }>>,
+        pub to: AccountId,
+        pub duration: Moment,
+        pub rent: Balance,
+    }
+
+    pub type LeaseOfferOf<T> =
+        LeaseOffer<<T as frame_system::Config>::AccountId, <T as Config>::Moment, BalanceOf<T>>;
+
+    /// A leased subdomain of `node`, which can be reclaimed after `expire`.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct Lease<Moment> {
+        pub node: DomainHash,
+        pub expire: Moment,
+    }
+
+    /// How the fees of a registration or renewal are paid.
+    #[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+    pub enum FeePayment<AssetId> {
+        /// In the native token, split by `FeeBurnShare` and `FeeTreasuryShare`.
+        Native,
+        /// In an asset accepted by the price oracle.
+        Asset(AssetId),
+        /// Already paid to the official account by the caller.
+        Prepaid,
+    }
+
+    impl<AssetId> From<Option<AssetId>> for FeePayment<AssetId> {
+        fn from(asset: Option<AssetId>) -> Self {
+            asset.map_or(FeePayment::Native, FeePayment::Asset)
+        }
+    }
+
+    pub type FeePaymentOf<T> = FeePayment<AssetIdOf<T>>;
+
+    pub type TransferOfferOf<T> = TransferOffer<
+        <T as frame_system::Config>::AccountId,
+        <T as frame_system::Config>::BlockNumber,
+        BalanceOf<T>,
+    >;
+
+    /// Reserves every name it matches, like an entry of `ReservedList` does for a single name.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum ReservationRule {
+        /// A single name.
+        Exact(DomainHash),
+        /// Every name starting with the label.
+        Prefix(BoundedVec<u8, ConstU32<{ crate::traits::LABEL_MAX_LEN as u32 }>>),
+        /// Every name with a length within `min..=max`.
+        Length { min: u32, max: u32 },
+    }
+
+    impl ReservationRule {
+        pub fn matches(&self, name: &[u8], node: DomainHash) -> bool {
+            match self {
+                ReservationRule::Exact(reserved) => *reserved == node,
+                ReservationRule::Prefix(prefix) => name.starts_with(prefix),
+                ReservationRule::Length { min, max } => {
+                    (*min as usize..=*max as usize).contains(&name.len())
+                }
+            }
+        }
+    }
+
+    /// Who can register names right now.
+    #[derive(
+        Encode, Decode, Clone, Copy, PartialEq, Eq, Default, RuntimeDebug, TypeInfo, MaxEncodedLen,
+    )]
+    pub enum RegistrationPhase {
+        /// Nobody.
+        Closed,
+        /// Only the accounts in `Whitelist`.
+        Whitelist,
+        /// Everybody.
+        #[default]
+        Open,
+    }
+
+    /// The current registration phase, renewals are allowed in every phase.
+    #[pallet::storage]
+    pub type Phase<T: Config> = StorageValue<_, RegistrationPhase, ValueQuery>;
+
+    /// Accounts which can register names in the `Whitelist` phase.
+    #[pallet::storage]
+    pub type Whitelist<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, ()>;
+
+    /// `base_node` -> the settings of the domains registered under it
+    #[pallet::storage]
+    pub type BaseNodes<T: Config> = StorageMap<_, Twox64Concat, DomainHash, TldConfigOf<T>>;
+
+    /// The base node redeem codes are issued for.
+    #[pallet::storage]
+    pub type DefaultBaseNode<T: Config> = StorageValue<_, DomainHash, ValueQuery>;
+
+    /// `name_hash` -> the base node it is registered under
+    #[pallet::storage]
+    pub type BaseNodeOf<T: Config> = StorageMap<_, Twox64Concat, DomainHash, DomainHash>;
+
+    /// `base_node` -> its label, for the base nodes created by `create_tld`
+    #[pallet::storage]
+    pub type TldLabels<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        DomainHash,
+        BoundedVec<u8, ConstU32<{ crate::traits::LABEL_MAX_LEN as u32 }>>,
+    >;
+
     /// `name_hash` -> Info{ `expire`, `capacity`, `deposity`, `register_fee`}
     #[pallet::storage]
     pub type RegistrarInfos<T: Config> =
@@ -116,10 +529,148 @@ pub mod pallet {
     #[pallet::storage]
     pub type ReservedList<T: Config> = StorageMap<_, Twox64Concat, DomainHash, (), ValueQuery>;
 
+    /// `name_hash` if held for an account -> `account`
+    ///
+    /// Unlike `reserved_list`, the holder itself is still able to register the name.
+    #[pallet::storage]
+    pub type ReservedFor<T: Config> = StorageMap<_, Twox64Concat, DomainHash, T::AccountId>;
+
+    /// Rules reserving every name they match, checked on registration.
+    #[pallet::storage]
+    pub type ReservationRules<T: Config> =
+        StorageValue<_, BoundedVec<ReservationRule, T::MaxReservationRules>, ValueQuery>;
+
+    /// `name_hash` in `reserved_list` -> (`claimant`, `deadline`)
+    ///
+    /// The name stays reserved for everyone else, also after the deadline passed.
+    #[pallet::storage]
+    pub type ReservedClaims<T: Config> =
+        StorageMap<_, Twox64Concat, DomainHash, (T::AccountId, T::Moment)>;
+
+    /// `name_hash` -> the pending offer to transfer it
+    #[pallet::storage]
+    pub type TransferOffers<T: Config> =
+        StorageMap<_, Twox64Concat, DomainHash, TransferOfferOf<T>>;
+
+    /// `name_hash` -> its running auction
+    #[pallet::storage]
+    pub type Auctions<T: Config> = StorageMap<_, Twox64Concat, DomainHash, AuctionOf<T>>;
+
+    /// (`name_hash`,`sample`) -> (`bidder`,`amount`) the highest bid at the end of
+    /// the sample, the samples without bids keep the one of the sample before.
+    #[pallet::storage]
+    pub type AuctionSamples<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        DomainHash,
+        Twox64Concat,
+        u32,
+        (T::AccountId, BalanceOf<T>),
+    >;
+
+    /// (`name_hash`,`bidder`) -> its highest bid which is still reserved, it may still win
+    #[pallet::storage]
+    pub type AuctionReserves<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        DomainHash,
+        Twox64Concat,
+        T::AccountId,
+        BalanceOf<T>,
+        ValueQuery,
+    >;
+
+    /// `name_hash` -> how many bidders have a reserved bid in its auction, see `claim_bid`
+    #[pallet::storage]
+    pub type AuctionBidders<T: Config> = StorageMap<_, Twox64Concat, DomainHash, u32, ValueQuery>;
+
+    /// `name_hash` -> the block its ended auction was closed in, see `settle_auction`
+    #[pallet::storage]
+    pub type AuctionClosings<T: Config> = StorageMap<_, Twox64Concat, DomainHash, T::BlockNumber>;
+
+    /// `subnode` -> the pending offer to lease it
+    #[pallet::storage]
+    pub type LeaseOffers<T: Config> = StorageMap<_, Twox64Concat, DomainHash, LeaseOfferOf<T>>;
+
+    /// `subnode` -> its lease
+    #[pallet::storage]
+    pub type SubnameLeases<T: Config> = StorageMap<_, Twox64Concat, DomainHash, Lease<T::Moment>>;
+
+    /// `subnode` -> its own expiry, subdomains without one expire with their root
+    #[pallet::storage]
+    pub type SubnameExpiries<T: Config> = StorageMap<_, Twox64Concat, DomainHash, T::Moment>;
+
+    /// `name_hash` -> (`owner`, `until`), the lock is lifted once the domain changes hands
+    #[pallet::storage]
+    pub type TransferLocks<T: Config> =
+        StorageMap<_, Twox64Concat, DomainHash, (T::AccountId, T::Moment)>;
+
+    /// Registrations ever made.
+    #[pallet::storage]
+    pub type TotalRegistrations<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Renewals ever made.
+    #[pallet::storage]
+    pub type TotalRenewals<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Registered domains which have not been burned.
+    #[pallet::storage]
+    pub type ActiveDomains<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// `name_hash` if only its owner (or operators) may renew it -> ()
+    #[pallet::storage]
+    pub type RenewLocked<T: Config> = StorageMap<_, Twox64Concat, DomainHash, (), ValueQuery>;
+
+    /// `commitment` -> (`committer`, `committed_at`, `deposit`)
+    #[pallet::storage]
+    pub type Commitments<T: Config> =
+        StorageMap<_, Twox64Concat, DomainHash, (T::AccountId, T::Moment, BalanceOf<T>)>;
+
+    /// `account` -> (`block_number`, names it has registered in that block),
+    /// the count of an earlier block is ignored instead of cleared.
+    #[pallet::storage]
+    pub type RegistrationsInBlock<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, (T::BlockNumber, u32), ValueQuery>;
+
+    /// `account` -> when it registered names within the last `RegistrationQuotaWindow`
+    #[pallet::storage]
+    pub type RecentRegistrations<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<T::Moment, T::RegistrationQuota>,
+        ValueQuery,
+    >;
+
+    /// `name_hash` -> auto-renewal
+    #[pallet::storage]
+    pub type AutoRenewals<T: Config> = StorageMap<_, Twox64Concat, DomainHash, AutoRenewalOf<T>>;
+
+    /// The auto-renewal looked at last, the next block continues after it.
+    #[pallet::storage]
+    pub type AutoRenewCursor<T: Config> = StorageValue<_, DomainHash>;
+
+    /// `expire / ExpiryBucket` -> `name_hash` -> ()
+    #[pallet::storage]
+    pub type ExpiringAt<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, T::Moment, Twox64Concat, DomainHash, ()>;
+
+    /// The first bucket of `ExpiringAt` no warnings were emitted for yet.
+    #[pallet::storage]
+    pub type NextExpiryBucket<T: Config> = StorageValue<_, T::Moment>;
+
+    /// The domain the `on_idle` sweeper looked at last, the next block continues after it.
+    #[pallet::storage]
+    pub type ReapCursor<T: Config> = StorageValue<_, DomainHash>;
+
     pub type RegistrarInfoOf<T> = RegistrarInfo<<T as Config>::Moment, BalanceOf<T>>;
 
+    pub type TldConfigOf<T> = TldConfig<<T as Config>::Moment>;
+
     #[pallet::genesis_config]
     pub struct GenesisConfig<T: Config> {
+        /// The first one is the default base node, `infos` are registered under it.
+        pub base_nodes: Vec<(DomainHash, TldConfigOf<T>)>,
         pub infos: Vec<(DomainHash, RegistrarInfoOf<T>)>,
         pub reserved_list: sp_std::collections::btree_set::BTreeSet<DomainHash>,
     }
@@ -128,6 +679,7 @@ pub mod pallet {
     impl<T: Config> Default for GenesisConfig<T> {
         fn default() -> Self {
             GenesisConfig {
+                base_nodes: Vec::with_capacity(0),
                 infos: Vec::with_capacity(0),
                 reserved_list: sp_std::collections::btree_set::BTreeSet::new(),
             }
@@ -137,9 +689,22 @@ pub mod pallet {
     #[pallet::genesis_build]
     impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
         fn build(&self) {
+            for (base_node, config) in self.base_nodes.iter() {
+                BaseNodes::<T>::insert(base_node, config);
+            }
+            let default_base_node = self.base_nodes.first().map(|(base_node, _)| *base_node);
+            if let Some(base_node) = default_base_node {
+                DefaultBaseNode::<T>::put(base_node);
+            }
+
             for (node, info) in self.infos.iter() {
                 RegistrarInfos::<T>::insert(node, info);
+                if let Some(base_node) = default_base_node {
+                    BaseNodeOf::<T>::insert(node, base_node);
+                }
+                Pallet::<T>::reindex_expiry(*node, None, Some(info.expire));
             }
+            ActiveDomains::<T>::put(self.infos.len() as u64);
 
             for node in self.reserved_list.iter() {
                 ReservedList::<T>::insert(node, ());
@@ -153,6 +718,7 @@ pub mod pallet {
         /// When a domain name is successfully registered, this moment will be logged.
         NameRegistered {
             name: Vec<u8>,
+            base_node: DomainHash,
             node: DomainHash,
             owner: T::AccountId,
             expire: T::Moment,
@@ -165,6 +731,13 @@ pub mod pallet {
             duration: T::Moment,
             expire: T::Moment,
         },
+        /// Several domain names were renewed by `renew_batch` for a single `price`,
+        /// each one also has its `NameRenewed`.
+        NamesRenewed {
+            nodes: Vec<DomainHash>,
+            duration: T::Moment,
+            price: BalanceOf<T>,
+        },
         /// When a sub-domain name is successfully registered, this moment will be logged.
         SubnameRegistered {
             label: Vec<u8>,
@@ -176,6 +749,195 @@ pub mod pallet {
         NameReserved { node: DomainHash },
         /// Cancel a reserved domain name.
         NameUnReserved { node: DomainHash },
+        /// Hold a domain name for an account.
+        NameReservedFor { node: DomainHash, who: T::AccountId },
+        /// Cancel the hold of a domain name.
+        NameUnReservedFor { node: DomainHash },
+        /// A reserved domain name can be claimed by `claimant` until `deadline`.
+        ReservedClaimAssigned {
+            node: DomainHash,
+            claimant: T::AccountId,
+            deadline: T::Moment,
+        },
+        /// A reserved domain name was registered by its claimant.
+        ReservedClaimed {
+            node: DomainHash,
+            claimant: T::AccountId,
+        },
+        /// Every name matching the rule is reserved.
+        ReservationRuleAdded { rule: ReservationRule },
+        /// The names matching the rule are no longer reserved by it.
+        ReservationRuleRemoved { rule: ReservationRule },
+        /// A new base node was created, names can be registered under it.
+        TldCreated {
+            label: Vec<u8>,
+            base_node: DomainHash,
+            config: TldConfigOf<T>,
+        },
+        /// The settings of a base node changed.
+        TldConfigChanged {
+            base_node: DomainHash,
+            config: TldConfigOf<T>,
+        },
+        /// The registration phase changed.
+        PhaseChanged { phase: RegistrationPhase },
+        /// The account can register names in the whitelist phase.
+        Whitelisted { who: T::AccountId },
+        /// The account was removed from the whitelist.
+        WhitelistRemoved { who: T::AccountId },
+        /// The records, resolver and approvals of a transferred domain name were cleared.
+        TransferReset { node: DomainHash, to: T::AccountId },
+        /// A domain name was offered to `to`, who can accept it until `deadline`.
+        TransferOffered {
+            node: DomainHash,
+            from: T::AccountId,
+            to: T::AccountId,
+            deadline: T::BlockNumber,
+        },
+        /// An offered domain name was accepted and transferred.
+        TransferAccepted {
+            node: DomainHash,
+            from: T::AccountId,
+            to: T::AccountId,
+        },
+        /// A transfer offer was withdrawn.
+        TransferOfferCancelled { node: DomainHash },
+        /// A domain name was offered to `to` for `price`, who can accept it until `deadline`.
+        SaleOffered {
+            node: DomainHash,
+            from: T::AccountId,
+            to: T::AccountId,
+            price: BalanceOf<T>,
+            deadline: T::BlockNumber,
+        },
+        /// `amount` of the price of a sold domain name went to the beneficiary of its royalty.
+        RoyaltyPaid {
+            node: DomainHash,
+            beneficiary: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// Transfers and approvals of a domain name are blocked until `until`.
+        DomainLocked { node: DomainHash, until: T::Moment },
+        /// The lock of a domain name was lifted.
+        DomainUnlocked { node: DomainHash },
+        /// A subdomain was offered to `to` for `duration` and `rent`.
+        LeaseOffered {
+            node: DomainHash,
+            subnode: DomainHash,
+            to: T::AccountId,
+            duration: T::Moment,
+            rent: BalanceOf<T>,
+        },
+        /// A subdomain was leased, or its lease extended, until `expire`.
+        SubnameLeased {
+            node: DomainHash,
+            subnode: DomainHash,
+            owner: T::AccountId,
+            expire: T::Moment,
+        },
+        /// A subdomain whose lease was over was burned by its parent.
+        SubnameReclaimed {
+            node: DomainHash,
+            subnode: DomainHash,
+        },
+        /// The first bid on a short name started its auction.
+        AuctionStarted {
+            node: DomainHash,
+            base_node: DomainHash,
+            end: T::Moment,
+        },
+        /// `bidder` is the highest bidder of the auction of `node` now.
+        BidPlaced {
+            node: DomainHash,
+            bidder: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// The auction of `node` was closed, it is settled with randomness known after `closed_at`.
+        AuctionClosed {
+            node: DomainHash,
+            closed_at: T::BlockNumber,
+        },
+        /// The auction of `node` is over and `winner` registered it.
+        AuctionSettled {
+            node: DomainHash,
+            winner: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// The bid `bidder` reserved in the settled auction of `node` was returned.
+        BidClaimed {
+            node: DomainHash,
+            bidder: T::AccountId,
+            amount: BalanceOf<T>,
+        },
+        /// The own expiry of a subdomain was set, `None` if it expires with its root again.
+        SubnameExpirySet {
+            node: DomainHash,
+            subnode: DomainHash,
+            expire: Option<T::Moment>,
+        },
+        /// Renewals of a domain name were locked to its owner or unlocked.
+        RenewLockChanged { node: DomainHash, locked: bool },
+        /// An account committed to registering a name.
+        Committed {
+            who: T::AccountId,
+            commitment: DomainHash,
+        },
+        /// A commitment was removed and its deposit returned to `who`.
+        CommitmentRemoved {
+            who: T::AccountId,
+            commitment: DomainHash,
+        },
+        /// A domain name is renewed automatically from now on.
+        AutoRenewEnabled {
+            node: DomainHash,
+            payer: T::AccountId,
+            duration: T::Moment,
+            pot: BalanceOf<T>,
+        },
+        /// More balance was reserved for an auto-renewal.
+        AutoRenewToppedUp {
+            node: DomainHash,
+            amount: BalanceOf<T>,
+        },
+        /// An auto-renewal was cancelled or could no longer be paid,
+        /// `refunded` was returned to its payer.
+        AutoRenewCancelled {
+            node: DomainHash,
+            refunded: BalanceOf<T>,
+        },
+        /// The domain expires within `ExpiryWarningPeriod`.
+        NameExpiringSoon { node: DomainHash, expire: T::Moment },
+        /// An expired domain was burned and its records cleared.
+        NameReaped {
+            node: DomainHash,
+            reaper: T::AccountId,
+            reward: BalanceOf<T>,
+        },
+        /// `slashed` of the deposit of a domain name went to the treasury for abuse,
+        /// `remaining` is left of it.
+        DepositSlashed {
+            node: DomainHash,
+            owner: T::AccountId,
+            slashed: BalanceOf<T>,
+            remaining: BalanceOf<T>,
+        },
+        /// A domain name was burned for abuse, after `DepositSlashed`.
+        NameBurnedForAbuse {
+            node: DomainHash,
+            owner: T::AccountId,
+        },
+        /// More subdomain slots were bought, `capacity` is the new total.
+        CapacityBought {
+            node: DomainHash,
+            capacity: u32,
+            fee: BalanceOf<T>,
+        },
+        /// A fee was paid in `asset` instead of the native token.
+        FeePaidInAsset {
+            payer: T::AccountId,
+            asset: AssetIdOf<T>,
+            amount: BalanceOf<T>,
+        },
     }
 
     #[pallet::error]
@@ -203,8 +965,132 @@ pub mod pallet {
         NotRenewable,
         /// You want to register in less time than the minimum time we set.
         RegistryDurationInvalid,
+        /// The domain would expire later than `MaxRegistrationDuration` from now.
+        RegistryDurationTooLong,
         /// Sorry, the registration center is currently closed, please pay attention to the official message and wait for the registration to open.
         RegistrarClosed,
+        /// The operation is paused by the managers for now.
+        OperationPaused,
+        /// You can afford the payment, but your balance would fall below the
+        /// existential deposit afterwards and your account would be reaped.
+        WouldBeDusted,
+        /// The name has just expired, only its previous owner can register it for now.
+        InCooldown,
+        /// There is no commitment of yours for this name, owner and secret.
+        CommitmentNotFound,
+        /// The commitment is too new, wait until it is `MinCommitmentAge` old.
+        CommitmentTooNew,
+        /// The commitment is older than `MaxCommitmentAge`, please commit again.
+        CommitmentTooOld,
+        /// The same commitment is still pending.
+        CommitmentExists,
+        /// Only the committer can remove a commitment before it is `MaxCommitmentAge` old.
+        CommitmentNotExpired,
+        /// Names can only be registered with `commit` and `reveal_register`.
+        CommitmentRequired,
+        /// The batch is empty or has more than `MaxBatchSize` names.
+        InvalidBatchSize,
+        /// More initial records than `MaxInitialRecords` allows.
+        TooManyRecords,
+        /// The domain name is already renewed automatically.
+        AutoRenewExists,
+        /// The domain name is not renewed automatically.
+        AutoRenewNotFound,
+        /// What is left in the auto-renew pot does not cover the renewal.
+        AutoRenewPotTooLow,
+        /// No slots to buy, or the capacity would exceed `MaxCapacity`.
+        InvalidCapacity,
+        /// The domain name is not in the reserved list.
+        NotReserved,
+        /// The reserved domain name is not assigned to you.
+        NoReservedClaim,
+        /// The claim window of the reserved domain name has passed.
+        ReservedClaimExpired,
+        /// There are already `MaxReservationRules` reservation rules.
+        TooManyReservationRules,
+        /// The same reservation rule exists already.
+        ReservationRuleExists,
+        /// There is no such reservation rule.
+        ReservationRuleNotFound,
+        /// Domains can't be registered under this base node.
+        UnknownBaseNode,
+        /// Registrations under this base node are closed for now.
+        TldClosed,
+        /// The base node exists already.
+        TldExists,
+        /// Only whitelisted accounts can register names in the whitelist phase.
+        NotWhitelisted,
+        /// The voucher was not signed by the official account.
+        InvalidVoucher,
+        /// The account registered too many names recently, try again later.
+        QuotaExceeded,
+        /// The domain name is not offered to you.
+        NoTransferOffer,
+        /// The transfer offer was not accepted in time.
+        TransferOfferExpired,
+        /// The domain name is locked by its owner, it can't be transferred or approved for now.
+        Locked,
+        /// The lock would already be over.
+        LockEndInPast,
+        /// The subdomain is not offered to you.
+        NoLeaseOffer,
+        /// The subdomain is not leased.
+        NotLeased,
+        /// The lease of the subdomain is not over yet.
+        LeaseNotExpired,
+        /// The subdomain is leased, its expiry follows the lease.
+        SubnameLeased,
+        /// The subdomain expired, although the domain it belongs to did not.
+        SubnameExpired,
+        /// Only names shorter than `MIN_REGISTRABLE_LEN` are auctioned, register the others.
+        NotAuctionable,
+        /// The bid is below `MinAuctionBid` or not above the highest bid.
+        BidTooLow,
+        /// The auction is over, it only has to be settled.
+        AuctionEnded,
+        /// The auction is still running.
+        AuctionNotEnded,
+        /// There is no auction for this name.
+        NoAuction,
+        /// The auction was closed, the randomness to settle it is not known yet.
+        RandomnessNotReady,
+        /// The auction is not settled yet, the bid may still win.
+        AuctionNotSettled,
+        /// The bidder has no reserved bid for this name.
+        NoBid,
+        /// Bids of the last auction of this name were not claimed yet, see `claim_bid`.
+        UnclaimedBids,
+        /// The auction has `MaxAuctionBidders` bidders with a reserved bid already.
+        TooManyBidders,
+        /// The winning bid is not reserved in full anymore.
+        InsufficientReserve,
+        /// The fees can't be paid in this asset.
+        AssetNotAccepted,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+            Self::warn_expiring()
+        }
+
+        fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            let used = Self::process_auto_renewals(remaining_weight);
+            used.saturating_add(Self::sweep_expired(remaining_weight.saturating_sub(used)))
+        }
+
+        fn integrity_test() {
+            let shares = T::FeeBurnShare::get().deconstruct() as u64
+                + T::FeeTreasuryShare::get().deconstruct() as u64;
+            assert!(
+                shares <= Perbill::one().deconstruct() as u64,
+                "FeeBurnShare and FeeTreasuryShare add up to more than the whole fee"
+            );
+            assert!(
+                T::AuctionEndingPeriod::get() <= T::AuctionPeriod::get(),
+                "AuctionEndingPeriod is longer than the whole AuctionPeriod"
+            );
+        }
     }
 
     #[pallet::call]
@@ -214,7 +1100,8 @@ pub mod pallet {
         #[pallet::call_index(0)]
         #[pallet::weight(T::WeightInfo::add_reserved())]
         pub fn add_reserved(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
-            let _who = T::ManagerOrigin::ensure_origin(origin)?;
+            let manager = T::ReserveOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&manager, &Call::<T>::add_reserved { node });
 
             ReservedList::<T>::insert(node, ());
 
@@ -226,123 +1113,42 @@ pub mod pallet {
         #[pallet::call_index(1)]
         #[pallet::weight(T::WeightInfo::remove_reserved())]
         pub fn remove_reserved(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
-            let _who = T::ManagerOrigin::ensure_origin(origin)?;
+            let manager = T::ReserveOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&manager, &Call::<T>::remove_reserved { node });
 
             ReservedList::<T>::remove(node);
+            ReservedClaims::<T>::remove(node);
 
             Self::deposit_event(Event::<T>::NameUnReserved { node });
             Ok(())
         }
-        /// Register a domain name.
+        /// Register a domain name under `base_node`.
         ///
         /// Note: The domain name must conform to the rules,
         /// while the interface is only responsible for
         /// registering domain names greater than 10 in length.
         ///
-        /// Ensure: The name must be unoccupied.
+        /// The registration fee is paid in `asset_id` if any, the deposit always
+        /// in the native token.
+        ///
+        /// Ensure: The name must be unoccupied and the base node open.
         #[pallet::call_index(2)]
         #[pallet::weight(T::WeightInfo::register(name.len() as u32))]
         #[frame_support::transactional]
         pub fn register(
             origin: OriginFor<T>,
+            base_node: DomainHash,
             name: Vec<u8>,
             owner: <T::Lookup as StaticLookup>::Source,
             duration: T::Moment,
+            asset_id: Option<AssetIdOf<T>>,
         ) -> DispatchResult {
             let caller = ensure_signed(origin)?;
             let owner = T::Lookup::lookup(owner)?;
 
-            ensure!(T::IsOpen::is_open(), Error::<T>::RegistrarClosed);
-
-            ensure!(
-                duration >= T::MinRegistrationDuration::get(),
-                Error::<T>::RegistryDurationInvalid
-            );
-
-            let (label, label_len) =
-                Label::new_with_len(&name).ok_or(Error::<T>::ParseLabelFailed)?;
+            ensure!(!T::RequireCommitment::get(), Error::<T>::CommitmentRequired);
 
-            use crate::traits::Available;
-
-            ensure!(label_len.is_registrable(), Error::<T>::LabelInvalid);
-
-            let official = T::Official::get_official_account()?;
-
-            let now = T::NowProvider::now();
-
-            let expire = now
-                .checked_add(&duration)
-                .ok_or(ArithmeticError::Overflow)?;
-
-            // 防止计算结果溢出
-            ensure!(
-                expire + T::GracePeriod::get() > now + T::GracePeriod::get(),
-                ArithmeticError::Overflow
-            );
-            let base_node = T::BaseNode::get();
-            let label_node = label.encode_with_node(&base_node);
-
-            ensure!(
-                !ReservedList::<T>::contains_key(label_node),
-                Error::<T>::Frozen
-            );
-
-            T::Registry::mint_subname(
-                &official,
-                base_node,
-                label_node,
-                owner.clone(),
-                0,
-                |maybe_pre_owner| -> DispatchResult {
-                    let register_fee = T::PriceOracle::register_fee(label_len, duration)
-                        .ok_or(ArithmeticError::Overflow)?;
-                    let deposit =
-                        T::PriceOracle::deposit_fee(label_len).ok_or(ArithmeticError::Overflow)?;
-                    let target_value = register_fee
-                        .checked_add(&deposit)
-                        .ok_or(ArithmeticError::Overflow)?;
-
-                    T::Currency::transfer(
-                        &caller,
-                        &official,
-                        target_value,
-                        ExistenceRequirement::KeepAlive,
-                    )?;
-                    RegistrarInfos::<T>::mutate(label_node, |info| -> DispatchResult {
-                        if let Some(info) = info.as_mut() {
-                            if let Some(pre_owner) = maybe_pre_owner {
-                                T::Currency::transfer(
-                                    &official,
-                                    pre_owner,
-                                    info.deposit,
-                                    ExistenceRequirement::KeepAlive,
-                                )?;
-                            }
-                            info.deposit = deposit;
-                            info.register_fee = register_fee;
-                            info.expire = expire;
-                        } else {
-                            let _ = info.insert(RegistrarInfoOf::<T> {
-                                deposit,
-                                register_fee,
-                                expire,
-                                capacity: T::DefaultCapacity::get(),
-                            });
-                        }
-                        Ok(())
-                    })?;
-                    Ok(())
-                },
-            )?;
-
-            Self::deposit_event(Event::<T>::NameRegistered {
-                name,
-                node: label_node,
-                owner,
-                expire,
-            });
-
-            Ok(())
+            Self::do_register(caller, base_node, name, owner, duration, asset_id.into()).map(|_| ())
         }
         /// Renew a domain name.
         ///
@@ -350,51 +1156,23 @@ pub mod pallet {
         ///  so the front-end needs to remind the user of the relationship between
         ///  the domain and that user at renewal time, as it is the caller's responsibility to pay.
         ///
+        /// Renewing an expired name within its grace period costs `GraceRenewalSurcharge` more.
+        /// The price is paid in `asset_id` if any.
+        ///
         /// Ensure: Name is within the renewable period.
         #[pallet::call_index(3)]
         #[pallet::weight(T::WeightInfo::renew(name.len() as u32))]
         #[frame_support::transactional]
-        pub fn renew(origin: OriginFor<T>, name: Vec<u8>, duration: T::Moment) -> DispatchResult {
+        pub fn renew(
+            origin: OriginFor<T>,
+            base_node: DomainHash,
+            name: Vec<u8>,
+            duration: T::Moment,
+            asset_id: Option<AssetIdOf<T>>,
+        ) -> DispatchResult {
             let caller = ensure_signed(origin)?;
 
-            ensure!(T::IsOpen::is_open(), Error::<T>::RegistrarClosed);
-
-            let (label, label_len) =
-                Label::new_with_len(&name).ok_or(Error::<T>::ParseLabelFailed)?;
-
-            let label_node = label.encode_with_node(&T::BaseNode::get());
-
-            RegistrarInfos::<T>::mutate(label_node, |info| -> DispatchResult {
-                let info = info.as_mut().ok_or(Error::<T>::NotExistOrOccupied)?;
-
-                let expire = info.expire;
-                let now = T::NowProvider::now();
-                let grace_period = T::GracePeriod::get();
-                ensure!(now <= expire + grace_period, Error::<T>::NotRenewable);
-                let target_expire = expire
-                    .checked_add(&duration)
-                    .ok_or(ArithmeticError::Overflow)?;
-                ensure!(
-                    target_expire + grace_period > now + grace_period,
-                    ArithmeticError::Overflow
-                );
-                let price = T::PriceOracle::renew_fee(label_len, duration)
-                    .ok_or(ArithmeticError::Overflow)?;
-                T::Currency::transfer(
-                    &caller,
-                    &T::Official::get_official_account()?,
-                    price,
-                    ExistenceRequirement::KeepAlive,
-                )?;
-                info.expire = target_expire;
-                Self::deposit_event(Event::<T>::NameRenewed {
-                    name,
-                    node: label_node,
-                    duration,
-                    expire: target_expire,
-                });
-                Ok(())
-            })
+            Self::do_renew(&caller, base_node, name, duration, asset_id.into()).map(|_| ())
         }
         /// Trade out your domain name, the caller can be operates.
         ///
@@ -416,178 +1194,2886 @@ pub mod pallet {
             let who = ensure_signed(origin)?;
             let to = T::Lookup::lookup(to)?;
 
-            ensure!(T::IsOpen::is_open(), Error::<T>::RegistrarClosed);
+            Self::ensure_enabled(Operation::Transfer)?;
+
+            Self::do_transfer(&who, &to, node)
+        }
+        /// Create a subdomain.
+        ///
+        /// Note: The total number of subdomains you can create is certain,
+        /// and the subdomains created by your subdomains will take up a
+        /// quota of your total subdomains.
+        ///
+        /// Ensure: The subdomain capacity is sufficient for use.
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::mint_subname(data.len() as u32))]
+        #[frame_support::transactional]
+        pub fn mint_subname(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            data: Vec<u8>,
+            to: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(to)?;
+
+            Self::ensure_enabled(Operation::MintSubname)?;
+
+            let capacity = Self::subname_capacity(node)?;
+            Self::do_mint_subname(&caller, node, &data, to, capacity)
+        }
+        /// Hold a domain name for `who`, only `who` is able to register it.
+        /// Only manager
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::reserve_for())]
+        pub fn reserve_for(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            who: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            let manager = T::ReserveOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::reserve_for {
+                    node,
+                    who: who.clone(),
+                },
+            );
+            let who = T::Lookup::lookup(who)?;
+
+            ReservedFor::<T>::insert(node, &who);
+
+            Self::deposit_event(Event::<T>::NameReservedFor { node, who });
+            Ok(())
+        }
+        /// Cancel the hold of a domain name.
+        /// Only manager
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::WeightInfo::clear_reserved_for())]
+        pub fn clear_reserved_for(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let manager = T::ReserveOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&manager, &Call::<T>::clear_reserved_for { node });
+
+            ReservedFor::<T>::remove(node);
+
+            Self::deposit_event(Event::<T>::NameUnReservedFor { node });
+            Ok(())
+        }
+        /// Lock (or unlock) renewals of your domain name, so that nobody but
+        /// you (or your operators) can renew it, e.g. to let it lapse deliberately.
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::set_renew_lock())]
+        pub fn set_renew_lock(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            locked: bool,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            T::Registry::available(&caller, node)?;
+
+            if locked {
+                RenewLocked::<T>::insert(node, ());
+            } else {
+                RenewLocked::<T>::remove(node);
+            }
+
+            Self::deposit_event(Event::<T>::RenewLockChanged { node, locked });
+            Ok(())
+        }
+        /// Commit to registering a name without revealing it, see [`Pallet::make_commitment`].
+        ///
+        /// The name can be registered with `reveal_register` once the commitment is
+        /// `MinCommitmentAge` old, so watching pending transactions reveals nothing to snipe.
+        ///
+        /// Note: `CommitmentDeposit` is reserved until the commitment is revealed or removed.
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::commit())]
+        pub fn commit(origin: OriginFor<T>, commitment: DomainHash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let now = T::NowProvider::now();
+            if let Some((committer, committed_at, deposit)) = Commitments::<T>::get(commitment) {
+                ensure!(
+                    now > committed_at + T::MaxCommitmentAge::get(),
+                    Error::<T>::CommitmentExists
+                );
+                T::Currency::unreserve(&committer, deposit);
+            }
+
+            let deposit = T::CommitmentDeposit::get();
+            T::Currency::reserve(&who, deposit)?;
+            Commitments::<T>::insert(commitment, (&who, now, deposit));
+
+            Self::deposit_event(Event::<T>::Committed { who, commitment });
+            Ok(())
+        }
+        /// Register a name committed to with `commit`.
+        ///
+        /// The deposit of the commitment is returned.
+        ///
+        /// Ensure: the commitment was made by the caller, is at least `MinCommitmentAge`
+        /// and at most `MaxCommitmentAge` old, the rest is the same as `register`.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::reveal_register(name.len() as u32))]
+        #[frame_support::transactional]
+        pub fn reveal_register(
+            origin: OriginFor<T>,
+            base_node: DomainHash,
+            name: Vec<u8>,
+            owner: <T::Lookup as StaticLookup>::Source,
+            duration: T::Moment,
+            secret: [u8; 32],
+            asset_id: Option<AssetIdOf<T>>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            let owner = T::Lookup::lookup(owner)?;
+
+            let commitment = Self::make_commitment(base_node, &name, &owner, &secret);
+            let (committer, committed_at, deposit) =
+                Commitments::<T>::take(commitment).ok_or(Error::<T>::CommitmentNotFound)?;
+            ensure!(committer == caller, Error::<T>::CommitmentNotFound);
+
+            let now = T::NowProvider::now();
+            ensure!(
+                now >= committed_at + T::MinCommitmentAge::get(),
+                Error::<T>::CommitmentTooNew
+            );
+            ensure!(
+                now <= committed_at + T::MaxCommitmentAge::get(),
+                Error::<T>::CommitmentTooOld
+            );
+            T::Currency::unreserve(&committer, deposit);
+
+            Self::do_register(caller, base_node, name, owner, duration, asset_id.into()).map(|_| ())
+        }
+        /// Register several names to the same `owner` for the same `duration`.
+        ///
+        /// Note: The fees of all names are paid with a single transfer,
+        /// if any name can't be registered none of them are.
+        ///
+        /// Ensure: At most `MaxBatchSize` names, each one as in `register`.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::register_batch(names.len() as u32))]
+        #[frame_support::transactional]
+        pub fn register_batch(
+            origin: OriginFor<T>,
+            base_node: DomainHash,
+            names: BoundedVec<Vec<u8>, T::MaxBatchSize>,
+            owner: <T::Lookup as StaticLookup>::Source,
+            duration: T::Moment,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            let owner = T::Lookup::lookup(owner)?;
+
+            ensure!(!T::RequireCommitment::get(), Error::<T>::CommitmentRequired);
+            ensure!(!names.is_empty(), Error::<T>::InvalidBatchSize);
+
+            let now = T::NowProvider::now();
+            let mut total = BalanceOf::<T>::zero();
+            let mut fees = BalanceOf::<T>::zero();
+            for name in names.iter() {
+                let (name, label) = Self::parse_label(name)?;
+                let label_node = label.encode_with_node(&base_node);
+                let (register_fee, deposit) =
+                    Self::register_cost(label_node, name.len(), duration, now)?;
+                total = total
+                    .checked_add(&register_fee)
+                    .and_then(|total| total.checked_add(&deposit))
+                    .ok_or(ArithmeticError::Overflow)?;
+                fees = fees.saturating_add(register_fee);
+            }
+
+            Self::ensure_not_dusted(&caller, total)?;
+            T::Currency::transfer(
+                &caller,
+                &T::Official::get_official_account()?,
+                total,
+                ExistenceRequirement::KeepAlive,
+            )?;
+            Self::distribute_fee(fees)?;
+
+            for name in names {
+                Self::do_register(
+                    caller.clone(),
+                    base_node,
+                    name,
+                    owner.clone(),
+                    duration,
+                    FeePayment::Prepaid,
+                )?;
+            }
+            Ok(())
+        }
+        /// Renew several names by the same `duration`.
+        ///
+        /// Note: The prices of all names are paid with a single transfer,
+        /// if any name can't be renewed none of them are.
+        ///
+        /// Ensure: At most `MaxBatchSize` names, each one as in `renew`.
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::renew_batch(names.len() as u32))]
+        #[frame_support::transactional]
+        pub fn renew_batch(
+            origin: OriginFor<T>,
+            base_node: DomainHash,
+            names: BoundedVec<Vec<u8>, T::MaxBatchSize>,
+            duration: T::Moment,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            ensure!(!names.is_empty(), Error::<T>::InvalidBatchSize);
+
+            let mut total = BalanceOf::<T>::zero();
+            let mut nodes = Vec::with_capacity(names.len());
+            for name in names {
+                let (_, label) = Self::parse_label(&name)?;
+                let price =
+                    Self::do_renew(&caller, base_node, name, duration, FeePayment::Prepaid)?;
+                total = total.checked_add(&price).ok_or(ArithmeticError::Overflow)?;
+                nodes.push(label.encode_with_node(&base_node));
+            }
+
+            Self::ensure_not_dusted(&caller, total)?;
+            T::Currency::transfer(
+                &caller,
+                &T::Official::get_official_account()?,
+                total,
+                ExistenceRequirement::KeepAlive,
+            )?;
+            Self::distribute_fee(total)?;
+            Self::deposit_event(Event::<T>::NamesRenewed {
+                nodes,
+                duration,
+                price: total,
+            });
+            Ok(())
+        }
+        /// Register a domain name and set up its resolver records in the same transaction,
+        /// so the domain never resolves to nothing.
+        ///
+        /// Note: The records are written for the `owner`, whoever the caller is.
+        ///
+        /// Ensure: At most `MaxInitialRecords` records, the rest is the same as `register`.
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::register_with_config(
+            name.len() as u32,
+            records.len() as u32
+        ))]
+        #[frame_support::transactional]
+        pub fn register_with_config(
+            origin: OriginFor<T>,
+            base_node: DomainHash,
+            name: Vec<u8>,
+            owner: <T::Lookup as StaticLookup>::Source,
+            duration: T::Moment,
+            records: Vec<<T::ResolverRecords as ResolverRecords>::Record>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            let owner = T::Lookup::lookup(owner)?;
+
+            ensure!(!T::RequireCommitment::get(), Error::<T>::CommitmentRequired);
+            ensure!(
+                records.len() <= T::MaxInitialRecords::get() as usize,
+                Error::<T>::TooManyRecords
+            );
+
+            let node =
+                Self::do_register(caller, base_node, name, owner, duration, FeePayment::Native)?;
+
+            T::ResolverRecords::set_records(node, records)
+        }
+        /// Renew a domain name automatically by `duration` whenever it gets
+        /// within `AutoRenewWindow` of its expiry.
+        ///
+        /// Note: `amount` is reserved from the caller and the renewals are paid from it,
+        /// the auto-renewal ends once it can't pay for a renewal anymore.
+        ///
+        /// Ensure: The caller has the permission of the domain.
+        #[pallet::call_index(14)]
+        #[pallet::weight(T::WeightInfo::enable_auto_renew())]
+        #[frame_support::transactional]
+        pub fn enable_auto_renew(
+            origin: OriginFor<T>,
+            base_node: DomainHash,
+            name: Vec<u8>,
+            duration: T::Moment,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            let (name, label) = Self::parse_label(&name)?;
+            let node = label.encode_with_node(&base_node);
+
+            T::Registry::available(&caller, node)?;
+            ensure!(
+                RegistrarInfos::<T>::contains_key(node),
+                Error::<T>::NotExistOrOccupied
+            );
+            ensure!(
+                !AutoRenewals::<T>::contains_key(node),
+                Error::<T>::AutoRenewExists
+            );
+
+            T::Currency::reserve(&caller, amount)?;
+            AutoRenewals::<T>::insert(
+                node,
+                AutoRenewalOf::<T> {
+                    payer: caller.clone(),
+                    base_node,
+                    name: name.try_into().map_err(|_| Error::<T>::LabelInvalid)?,
+                    duration,
+                    pot: amount,
+                },
+            );
+
+            Self::deposit_event(Event::<T>::AutoRenewEnabled {
+                node,
+                payer: caller,
+                duration,
+                pot: amount,
+            });
+            Ok(())
+        }
+        /// Reserve more balance for the auto-renewal of `node`.
+        ///
+        /// Ensure: The caller is the payer of the auto-renewal.
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::top_up())]
+        #[frame_support::transactional]
+        pub fn top_up(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            AutoRenewals::<T>::try_mutate(node, |renewal| -> DispatchResult {
+                let renewal = renewal.as_mut().ok_or(Error::<T>::AutoRenewNotFound)?;
+                ensure!(renewal.payer == caller, Error::<T>::NotOwned);
+
+                renewal.pot = renewal
+                    .pot
+                    .checked_add(&amount)
+                    .ok_or(ArithmeticError::Overflow)?;
+                T::Currency::reserve(&caller, amount)
+            })?;
+
+            Self::deposit_event(Event::<T>::AutoRenewToppedUp { node, amount });
+            Ok(())
+        }
+        /// Stop renewing `node` automatically, the rest of the pot is returned to its payer.
+        ///
+        /// Ensure: The caller is the payer or has the permission of the domain.
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::cancel_auto_renew())]
+        pub fn cancel_auto_renew(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            let renewal = AutoRenewals::<T>::get(node).ok_or(Error::<T>::AutoRenewNotFound)?;
+            if renewal.payer != caller {
+                T::Registry::available(&caller, node)?;
+            }
+
+            Self::end_auto_renew(node, renewal);
+            Ok(())
+        }
+        /// Burn a domain name which is past its grace period and re-registration cooldown,
+        /// clearing its registrar info and records.
+        ///
+        /// Note: The caller gets `ReapReward` of the deposit, the rest is returned to the owner.
+        ///
+        /// Ensure: The domain name has expired, subdomains have to be burned first.
+        #[pallet::call_index(17)]
+        #[pallet::weight(T::WeightInfo::reap_expired())]
+        #[frame_support::transactional]
+        pub fn reap_expired(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            Self::do_reap(&caller, node, T::ReapReward::get())
+        }
+        /// Buy `extra_slots` more subdomains for a domain name.
+        ///
+        /// Note: The fee is taken from the price oracle and is not refunded,
+        /// the capacity stays with the domain name when it is transferred.
+        ///
+        /// Ensure: The capacity stays within `MaxCapacity`.
+        #[pallet::call_index(18)]
+        #[pallet::weight(T::WeightInfo::buy_capacity())]
+        #[frame_support::transactional]
+        pub fn buy_capacity(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            extra_slots: u32,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            T::Registry::available(&caller, node)?;
+            ensure!(extra_slots > 0, Error::<T>::InvalidCapacity);
+
+            let capacity =
+                RegistrarInfos::<T>::try_mutate(node, |info| -> Result<_, DispatchError> {
+                    let info = info.as_mut().ok_or(Error::<T>::NotExistOrOccupied)?;
+                    let capacity = info
+                        .capacity
+                        .checked_add(extra_slots)
+                        .filter(|capacity| *capacity <= T::MaxCapacity::get())
+                        .ok_or(Error::<T>::InvalidCapacity)?;
+                    info.capacity = capacity;
+                    Ok(capacity)
+                })?;
+
+            let fee = T::PriceOracle::capacity_fee(extra_slots).ok_or(ArithmeticError::Overflow)?;
+            Self::ensure_not_dusted(&caller, fee)?;
+            T::Currency::transfer(
+                &caller,
+                &T::Official::get_official_account()?,
+                fee,
+                ExistenceRequirement::KeepAlive,
+            )?;
+            Self::distribute_fee(fee)?;
+
+            Self::deposit_event(Event::<T>::CapacityBought {
+                node,
+                capacity,
+                fee,
+            });
+            Ok(())
+        }
+        /// Let `claimant` register a reserved domain name until `claim_window` from now.
+        /// Only manager
+        ///
+        /// Ensure: The domain name is in the reserved list.
+        #[pallet::call_index(19)]
+        #[pallet::weight(T::WeightInfo::assign_reserved_claim())]
+        pub fn assign_reserved_claim(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            claimant: <T::Lookup as StaticLookup>::Source,
+            claim_window: T::Moment,
+        ) -> DispatchResult {
+            let manager = T::ReserveOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::assign_reserved_claim {
+                    node,
+                    claimant: claimant.clone(),
+                    claim_window,
+                },
+            );
+            let claimant = T::Lookup::lookup(claimant)?;
+
+            ensure!(
+                ReservedList::<T>::contains_key(node),
+                Error::<T>::NotReserved
+            );
+            let deadline = T::NowProvider::now()
+                .checked_add(&claim_window)
+                .ok_or(ArithmeticError::Overflow)?;
+
+            ReservedClaims::<T>::insert(node, (&claimant, deadline));
+
+            Self::deposit_event(Event::<T>::ReservedClaimAssigned {
+                node,
+                claimant,
+                deadline,
+            });
+            Ok(())
+        }
+        /// Register a reserved domain name assigned to the caller.
+        ///
+        /// Note: The normal fees are paid, the domain name leaves the reserved list.
+        ///
+        /// Ensure: The claim window has not passed yet.
+        #[pallet::call_index(20)]
+        #[pallet::weight(T::WeightInfo::claim_reserved(name.len() as u32))]
+        #[frame_support::transactional]
+        pub fn claim_reserved(
+            origin: OriginFor<T>,
+            base_node: DomainHash,
+            name: Vec<u8>,
+            duration: T::Moment,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            let (name, label) = Self::parse_label(&name)?;
+            let node = label.encode_with_node(&base_node);
+            let (claimant, deadline) =
+                ReservedClaims::<T>::get(node).ok_or(Error::<T>::NoReservedClaim)?;
+            ensure!(claimant == caller, Error::<T>::NoReservedClaim);
+            ensure!(
+                T::NowProvider::now() <= deadline,
+                Error::<T>::ReservedClaimExpired
+            );
+
+            ReservedClaims::<T>::remove(node);
+            ReservedList::<T>::remove(node);
+            Self::do_register(
+                caller.clone(),
+                base_node,
+                name,
+                caller.clone(),
+                duration,
+                FeePayment::Native,
+            )?;
+
+            Self::deposit_event(Event::<T>::ReservedClaimed {
+                node,
+                claimant: caller,
+            });
+            Ok(())
+        }
+        /// Reserve every domain name matching `rule`.
+        /// Only manager
+        #[pallet::call_index(21)]
+        #[pallet::weight(T::WeightInfo::add_reservation_rule())]
+        pub fn add_reservation_rule(origin: OriginFor<T>, rule: ReservationRule) -> DispatchResult {
+            let manager = T::ReserveOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::add_reservation_rule { rule: rule.clone() },
+            );
+
+            ReservationRules::<T>::try_mutate(|rules| -> DispatchResult {
+                ensure!(!rules.contains(&rule), Error::<T>::ReservationRuleExists);
+                rules
+                    .try_push(rule.clone())
+                    .map_err(|_| Error::<T>::TooManyReservationRules)?;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::<T>::ReservationRuleAdded { rule });
+            Ok(())
+        }
+        /// Remove a reservation rule, names in `ReservedList` stay reserved.
+        /// Only manager
+        #[pallet::call_index(22)]
+        #[pallet::weight(T::WeightInfo::remove_reservation_rule())]
+        pub fn remove_reservation_rule(
+            origin: OriginFor<T>,
+            rule: ReservationRule,
+        ) -> DispatchResult {
+            let manager = T::ReserveOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::remove_reservation_rule { rule: rule.clone() },
+            );
+
+            ReservationRules::<T>::try_mutate(|rules| -> DispatchResult {
+                let index = rules
+                    .iter()
+                    .position(|r| r == &rule)
+                    .ok_or(Error::<T>::ReservationRuleNotFound)?;
+                rules.remove(index);
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::<T>::ReservationRuleRemoved { rule });
+            Ok(())
+        }
+        /// Change the settings of a base node, they apply to the domains
+        /// registered under it, including existing ones.
+        /// Only manager
+        #[pallet::call_index(23)]
+        #[pallet::weight(T::WeightInfo::set_tld_config())]
+        pub fn set_tld_config(
+            origin: OriginFor<T>,
+            base_node: DomainHash,
+            config: TldConfigOf<T>,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_tld_config {
+                    base_node,
+                    config: config.clone(),
+                },
+            );
+
+            ensure!(
+                BaseNodes::<T>::contains_key(base_node),
+                Error::<T>::UnknownBaseNode
+            );
+            BaseNodes::<T>::insert(base_node, &config);
+
+            Self::deposit_event(Event::<T>::TldConfigChanged { base_node, config });
+            Ok(())
+        }
+        /// Create the base node of the top level domain `label`,
+        /// its token is minted to the official account.
+        /// Only manager
+        #[pallet::call_index(24)]
+        #[pallet::weight(T::WeightInfo::create_tld(label.len() as u32))]
+        #[frame_support::transactional]
+        pub fn create_tld(
+            origin: OriginFor<T>,
+            label: Vec<u8>,
+            config: TldConfigOf<T>,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::create_tld {
+                    label: label.clone(),
+                    config: config.clone(),
+                },
+            );
+
+            let (label, base_label) = Self::parse_label(&label)?;
+            let base_node = base_label.to_basenode();
+            ensure!(
+                !BaseNodes::<T>::contains_key(base_node),
+                Error::<T>::TldExists
+            );
+
+            T::Registry::mint_base_node(base_node)?;
+            // the base node never expires, so its subdomains stay useable.
+            RegistrarInfos::<T>::insert(
+                base_node,
+                RegistrarInfoOf::<T> {
+                    expire: T::Moment::max_value(),
+                    capacity: config.capacity,
+                    deposit: Zero::zero(),
+                    register_fee: Zero::zero(),
+                },
+            );
+            BaseNodes::<T>::insert(base_node, &config);
+            TldLabels::<T>::insert(
+                base_node,
+                BoundedVec::try_from(label.clone()).map_err(|_| Error::<T>::LabelInvalid)?,
+            );
+
+            Self::deposit_event(Event::<T>::TldCreated {
+                label,
+                base_node,
+                config,
+            });
+            Ok(())
+        }
+        /// Move the registrar to another registration phase.
+        /// Only manager
+        #[pallet::call_index(25)]
+        #[pallet::weight(T::WeightInfo::set_phase())]
+        pub fn set_phase(origin: OriginFor<T>, phase: RegistrationPhase) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&manager, &Call::<T>::set_phase { phase });
+
+            Phase::<T>::put(phase);
+
+            Self::deposit_event(Event::<T>::PhaseChanged { phase });
+            Ok(())
+        }
+        /// Let `who` register names in the whitelist phase.
+        /// Only manager
+        #[pallet::call_index(26)]
+        #[pallet::weight(T::WeightInfo::add_whitelisted())]
+        pub fn add_whitelisted(
+            origin: OriginFor<T>,
+            who: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::add_whitelisted { who: who.clone() },
+            );
+            let who = T::Lookup::lookup(who)?;
+
+            Whitelist::<T>::insert(&who, ());
+
+            Self::deposit_event(Event::<T>::Whitelisted { who });
+            Ok(())
+        }
+        /// Remove `who` from the whitelist.
+        /// Only manager
+        #[pallet::call_index(27)]
+        #[pallet::weight(T::WeightInfo::remove_whitelisted())]
+        pub fn remove_whitelisted(
+            origin: OriginFor<T>,
+            who: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::remove_whitelisted { who: who.clone() },
+            );
+            let who = T::Lookup::lookup(who)?;
+
+            Whitelist::<T>::remove(&who);
+
+            Self::deposit_event(Event::<T>::WhitelistRemoved { who });
+            Ok(())
+        }
+        /// Whitelist the caller with a voucher, the signature of the official
+        /// account over [`Pallet::voucher_payload`] of the caller.
+        #[pallet::call_index(28)]
+        #[pallet::weight(T::WeightInfo::redeem_voucher())]
+        pub fn redeem_voucher(
+            origin: OriginFor<T>,
+            voucher: T::VoucherSignature,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let signer = T::Official::get_official_account()?;
+            ensure!(
+                voucher.verify(&Self::voucher_payload(&who)[..], &signer),
+                Error::<T>::InvalidVoucher
+            );
+
+            Whitelist::<T>::insert(&who, ());
+
+            Self::deposit_event(Event::<T>::Whitelisted { who });
+            Ok(())
+        }
+        /// Same as `transfer`, but `to` gets the domain name without the records,
+        /// resolver and controller set by its previous owner.
+        #[pallet::call_index(29)]
+        #[pallet::weight(T::WeightInfo::transfer_and_reset())]
+        #[frame_support::transactional]
+        pub fn transfer_and_reset(
+            origin: OriginFor<T>,
+            to: <T::Lookup as StaticLookup>::Source,
+            node: DomainHash,
+        ) -> DispatchResult {
+            let to = T::Lookup::lookup(to)?;
+
+            Self::transfer(origin, T::Lookup::unlookup(to.clone()), node)?;
+
+            T::ResolverRecords::clear_records(node);
+            T::Registry::set_resolver(node, T::DefaultResolver::get());
+            T::Registry::clear_approvals(node);
+
+            Self::deposit_event(Event::<T>::TransferReset { node, to });
+            Ok(())
+        }
+        /// Offer a domain name to `to`, it is only transferred once `to` accepts it
+        /// within `TransferOfferExpiry` blocks. A new offer replaces the pending one.
+        ///
+        /// Ensure: The caller has the permission to transfer the domain name
+        /// when the offer is made and when it is accepted, soulbound names can't be offered.
+        #[pallet::call_index(30)]
+        #[pallet::weight(T::WeightInfo::offer_transfer())]
+        pub fn offer_transfer(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            to: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            let from = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(to)?;
+
+            let deadline = Self::do_offer_transfer(&from, node, &to, Zero::zero())?;
+
+            Self::deposit_event(Event::<T>::TransferOffered {
+                node,
+                from,
+                to,
+                deadline,
+            });
+            Ok(())
+        }
+        /// Accept a domain name offered to the caller, and pay its price if it is sold.
+        #[pallet::call_index(31)]
+        #[pallet::weight(T::WeightInfo::accept_transfer())]
+        #[frame_support::transactional]
+        pub fn accept_transfer(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let to = ensure_signed(origin)?;
+
+            let offer = TransferOffers::<T>::get(node)
+                .filter(|offer| offer.to == to)
+                .ok_or(Error::<T>::NoTransferOffer)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() <= offer.deadline,
+                Error::<T>::TransferOfferExpired
+            );
+
+            TransferOffers::<T>::remove(node);
+            if offer.price.is_zero() {
+                Self::transfer(
+                    frame_system::RawOrigin::Signed(offer.from.clone()).into(),
+                    T::Lookup::unlookup(to.clone()),
+                    node,
+                )?;
+            } else {
+                Self::ensure_enabled(Operation::Transfer)?;
+                Self::do_transfer_sold(&offer.from, &to, node, offer.price)?;
+            }
+
+            Self::deposit_event(Event::<T>::TransferAccepted {
+                node,
+                from: offer.from,
+                to,
+            });
+            Ok(())
+        }
+        /// Withdraw the pending transfer offer of a domain name.
+        #[pallet::call_index(32)]
+        #[pallet::weight(T::WeightInfo::cancel_transfer_offer())]
+        pub fn cancel_transfer_offer(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let offer = TransferOffers::<T>::get(node).ok_or(Error::<T>::NoTransferOffer)?;
+            if offer.from != who {
+                T::Registry::available(&who, node)?;
+            }
+
+            TransferOffers::<T>::remove(node);
+
+            Self::deposit_event(Event::<T>::TransferOfferCancelled { node });
+            Ok(())
+        }
+        /// Transfer several domain names to `to`, e.g. when moving to a new account.
+        ///
+        /// Note: If any of them can't be transferred none of them are.
+        ///
+        /// Ensure: At most `MaxBatchSize` domain names, each one as in `transfer`.
+        #[pallet::call_index(33)]
+        #[pallet::weight(T::WeightInfo::transfer_batch(nodes.len() as u32))]
+        #[frame_support::transactional]
+        pub fn transfer_batch(
+            origin: OriginFor<T>,
+            to: <T::Lookup as StaticLookup>::Source,
+            nodes: BoundedVec<DomainHash, T::MaxBatchSize>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(to)?;
+
+            Self::ensure_enabled(Operation::Transfer)?;
+
+            for node in nodes.iter() {
+                Self::check_not_expired(*node)?;
+            }
+            T::Registry::transfer_batch(&who, &to, &nodes)
+        }
+        /// Create several subdomains of `node` for the same `to`.
+        ///
+        /// Note: The capacity of `node` is looked up once for the whole batch,
+        /// if any subdomain can't be created none of them are.
+        ///
+        /// Ensure: At most `MaxBatchSize` labels, each one as in `mint_subname`.
+        #[pallet::call_index(40)]
+        // longer batches and labels are rejected before doing any work.
+        #[pallet::weight(labels.iter().take(T::MaxBatchSize::get() as usize).fold(Weight::zero(), |weight, label| {
+            let len = label.len().min(crate::traits::LABEL_MAX_LEN) as u32;
+            weight.saturating_add(T::WeightInfo::mint_subname(len))
+        }))]
+        #[frame_support::transactional]
+        pub fn mint_subnames(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            labels: Vec<Vec<u8>>,
+            to: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(to)?;
+
+            Self::ensure_enabled(Operation::MintSubname)?;
+            ensure!(
+                !labels.is_empty() && labels.len() <= T::MaxBatchSize::get() as usize,
+                Error::<T>::InvalidBatchSize
+            );
+
+            let capacity = Self::subname_capacity(node)?;
+            for data in labels {
+                Self::do_mint_subname(&caller, node, &data, to.clone(), capacity)?;
+            }
+            Ok(())
+        }
+        /// Block transfers and approvals of a domain name until `until`, also for
+        /// its operators and approved accounts, e.g. to protect a valuable name
+        /// from a compromised hot wallet. Locking again moves the end of the lock.
+        ///
+        /// Ensure: Only the owner of the domain name.
+        #[pallet::call_index(34)]
+        #[pallet::weight(T::WeightInfo::lock())]
+        pub fn lock(origin: OriginFor<T>, node: DomainHash, until: T::Moment) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                T::Registry::owner_of(node).as_ref() == Some(&who),
+                Error::<T>::NotOwned
+            );
+            ensure!(until > T::NowProvider::now(), Error::<T>::LockEndInPast);
+
+            TransferLocks::<T>::insert(node, (who, until));
+
+            Self::deposit_event(Event::<T>::DomainLocked { node, until });
+            Ok(())
+        }
+        /// Lift the lock of a domain name before it ends.
+        ///
+        /// Ensure: Only the owner of the domain name.
+        #[pallet::call_index(35)]
+        #[pallet::weight(T::WeightInfo::unlock())]
+        pub fn unlock(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                T::Registry::owner_of(node).as_ref() == Some(&who),
+                Error::<T>::NotOwned
+            );
+            TransferLocks::<T>::remove(node);
+
+            Self::deposit_event(Event::<T>::DomainUnlocked { node });
+            Ok(())
+        }
+        /// Offer the subdomain `data` of `node` to `to` for `duration`, `to` pays
+        /// `rent` to the owner of `node` when accepting it. After the lease is over
+        /// the subdomain can be reclaimed with `reclaim_subname`.
+        ///
+        /// Offering the subdomain again to its current holder extends its lease.
+        ///
+        /// Ensure: The caller has the permission of `node`.
+        #[pallet::call_index(36)]
+        #[pallet::weight(T::WeightInfo::offer_subname_lease(data.len() as u32))]
+        pub fn offer_subname_lease(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            data: Vec<u8>,
+            to: <T::Lookup as StaticLookup>::Source,
+            duration: T::Moment,
+            rent: BalanceOf<T>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(to)?;
+
+            Self::ensure_enabled(Operation::MintSubname)?;
+            T::Registry::available(&caller, node)?;
+
+            let (data, label) = Self::parse_label(&data)?;
+            let subnode = label.encode_with_node(&node);
+            LeaseOffers::<T>::insert(
+                subnode,
+                LeaseOfferOf::<T> {
+                    node,
+                    label: data.try_into().map_err(|_| Error::<T>::LabelInvalid)?,
+                    to: to.clone(),
+                    duration,
+                    rent,
+                },
+            );
+
+            Self::deposit_event(Event::<T>::LeaseOffered {
+                node,
+                subnode,
+                to,
+                duration,
+                rent,
+            });
+            Ok(())
+        }
+        /// Take the subdomain lease offered to the caller and pay its rent.
+        #[pallet::call_index(37)]
+        #[pallet::weight(T::WeightInfo::accept_subname_lease())]
+        #[frame_support::transactional]
+        pub fn accept_subname_lease(origin: OriginFor<T>, subnode: DomainHash) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            Self::ensure_enabled(Operation::MintSubname)?;
+
+            let offer = LeaseOffers::<T>::take(subnode)
+                .filter(|offer| offer.to == caller)
+                .ok_or(Error::<T>::NoLeaseOffer)?;
+            let node_owner =
+                T::Registry::owner_of(offer.node).ok_or(Error::<T>::NotExistOrOccupied)?;
+
+            let now = T::NowProvider::now();
+            let expire = match T::Registry::owner_of(subnode) {
+                // extends the lease of its current holder.
+                Some(holder) => {
+                    ensure!(holder == caller, Error::<T>::NotExistOrOccupied);
+                    let lease =
+                        SubnameLeases::<T>::get(subnode).ok_or(Error::<T>::NotExistOrOccupied)?;
+                    lease.expire.max(now).checked_add(&offer.duration)
+                }
+                None => {
+                    let capacity = Self::subname_capacity(offer.node)?;
+                    T::Registry::mint_subname(
+                        &node_owner,
+                        offer.node,
+                        subnode,
+                        caller.clone(),
+                        capacity,
+                        |_| Ok(()),
+                    )?;
+                    Self::deposit_event(Event::<T>::SubnameRegistered {
+                        label: offer.label.into_inner(),
+                        subnode,
+                        owner: caller.clone(),
+                        node: offer.node,
+                    });
+                    now.checked_add(&offer.duration)
+                }
+            }
+            .ok_or(ArithmeticError::Overflow)?;
+
+            T::Currency::transfer(
+                &caller,
+                &node_owner,
+                offer.rent,
+                ExistenceRequirement::KeepAlive,
+            )?;
+            SubnameLeases::<T>::insert(
+                subnode,
+                Lease {
+                    node: offer.node,
+                    expire,
+                },
+            );
+            SubnameExpiries::<T>::insert(subnode, expire);
+
+            Self::deposit_event(Event::<T>::SubnameLeased {
+                node: offer.node,
+                subnode,
+                owner: caller,
+                expire,
+            });
+            Ok(())
+        }
+        /// Burn a subdomain whose lease is over, so it can be minted or leased again.
+        ///
+        /// Ensure: The caller has the permission of the parent domain,
+        /// the subdomain has no subdomains of its own.
+        #[pallet::call_index(38)]
+        #[pallet::weight(T::WeightInfo::reclaim_subname())]
+        #[frame_support::transactional]
+        pub fn reclaim_subname(origin: OriginFor<T>, subnode: DomainHash) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            let lease = SubnameLeases::<T>::get(subnode).ok_or(Error::<T>::NotLeased)?;
+            T::Registry::available(&caller, lease.node)?;
+            T::Registry::check_fuses(subnode, crate::registry::fuses::PARENT_CANNOT_CONTROL)?;
+            ensure!(
+                T::NowProvider::now() > lease.expire,
+                Error::<T>::LeaseNotExpired
+            );
+
+            SubnameLeases::<T>::remove(subnode);
+            T::Registry::burn_unchecked(&caller, subnode)?;
+
+            Self::deposit_event(Event::<T>::SubnameReclaimed {
+                node: lease.node,
+                subnode,
+            });
+            Ok(())
+        }
+        /// Let the subdomain `data` of `node` expire at `expire`, before the domain
+        /// it belongs to. Resolvers stop serving it and no subdomains can be minted
+        /// under it afterwards. `None` makes it expire with its root again.
+        ///
+        /// Ensure: The caller has the permission of `node`, the subdomain is not leased.
+        #[pallet::call_index(39)]
+        #[pallet::weight(T::WeightInfo::set_subname_expiry(data.len() as u32))]
+        pub fn set_subname_expiry(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            data: Vec<u8>,
+            expire: Option<T::Moment>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            T::Registry::available(&caller, node)?;
+
+            let (_, label) = Self::parse_label(&data)?;
+            let subnode = label.encode_with_node(&node);
+            ensure!(
+                T::Registry::owner_of(subnode).is_some(),
+                Error::<T>::NotExistOrOccupied
+            );
+            ensure!(
+                !SubnameLeases::<T>::contains_key(subnode),
+                Error::<T>::SubnameLeased
+            );
+            T::Registry::check_fuses(subnode, crate::registry::fuses::PARENT_CANNOT_CONTROL)?;
+
+            SubnameExpiries::<T>::set(subnode, expire);
+
+            Self::deposit_event(Event::<T>::SubnameExpirySet {
+                node,
+                subnode,
+                expire,
+            });
+            Ok(())
+        }
+        /// Bid `amount` on `name`, which is too short to be registered.
+        /// The first bid starts its auction, which ends `AuctionPeriod` later.
+        ///
+        /// Note: The bid is reserved until someone bids more in the same sample, or until
+        /// the auction is settled if it leads any earlier sample.
+        /// Raising your own bid only reserves the difference.
+        ///
+        /// Ensure: The name can't be registered by anyone right now,
+        /// the bid is at least `MinAuctionBid` and above the highest one.
+        #[pallet::call_index(41)]
+        #[pallet::weight(T::WeightInfo::bid(name.len() as u32, Pallet::<T>::candle().samples()))]
+        #[frame_support::transactional]
+        pub fn bid(
+            origin: OriginFor<T>,
+            base_node: DomainHash,
+            name: Vec<u8>,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            use crate::traits::{Available, Registrar};
+
+            let caller = ensure_signed(origin)?;
+
+            Self::ensure_enabled(Operation::Register)?;
+
+            let name =
+                T::LabelPolicy::normalize_auctionable(&name).ok_or(Error::<T>::ParseLabelFailed)?;
+            ensure!(name.len().is_anctionable(), Error::<T>::NotAuctionable);
+            let node = Label::from_normalized(&name).encode_with_node(&base_node);
+            let now = T::NowProvider::now();
+
+            let auction = match Auctions::<T>::get(node) {
+                Some(auction) => {
+                    ensure!(now < auction.end, Error::<T>::AuctionEnded);
+                    ensure!(amount > auction.amount, Error::<T>::BidTooLow);
+                    auction
+                }
+                None => {
+                    ensure!(
+                        !AuctionBidders::<T>::contains_key(node),
+                        Error::<T>::UnclaimedBids
+                    );
+                    let tld = BaseNodes::<T>::get(base_node).ok_or(Error::<T>::UnknownBaseNode)?;
+                    ensure!(tld.open, Error::<T>::TldClosed);
+                    ensure!(
+                        !ReservedList::<T>::contains_key(node)
+                            && !ReservedFor::<T>::contains_key(node)
+                            && !Self::reserved_by_rule(&name, node),
+                        Error::<T>::Frozen
+                    );
+                    if RegistrarInfos::<T>::contains_key(node) {
+                        Self::check_expires_registrable(node)?;
+                        ensure!(!Self::in_cooldown(node, now), Error::<T>::InCooldown);
+                    }
+                    ensure!(amount >= T::MinAuctionBid::get(), Error::<T>::BidTooLow);
+
+                    let end = now
+                        .checked_add(&T::AuctionPeriod::get())
+                        .ok_or(ArithmeticError::Overflow)?;
+                    Self::deposit_event(Event::<T>::AuctionStarted {
+                        node,
+                        base_node,
+                        end,
+                    });
+                    AuctionOf::<T> {
+                        base_node,
+                        name: name.try_into().map_err(|_| Error::<T>::LabelInvalid)?,
+                        bidder: caller.clone(),
+                        amount,
+                        end,
+                    }
+                }
+            };
+
+            let sample = Self::candle()
+                .status(auction.end, now)
+                .sample()
+                .ok_or(Error::<T>::AuctionEnded)?;
+            // outbid in its own sample, the previous leader only needs its highest bid
+            // among the earlier samples it leads, if any.
+            if auction.bidder != caller && AuctionSamples::<T>::contains_key(node, sample) {
+                let needed = AuctionSamples::<T>::iter_prefix(node)
+                    .filter(|(earlier, (leader, _))| *earlier < sample && *leader == auction.bidder)
+                    .map(|(_, (_, amount))| amount)
+                    .max()
+                    .unwrap_or_default();
+                let reserved = Self::take_auction_reserve(node, &auction.bidder);
+                T::Currency::unreserve(&auction.bidder, reserved.saturating_sub(needed));
+                if !needed.is_zero() {
+                    Self::put_auction_reserve(node, &auction.bidder, needed)?;
+                }
+            }
+            let reserved = Self::take_auction_reserve(node, &caller);
+            Self::put_auction_reserve(node, &caller, amount)?;
+            T::Currency::reserve(&caller, amount.saturating_sub(reserved))?;
+            AuctionSamples::<T>::insert(node, sample, (caller.clone(), amount));
+
+            Auctions::<T>::insert(
+                node,
+                AuctionOf::<T> {
+                    bidder: caller.clone(),
+                    amount,
+                    ..auction
+                },
+            );
+
+            Self::deposit_event(Event::<T>::BidPlaced {
+                node,
+                bidder: caller,
+                amount,
+            });
+            Ok(())
+        }
+        /// Register the name of a finished auction to its winner, for the minimum
+        /// duration of its base node. The winning bid is paid like a registration fee,
+        /// the other reserved bids are returned by `claim_bid`.
+        ///
+        /// If bids of several samples could win, the first call closes the auction
+        /// and it is settled once `Randomness` known after that draws the sample.
+        ///
+        /// Ensure: The auction is over, anyone can call it.
+        #[pallet::call_index(42)]
+        #[pallet::weight(T::WeightInfo::settle_auction(Pallet::<T>::candle().samples()))]
+        #[frame_support::transactional]
+        pub fn settle_auction(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let _ = ensure_signed(origin)?;
+
+            let auction = Auctions::<T>::get(node).ok_or(Error::<T>::NoAuction)?;
+            ensure!(
+                T::NowProvider::now() >= auction.end,
+                Error::<T>::AuctionNotEnded
+            );
+
+            let leaders = AuctionSamples::<T>::iter_prefix(node).collect::<Vec<_>>();
+            let (winner, amount) = if leaders.len() <= 1 {
+                // the same bid wins whichever sample is drawn.
+                (auction.bidder.clone(), auction.amount)
+            } else {
+                let Some(closed_at) = AuctionClosings::<T>::get(node) else {
+                    let closed_at = frame_system::Pallet::<T>::block_number();
+                    AuctionClosings::<T>::insert(node, closed_at);
+
+                    Self::deposit_event(Event::<T>::AuctionClosed { node, closed_at });
+                    return Ok(());
+                };
+                let (seed, known_since) = T::Randomness::random(&(b"pns/auction", node).encode());
+                ensure!(known_since > closed_at, Error::<T>::RandomnessNotReady);
+
+                let drawn = Self::candle().draw(seed.as_ref());
+                crate::auction::winner_of(leaders, drawn)
+                    .unwrap_or((auction.bidder.clone(), auction.amount))
+            };
+
+            Auctions::<T>::remove(node);
+            AuctionClosings::<T>::remove(node);
+            let _ = AuctionSamples::<T>::clear_prefix(node, u32::MAX, None);
+            let reserved = Self::take_auction_reserve(node, &winner);
+            T::Currency::unreserve(&winner, reserved.saturating_sub(amount));
+
+            let missing = T::Currency::repatriate_reserved(
+                &winner,
+                &T::Official::get_official_account()?,
+                amount,
+                BalanceStatus::Free,
+            )?;
+            ensure!(missing.is_zero(), Error::<T>::InsufficientReserve);
+            Self::distribute_fee(amount)?;
+
+            let duration = BaseNodes::<T>::get(auction.base_node)
+                .ok_or(Error::<T>::UnknownBaseNode)?
+                .min_duration;
+            Self::mint_prepaid(
+                auction.name.into_inner(),
+                auction.base_node,
+                node,
+                winner.clone(),
+                duration,
+            )?;
+
+            Self::deposit_event(Event::<T>::AuctionSettled {
+                node,
+                winner,
+                amount,
+            });
+            Ok(())
+        }
+        /// Offer a domain name to `to` for `price`, paid when `to` accepts it
+        /// within `TransferOfferExpiry` blocks. A new offer replaces the pending one.
+        ///
+        /// Note: The royalty of the domain name is taken from the price, see `registry::set_royalty`.
+        ///
+        /// Ensure: Same as `offer_transfer`.
+        #[pallet::call_index(43)]
+        #[pallet::weight(T::WeightInfo::offer_sale())]
+        pub fn offer_sale(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            to: <T::Lookup as StaticLookup>::Source,
+            price: BalanceOf<T>,
+        ) -> DispatchResult {
+            let from = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(to)?;
+
+            let deadline = Self::do_offer_transfer(&from, node, &to, price)?;
+
+            Self::deposit_event(Event::<T>::SaleOffered {
+                node,
+                from,
+                to,
+                price,
+                deadline,
+            });
+            Ok(())
+        }
+        /// Add several domains to the reserved list.
+        ///
+        /// Ensure: At most `MaxBatchSize` domain names, more than `ReserveBatchThreshold`
+        /// of them only with the `GovernanceOrigin`.
+        #[pallet::call_index(44)]
+        #[pallet::weight(T::WeightInfo::add_reserved().saturating_mul(nodes.len() as u64))]
+        pub fn add_reserved_batch(
+            origin: OriginFor<T>,
+            nodes: BoundedVec<DomainHash, T::MaxBatchSize>,
+        ) -> DispatchResult {
+            let manager = Self::ensure_reserve_batch_origin(origin, nodes.len())?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::add_reserved_batch {
+                    nodes: nodes.clone(),
+                },
+            );
+            ensure!(!nodes.is_empty(), Error::<T>::InvalidBatchSize);
+
+            for node in nodes {
+                ReservedList::<T>::insert(node, ());
+
+                Self::deposit_event(Event::<T>::NameReserved { node });
+            }
+            Ok(())
+        }
+        /// Remove several domains from the reserved list.
+        ///
+        /// Ensure: Same as `add_reserved_batch`.
+        #[pallet::call_index(45)]
+        #[pallet::weight(T::WeightInfo::remove_reserved().saturating_mul(nodes.len() as u64))]
+        pub fn remove_reserved_batch(
+            origin: OriginFor<T>,
+            nodes: BoundedVec<DomainHash, T::MaxBatchSize>,
+        ) -> DispatchResult {
+            let manager = Self::ensure_reserve_batch_origin(origin, nodes.len())?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::remove_reserved_batch {
+                    nodes: nodes.clone(),
+                },
+            );
+            ensure!(!nodes.is_empty(), Error::<T>::InvalidBatchSize);
+
+            for node in nodes {
+                ReservedList::<T>::remove(node);
+                ReservedClaims::<T>::remove(node);
+
+                Self::deposit_event(Event::<T>::NameUnReserved { node });
+            }
+            Ok(())
+        }
+        /// Slash `share` of the deposit of `node` to the `Treasury` for proven abuse,
+        /// and burn `node` if `burn`, returning the rest of the deposit to its owner.
+        ///
+        /// Ensure: `node` is a registered domain name, subdomains have to be burned first.
+        /// Only judicial origin
+        #[pallet::call_index(46)]
+        #[pallet::weight(T::WeightInfo::slash_deposit())]
+        #[frame_support::transactional]
+        pub fn slash_deposit(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            share: Perbill,
+            burn: bool,
+        ) -> DispatchResult {
+            let judge = T::JudicialOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &judge,
+                &Call::<T>::slash_deposit { node, share, burn },
+            );
+
+            let owner = T::Registry::owner_of(node).ok_or(Error::<T>::NotExistOrOccupied)?;
+            let (slashed, remaining) =
+                RegistrarInfos::<T>::try_mutate(node, |info| -> Result<_, DispatchError> {
+                    let info = info.as_mut().ok_or(Error::<T>::NotExistOrOccupied)?;
+                    let slashed = share * info.deposit;
+                    info.deposit -= slashed;
+                    Ok((slashed, info.deposit))
+                })?;
+            if !slashed.is_zero() {
+                let imbalance = T::Currency::withdraw(
+                    &T::Official::get_official_account()?,
+                    slashed,
+                    WithdrawReasons::TRANSFER,
+                    ExistenceRequirement::KeepAlive,
+                )?;
+                T::Treasury::on_unbalanced(imbalance);
+            }
+
+            Self::deposit_event(Event::<T>::DepositSlashed {
+                node,
+                owner: owner.clone(),
+                slashed,
+                remaining,
+            });
+
+            if burn {
+                T::Registry::thaw_unchecked(node);
+                T::Registry::burn_unchecked(&judge, node)?;
+                T::ResolverRecords::clear_records(node);
+                RenewLocked::<T>::remove(node);
+                if let Some(renewal) = AutoRenewals::<T>::get(node) {
+                    Self::end_auto_renew(node, renewal);
+                }
+
+                Self::deposit_event(Event::<T>::NameBurnedForAbuse { node, owner });
+            }
+            Ok(())
+        }
+        /// Return the bid `bidder` reserved in the auction of `node` once it is settled.
+        ///
+        /// Ensure: The auction was settled, anyone can call it.
+        #[pallet::call_index(48)]
+        #[pallet::weight(T::WeightInfo::claim_bid())]
+        pub fn claim_bid(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            bidder: T::AccountId,
+        ) -> DispatchResult {
+            let _ = ensure_signed(origin)?;
+
+            ensure!(
+                !Auctions::<T>::contains_key(node),
+                Error::<T>::AuctionNotSettled
+            );
+            let amount = Self::take_auction_reserve(node, &bidder);
+            ensure!(!amount.is_zero(), Error::<T>::NoBid);
+            T::Currency::unreserve(&bidder, amount);
+
+            Self::deposit_event(Event::<T>::BidClaimed {
+                node,
+                bidder,
+                amount,
+            });
+            Ok(())
+        }
+        /// Remove `commitment` and return its deposit to the committer.
+        ///
+        /// Ensure: The caller made the commitment, or it is older than `MaxCommitmentAge`.
+        #[pallet::call_index(47)]
+        #[pallet::weight(T::WeightInfo::remove_commitment())]
+        pub fn remove_commitment(origin: OriginFor<T>, commitment: DomainHash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (committer, committed_at, deposit) =
+                Commitments::<T>::get(commitment).ok_or(Error::<T>::CommitmentNotFound)?;
+            ensure!(
+                committer == who
+                    || T::NowProvider::now() > committed_at + T::MaxCommitmentAge::get(),
+                Error::<T>::CommitmentNotExpired
+            );
+
+            Commitments::<T>::remove(commitment);
+            T::Currency::unreserve(&committer, deposit);
+
+            Self::deposit_event(Event::<T>::CommitmentRemoved {
+                who: committer,
+                commitment,
+            });
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// The `GovernanceOrigin` for batches of more than `ReserveBatchThreshold` names,
+        /// the `ReserveOrigin` for smaller ones.
+        fn ensure_reserve_batch_origin(
+            origin: OriginFor<T>,
+            len: usize,
+        ) -> Result<T::AccountId, DispatchError> {
+            if len > T::ReserveBatchThreshold::get() as usize {
+                Ok(T::GovernanceOrigin::ensure_origin(origin)?)
+            } else {
+                Ok(T::ReserveOrigin::ensure_origin(origin)?)
+            }
+        }
+
+        /// Ensures the registrar is open and `op` is not paused.
+        pub(crate) fn ensure_enabled(op: Operation) -> DispatchResult {
+            ensure!(T::IsOpen::is_open(), Error::<T>::RegistrarClosed);
+            ensure!(T::IsOpen::is_enabled(op), Error::<T>::OperationPaused);
+            Ok(())
+        }
+
+        /// Transfers `node` to `to` if `who` has the permission and it did not expire.
+        #[frame_support::require_transactional]
+        pub(crate) fn do_transfer(
+            who: &T::AccountId,
+            to: &T::AccountId,
+            node: DomainHash,
+        ) -> DispatchResult {
+            Self::check_not_expired(node)?;
+            T::Registry::transfer(who, to, node)
+        }
+
+        /// Same as `do_transfer` for `node` sold for `price`, which `to` pays
+        /// to its owner, minus the royalty of `node` paid to the beneficiary.
+        #[frame_support::require_transactional]
+        pub(crate) fn do_transfer_sold(
+            who: &T::AccountId,
+            to: &T::AccountId,
+            node: DomainHash,
+            price: BalanceOf<T>,
+        ) -> DispatchResult {
+            Self::check_not_expired(node)?;
+            T::Registry::transfer_sold(who, to, node, |owner, royalty| {
+                let mut rest = price;
+                if let Some(royalty) = royalty {
+                    let amount = Perbill::from_rational(
+                        u32::from(royalty.basis_points),
+                        u32::from(crate::registry::MAX_BASIS_POINTS),
+                    ) * price;
+                    if !amount.is_zero() {
+                        T::Currency::transfer(
+                            to,
+                            &royalty.beneficiary,
+                            amount,
+                            ExistenceRequirement::KeepAlive,
+                        )?;
+                        rest -= amount;
+
+                        Self::deposit_event(Event::<T>::RoyaltyPaid {
+                            node,
+                            beneficiary: royalty.beneficiary.clone(),
+                            amount,
+                        });
+                    }
+                }
+                T::Currency::transfer(to, owner, rest, ExistenceRequirement::KeepAlive)
+            })
+        }
+
+        fn check_not_expired(node: DomainHash) -> DispatchResult {
+            if let Some(info) = RegistrarInfos::<T>::get(node) {
+                let now = T::NowProvider::now();
+                ensure!(
+                    info.expire + Self::grace_period(node) > now,
+                    Error::<T>::NotOwned
+                );
+            }
+            Ok(())
+        }
+
+        /// Stores the offer of `node` to `to` made by `from`, returns its deadline.
+        fn do_offer_transfer(
+            from: &T::AccountId,
+            node: DomainHash,
+            to: &T::AccountId,
+            price: BalanceOf<T>,
+        ) -> Result<T::BlockNumber, DispatchError> {
+            Self::ensure_enabled(Operation::Transfer)?;
+            T::Registry::available(from, node)?;
+            T::Registry::check_fuses(node, crate::registry::fuses::CANNOT_TRANSFER)?;
+
+            let deadline = frame_system::Pallet::<T>::block_number()
+                .saturating_add(T::TransferOfferExpiry::get());
+            TransferOffers::<T>::insert(
+                node,
+                TransferOfferOf::<T> {
+                    from: from.clone(),
+                    to: to.clone(),
+                    deadline,
+                    price,
+                },
+            );
+            Ok(deadline)
+        }
+
+        /// Registers `name` under `base_node` to `owner` with `caller` paying for it
+        /// as told by `payment`.
+        ///
+        /// Returns the node of the registered name.
+        pub(crate) fn do_register(
+            caller: T::AccountId,
+            base_node: DomainHash,
+            name: Vec<u8>,
+            owner: T::AccountId,
+            duration: T::Moment,
+            payment: FeePaymentOf<T>,
+        ) -> Result<DomainHash, DispatchError> {
+            Self::ensure_enabled(Operation::Register)?;
+            match Phase::<T>::get() {
+                RegistrationPhase::Closed => return Err(Error::<T>::RegistrarClosed.into()),
+                RegistrationPhase::Whitelist => {
+                    ensure!(
+                        Whitelist::<T>::contains_key(&caller),
+                        Error::<T>::NotWhitelisted
+                    )
+                }
+                RegistrationPhase::Open => {}
+            }
+            let tld = BaseNodes::<T>::get(base_node).ok_or(Error::<T>::UnknownBaseNode)?;
+            ensure!(tld.open, Error::<T>::TldClosed);
+            Self::use_quota(&caller)?;
+
+            ensure!(
+                duration >= tld.min_duration,
+                Error::<T>::RegistryDurationInvalid
+            );
+            ensure!(
+                duration <= T::MaxRegistrationDuration::get(),
+                Error::<T>::RegistryDurationTooLong
+            );
+
+            let (name, label) = Self::parse_label(&name)?;
+            let label_len = name.len();
+
+            use crate::traits::Available;
+
+            ensure!(label_len.is_registrable(), Error::<T>::LabelInvalid);
+
+            let official = T::Official::get_official_account()?;
+
+            let now = T::NowProvider::now();
+
+            let expire = now
+                .checked_add(&duration)
+                .ok_or(ArithmeticError::Overflow)?;
+
+            // 防止计算结果溢出
+            ensure!(
+                expire + tld.grace_period > now + tld.grace_period,
+                ArithmeticError::Overflow
+            );
+            let label_node = label.encode_with_node(&base_node);
+
+            ensure!(
+                !ReservedList::<T>::contains_key(label_node),
+                Error::<T>::Frozen
+            );
+            ensure!(
+                !Self::reserved_by_rule(&name, label_node),
+                Error::<T>::Frozen
+            );
+
+            let reserved_for = ReservedFor::<T>::get(label_node);
+            if let Some(holder) = reserved_for.as_ref() {
+                ensure!(holder == &caller, Error::<T>::Frozen);
+            }
+
+            T::Registry::mint_subname(
+                &official,
+                base_node,
+                label_node,
+                owner.clone(),
+                0,
+                |maybe_pre_owner| -> DispatchResult {
+                    if let Some(pre_owner) = maybe_pre_owner {
+                        ensure!(
+                            pre_owner == &caller || !Self::in_cooldown(label_node, now),
+                            Error::<T>::InCooldown
+                        );
+                        T::Lifecycle::on_expired(label_node, pre_owner);
+                    }
+                    let (register_fee, deposit) =
+                        Self::register_cost(label_node, label_len, duration, now)?;
+                    match payment {
+                        FeePayment::Native => {
+                            let target_value = register_fee
+                                .checked_add(&deposit)
+                                .ok_or(ArithmeticError::Overflow)?;
+
+                            Self::ensure_not_dusted(&caller, target_value)?;
+                            T::Currency::transfer(
+                                &caller,
+                                &official,
+                                target_value,
+                                ExistenceRequirement::KeepAlive,
+                            )?;
+                            Self::distribute_fee(register_fee)?;
+                        }
+                        FeePayment::Asset(asset) => {
+                            Self::ensure_not_dusted(&caller, deposit)?;
+                            T::Currency::transfer(
+                                &caller,
+                                &official,
+                                deposit,
+                                ExistenceRequirement::KeepAlive,
+                            )?;
+                            Self::pay_in_asset(&caller, asset, register_fee)?;
+                        }
+                        FeePayment::Prepaid => {}
+                    }
+                    RegistrarInfos::<T>::mutate(label_node, |info| -> DispatchResult {
+                        if let Some(info) = info.as_mut() {
+                            if let Some(pre_owner) = maybe_pre_owner {
+                                T::Currency::transfer(
+                                    &official,
+                                    pre_owner,
+                                    info.deposit,
+                                    ExistenceRequirement::KeepAlive,
+                                )?;
+                            }
+                            Self::reindex_expiry(label_node, Some(info.expire), Some(expire));
+                            info.deposit = deposit;
+                            info.register_fee = register_fee;
+                            info.expire = expire;
+                        } else {
+                            let _ = info.insert(RegistrarInfoOf::<T> {
+                                deposit,
+                                register_fee,
+                                expire,
+                                capacity: tld.capacity,
+                            });
+                            ActiveDomains::<T>::mutate(|count| *count = count.saturating_add(1));
+                            Self::reindex_expiry(label_node, None, Some(expire));
+                        }
+                        Ok(())
+                    })?;
+                    Ok(())
+                },
+            )?;
+
+            BaseNodeOf::<T>::insert(label_node, base_node);
+            if reserved_for.is_some() {
+                ReservedFor::<T>::remove(label_node);
+            }
+            // the lock was a choice of the previous owner.
+            RenewLocked::<T>::remove(label_node);
+            TotalRegistrations::<T>::mutate(|count| *count = count.saturating_add(1));
+            T::PriceOracle::note_registration();
+
+            T::Registry::set_resolver(label_node, T::DefaultResolver::get());
+            T::Lifecycle::on_registered(label_node, &owner, expire);
+
+            Self::deposit_event(Event::<T>::NameRegistered {
+                name,
+                base_node,
+                node: label_node,
+                owner,
+                expire,
+            });
+
+            Ok(label_node)
+        }
+
+        /// Renews `name` under `base_node` by `duration` and returns its price,
+        /// `caller` pays for it here unless the price is settled separately (`prepaid`).
+        pub(crate) fn do_renew(
+            caller: &T::AccountId,
+            base_node: DomainHash,
+            name: Vec<u8>,
+            duration: T::Moment,
+            payment: FeePaymentOf<T>,
+        ) -> Result<BalanceOf<T>, DispatchError> {
+            Self::ensure_enabled(Operation::Renew)?;
+
+            let (name, label) = Self::parse_label(&name)?;
+            let label_len = name.len();
+
+            let label_node = label.encode_with_node(&base_node);
+            let grace_period = BaseNodes::<T>::get(base_node)
+                .ok_or(Error::<T>::UnknownBaseNode)?
+                .grace_period;
+
+            if RenewLocked::<T>::contains_key(label_node) {
+                T::Registry::available(caller, label_node)?;
+            }
+
+            RegistrarInfos::<T>::mutate(label_node, |info| -> Result<_, DispatchError> {
+                let info = info.as_mut().ok_or(Error::<T>::NotExistOrOccupied)?;
+
+                let expire = info.expire;
+                let now = T::NowProvider::now();
+                ensure!(now <= expire + grace_period, Error::<T>::NotRenewable);
+                let target_expire = expire
+                    .checked_add(&duration)
+                    .ok_or(ArithmeticError::Overflow)?;
+                ensure!(
+                    target_expire + grace_period > now + grace_period,
+                    ArithmeticError::Overflow
+                );
+                ensure!(
+                    target_expire <= now + T::MaxRegistrationDuration::get(),
+                    Error::<T>::RegistryDurationTooLong
+                );
+                let price = Self::renew_cost(label_len, duration, expire, now)?;
+                match payment {
+                    FeePayment::Native => {
+                        Self::ensure_not_dusted(caller, price)?;
+                        T::Currency::transfer(
+                            caller,
+                            &T::Official::get_official_account()?,
+                            price,
+                            ExistenceRequirement::KeepAlive,
+                        )?;
+                        Self::distribute_fee(price)?;
+                    }
+                    FeePayment::Asset(asset) => Self::pay_in_asset(caller, asset, price)?,
+                    FeePayment::Prepaid => {}
+                }
+                info.expire = target_expire;
+                Self::reindex_expiry(label_node, Some(expire), Some(target_expire));
+                TotalRenewals::<T>::mutate(|count| *count = count.saturating_add(1));
+                T::Lifecycle::on_renewed(label_node, target_expire);
+                Self::deposit_event(Event::<T>::NameRenewed {
+                    name,
+                    node: label_node,
+                    duration,
+                    expire: target_expire,
+                });
+                Ok(price)
+            })
+        }
+
+        /// Pays `fee` to the official account in `asset`, at the rate of the price oracle.
+        ///
+        /// Unlike native fees, it is not split by `FeeBurnShare` and `FeeTreasuryShare`.
+        fn pay_in_asset(
+            payer: &T::AccountId,
+            asset: AssetIdOf<T>,
+            fee: BalanceOf<T>,
+        ) -> DispatchResult {
+            let amount =
+                T::PriceOracle::asset_amount(asset, fee).ok_or(Error::<T>::AssetNotAccepted)?;
+            <T::Assets as fungibles::Transfer<T::AccountId>>::transfer(
+                asset,
+                payer,
+                &T::Official::get_official_account()?,
+                amount,
+                true,
+            )?;
+            Self::deposit_event(Event::<T>::FeePaidInAsset {
+                payer: payer.clone(),
+                asset,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Renewal fee of a label of `label_len` expiring at `expire` for `duration`.
+        ///
+        /// Names renewed during their grace period pay the `GraceRenewalSurcharge`.
+        pub(crate) fn renew_cost(
+            label_len: usize,
+            duration: T::Moment,
+            expire: T::Moment,
+            now: T::Moment,
+        ) -> Result<BalanceOf<T>, DispatchError> {
+            let mut price =
+                T::PriceOracle::renew_fee(label_len, duration).ok_or(ArithmeticError::Overflow)?;
+            if now > expire {
+                price = price
+                    .checked_add(&(T::GraceRenewalSurcharge::get() * price))
+                    .ok_or(ArithmeticError::Overflow)?;
+            }
+            Ok(price)
+        }
+
+        /// Registration fee and deposit of a label of `label_len` for `duration`.
+        ///
+        /// The registration fee includes the premium if `label_node` left its grace period
+        /// only recently.
+        pub(crate) fn register_cost(
+            label_node: DomainHash,
+            label_len: usize,
+            duration: T::Moment,
+            now: T::Moment,
+        ) -> Result<(BalanceOf<T>, BalanceOf<T>), DispatchError> {
+            let mut register_fee = T::PriceOracle::register_fee(label_len, duration)
+                .ok_or(ArithmeticError::Overflow)?;
+            if let Some(info) = RegistrarInfos::<T>::get(label_node) {
+                let grace_end = info.expire + Self::grace_period(label_node);
+                if now > grace_end {
+                    let premium = T::PriceOracle::premium(label_len, now - grace_end)
+                        .ok_or(ArithmeticError::Overflow)?;
+                    register_fee = register_fee
+                        .checked_add(&premium)
+                        .ok_or(ArithmeticError::Overflow)?;
+                }
+            }
+            let deposit =
+                T::PriceOracle::deposit_fee(label_len).ok_or(ArithmeticError::Overflow)?;
+            Ok((register_fee, deposit))
+        }
+
+        /// Renews the due auto-renewals after the cursor, as many as `limit` allows.
+        pub(crate) fn process_auto_renewals(limit: Weight) -> Weight {
+            let per_renewal = T::WeightInfo::auto_renew();
+            let mut used = T::DbWeight::get().reads(1);
+            if used.any_gt(limit)
+                || !T::IsOpen::is_open()
+                || !T::IsOpen::is_enabled(Operation::Renew)
+            {
+                return used;
+            }
+
+            let mut renewals = match AutoRenewCursor::<T>::get() {
+                Some(last) => AutoRenewals::<T>::iter_from(AutoRenewals::<T>::hashed_key_for(last)),
+                None => AutoRenewals::<T>::iter(),
+            };
+            let mut batch = Vec::new();
+            let mut exhausted = false;
+            while batch.len() < T::MaxAutoRenewalsPerBlock::get() as usize
+                && used.saturating_add(per_renewal).all_lte(limit)
+            {
+                let Some(renewal) = renewals.next() else {
+                    exhausted = true;
+                    break;
+                };
+                used = used.saturating_add(per_renewal);
+                batch.push(renewal);
+            }
+
+            if exhausted {
+                AutoRenewCursor::<T>::kill();
+            } else if let Some((last, _)) = batch.last() {
+                AutoRenewCursor::<T>::put(last);
+            }
+
+            let now = T::NowProvider::now();
+            for (node, renewal) in batch {
+                Self::auto_renew(node, renewal, now);
+            }
+            used
+        }
+
+        /// Renews `node` from the pot if it is due, ends the auto-renewal if that fails.
+        fn auto_renew(node: DomainHash, renewal: AutoRenewalOf<T>, now: T::Moment) {
+            let Some(info) = RegistrarInfos::<T>::get(node) else {
+                return Self::end_auto_renew(node, renewal);
+            };
+            if now + T::AutoRenewWindow::get() < info.expire {
+                return;
+            }
+
+            use frame_support::storage::{with_transaction, TransactionOutcome};
+
+            let paid = with_transaction(|| {
+                let paid = Self::do_renew(
+                    &renewal.payer,
+                    renewal.base_node,
+                    renewal.name.to_vec(),
+                    renewal.duration,
+                    FeePayment::Prepaid,
+                )
+                .and_then(|price| {
+                    ensure!(price <= renewal.pot, Error::<T>::AutoRenewPotTooLow);
+                    let missing = T::Currency::repatriate_reserved(
+                        &renewal.payer,
+                        &T::Official::get_official_account()?,
+                        price,
+                        BalanceStatus::Free,
+                    )?;
+                    ensure!(missing.is_zero(), Error::<T>::AutoRenewPotTooLow);
+                    Self::distribute_fee(price)?;
+                    Ok(price)
+                });
+                if paid.is_ok() {
+                    TransactionOutcome::Commit(paid)
+                } else {
+                    TransactionOutcome::Rollback(paid)
+                }
+            });
+
+            match paid {
+                Ok(price) => AutoRenewals::<T>::insert(
+                    node,
+                    AutoRenewalOf::<T> {
+                        pot: renewal.pot - price,
+                        ..renewal
+                    },
+                ),
+                Err(_) => Self::end_auto_renew(node, renewal),
+            }
+        }
+
+        /// Removes the auto-renewal of `node` and returns its pot to the payer.
+        fn end_auto_renew(node: DomainHash, renewal: AutoRenewalOf<T>) {
+            let unreturned = T::Currency::unreserve(&renewal.payer, renewal.pot);
+            AutoRenewals::<T>::remove(node);
+            Self::deposit_event(Event::<T>::AutoRenewCancelled {
+                node,
+                refunded: renewal.pot - unreturned,
+            });
+        }
+
+        /// Burns `node` if it can be registered by anyone again,
+        /// `reaper` is paid `reward` of its deposit.
+        pub(crate) fn do_reap(
+            reaper: &T::AccountId,
+            node: DomainHash,
+            reward: Perbill,
+        ) -> DispatchResult {
+            use crate::traits::Registrar;
+
+            Self::check_expires_registrable(node)?;
+            ensure!(
+                !Self::in_cooldown(node, T::NowProvider::now()),
+                Error::<T>::InCooldown
+            );
+
+            let reward =
+                RegistrarInfos::<T>::try_mutate(node, |info| -> Result<_, DispatchError> {
+                    let info = info.as_mut().ok_or(Error::<T>::NotExistOrOccupied)?;
+                    let reward = reward * info.deposit;
+                    info.deposit -= reward;
+                    Ok(reward)
+                })?;
+            if !reward.is_zero() {
+                T::Currency::transfer(
+                    &T::Official::get_official_account()?,
+                    reaper,
+                    reward,
+                    ExistenceRequirement::KeepAlive,
+                )?;
+            }
+
+            if let Some(owner) = T::Registry::owner_of(node) {
+                T::Lifecycle::on_expired(node, &owner);
+            }
+            T::Registry::burn_unchecked(reaper, node)?;
+            T::ResolverRecords::clear_records(node);
+            RenewLocked::<T>::remove(node);
+            if let Some(renewal) = AutoRenewals::<T>::get(node) {
+                Self::end_auto_renew(node, renewal);
+            }
+
+            Self::deposit_event(Event::<T>::NameReaped {
+                node,
+                reaper: reaper.clone(),
+                reward,
+            });
+            Ok(())
+        }
+
+        /// Reaps the expired domains after the cursor, as many as `limit` allows.
+        ///
+        /// The deposits are returned to the owners in full.
+        pub(crate) fn sweep_expired(limit: Weight) -> Weight {
+            use frame_support::storage::{with_transaction, TransactionOutcome};
+
+            let per_reap = T::WeightInfo::reap_expired();
+            let mut used = T::DbWeight::get().reads(2);
+            if used.any_gt(limit) {
+                return used;
+            }
+            let Ok(official) = T::Official::get_official_account() else {
+                return used;
+            };
+
+            let mut infos = match ReapCursor::<T>::get() {
+                Some(last) => {
+                    RegistrarInfos::<T>::iter_from(RegistrarInfos::<T>::hashed_key_for(last))
+                }
+                None => RegistrarInfos::<T>::iter(),
+            };
+            let now = T::NowProvider::now();
+            let mut looked_at = 0;
+            let mut last = None;
+            let mut expired = Vec::new();
+            let mut exhausted = false;
+            while looked_at < T::MaxReapsPerBlock::get()
+                && used.saturating_add(per_reap).all_lte(limit)
+            {
+                let Some((node, info)) = infos.next() else {
+                    exhausted = true;
+                    break;
+                };
+                looked_at += 1;
+                used = used.saturating_add(per_reap);
+                last = Some(node);
+                // base nodes never expire, their `expire` is the max value.
+                let reapable_after = info
+                    .expire
+                    .saturating_add(Self::grace_period(node))
+                    .saturating_add(T::ReRegistrationCooldown::get());
+                if now > reapable_after {
+                    expired.push(node);
+                }
+            }
+
+            if exhausted {
+                ReapCursor::<T>::kill();
+            } else if let Some(last) = last {
+                ReapCursor::<T>::put(last);
+            }
+
+            for node in expired {
+                let _ = with_transaction(|| {
+                    let reaped = Self::do_reap(&official, node, Perbill::zero());
+                    if reaped.is_ok() {
+                        TransactionOutcome::Commit(reaped)
+                    } else {
+                        TransactionOutcome::Rollback(reaped)
+                    }
+                });
+            }
+            used
+        }
+
+        /// Burns and hands to the treasury their shares of `fee`,
+        /// which was just paid to the official account.
+        fn distribute_fee(fee: BalanceOf<T>) -> DispatchResult {
+            let burned = T::FeeBurnShare::get() * fee;
+            let to_treasury = T::FeeTreasuryShare::get() * fee;
+            let taken = burned.saturating_add(to_treasury);
+            if taken.is_zero() {
+                return Ok(());
+            }
+
+            let imbalance = T::Currency::withdraw(
+                &T::Official::get_official_account()?,
+                taken,
+                WithdrawReasons::FEE,
+                ExistenceRequirement::KeepAlive,
+            )?;
+            // dropping the burned part reduces the total issuance.
+            let (_burned, to_treasury) = imbalance.split(burned);
+            T::Treasury::on_unbalanced(to_treasury);
+            Ok(())
+        }
+
+        /// Counts a registration of `who` against its `RegistrationQuota`.
+        fn use_quota(who: &T::AccountId) -> DispatchResult {
+            if T::RegistrationQuota::get() == 0 {
+                return Ok(());
+            }
+            let now = T::NowProvider::now();
+            let window = T::RegistrationQuotaWindow::get();
+            RecentRegistrations::<T>::try_mutate(who, |recent| {
+                recent.retain(|at| *at + window > now);
+                recent
+                    .try_push(now)
+                    .map_err(|_| Error::<T>::QuotaExceeded.into())
+            })
+        }
+
+        /// What the official account signs to give `who` a whitelist voucher.
+        pub fn voucher_payload(who: &T::AccountId) -> Vec<u8> {
+            (b"pns/whitelist", who).encode()
+        }
+
+        /// Normalizes `name` with `T::LabelPolicy`, returning it with its label.
+        pub fn parse_label(name: &[u8]) -> Result<(Vec<u8>, Label), Error<T>> {
+            let name = T::LabelPolicy::normalize(name).ok_or(Error::<T>::ParseLabelFailed)?;
+            let label = Label::from_normalized(&name);
+            Ok((name, label))
+        }
+
+        /// Mint the subdomain `data` of `node` to `to`, see `mint_subname`.
+        #[frame_support::require_transactional]
+        fn do_mint_subname(
+            caller: &T::AccountId,
+            node: DomainHash,
+            data: &[u8],
+            to: T::AccountId,
+            capacity: u32,
+        ) -> DispatchResult {
+            let (data, label) = Self::parse_label(data)?;
+            let label_node = label.encode_with_node(&node);
+            T::Registry::mint_subname(caller, node, label_node, to.clone(), capacity, |_| Ok(()))?;
+            Self::deposit_event(Event::<T>::SubnameRegistered {
+                label: data,
+                subnode: label_node,
+                owner: to,
+                node,
+            });
+
+            Ok(())
+        }
+
+        /// How many subdomains `node` can have.
+        fn subname_capacity(node: DomainHash) -> Result<u32, DispatchError> {
+            match RegistrarInfos::<T>::get(node) {
+                Some(info) => Ok(info.capacity),
+                None => Ok(Self::tld_of(T::Registry::root_of(node))
+                    .ok_or(Error::<T>::UnknownBaseNode)?
+                    .capacity),
+            }
+        }
+
+        /// End of the lock of `node`, `None` if it is not locked.
+        pub fn transfer_lock(node: DomainHash) -> Option<T::Moment> {
+            let (owner, until) = TransferLocks::<T>::get(node)?;
+            (T::NowProvider::now() < until && T::Registry::owner_of(node) == Some(owner))
+                .then_some(until)
+        }
+
+        /// Settings of the base node `node` is registered under.
+        pub fn tld_of(node: DomainHash) -> Option<TldConfigOf<T>> {
+            BaseNodeOf::<T>::get(node).and_then(BaseNodes::<T>::get)
+        }
+
+        /// Grace period of `node`, zero if it is not registered under any base node.
+        pub(crate) fn grace_period(node: DomainHash) -> T::Moment {
+            Self::tld_of(node)
+                .map(|tld| tld.grace_period)
+                .unwrap_or_default()
+        }
+
+        /// Whether `name` is reserved by one of the `ReservationRules`.
+        pub(crate) fn reserved_by_rule(name: &[u8], node: DomainHash) -> bool {
+            ReservationRules::<T>::get()
+                .iter()
+                .any(|rule| rule.matches(name, node))
+        }
+
+        /// Bucket of `ExpiringAt` a domain expiring at `expire` is indexed in.
+        pub(crate) fn expiry_bucket(expire: T::Moment) -> T::Moment {
+            expire / T::ExpiryBucket::get().max(One::one())
+        }
+
+        /// Bucket of `ExpiringAt` a domain expiring at `expire` is indexed in now,
+        /// the next one to be warned about if its own bucket already was.
+        fn indexed_bucket(expire: T::Moment) -> T::Moment {
+            let bucket = Self::expiry_bucket(expire);
+            NextExpiryBucket::<T>::get().map_or(bucket, |next| bucket.max(next))
+        }
+
+        /// Moves `node` from the expiry bucket of `old` to the one of `new`.
+        pub(crate) fn reindex_expiry(
+            node: DomainHash,
+            old: Option<T::Moment>,
+            new: Option<T::Moment>,
+        ) {
+            if let Some(old) = old {
+                ExpiringAt::<T>::remove(Self::indexed_bucket(old), node);
+            }
+            if let Some(new) = new {
+                ExpiringAt::<T>::insert(Self::indexed_bucket(new), node, ());
+            }
+        }
+
+        /// Emits `NameExpiringSoon` for the buckets which entered the warning period,
+        /// at most `MaxExpiryWarningsPerBlock` of them out of `MaxExpiryBucketsPerBlock` buckets.
+        pub(crate) fn warn_expiring() -> Weight {
+            let now = T::NowProvider::now();
+            let target = Self::expiry_bucket(now + T::ExpiryWarningPeriod::get());
+            let mut bucket =
+                NextExpiryBucket::<T>::get().unwrap_or_else(|| Self::expiry_bucket(now));
+
+            let max = T::MaxExpiryWarningsPerBlock::get();
+            let max_buckets = T::MaxExpiryBucketsPerBlock::get();
+            let mut warned = 0;
+            let mut scanned = 0;
+            while bucket <= target && warned < max && scanned < max_buckets {
+                scanned += 1;
+                let remaining = max - warned;
+                let mut drained = 0;
+                for (node, ()) in ExpiringAt::<T>::drain_prefix(bucket).take(remaining as usize) {
+                    drained += 1;
+                    if let Some(info) = RegistrarInfos::<T>::get(node) {
+                        Self::deposit_event(Event::<T>::NameExpiringSoon {
+                            node,
+                            expire: info.expire,
+                        });
+                    }
+                }
+                warned += drained;
+                if drained < remaining {
+                    bucket += One::one();
+                }
+            }
+            NextExpiryBucket::<T>::put(bucket);
+
+            T::WeightInfo::warn_expiring(warned, scanned)
+        }
+    }
+}
+
+use crate::traits::{Label, Official, OnDomainLifecycle, Operation, Registry};
+use codec::{Decode, Encode};
+use frame_support::{
+    dispatch::{DispatchResult, Weight},
+    traits::{Currency, Get, IsSubType, Time},
+};
+use scale_info::TypeInfo;
+use sp_runtime::{
+    traits::{CheckedAdd, CheckedSub, DispatchInfoOf, SignedExtension, Zero},
+    transaction_validity::{
+        InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+    },
+    ArithmeticError,
+};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Upper bound of names checked by one [`Pallet::availability_many`] call.
+pub const MAX_AVAILABILITY_QUERIES: usize = 64;
+
+pub trait WeightInfo {
+    fn mint_subname(len: u32) -> Weight;
+    fn register(len: u32) -> Weight;
+    /// `register_batch` of `n` names of the longest length.
+    fn register_batch(n: u32) -> Weight;
+    fn renew(len: u32) -> Weight;
+    /// `renew_batch` of `n` names of the longest length.
+    fn renew_batch(n: u32) -> Weight;
+    fn transfer() -> Weight;
+    /// `transfer_batch` of `n` names.
+    fn transfer_batch(n: u32) -> Weight;
+    fn add_reserved() -> Weight;
+    fn remove_reserved() -> Weight;
+    fn reserve_for() -> Weight;
+    fn clear_reserved_for() -> Weight;
+    fn set_renew_lock() -> Weight;
+    fn commit() -> Weight;
+    fn reveal_register(len: u32) -> Weight;
+    fn remove_commitment() -> Weight;
+    fn register_with_config(len: u32, records: u32) -> Weight;
+    fn enable_auto_renew() -> Weight;
+    fn top_up() -> Weight;
+    fn cancel_auto_renew() -> Weight;
+    /// A single renewal done by the `on_idle` hook.
+    fn auto_renew() -> Weight;
+    /// `on_initialize` emitting `n` expiry warnings out of `buckets` buckets.
+    fn warn_expiring(n: u32, buckets: u32) -> Weight;
+    fn reap_expired() -> Weight;
+    fn slash_deposit() -> Weight;
+    fn buy_capacity() -> Weight;
+    fn assign_reserved_claim() -> Weight;
+    fn claim_reserved(len: u32) -> Weight;
+    fn add_reservation_rule() -> Weight;
+    fn remove_reservation_rule() -> Weight;
+    fn set_tld_config() -> Weight;
+    fn create_tld(len: u32) -> Weight;
+    fn set_phase() -> Weight;
+    fn add_whitelisted() -> Weight;
+    fn remove_whitelisted() -> Weight;
+    fn redeem_voucher() -> Weight;
+    fn transfer_and_reset() -> Weight;
+    fn offer_transfer() -> Weight;
+    fn accept_transfer() -> Weight;
+    fn cancel_transfer_offer() -> Weight;
+    fn offer_sale() -> Weight;
+    fn lock() -> Weight;
+    fn unlock() -> Weight;
+    fn offer_subname_lease(len: u32) -> Weight;
+    fn accept_subname_lease() -> Weight;
+    fn reclaim_subname() -> Weight;
+    fn set_subname_expiry(len: u32) -> Weight;
+    fn bid(len: u32, samples: u32) -> Weight;
+    fn settle_auction(samples: u32) -> Weight;
+    fn claim_bid() -> Weight;
+}
+
+impl<T: Config> crate::traits::Registrar for Pallet<T> {
+    type Balance = BalanceOf<T>;
+    type AccountId = T::AccountId;
+    type Moment = T::Moment;
+
+    fn check_expires_registrable(node: DomainHash) -> sp_runtime::DispatchResult {
+        let now = T::NowProvider::now();
+
+        let expire = RegistrarInfos::<T>::get(node)
+            .ok_or(Error::<T>::NotExistOrOccupied)?
+            .expire;
+
+        frame_support::ensure!(
+            now > expire + Self::grace_period(node),
+            Error::<T>::Occupied
+        );
+
+        Ok(())
+    }
+
+    // fn for_auction_set_expires(
+    // 	node: DomainHash,
+    // 	deposit: Self::Balance,
+    // 	register_fee: Self::Balance,
+    // ) {
+    // RegistrarInfos::<T>::mutate(node, |info| {
+    // 	let info = info.get_or_insert(RegistrarInfoOf::<T> {
+    // 		expire: Default::default(),
+    // 		capacity: T::DefaultCapacity::get(),
+    // 		deposit: ,
+    // 		register_fee:,
+    // 	});
+    // 	info.expire = frame_system::Pallet::<T>::block_number();
+
+    // 	info.deposit = deposit;
+    // 	info.register_fee = register_fee;
+    // })
+    // }
+
+    fn check_expires_renewable(node: DomainHash) -> sp_runtime::DispatchResult {
+        let now = T::NowProvider::now();
+
+        let expire = RegistrarInfos::<T>::get(node)
+            .ok_or(Error::<T>::NotExistOrOccupied)?
+            .expire;
+
+        frame_support::ensure!(
+            now < expire + Self::grace_period(node),
+            Error::<T>::NotRenewable
+        );
+
+        Ok(())
+    }
+
+    fn check_expires_useable(node: DomainHash) -> sp_runtime::DispatchResult {
+        let now = T::NowProvider::now();
+
+        if let Some(expire) = SubnameExpiries::<T>::get(node) {
+            frame_support::ensure!(now < expire, Error::<T>::SubnameExpired);
+        }
+
+        let expire = RegistrarInfos::<T>::get(T::Registry::root_of(node))
+            .ok_or(Error::<T>::NotExistOrOccupied)?
+            .expire;
+
+        frame_support::ensure!(now < expire, Error::<T>::NotUseable);
+
+        Ok(())
+    }
+
+    fn check_unlocked(node: DomainHash) -> sp_runtime::DispatchResult {
+        frame_support::ensure!(Self::transfer_lock(node).is_none(), Error::<T>::Locked);
+
+        Ok(())
+    }
+
+    fn clear_registrar_info(
+        node: DomainHash,
+        owner: &Self::AccountId,
+    ) -> sp_runtime::DispatchResult {
+        let official = T::Official::get_official_account()?;
+        RegistrarInfos::<T>::try_mutate_exists(node, |maybe_info| -> DispatchResult {
+            if let Some(info) = maybe_info.take() {
+                T::Currency::transfer(
+                    &official,
+                    owner,
+                    info.deposit,
+                    frame_support::traits::ExistenceRequirement::AllowDeath,
+                )?;
+                ActiveDomains::<T>::mutate(|count| *count = count.saturating_sub(1));
+                Self::reindex_expiry(node, Some(info.expire), None);
+                BaseNodeOf::<T>::remove(node);
+                TransferOffers::<T>::remove(node);
+            }
+            Ok(())
+        })
+    }
+
+    fn clear_subname_info(node: DomainHash) {
+        SubnameExpiries::<T>::remove(node);
+        SubnameLeases::<T>::remove(node);
+    }
+
+    fn on_transferred(node: DomainHash, from: &Self::AccountId, to: &Self::AccountId) {
+        T::Lifecycle::on_transferred(node, from, to);
+    }
+
+    fn for_redeem_code(
+        name: Vec<u8>,
+        to: Self::AccountId,
+        duration: Self::Moment,
+        label: Label,
+    ) -> DispatchResult {
+        Self::for_redeem_code_in(DefaultBaseNode::<T>::get(), name, to, duration, label)
+    }
+
+    fn for_redeem_code_in(
+        base_node: DomainHash,
+        name: Vec<u8>,
+        to: Self::AccountId,
+        duration: Self::Moment,
+        label: Label,
+    ) -> DispatchResult {
+        Self::ensure_enabled(Operation::Redeem)?;
+        let (name, _) = Self::parse_label(&name)?;
+
+        Self::mint_prepaid(
+            name,
+            base_node,
+            label.encode_with_node(&base_node),
+            to,
+            duration,
+        )
+    }
+
+    fn for_renew_code(
+        caller: &Self::AccountId,
+        name: Vec<u8>,
+        duration: Self::Moment,
+    ) -> DispatchResult {
+        Self::ensure_enabled(Operation::Redeem)?;
+        Self::do_renew(
+            caller,
+            DefaultBaseNode::<T>::get(),
+            name,
+            duration,
+            FeePayment::Prepaid,
+        )
+        .map(|_| ())
+    }
+
+    fn basenode() -> DomainHash {
+        DefaultBaseNode::<T>::get()
+    }
+
+    fn base_nodes() -> Vec<DomainHash> {
+        BaseNodes::<T>::iter_keys().collect()
+    }
+
+    fn label_of(name: &[u8]) -> Option<(Label, usize)> {
+        let (name, label) = Self::parse_label(name).ok()?;
+        Some((label, name.len()))
+    }
+}
+
+impl WeightInfo for () {
+    fn mint_subname(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn register(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn register_batch(_n: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn renew(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn renew_batch(_n: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn transfer() -> Weight {
+        Weight::zero()
+    }
+
+    fn transfer_batch(_n: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn add_reserved() -> Weight {
+        Weight::zero()
+    }
+
+    fn remove_reserved() -> Weight {
+        Weight::zero()
+    }
+
+    fn reserve_for() -> Weight {
+        Weight::zero()
+    }
+
+    fn clear_reserved_for() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_renew_lock() -> Weight {
+        Weight::zero()
+    }
+
+    fn commit() -> Weight {
+        Weight::zero()
+    }
+
+    fn reveal_register(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn remove_commitment() -> Weight {
+        Weight::zero()
+    }
+
+    fn register_with_config(_len: u32, _records: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn enable_auto_renew() -> Weight {
+        Weight::zero()
+    }
 
-            if let Some(info) = RegistrarInfos::<T>::get(node) {
-                let now = T::NowProvider::now();
-                ensure!(
-                    info.expire + T::GracePeriod::get() > now,
-                    Error::<T>::NotOwned
-                );
-            }
-            T::Registry::transfer(&who, &to, node)?;
-            Ok(())
-        }
-        /// Create a subdomain.
-        ///
-        /// Note: The total number of subdomains you can create is certain,
-        /// and the subdomains created by your subdomains will take up a
-        /// quota of your total subdomains.
-        ///
-        /// Ensure: The subdomain capacity is sufficient for use.
-        #[pallet::call_index(5)]
-        #[pallet::weight(T::WeightInfo::mint_subname(data.len() as u32))]
-        #[frame_support::transactional]
-        pub fn mint_subname(
-            origin: OriginFor<T>,
-            node: DomainHash,
-            data: Vec<u8>,
-            to: <T::Lookup as StaticLookup>::Source,
-        ) -> DispatchResult {
-            let caller = ensure_signed(origin)?;
-            let to = T::Lookup::lookup(to)?;
+    fn top_up() -> Weight {
+        Weight::zero()
+    }
 
-            ensure!(T::IsOpen::is_open(), Error::<T>::RegistrarClosed);
+    fn cancel_auto_renew() -> Weight {
+        Weight::zero()
+    }
 
-            let capacity = RegistrarInfos::<T>::get(node)
-                .map(|info| info.capacity)
-                .unwrap_or_else(T::DefaultCapacity::get);
-            let (label, _) = Label::new_with_len(&data).ok_or(Error::<T>::ParseLabelFailed)?;
-            let label_node = label.encode_with_node(&node);
-            T::Registry::mint_subname(&caller, node, label_node, to.clone(), capacity, |_| Ok(()))?;
-            Self::deposit_event(Event::<T>::SubnameRegistered {
-                label: data,
-                subnode: label_node,
-                owner: to,
-                node,
-            });
+    fn auto_renew() -> Weight {
+        Weight::zero()
+    }
 
-            Ok(())
-        }
+    fn warn_expiring(_n: u32, _buckets: u32) -> Weight {
+        Weight::zero()
     }
-}
 
-use crate::traits::{Label, Official, Registry};
-use frame_support::{
-    dispatch::{DispatchResult, Weight},
-    traits::{Currency, Get, Time},
-};
-use sp_runtime::{
-    traits::{CheckedAdd, Zero},
-    ArithmeticError,
-};
-use sp_std::vec::Vec;
+    fn reap_expired() -> Weight {
+        Weight::zero()
+    }
 
-pub trait WeightInfo {
-    fn mint_subname(len: u32) -> Weight;
-    fn register(len: u32) -> Weight;
-    fn renew(len: u32) -> Weight;
-    fn transfer() -> Weight;
-    fn add_reserved() -> Weight;
-    fn remove_reserved() -> Weight;
-}
+    fn slash_deposit() -> Weight {
+        Weight::zero()
+    }
 
-impl<T: Config> crate::traits::Registrar for Pallet<T> {
-    type Balance = BalanceOf<T>;
-    type AccountId = T::AccountId;
-    type Moment = T::Moment;
+    fn buy_capacity() -> Weight {
+        Weight::zero()
+    }
 
-    fn check_expires_registrable(node: DomainHash) -> sp_runtime::DispatchResult {
-        let now = T::NowProvider::now();
+    fn assign_reserved_claim() -> Weight {
+        Weight::zero()
+    }
 
-        let expire = RegistrarInfos::<T>::get(node)
-            .ok_or(Error::<T>::NotExistOrOccupied)?
-            .expire;
+    fn claim_reserved(_len: u32) -> Weight {
+        Weight::zero()
+    }
 
-        frame_support::ensure!(now > expire + T::GracePeriod::get(), Error::<T>::Occupied);
+    fn add_reservation_rule() -> Weight {
+        Weight::zero()
+    }
 
-        Ok(())
+    fn remove_reservation_rule() -> Weight {
+        Weight::zero()
     }
 
-    // fn for_auction_set_expires(
-    // 	node: DomainHash,
-    // 	deposit: Self::Balance,
-    // 	register_fee: Self::Balance,
-    // ) {
-    // RegistrarInfos::<T>::mutate(node, |info| {
-    // 	let info = info.get_or_insert(RegistrarInfoOf::<T> {
-    // 		expire: Default::default(),
-    // 		capacity: T::DefaultCapacity::get(),
-    // 		deposit: ,
-    // 		register_fee:,
-    // 	});
-    // 	info.expire = frame_system::Pallet::<T>::block_number();
+    fn set_tld_config() -> Weight {
+        Weight::zero()
+    }
 
-    // 	info.deposit = deposit;
-    // 	info.register_fee = register_fee;
-    // })
-    // }
+    fn create_tld(_len: u32) -> Weight {
+        Weight::zero()
+    }
 
-    fn check_expires_renewable(node: DomainHash) -> sp_runtime::DispatchResult {
-        let now = T::NowProvider::now();
+    fn set_phase() -> Weight {
+        Weight::zero()
+    }
 
-        let expire = RegistrarInfos::<T>::get(node)
-            .ok_or(Error::<T>::NotExistOrOccupied)?
-            .expire;
+    fn add_whitelisted() -> Weight {
+        Weight::zero()
+    }
 
-        frame_support::ensure!(
-            now < expire + T::GracePeriod::get(),
-            Error::<T>::NotRenewable
-        );
+    fn remove_whitelisted() -> Weight {
+        Weight::zero()
+    }
 
-        Ok(())
+    fn redeem_voucher() -> Weight {
+        Weight::zero()
     }
 
-    fn check_expires_useable(node: DomainHash) -> sp_runtime::DispatchResult {
-        let now = T::NowProvider::now();
+    fn transfer_and_reset() -> Weight {
+        Weight::zero()
+    }
 
-        let expire = RegistrarInfos::<T>::get(node)
-            .ok_or(Error::<T>::NotExistOrOccupied)?
-            .expire;
+    fn offer_transfer() -> Weight {
+        Weight::zero()
+    }
 
-        frame_support::ensure!(now < expire, Error::<T>::NotUseable);
+    fn accept_transfer() -> Weight {
+        Weight::zero()
+    }
 
-        Ok(())
+    fn cancel_transfer_offer() -> Weight {
+        Weight::zero()
     }
 
-    fn clear_registrar_info(
+    fn offer_sale() -> Weight {
+        Weight::zero()
+    }
+
+    fn lock() -> Weight {
+        Weight::zero()
+    }
+
+    fn unlock() -> Weight {
+        Weight::zero()
+    }
+
+    fn offer_subname_lease(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn accept_subname_lease() -> Weight {
+        Weight::zero()
+    }
+
+    fn reclaim_subname() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_subname_expiry(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn bid(_len: u32, _samples: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn settle_auction(_samples: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn claim_bid() -> Weight {
+        Weight::zero()
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn get_info(id: DomainHash) -> Option<RegistrarInfoOf<T>> {
+        RegistrarInfos::<T>::get(id)
+    }
+    pub fn all() -> Vec<(DomainHash, RegistrarInfoOf<T>)> {
+        RegistrarInfos::<T>::iter().collect::<Vec<_>>()
+    }
+    /// Labels of the top level domains created by `create_tld`.
+    pub fn tlds() -> Vec<Vec<u8>> {
+        TldLabels::<T>::iter_values()
+            .map(|label| label.into_inner())
+            .collect()
+    }
+    /// Whether `name` can be registered under `base_node` right now.
+    pub fn availability(base_node: DomainHash, name: &[u8]) -> pns_types::AvailabilityStatus {
+        use crate::traits::{Available, Registrar};
+        use pns_types::AvailabilityStatus;
+
+        let Ok((name, label)) = Self::parse_label(name) else {
+            return AvailabilityStatus::Invalid;
+        };
+        if !name.len().is_registrable() || !BaseNodes::<T>::contains_key(base_node) {
+            return AvailabilityStatus::Invalid;
+        }
+
+        let node = label.encode_with_node(&base_node);
+        if ReservedList::<T>::contains_key(node)
+            || ReservedFor::<T>::contains_key(node)
+            || Self::reserved_by_rule(&name, node)
+        {
+            return AvailabilityStatus::Reserved;
+        }
+
+        if RegistrarInfos::<T>::contains_key(node)
+            && (Self::check_expires_registrable(node).is_err()
+                || Self::in_cooldown(node, T::NowProvider::now()))
+        {
+            return AvailabilityStatus::Registered;
+        }
+
+        AvailabilityStatus::Available
+    }
+    /// [`Pallet::availability`] of each of `names`, in input order.
+    ///
+    /// Only the first [`MAX_AVAILABILITY_QUERIES`] names are checked.
+    pub fn availability_many(
+        base_node: DomainHash,
+        names: Vec<Vec<u8>>,
+    ) -> Vec<pns_types::AvailabilityStatus> {
+        names
+            .iter()
+            .take(MAX_AVAILABILITY_QUERIES)
+            .map(|name| Self::availability(base_node, name))
+            .collect()
+    }
+    /// The fee `register` charges for `name` under `base_node` for `duration`,
+    /// premium included, `None` if `name` is invalid.
+    pub fn register_fee(
+        base_node: DomainHash,
+        name: &[u8],
+        duration: T::Moment,
+    ) -> Option<BalanceOf<T>> {
+        let (name, label) = Self::parse_label(name).ok()?;
+        let node = label.encode_with_node(&base_node);
+        Self::register_cost(node, name.len(), duration, T::NowProvider::now())
+            .ok()
+            .map(|(fee, _)| fee)
+    }
+    /// The fee `renew` charges for `name` under `base_node` for `duration`,
+    /// `None` if `name` is invalid or not registered.
+    pub fn renew_fee(
+        base_node: DomainHash,
+        name: &[u8],
+        duration: T::Moment,
+    ) -> Option<BalanceOf<T>> {
+        let (name, label) = Self::parse_label(name).ok()?;
+        let info = RegistrarInfos::<T>::get(label.encode_with_node(&base_node))?;
+        Self::renew_cost(name.len(), duration, info.expire, T::NowProvider::now()).ok()
+    }
+    /// The deposit `register` charges for `name`, `None` if `name` is invalid.
+    pub fn deposit_fee(name: &[u8]) -> Option<BalanceOf<T>> {
+        let (name, _) = Self::parse_label(name).ok()?;
+        T::PriceOracle::deposit_fee(name.len())
+    }
+    /// The commitment `commit` expects for registering `name` under `base_node`
+    /// to `owner`, `secret` keeps others from guessing the name from it.
+    pub fn make_commitment(
+        base_node: DomainHash,
+        name: &[u8],
+        owner: &T::AccountId,
+        secret: &[u8; 32],
+    ) -> DomainHash {
+        DomainHash::from(sp_io::hashing::blake2_256(
+            &(owner, name, base_node, secret).encode(),
+        ))
+    }
+    pub fn stats() -> pns_types::PnsStats {
+        pns_types::PnsStats {
+            registrations: TotalRegistrations::<T>::get(),
+            renewals: TotalRenewals::<T>::get(),
+            active_domains: ActiveDomains::<T>::get(),
+        }
+    }
+    /// Whether `node` is past its grace period but still within the
+    /// re-registration cooldown of its previous owner.
+    fn in_cooldown(node: DomainHash, now: T::Moment) -> bool {
+        RegistrarInfos::<T>::get(node).map_or(false, |info| {
+            let grace_end = info.expire + Self::grace_period(node);
+            now > grace_end && now <= grace_end + T::ReRegistrationCooldown::get()
+        })
+    }
+
+    /// Removes the bid `bidder` reserved in the auction of `node`, it is still reserved.
+    fn take_auction_reserve(node: DomainHash, bidder: &T::AccountId) -> BalanceOf<T> {
+        let reserved = AuctionReserves::<T>::take(node, bidder);
+        if !reserved.is_zero() {
+            AuctionBidders::<T>::mutate_exists(node, |count| {
+                *count = count
+                    .and_then(|count| count.checked_sub(1))
+                    .filter(|count| *count > 0);
+            });
+        }
+        reserved
+    }
+
+    /// Records `amount` as the bid `bidder` reserved in the auction of `node`,
+    /// which had none, see `take_auction_reserve`.
+    fn put_auction_reserve(
         node: DomainHash,
-        owner: &Self::AccountId,
-    ) -> sp_runtime::DispatchResult {
-        let official = T::Official::get_official_account()?;
-        RegistrarInfos::<T>::mutate_exists(node, |info| -> Option<()> {
-            if let Some(info) = info {
-                T::Currency::transfer(
-                    &official,
-                    owner,
-                    info.deposit,
-                    frame_support::traits::ExistenceRequirement::AllowDeath,
-                )
-                .ok()?;
-            }
-            None
-        });
+        bidder: &T::AccountId,
+        amount: BalanceOf<T>,
+    ) -> DispatchResult {
+        AuctionBidders::<T>::try_mutate(node, |count| -> DispatchResult {
+            frame_support::ensure!(
+                *count < T::MaxAuctionBidders::get(),
+                Error::<T>::TooManyBidders
+            );
+            *count += 1;
+            Ok(())
+        })?;
+        AuctionReserves::<T>::insert(node, bidder, amount);
         Ok(())
     }
 
-    fn for_redeem_code(
+    /// How the `AuctionEndingPeriod` of the auctions is sampled.
+    pub(crate) fn candle() -> crate::auction::Candle<T::Moment> {
+        crate::auction::Candle {
+            ending_period: T::AuctionEndingPeriod::get(),
+            sample_length: T::AuctionSampleLength::get(),
+        }
+    }
+
+    /// Registers `name` as `label_node` under `base_node` to `to` for `duration`
+    /// without a deposit, its price was settled elsewhere (redeem codes, auctions).
+    pub(crate) fn mint_prepaid(
         name: Vec<u8>,
-        to: Self::AccountId,
-        duration: Self::Moment,
-        label: Label,
+        base_node: DomainHash,
+        label_node: DomainHash,
+        to: T::AccountId,
+        duration: T::Moment,
     ) -> DispatchResult {
         let official = T::Official::get_official_account()?;
+        let tld = BaseNodes::<T>::get(base_node).ok_or(Error::<T>::UnknownBaseNode)?;
         let now = T::NowProvider::now();
         let expire = now
             .checked_add(&duration)
             .ok_or(ArithmeticError::Overflow)?;
         // 防止计算结果溢出
         frame_support::ensure!(
-            expire + T::GracePeriod::get() > now + T::GracePeriod::get(),
+            expire + tld.grace_period > now + tld.grace_period,
             ArithmeticError::Overflow
         );
-        let base_node = T::BaseNode::get();
-        let label_node = label.encode_with_node(&base_node);
 
         T::Registry::mint_subname(
             &official,
@@ -605,7 +4091,9 @@ impl<T: Config> crate::traits::Registrar for Pallet<T> {
                                 info.deposit,
                                 frame_support::traits::ExistenceRequirement::KeepAlive,
                             )?;
+                            T::Lifecycle::on_expired(label_node, pre_owner);
                         }
+                        Self::reindex_expiry(label_node, Some(info.expire), Some(expire));
                         info.deposit = Zero::zero();
                         info.register_fee = Zero::zero();
                         info.expire = expire;
@@ -614,16 +4102,26 @@ impl<T: Config> crate::traits::Registrar for Pallet<T> {
                             deposit: Zero::zero(),
                             register_fee: Zero::zero(),
                             expire,
-                            capacity: T::DefaultCapacity::get(),
+                            capacity: tld.capacity,
                         });
+                        ActiveDomains::<T>::mutate(|count| *count = count.saturating_add(1));
+                        Self::reindex_expiry(label_node, None, Some(expire));
                     }
                     Ok(())
                 })?;
                 Ok(())
             },
         )?;
+        BaseNodeOf::<T>::insert(label_node, base_node);
+        T::Registry::set_resolver(label_node, T::DefaultResolver::get());
+        // the lock was a choice of the previous owner.
+        RenewLocked::<T>::remove(label_node);
+        TotalRegistrations::<T>::mutate(|count| *count = count.saturating_add(1));
+        T::PriceOracle::note_registration();
+        T::Lifecycle::on_registered(label_node, &to, expire);
         Self::deposit_event(Event::<T>::NameRegistered {
             name,
+            base_node,
             node: label_node,
             owner: to,
             expire,
@@ -632,42 +4130,122 @@ impl<T: Config> crate::traits::Registrar for Pallet<T> {
         Ok(())
     }
 
-    fn basenode() -> DomainHash {
-        T::BaseNode::get()
+    /// Paying `amount` with `KeepAlive` fails when it would leave `who` below
+    /// the existential deposit, report that case separately from not having
+    /// enough balance at all.
+    fn ensure_not_dusted(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+        let free = T::Currency::free_balance(who);
+        if let Some(left) = free.checked_sub(&amount) {
+            frame_support::ensure!(
+                left >= T::Currency::minimum_balance(),
+                Error::<T>::WouldBeDusted
+            );
+        }
+        Ok(())
     }
 }
 
-impl WeightInfo for () {
-    fn mint_subname(_len: u32) -> Weight {
-        Weight::zero()
-    }
+/// Limits the number of names one account can register per block, a
+/// `register_batch` counts as many names as it has.
+///
+/// Excess registrations are rejected while validating the transaction,
+/// so they never take up block space.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckRegistrationLimit<T: Config + Send + Sync>(PhantomData<T>);
 
-    fn register(_len: u32) -> Weight {
-        Weight::zero()
+impl<T: Config + Send + Sync> CheckRegistrationLimit<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
     }
 
-    fn renew(_len: u32) -> Weight {
-        Weight::zero()
+    /// How many names `call` registers.
+    fn registrations(call: &T::RuntimeCall) -> u32
+    where
+        T::RuntimeCall: IsSubType<Call<T>>,
+    {
+        match call.is_sub_type() {
+            Some(Call::register_batch { names, .. }) => names.len() as u32,
+            Some(Call::register { .. })
+            | Some(Call::reveal_register { .. })
+            | Some(Call::register_with_config { .. })
+            | Some(Call::claim_reserved { .. }) => 1,
+            _ => 0,
+        }
     }
 
-    fn transfer() -> Weight {
-        Weight::zero()
+    /// Registrations `who` has already made in the current block.
+    fn registrations_in_block(who: &T::AccountId) -> u32 {
+        let (block, count) = RegistrationsInBlock::<T>::get(who);
+        if block == frame_system::Pallet::<T>::block_number() {
+            count
+        } else {
+            0
+        }
     }
+}
 
-    fn add_reserved() -> Weight {
-        Weight::zero()
+impl<T: Config + Send + Sync> Default for CheckRegistrationLimit<T> {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn remove_reserved() -> Weight {
-        Weight::zero()
+impl<T: Config + Send + Sync> core::fmt::Debug for CheckRegistrationLimit<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "CheckRegistrationLimit")
     }
 }
 
-impl<T: Config> Pallet<T> {
-    pub fn get_info(id: DomainHash) -> Option<RegistrarInfoOf<T>> {
-        RegistrarInfos::<T>::get(id)
+impl<T: Config + Send + Sync> SignedExtension for CheckRegistrationLimit<T>
+where
+    T::RuntimeCall: IsSubType<Call<T>>,
+{
+    const IDENTIFIER: &'static str = "CheckRegistrationLimit";
+    type AccountId = T::AccountId;
+    type Call = T::RuntimeCall;
+    type AdditionalSigned = ();
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
     }
-    pub fn all() -> Vec<(DomainHash, RegistrarInfoOf<T>)> {
-        RegistrarInfos::<T>::iter().collect::<Vec<_>>()
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> TransactionValidity {
+        let registrations = Self::registrations(call);
+        if registrations > 0
+            && Self::registrations_in_block(who).saturating_add(registrations)
+                > T::MaxRegistrationsPerBlock::get()
+        {
+            return Err(InvalidTransaction::ExhaustsResources.into());
+        }
+        Ok(ValidTransaction::default())
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> Result<Self::Pre, TransactionValidityError> {
+        let registrations = Self::registrations(call);
+        if registrations > 0 {
+            let count = Self::registrations_in_block(who).saturating_add(registrations);
+            if count > T::MaxRegistrationsPerBlock::get() {
+                return Err(InvalidTransaction::ExhaustsResources.into());
+            }
+            RegistrationsInBlock::<T>::insert(
+                who,
+                (frame_system::Pallet::<T>::block_number(), count),
+            );
+        }
+        Ok(())
     }
 }
@@ -3,6 +3,9 @@
 //! This module is a high-level abstraction of the NFT module,
 //! and provides `PnsOfficial` storage.
 //!
+//! The domains are tokens of `Config::Nft`, usually the bundled [`crate::nft`],
+//! see [`crate::nft_adapter`] for runtimes which already run a standard nft pallet.
+//!
 //!
 //! ## Introduction
 //!
@@ -15,28 +18,111 @@
 //! - `approval_for_all` - share the permissions of all your domains to other accounts
 //! - `set_resolver` - set the resolver address of a domain name, which requires permission to operate that domain
 //! - `burn` - destroy a domain, return it to the owner if there is a deposit, requires the domain's operational privileges
+//! - `burn_recursive` - same as `burn`, but burns its subdomains first, a bounded number per call
 //! - `set_official` - Set official account, needs manager privileges
 //! - `approve` - share the permission of a domain to another account, requires the permission of the domain
-
+//! - `transfer_all` - transfer the domains you own to another account, a bounded number per call
+//! - `set_primary_name` - choose the domain your account is displayed with (reverse resolution), only a domain you own
+//! - `burn_fuses` - irreversibly gives up permissions of a domain, see [`fuses`], only its owner.
+//!   Burning `CANNOT_TRANSFER` makes a domain soulbound, it stays with its owner until it is burned
+//! - `burn_child_fuses` - irreversibly gives up permissions of a subdomain, including the control of its parent, requires the permission of the parent
+//! - `set_controller` - lets an account edit the records of a domain and create its subdomains, but not transfer or burn it, requires the permission of the domain
+//! - `set_ttl` - set how long resolvers may cache the records of a domain, requires the permission of the domain or being its controller
+//! - `clear_records` - invalidates all resolver records of a domain at once, same requirements as `set_ttl`
+//! - `set_token_metadata` - set the metadata of the token of a domain, e.g. for marketplaces, same requirements as `set_ttl`
+//! - `set_base_uri` - set the uri prefix of every token uri, see [`Pallet::token_uri`], needs manager privileges
+//! - `set_token_uri_suffix` - set the part of the token uri of a domain after the base uri, same requirements as `set_ttl`
+//! - `set_royalty` - set the cut of the sale price of a subdomain its beneficiary gets when it is sold, requires the permission of the parent
+//! - `set_default_royalty` - set the royalty of the domains without one, needs manager privileges
+//! - `freeze` - blocks transfers, burns, subdomains and resolver changes of a disputed domain, needs manager privileges
+//! - `thaw` - lifts the freeze of a domain, needs manager privileges
+//! - `emergency_freeze` - freezes a domain until governance confirms it, needs the governance origin
+//! - `appeal_freeze` - disputes an emergency freeze by reserving `AppealBond` and opening a case of the `Arbitration`, only the owner of the domain within the appeal period
+//! - `confirm_freeze` - upholds an emergency freeze (slashing the bond of an appeal) or lifts it once its appeal is ruled on, needs the governance origin
+//! - `release_freeze` - lifts an emergency freeze governance did not confirm in time, anyone can call it
+
+use codec::{Decode, Encode, MaxEncodedLen};
 pub use pallet::*;
-use sp_runtime::DispatchError;
+use scale_info::TypeInfo;
+use sp_runtime::{DispatchError, RuntimeDebug};
 use sp_std::vec::Vec;
 
+/// Upper bound of subnodes returned by one [`Pallet::subnodes_of`] call.
+pub const MAX_SUBNODES_PAGE: usize = 64;
+
+/// Basis points of the whole sale price, the upper bound of [`Royalty::basis_points`].
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+/// The cut of the sale price of a domain `beneficiary` gets, see `set_royalty`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Royalty<AccountId> {
+    pub beneficiary: AccountId,
+    /// Hundredths of a percent of the price, at most [`MAX_BASIS_POINTS`].
+    pub basis_points: u16,
+}
+
+pub type RoyaltyOf<T> = Royalty<<T as frame_system::Config>::AccountId>;
+
+/// A freeze of a domain by the `GovernanceOrigin`, lifted at `expires_at`
+/// unless it is confirmed, see `emergency_freeze`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct EmergencyFreeze<AccountId, Balance, BlockNumber> {
+    /// Whether the resolvers keep serving the records of the domain.
+    pub serve_records: bool,
+    /// The owner can appeal the freeze until this block.
+    pub appeal_until: BlockNumber,
+    /// The freeze is lifted at this block if governance did not confirm it.
+    pub expires_at: BlockNumber,
+    /// The owner who appealed and the bond reserved for it.
+    pub appeal: Option<(AccountId, Balance)>,
+}
+
+pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<
+    <T as frame_system::Config>::AccountId,
+>>::Balance;
+
+pub type EmergencyFreezeOf<T> = EmergencyFreeze<
+    <T as frame_system::Config>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Config>::BlockNumber,
+>;
+
+/// The class the domains are minted in, see [`crate::traits::NftBackend`].
+pub type ClassIdOf<T> = <<T as Config>::Nft as crate::traits::NftBackend<
+    <T as frame_system::Config>::AccountId,
+>>::ClassId;
+
+/// Permissions of a domain which can be burned, burned fuses stay so until
+/// the domain is burned or registered again.
+pub mod fuses {
+    /// The domain can't be transferred, also not by operators or through
+    /// transfer offers, which binds it to its owner (soulbound).
+    pub const CANNOT_TRANSFER: u32 = 1 << 0;
+    /// The resolver of the domain can't be changed.
+    pub const CANNOT_SET_RESOLVER: u32 = 1 << 1;
+    /// No subdomains can be created under the domain.
+    pub const CANNOT_CREATE_SUBDOMAIN: u32 = 1 << 2;
+    /// The parent of the subdomain can't reclaim it or change its expiry,
+    /// only the parent can burn it.
+    pub const PARENT_CANNOT_CONTROL: u32 = 1 << 3;
+
+    pub const ALL: u32 =
+        CANNOT_TRANSFER | CANNOT_SET_RESOLVER | CANNOT_CREATE_SUBDOMAIN | PARENT_CANNOT_CONTROL;
+}
+
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
-    use crate::{nft, traits::Registrar};
+    use crate::traits::{Arbitration, AuditLog, NftBackend, Registrar};
     use frame_support::pallet_prelude::*;
-    use frame_support::traits::EnsureOrigin;
+    use frame_support::traits::{EnsureOrigin, ReservableCurrency};
     use frame_system::{ensure_signed, pallet_prelude::*};
-    use pns_types::{DomainHash, DomainTracing, Record};
-    use sp_runtime::traits::{StaticLookup, Zero};
+    use pns_types::{DomainHash, DomainTracing};
+    use sp_runtime::traits::StaticLookup;
+    use sp_std::collections::btree_map::BTreeMap;
 
     #[pallet::config]
-    pub trait Config:
-        frame_system::Config
-        + crate::nft::Config<ClassData = (), TokenData = Record, TokenId = DomainHash>
-    {
+    pub trait Config: frame_system::Config {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
         type WeightInfo: WeightInfo;
@@ -46,6 +132,41 @@ pub mod pallet {
         type ResolverId: Parameter + Default + MaxEncodedLen;
 
         type ManagerOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+        /// Records freezes, thaws and the official, uri and royalty changes.
+        type AuditLog: AuditLog<Self::AccountId>;
+
+        /// The nft pallet the domains are minted in.
+        type Nft: NftBackend<Self::AccountId>;
+
+        /// The maximum length of the base uri and of the token uri suffixes.
+        type MaxUriLength: Get<u32>;
+
+        /// Freezes domains in emergencies, see `emergency_freeze`.
+        type GovernanceOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+        /// Reserves the bonds of the appeals.
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// How long the owner can appeal an emergency freeze.
+        #[pallet::constant]
+        type AppealPeriod: Get<Self::BlockNumber>;
+
+        /// How long an emergency freeze lasts unless governance confirms it.
+        #[pallet::constant]
+        type FreezeConfirmPeriod: Get<Self::BlockNumber>;
+
+        /// Reserved from the owner appealing an emergency freeze, slashed if it is upheld.
+        #[pallet::constant]
+        type AppealBond: Get<BalanceOf<Self>>;
+
+        /// Each appeal of an emergency freeze becomes a case of it, which has to be
+        /// ruled on before governance can confirm the freeze.
+        type Arbitration: Arbitration<Self::AccountId>;
+
+        /// The most domains a single `transfer_all` moves, whatever its `limit`.
+        #[pallet::constant]
+        type MaxTransferAll: Get<u32>;
     }
 
     #[pallet::pallet]
@@ -67,11 +188,82 @@ pub mod pallet {
     pub type OperatorApprovals<T: Config> =
         StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, (), ValueQuery>;
 
-    /// (`node`,`account`) `node` -> `account`
+    /// (`node`,`account`) `node` -> `account`, cleared when `node` is transferred or burned
     #[pallet::storage]
     pub type TokenApprovals<T: Config> =
         StorageDoubleMap<_, Twox64Concat, DomainHash, Twox64Concat, T::AccountId, (), ValueQuery>;
 
+    /// `name_hash` -> the account which may edit its records and create its subdomains
+    #[pallet::storage]
+    pub type Controllers<T: Config> = StorageMap<_, Twox64Concat, DomainHash, T::AccountId>;
+
+    /// `name_hash` -> its burned [`fuses`]
+    #[pallet::storage]
+    pub type Fuses<T: Config> = StorageMap<_, Twox64Concat, DomainHash, u32, ValueQuery>;
+
+    /// `name_hash` -> number of subdomains counted against its capacity, see `mint_subname`
+    #[pallet::storage]
+    pub type Children<T: Config> = StorageMap<_, Twox64Concat, DomainHash, u32, ValueQuery>;
+
+    /// `name_hash` -> ttl in seconds its records are served with
+    #[pallet::storage]
+    pub type Ttl<T: Config> = StorageMap<_, Twox64Concat, DomainHash, u32>;
+
+    /// `name_hash` -> version of its resolver records, bumping it invalidates all of them
+    ///
+    /// Never reset, also not when the domain is burned, so old records can't come back.
+    #[pallet::storage]
+    pub type RecordVersions<T: Config> = StorageMap<_, Twox64Concat, DomainHash, u32, ValueQuery>;
+
+    /// (`account`,`name_hash`) every domain `account` owns
+    #[pallet::storage]
+    pub type OwnedNodes<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, DomainHash, ()>;
+
+    /// `account` -> number of domains it owns in [`OwnedNodes`]
+    #[pallet::storage]
+    pub type OwnedTokenCount<T: Config> =
+        StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+    /// (`parent`,`child`) the direct children of a node, their number is bounded by its capacity
+    #[pallet::storage]
+    pub type Subnodes<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, DomainHash, Twox64Concat, DomainHash, ()>;
+
+    /// `child` -> `parent` it is listed under in [`Subnodes`]
+    #[pallet::storage]
+    pub type ParentOf<T: Config> = StorageMap<_, Twox64Concat, DomainHash, DomainHash>;
+
+    /// `name_hash` -> () the domains frozen by the manager
+    #[pallet::storage]
+    pub type FrozenNodes<T: Config> = StorageMap<_, Twox64Concat, DomainHash, ()>;
+
+    /// `name_hash` -> the emergency freeze of a frozen domain, until it is confirmed or lifted
+    #[pallet::storage]
+    pub type EmergencyFreezes<T: Config> =
+        StorageMap<_, Twox64Concat, DomainHash, EmergencyFreezeOf<T>>;
+
+    /// The prefix of every token uri, e.g. `https://meta.pns.link/`
+    #[pallet::storage]
+    pub type BaseUri<T: Config> = StorageValue<_, BoundedVec<u8, T::MaxUriLength>, ValueQuery>;
+
+    /// `name_hash` -> the part of its token uri after [`BaseUri`], its hex form if there is none
+    #[pallet::storage]
+    pub type TokenUriSuffixes<T: Config> =
+        StorageMap<_, Twox64Concat, DomainHash, BoundedVec<u8, T::MaxUriLength>>;
+
+    /// The royalty of the domains which have none in [`Royalties`]
+    #[pallet::storage]
+    pub type DefaultRoyalty<T: Config> = StorageValue<_, RoyaltyOf<T>>;
+
+    /// `name_hash` -> its royalty, set by its parent
+    #[pallet::storage]
+    pub type Royalties<T: Config> = StorageMap<_, Twox64Concat, DomainHash, RoyaltyOf<T>>;
+
+    /// `account` -> `name_hash` it is displayed with, ignored once the account does not own it
+    #[pallet::storage]
+    pub type PrimaryNames<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, DomainHash>;
+
     #[pallet::genesis_config]
     pub struct GenesisConfig<T: Config> {
         pub origin: Vec<(DomainHash, DomainTracing)>,
@@ -124,28 +316,118 @@ pub mod pallet {
             operator: T::AccountId,
             approved: bool,
         },
-        /// Logged when a node is traded.
+        /// Logged when `owner` approves `approved` to operate `node`.
+        TokenApproved {
+            node: DomainHash,
+            owner: T::AccountId,
+            approved: T::AccountId,
+        },
+        /// Logged when the approval of `approved` to operate `node` is revoked.
+        TokenApprovalRevoked {
+            node: DomainHash,
+            owner: T::AccountId,
+            approved: T::AccountId,
+        },
+        /// Logged when every approval and the controller of a node are removed at once.
+        ///
+        /// Transfers and burns also drop the approvals, without this event.
+        TokenApprovalsCleared { node: DomainHash },
+        /// Logged when the manager changes the official account, which gets the base nodes.
+        OfficialChanged {
+            old: Option<T::AccountId>,
+            new: T::AccountId,
+        },
+        /// Logged when a node is traded, its token approvals are dropped.
         Transferred {
             from: T::AccountId,
             to: T::AccountId,
-            class_id: T::ClassId,
-            token_id: T::TokenId,
+            class_id: ClassIdOf<T>,
+            token_id: DomainHash,
         },
         /// Logged when a node is minted.
         TokenMinted {
-            class_id: T::ClassId,
-            token_id: T::TokenId,
+            class_id: ClassIdOf<T>,
+            token_id: DomainHash,
             node: DomainHash,
             owner: T::AccountId,
         },
         /// Logged when a node is burned.
         TokenBurned {
-            class_id: T::ClassId,
-            token_id: T::TokenId,
+            class_id: ClassIdOf<T>,
+            token_id: DomainHash,
             node: DomainHash,
             owner: T::AccountId,
             caller: T::AccountId,
         },
+        /// Logged when a `burn_recursive` call is done, `burned` counts the subdomains.
+        ///
+        /// `node` itself is only burned once it has no subdomains left, call again otherwise.
+        SubtreeBurned {
+            node: DomainHash,
+            burned: u32,
+            node_burned: bool,
+        },
+        /// Logged when a `transfer_all` batch is done.
+        ///
+        /// `next` is the cursor to continue with, `None` if nothing is left.
+        BatchTransferred {
+            from: T::AccountId,
+            to: T::AccountId,
+            transferred: u32,
+            skipped: u32,
+            next: Option<DomainHash>,
+        },
+        /// Logged when the controller of a node changes, `None` if it was removed.
+        ControllerChanged {
+            node: DomainHash,
+            controller: Option<T::AccountId>,
+        },
+        /// Logged when the ttl of a node changes, `None` if it was removed.
+        TtlChanged { node: DomainHash, ttl: Option<u32> },
+        /// Logged when all records of a node are invalidated, `version` is the new record version.
+        RecordsCleared { node: DomainHash, version: u32 },
+        /// Logged when the token metadata of a node changes.
+        TokenMetadataSet { node: DomainHash },
+        /// Logged when the manager changes the base uri of the token uris.
+        BaseUriChanged { uri: Vec<u8> },
+        /// Logged when the token uri suffix of a node changes, `None` if it was removed.
+        TokenUriSuffixSet {
+            node: DomainHash,
+            suffix: Option<Vec<u8>>,
+        },
+        /// Logged when the royalty of a node changes, `None` if it was removed.
+        RoyaltySet {
+            node: DomainHash,
+            royalty: Option<RoyaltyOf<T>>,
+        },
+        /// Logged when the manager changes the default royalty, `None` if it was removed.
+        DefaultRoyaltySet { royalty: Option<RoyaltyOf<T>> },
+        /// Logged when the manager freezes a node.
+        Frozen { node: DomainHash },
+        /// Logged when the manager lifts the freeze of a node.
+        Thawed { node: DomainHash },
+        /// Logged when governance freezes a node until `expires_at` unless it confirms it.
+        EmergencyFrozen {
+            node: DomainHash,
+            serve_records: bool,
+            appeal_until: T::BlockNumber,
+            expires_at: T::BlockNumber,
+        },
+        /// Logged when the owner appeals an emergency freeze.
+        FreezeAppealed {
+            node: DomainHash,
+            owner: T::AccountId,
+            bond: BalanceOf<T>,
+        },
+        /// Logged when governance upholds an emergency freeze, it stays until the node is thawed.
+        FreezeConfirmed { node: DomainHash },
+        /// Logged when fuses of a node are burned, `fuses` are all of its burned fuses.
+        FusesBurned { node: DomainHash, fuses: u32 },
+        /// Logged when an account chooses its primary name, `None` if it was cleared.
+        PrimaryNameSet {
+            who: T::AccountId,
+            node: Option<DomainHash>,
+        },
     }
 
     #[pallet::error]
@@ -169,15 +451,174 @@ pub mod pallet {
         ApprovalFailure,
         /// Pns official account is not initialized, please feedback to the official.
         OfficialNotInitiated,
+        /// The base node has been minted already.
+        BaseNodeExists,
+        /// Only the owner of the domain can do this.
+        NotOwned,
+        /// A fuse of the domain forbids this.
+        FuseBurned,
+        /// Not a fuse of [`fuses`].
+        UnknownFuse,
+        /// `PARENT_CANNOT_CONTROL` can only be burned by the parent, see `burn_child_fuses`.
+        ParentFuse,
+        /// The domain is frozen by the manager.
+        Frozen,
+        /// The domain has no emergency freeze.
+        NotEmergencyFrozen,
+        /// The appeal period of the emergency freeze is over.
+        AppealClosed,
+        /// The emergency freeze was appealed already.
+        AlreadyAppealed,
+        /// The emergency freeze is lifted already, see `release_freeze`.
+        FreezeExpired,
+        /// The emergency freeze is not over yet.
+        FreezeNotExpired,
+        /// The appeal of the emergency freeze was not ruled on yet.
+        AppealPending,
+        /// The uri is longer than `MaxUriLength`.
+        UriTooLong,
+        /// A royalty can't be more than `MAX_BASIS_POINTS`.
+        RoyaltyTooHigh,
     }
 
     // helper
     impl<T: Config> Pallet<T> {
+        /// The primary name of `who`, `None` if it has none or does not own it anymore.
+        pub fn primary_name(who: &T::AccountId) -> Option<DomainHash> {
+            PrimaryNames::<T>::get(who).filter(|node| {
+                T::Nft::owner(*node).as_ref() == Some(who)
+                    && T::Registrar::check_expires_useable(*node).is_ok()
+            })
+        }
+        /// Whether `who` owns `node` or is its controller, both may edit its records.
+        pub fn controls(who: &T::AccountId, node: DomainHash) -> bool {
+            Controllers::<T>::get(node).as_ref() == Some(who)
+                || T::Nft::owner(node).as_ref() == Some(who)
+        }
+        /// Ttl in seconds the records of `node` should be served with, `None` if it has none.
+        pub fn ttl(node: DomainHash) -> Option<u32> {
+            Ttl::<T>::get(node)
+        }
+        /// Every domain `owner` owns, in no particular order.
+        pub fn domains_of(owner: &T::AccountId) -> Vec<DomainHash> {
+            OwnedNodes::<T>::iter_key_prefix(owner).collect()
+        }
+        /// How many domains `owner` owns, without iterating over them.
+        pub fn balance_of(owner: &T::AccountId) -> u32 {
+            OwnedTokenCount::<T>::get(owner)
+        }
+        /// Up to [`MAX_SUBNODES_PAGE`] direct children of `node`, after `start_after` if given.
+        ///
+        /// Also returns the cursor to continue with, `None` if nothing is left.
+        pub fn subnodes_of(
+            node: DomainHash,
+            start_after: Option<DomainHash>,
+        ) -> (Vec<DomainHash>, Option<DomainHash>) {
+            let subnodes = match start_after {
+                Some(subnode) => {
+                    let raw_key = Subnodes::<T>::hashed_key_for(node, subnode);
+                    Subnodes::<T>::iter_key_prefix_from(node, raw_key)
+                        .take(MAX_SUBNODES_PAGE)
+                        .collect::<Vec<_>>()
+                }
+                None => Subnodes::<T>::iter_key_prefix(node)
+                    .take(MAX_SUBNODES_PAGE)
+                    .collect::<Vec<_>>(),
+            };
+
+            let next = if subnodes.len() == MAX_SUBNODES_PAGE {
+                subnodes.last().copied()
+            } else {
+                None
+            };
+            (subnodes, next)
+        }
+        /// Version of the resolver records of `node`, see `clear_records`.
+        pub fn record_version(node: DomainHash) -> u32 {
+            RecordVersions::<T>::get(node)
+        }
+        /// The royalty owed when `node` is sold, its own or the default one.
+        pub fn royalty_of(node: DomainHash) -> Option<RoyaltyOf<T>> {
+            Royalties::<T>::get(node).or_else(DefaultRoyalty::<T>::get)
+        }
+        /// Whether `node` can't be transferred anymore, see [`fuses::CANNOT_TRANSFER`].
+        pub fn is_soulbound(node: DomainHash) -> bool {
+            Fuses::<T>::get(node) & fuses::CANNOT_TRANSFER != 0
+        }
+        /// The uri marketplaces fetch the name and image of a token from,
+        /// [`BaseUri`] followed by the suffix of the token or its `0x` prefixed hex form.
+        ///
+        /// `None` if `token` does not exist or is not of the PNS class.
+        pub fn token_uri(class_id: ClassIdOf<T>, token: DomainHash) -> Option<Vec<u8>> {
+            const HEX: &[u8; 16] = b"0123456789abcdef";
+
+            if class_id != T::Nft::class_id() || T::Nft::owner(token).is_none() {
+                return None;
+            }
+
+            let mut uri = BaseUri::<T>::get().into_inner();
+            match TokenUriSuffixes::<T>::get(token) {
+                Some(suffix) => uri.extend(suffix),
+                None => {
+                    uri.extend_from_slice(b"0x");
+                    uri.extend(
+                        token.as_bytes().iter().flat_map(|byte| {
+                            [HEX[(byte >> 4) as usize], HEX[(byte & 0x0f) as usize]]
+                        }),
+                    );
+                }
+            }
+            Some(uri)
+        }
+        /// Fails if `node` is frozen by the manager.
+        pub fn check_frozen(node: DomainHash) -> DispatchResult {
+            ensure!(!Self::is_frozen(node), Error::<T>::Frozen);
+            Ok(())
+        }
+        /// Whether `node` is frozen, an emergency freeze only until it expires.
+        pub fn is_frozen(node: DomainHash) -> bool {
+            FrozenNodes::<T>::contains_key(node)
+                && EmergencyFreezes::<T>::get(node).map_or(true, |freeze| {
+                    frame_system::Pallet::<T>::block_number() < freeze.expires_at
+                })
+        }
+        /// Whether the resolvers serve the records of `node`, not during an
+        /// emergency freeze which asked them to stop.
+        pub fn records_served(node: DomainHash) -> bool {
+            !Self::is_frozen(node)
+                || EmergencyFreezes::<T>::get(node).map_or(true, |freeze| freeze.serve_records)
+        }
+        /// Lifts the freeze of `node` and returns the bond of its appeal, if any.
+        pub(crate) fn lift_freeze(node: DomainHash) {
+            FrozenNodes::<T>::remove(node);
+            T::Arbitration::drop_appeal(node);
+            if let Some((owner, bond)) =
+                EmergencyFreezes::<T>::take(node).and_then(|freeze| freeze.appeal)
+            {
+                T::Currency::unreserve(&owner, bond);
+            }
+
+            Self::deposit_event(Event::<T>::Thawed { node });
+        }
+        /// Fails if any of `fuses` of `node` is burned.
+        pub fn check_fuses(node: DomainHash, fuses: u32) -> DispatchResult {
+            ensure!(Fuses::<T>::get(node) & fuses == 0, Error::<T>::FuseBurned);
+            Ok(())
+        }
+        fn do_burn_fuses(node: DomainHash, fuses: u32) -> DispatchResult {
+            ensure!(fuses & !fuses::ALL == 0, Error::<T>::UnknownFuse);
+
+            let fuses = Fuses::<T>::mutate(node, |burned| {
+                *burned |= fuses;
+                *burned
+            });
+
+            Self::deposit_event(Event::<T>::FusesBurned { node, fuses });
+            Ok(())
+        }
         #[inline]
         pub fn verify(caller: &T::AccountId, node: DomainHash) -> DispatchResult {
-            let owner = &nft::Pallet::<T>::tokens(T::ClassId::zero(), node)
-                .ok_or(Error::<T>::NotExist)?
-                .owner;
+            let owner = &T::Nft::owner(node).ok_or(Error::<T>::NotExist)?;
 
             Self::verify_with_owner(caller, node, owner)?;
 
@@ -201,32 +642,61 @@ pub mod pallet {
         }
     }
     impl<T: Config> Pallet<T> {
-        pub(crate) fn do_burn(caller: T::AccountId, token: T::TokenId) -> DispatchResult {
-            let class_id = T::ClassId::zero();
-            let Some(token_info) = nft::Pallet::<T>::tokens(class_id, token) else {
+        pub(crate) fn do_burn(caller: T::AccountId, token: DomainHash) -> DispatchResult {
+            Self::burn_token(caller, token, true)
+        }
+
+        /// Burns `token`, the permission of `caller` is only checked if `check_permission`.
+        pub(crate) fn burn_token(
+            caller: T::AccountId,
+            token: DomainHash,
+            check_permission: bool,
+        ) -> DispatchResult {
+            let Some(token_owner) = T::Nft::owner(token) else {
                 return Err(Error::<T>::NotExist.into())
             };
 
-            let token_owner = token_info.owner;
-            ensure!(token_info.data.children == 0, Error::<T>::SubnodeNotClear);
+            ensure!(Children::<T>::get(token) == 0, Error::<T>::SubnodeNotClear);
 
-            Self::verify_with_owner(&caller, token, &token_owner)?;
+            if check_permission {
+                Self::verify_with_owner(&caller, token, &token_owner)?;
+            }
+            Self::check_frozen(token)?;
+            Fuses::<T>::remove(token);
+            Controllers::<T>::remove(token);
+            Ttl::<T>::remove(token);
+            TokenUriSuffixes::<T>::remove(token);
+            Royalties::<T>::remove(token);
 
             let Some(origin) = RuntimeOrigin::<T>::get(token) else {
                     return Err(Error::<T>::BanBurnBaseNode.into())
             };
 
+            let parent = ParentOf::<T>::take(token);
+            if let Some(parent) = parent {
+                Subnodes::<T>::remove(parent, token);
+            }
+
             match origin {
-                DomainTracing::RuntimeOrigin(origin) => Self::sub_children(origin, class_id)?,
+                DomainTracing::RuntimeOrigin(origin) => {
+                    Self::sub_children(origin)?;
+                    // a nested subdomain is also counted by its parent, see `mint_subname`.
+                    if let Some(parent) = parent.filter(|parent| *parent != origin) {
+                        Self::sub_children(parent)?;
+                    }
+                    T::Registrar::clear_subname_info(token);
+                }
                 DomainTracing::Root => {
                     T::Registrar::clear_registrar_info(token, &token_owner)?;
                 }
             }
 
-            nft::Pallet::<T>::burn(&token_owner, (class_id, token))?;
+            T::Nft::burn(&token_owner, token)?;
+            Self::unindex_owned(&token_owner, token);
+            let _ = TokenApprovals::<T>::clear_prefix(token, u32::MAX, None);
 
             Self::deposit_event(Event::<T>::TokenBurned {
-                class_id,
+                class_id: T::Nft::class_id(),
                 token_id: token,
                 node: token,
                 owner: token_owner,
@@ -249,39 +719,47 @@ pub mod pallet {
             // `[maybe_pre_owner]`
             do_payments: impl FnOnce(Option<&T::AccountId>) -> DispatchResult,
         ) -> DispatchResult {
-            let class_id = T::ClassId::zero();
             // dot: hash 0xce159cf34380757d1932a8e4a74e85e85957b0a7a52d9c566c0a3c8d6133d0f7
             // [206, 21, 156, 243, 67, 128, 117, 125, 25, 50, 168, 228, 167, 78, 133, 232, 89, 87,
             // 176, 167, 165, 45, 156, 86, 108, 10, 60, 141, 97, 51, 208, 247]
-            let Some(node_info) = nft::Pallet::<T>::tokens(class_id, node) else {
+            let Some(node_owner) = T::Nft::owner(node) else {
                 return Err(Error::<T>::NotExist.into());
             };
 
-            let node_owner = node_info.owner;
-
-            Self::verify_with_owner(owner, node, &node_owner)?;
+            if Controllers::<T>::get(node).as_ref() != Some(owner) {
+                Self::verify_with_owner(owner, node, &node_owner)?;
+            }
+            Self::check_fuses(node, fuses::CANNOT_CREATE_SUBDOMAIN)?;
+            Self::check_frozen(node)?;
 
-            if let Some(info) = nft::Tokens::<T>::get(class_id, label_node) {
+            if let Some(from) = T::Nft::owner(label_node) {
                 T::Registrar::check_expires_registrable(label_node)?;
-
-                let from = info.owner;
+                Self::check_frozen(label_node)?;
+                // the fuses were a choice of the previous owner.
+                Fuses::<T>::remove(label_node);
 
                 do_payments(Some(&from))?;
 
-                nft::Pallet::<T>::transfer(&from, &to, (class_id, label_node))?;
+                T::Nft::transfer(&from, &to, label_node)?;
+                Self::unindex_owned(&from, label_node);
+                Self::index_owned(&to, label_node);
+                let _ = TokenApprovals::<T>::clear_prefix(label_node, u32::MAX, None);
             } else {
                 do_payments(None)?;
 
-                nft::Pallet::<T>::mint(&to, (class_id, label_node), metadata, Default::default())?;
+                T::Nft::mint(&to, label_node, metadata)?;
+                Self::index_owned(&to, label_node);
+                Subnodes::<T>::insert(node, label_node, ());
+                ParentOf::<T>::insert(label_node, node);
 
                 if let Some(origin) = RuntimeOrigin::<T>::get(node) {
                     match origin {
                         DomainTracing::RuntimeOrigin(origin) => {
-                            T::Registrar::check_expires_useable(origin)?;
+                            T::Registrar::check_expires_useable(node)?;
 
-                            Self::add_children_with_check(origin, class_id, capacity)?;
+                            Self::add_children_with_check(origin, capacity)?;
 
-                            Self::add_children(node, class_id)?;
+                            Self::add_children(node)?;
 
                             RuntimeOrigin::<T>::insert(
                                 label_node,
@@ -289,7 +767,7 @@ pub mod pallet {
                             );
                         }
                         DomainTracing::Root => {
-                            Self::add_children_with_check(node, class_id, capacity)?;
+                            Self::add_children_with_check(node, capacity)?;
 
                             RuntimeOrigin::<T>::insert(
                                 label_node,
@@ -298,13 +776,13 @@ pub mod pallet {
                         }
                     }
                 } else {
-                    Self::add_children(node, class_id)?;
+                    Self::add_children(node)?;
 
                     RuntimeOrigin::<T>::insert(label_node, DomainTracing::Root);
                 }
             }
             Self::deposit_event(Event::<T>::TokenMinted {
-                class_id,
+                class_id: T::Nft::class_id(),
                 token_id: label_node,
                 node,
                 owner: to,
@@ -312,29 +790,18 @@ pub mod pallet {
 
             Ok(())
         }
-        pub(crate) fn add_children(node: DomainHash, class_id: T::ClassId) -> DispatchResult {
-            nft::Tokens::<T>::mutate(class_id, node, |data| -> DispatchResult {
-                let Some(info) = data else {
-                    return Err(Error::<T>::NotExist.into())
-                };
+        pub(crate) fn add_children(node: DomainHash) -> DispatchResult {
+            ensure!(T::Nft::owner(node).is_some(), Error::<T>::NotExist);
 
-                let node_children = info.data.children;
-                info.data.children = node_children + 1;
-                Ok(())
-            })
+            Children::<T>::mutate(node, |children| *children += 1);
+            Ok(())
         }
-        fn add_children_with_check(
-            node: DomainHash,
-            class_id: T::ClassId,
-            capacity: u32,
-        ) -> DispatchResult {
-            nft::Tokens::<T>::mutate(class_id, node, |data| -> DispatchResult {
-                let Some(info) = data else {
-                    return Err(Error::<T>::NotExist.into())
-                };
-                let node_children = info.data.children;
-                ensure!(node_children < capacity, Error::<T>::CapacityNotEnough);
-                info.data.children = node_children + 1;
+        fn add_children_with_check(node: DomainHash, capacity: u32) -> DispatchResult {
+            ensure!(T::Nft::owner(node).is_some(), Error::<T>::NotExist);
+
+            Children::<T>::try_mutate(node, |children| -> DispatchResult {
+                ensure!(*children < capacity, Error::<T>::CapacityNotEnough);
+                *children += 1;
                 Ok(())
             })
         }
@@ -346,15 +813,57 @@ pub mod pallet {
         pub fn do_transfer(
             from: &T::AccountId,
             to: &T::AccountId,
-            token: T::TokenId,
+            token: DomainHash,
         ) -> DispatchResult {
-            let class_id = T::ClassId::zero();
-            let token_info =
-                nft::Pallet::<T>::tokens(class_id, token).ok_or(Error::<T>::NotExist)?;
+            let owner = Self::check_transferable(from, token)?;
+
+            T::Nft::transfer(&owner, to, token)?;
+            Self::transferred(token, owner, to);
 
-            let owner = token_info.owner;
+            Ok(())
+        }
+
+        /// Same as `do_transfer` for several tokens, moved by a single batch
+        /// transfer of the nft pallet per owner. Ensure `from` is a caller.
+        #[cfg_attr(
+            not(feature = "runtime-benchmarks"),
+            frame_support::require_transactional
+        )]
+        pub fn do_transfer_batch(
+            from: &T::AccountId,
+            to: &T::AccountId,
+            tokens: &[DomainHash],
+        ) -> DispatchResult {
+            let mut by_owner = BTreeMap::<T::AccountId, Vec<DomainHash>>::new();
+            for token in tokens {
+                let owner = Self::check_transferable(from, *token)?;
+                let owned = by_owner.entry(owner).or_default();
+                // the second transfer of a token would not be from its owner anymore.
+                ensure!(!owned.contains(token), Error::<T>::NoPermission);
+                owned.push(*token);
+            }
+
+            for (owner, tokens) in by_owner {
+                T::Nft::transfer_batch(&owner, to, &tokens)?;
+                for token in tokens {
+                    Self::transferred(token, owner.clone(), to);
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Fails if `from` can't transfer `token` now, returns its owner.
+        fn check_transferable(
+            from: &T::AccountId,
+            token: DomainHash,
+        ) -> Result<T::AccountId, DispatchError> {
+            let owner = T::Nft::owner(token).ok_or(Error::<T>::NotExist)?;
 
             Self::verify_with_owner(from, token, &owner)?;
+            T::Registrar::check_unlocked(token)?;
+            Self::check_fuses(token, fuses::CANNOT_TRANSFER)?;
+            Self::check_frozen(token)?;
 
             let Some(origin) = RuntimeOrigin::<T>::get(token) else {
                 return Err(Error::<T>::NotExist.into())
@@ -369,26 +878,48 @@ pub mod pallet {
                 }
             }
 
-            nft::Pallet::<T>::transfer(&owner, to, (class_id, token))?;
+            Ok(owner)
+        }
+
+        pub(crate) fn transferred(token: DomainHash, owner: T::AccountId, to: &T::AccountId) {
+            Self::unindex_owned(&owner, token);
+            Self::index_owned(to, token);
+            // approvals were given by the previous owner.
+            let _ = TokenApprovals::<T>::clear_prefix(token, u32::MAX, None);
+            T::Registrar::on_transferred(token, &owner, to);
 
             Self::deposit_event(Event::<T>::Transferred {
                 from: owner,
                 to: to.clone(),
-                class_id,
+                class_id: T::Nft::class_id(),
                 token_id: token,
             });
+        }
 
-            Ok(())
+        /// Adds `node` to the domains of `owner`, see [`OwnedNodes`].
+        pub(crate) fn index_owned(owner: &T::AccountId, node: DomainHash) {
+            if !OwnedNodes::<T>::contains_key(owner, node) {
+                OwnedNodes::<T>::insert(owner, node, ());
+                OwnedTokenCount::<T>::mutate(owner, |count| *count = count.saturating_add(1));
+            }
+        }
+
+        /// Removes `node` from the domains of `owner`, see [`OwnedNodes`].
+        pub(crate) fn unindex_owned(owner: &T::AccountId, node: DomainHash) {
+            if OwnedNodes::<T>::take(owner, node).is_some() {
+                OwnedTokenCount::<T>::mutate_exists(owner, |count| {
+                    *count = count
+                        .map(|count| count.saturating_sub(1))
+                        .filter(|count| *count != 0);
+                });
+            }
         }
 
-        fn sub_children(node: DomainHash, class_id: T::ClassId) -> DispatchResult {
-            nft::Tokens::<T>::mutate(class_id, node, |data| -> DispatchResult {
-                let Some(info) = data else {
-                    return  Err(Error::<T>::NotExist.into())
-                };
+        fn sub_children(node: DomainHash) -> DispatchResult {
+            ensure!(T::Nft::owner(node).is_some(), Error::<T>::NotExist);
 
-                let node_children = info.data.children;
-                info.data.children = node_children
+            Children::<T>::try_mutate(node, |children| -> DispatchResult {
+                *children = children
                     .checked_sub(1)
                     .ok_or(sp_runtime::ArithmeticError::Overflow)?;
                 Ok(())
@@ -447,6 +978,8 @@ pub mod pallet {
         ) -> DispatchResult {
             let caller = ensure_signed(origin)?;
             Self::verify(&caller, node)?;
+            Self::check_fuses(node, fuses::CANNOT_SET_RESOLVER)?;
+            Self::check_frozen(node)?;
             Resolver::<T>::mutate(node, |rs| *rs = resolver.clone());
 
             Self::deposit_event(Event::<T>::NewResolver { node, resolver });
@@ -472,25 +1005,31 @@ pub mod pallet {
         #[pallet::weight(T::WeightInfo::set_official())]
         #[frame_support::transactional]
         pub fn set_official(origin: OriginFor<T>, official: T::AccountId) -> DispatchResult {
-            let _who = T::ManagerOrigin::ensure_origin(origin)?;
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_official {
+                    official: official.clone(),
+                },
+            );
             let old_official = Official::<T>::take();
 
             Official::<T>::put(&official);
 
-            if let Some(old_official) = old_official {
-                nft::Pallet::<T>::transfer(
-                    &old_official,
-                    &official,
-                    (T::ClassId::zero(), T::Registrar::basenode()),
-                )?;
+            if let Some(old_official) = &old_official {
+                for base_node in T::Registrar::base_nodes() {
+                    T::Nft::transfer(old_official, &official, base_node)?;
+                    Self::unindex_owned(old_official, base_node);
+                    Self::index_owned(&official, base_node);
+                }
             }
 
-            nft::Classes::<T>::mutate(T::ClassId::zero(), |info| {
-                if let Some(info) = info {
-                    info.owner = official;
-                }
-            });
+            T::Nft::set_class_owner(&official)?;
 
+            Self::deposit_event(Event::<T>::OfficialChanged {
+                old: old_official,
+                new: official,
+            });
             Ok(())
         }
         #[pallet::call_index(4)]
@@ -503,20 +1042,564 @@ pub mod pallet {
         ) -> DispatchResult {
             let sender = ensure_signed(origin)?;
 
-            let owner = nft::Pallet::<T>::tokens(T::ClassId::zero(), node)
-                .ok_or(Error::<T>::NotExist)?
-                .owner;
+            let owner = T::Nft::owner(node).ok_or(Error::<T>::NotExist)?;
 
             ensure!(to != owner, Error::<T>::ApprovalFailure);
 
             Self::verify_with_owner(&sender, node, &owner)?;
 
             if approved {
-                TokenApprovals::<T>::insert(node, to, ());
+                T::Registrar::check_unlocked(node)?;
+                TokenApprovals::<T>::insert(node, &to, ());
+
+                Self::deposit_event(Event::<T>::TokenApproved {
+                    node,
+                    owner,
+                    approved: to,
+                });
+            } else {
+                TokenApprovals::<T>::remove(node, &to);
+
+                Self::deposit_event(Event::<T>::TokenApprovalRevoked {
+                    node,
+                    owner,
+                    approved: to,
+                });
+            }
+
+            Ok(())
+        }
+        /// Transfer up to `limit` of the domains you own to `to`, e.g. when
+        /// moving to a new account.
+        ///
+        /// Domains which can not be transferred (such as expired ones) are skipped.
+        /// If you own more than `limit` domains, call again with the `next`
+        /// cursor of the `BatchTransferred` event as `start_after`.
+        ///
+        /// Ensure: `limit` is at most `MaxTransferAll`, a larger one is lowered to it.
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::transfer_all((*limit).min(T::MaxTransferAll::get())))]
+        pub fn transfer_all(
+            origin: OriginFor<T>,
+            to: <T::Lookup as StaticLookup>::Source,
+            limit: u32,
+            start_after: Option<DomainHash>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(to)?;
+            let limit = limit.min(T::MaxTransferAll::get());
+
+            let nodes = match start_after {
+                Some(node) => {
+                    let raw_key = OwnedNodes::<T>::hashed_key_for(&caller, node);
+                    OwnedNodes::<T>::iter_key_prefix_from(&caller, raw_key)
+                        .take(limit as usize)
+                        .collect::<Vec<_>>()
+                }
+                None => OwnedNodes::<T>::iter_key_prefix(&caller)
+                    .take(limit as usize)
+                    .collect::<Vec<_>>(),
+            };
+
+            let mut transferred = 0_u32;
+            let mut skipped = 0_u32;
+            for node in nodes.iter() {
+                match frame_support::storage::with_storage_layer(|| {
+                    Self::do_transfer(&caller, &to, *node)
+                }) {
+                    Ok(()) => transferred += 1,
+                    Err(_) => skipped += 1,
+                }
+            }
+
+            let next = if nodes.len() == limit as usize {
+                nodes.last().copied()
+            } else {
+                None
+            };
+
+            Self::deposit_event(Event::<T>::BatchTransferred {
+                from: caller,
+                to,
+                transferred,
+                skipped,
+                next,
+            });
+
+            Ok(())
+        }
+        /// Display your account as `node`, e.g. `alice.dot` instead of its address.
+        /// `None` clears it.
+        ///
+        /// Note: The primary name is ignored once you don't own `node` anymore
+        /// or it expired, see `primary_name`.
+        ///
+        /// Ensure: Only the owner of `node`, operators are not enough.
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::set_primary_name())]
+        pub fn set_primary_name(origin: OriginFor<T>, node: Option<DomainHash>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            match node {
+                Some(node) => {
+                    let owner = T::Nft::owner(node).ok_or(Error::<T>::NotExist)?;
+                    ensure!(owner == who, Error::<T>::NotOwned);
+                    T::Registrar::check_expires_useable(node)?;
+
+                    PrimaryNames::<T>::insert(&who, node);
+                }
+                None => PrimaryNames::<T>::remove(&who),
+            }
+
+            Self::deposit_event(Event::<T>::PrimaryNameSet { who, node });
+            Ok(())
+        }
+        /// Irreversibly give up permissions of `node`, e.g. `CANNOT_TRANSFER`
+        /// to prove a name stays with its owner. See [`fuses`].
+        ///
+        /// Ensure: Only the owner of `node`, `PARENT_CANNOT_CONTROL` is up to the parent.
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::WeightInfo::burn_fuses())]
+        pub fn burn_fuses(origin: OriginFor<T>, node: DomainHash, fuses: u32) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let owner = T::Nft::owner(node).ok_or(Error::<T>::NotExist)?;
+            ensure!(owner == who, Error::<T>::NotOwned);
+            ensure!(
+                fuses & fuses::PARENT_CANNOT_CONTROL == 0,
+                Error::<T>::ParentFuse
+            );
+
+            Self::do_burn_fuses(node, fuses)
+        }
+        /// Irreversibly give up permissions of the subdomain `data` of `node`,
+        /// e.g. `PARENT_CANNOT_CONTROL` to issue subdomains which can't be taken back.
+        /// See [`fuses`].
+        ///
+        /// Ensure: The caller has the permission of `node`.
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::burn_child_fuses())]
+        pub fn burn_child_fuses(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            data: Vec<u8>,
+            fuses: u32,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            Self::verify(&caller, node)?;
+
+            let (label, _) = T::Registrar::label_of(&data).ok_or(Error::<T>::NotExist)?;
+            let subnode = label.encode_with_node(&node);
+            ensure!(T::Nft::owner(subnode).is_some(), Error::<T>::NotExist);
+
+            Self::do_burn_fuses(subnode, fuses)
+        }
+        /// Let `controller` edit the records of `node` and create its subdomains,
+        /// without being able to transfer or burn it. `None` removes the controller.
+        ///
+        /// Note: The controller stays when `node` is transferred, unless it is
+        /// transferred with `transfer_and_reset`.
+        ///
+        /// Ensure: The caller has the permission of `node`.
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::set_controller())]
+        pub fn set_controller(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            controller: Option<<T::Lookup as StaticLookup>::Source>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            let controller = controller.map(T::Lookup::lookup).transpose()?;
+
+            Self::verify(&caller, node)?;
+
+            Controllers::<T>::set(node, controller.clone());
+
+            Self::deposit_event(Event::<T>::ControllerChanged { node, controller });
+            Ok(())
+        }
+        /// Set the ttl in seconds all records of `node` are served with,
+        /// `None` falls back to the defaults of the resolver.
+        ///
+        /// Ensure: The caller has the permission of `node` or is its controller.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::set_ttl())]
+        pub fn set_ttl(origin: OriginFor<T>, node: DomainHash, ttl: Option<u32>) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            if !Self::controls(&caller, node) {
+                Self::verify(&caller, node)?;
+            }
+            Self::check_frozen(node)?;
+
+            Ttl::<T>::set(node, ttl);
+
+            Self::deposit_event(Event::<T>::TtlChanged { node, ttl });
+            Ok(())
+        }
+        /// Invalidate every record of `node` at once by bumping its record version,
+        /// resolvers only serve records written with the current version.
+        ///
+        /// Note: The old records are not removed from storage.
+        ///
+        /// Ensure: The caller has the permission of `node` or is its controller.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::clear_records())]
+        pub fn clear_records(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            if !Self::controls(&caller, node) {
+                Self::verify(&caller, node)?;
+            }
+            Self::check_frozen(node)?;
+
+            let version = RecordVersions::<T>::mutate(node, |version| {
+                *version = version.wrapping_add(1);
+                *version
+            });
+
+            Self::deposit_event(Event::<T>::RecordsCleared { node, version });
+            Ok(())
+        }
+        /// Freeze a disputed `node`: it can't be transferred or burned, and neither
+        /// its subdomains nor its resolver or records can be changed until it is thawed.
+        /// Only manager
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::freeze())]
+        pub fn freeze(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&manager, &Call::<T>::freeze { node });
+
+            ensure!(T::Nft::owner(node).is_some(), Error::<T>::NotExist);
+
+            FrozenNodes::<T>::insert(node, ());
+
+            Self::deposit_event(Event::<T>::Frozen { node });
+            Ok(())
+        }
+        /// Lift the freeze of `node`.
+        /// Only manager
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::thaw())]
+        pub fn thaw(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&manager, &Call::<T>::thaw { node });
+
+            Self::lift_freeze(node);
+            Ok(())
+        }
+        /// Burn up to `max_children` subdomains of `node`, deepest first,
+        /// then `node` itself if none are left.
+        ///
+        /// Note: The subdomains are burned regardless of their owners,
+        /// unless their `PARENT_CANNOT_CONTROL` fuse is burned.
+        ///
+        /// Ensure: The caller has the permission of `node`.
+        #[pallet::call_index(14)]
+        #[pallet::weight(T::WeightInfo::burn_recursive(*max_children))]
+        pub fn burn_recursive(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            max_children: u32,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            Self::verify(&caller, node)?;
+
+            let mut burned = 0_u32;
+            while burned < max_children {
+                let mut leaf = node;
+                while let Some(child) = Subnodes::<T>::iter_key_prefix(leaf).next() {
+                    leaf = child;
+                }
+                if leaf == node {
+                    break;
+                }
+
+                Self::check_fuses(leaf, fuses::PARENT_CANNOT_CONTROL)?;
+                Self::burn_token(caller.clone(), leaf, false)?;
+                burned += 1;
+            }
+
+            let node_burned = Subnodes::<T>::iter_key_prefix(node).next().is_none();
+            if node_burned {
+                Self::burn_token(caller, node, true)?;
+            }
+
+            Self::deposit_event(Event::<T>::SubtreeBurned {
+                node,
+                burned,
+                node_burned,
+            });
+            Ok(())
+        }
+        /// Set the metadata of the token of `node`, as long as the nft pallet allows,
+        /// e.g. the avatar or description marketplaces show.
+        ///
+        /// Ensure: The caller has the permission of `node` or is its controller.
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::set_token_metadata(data.len() as u32))]
+        pub fn set_token_metadata(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            data: Vec<u8>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            if !Self::controls(&caller, node) {
+                Self::verify(&caller, node)?;
+            }
+            Self::check_frozen(node)?;
+
+            T::Nft::set_metadata(node, data)?;
+
+            Self::deposit_event(Event::<T>::TokenMetadataSet { node });
+            Ok(())
+        }
+        /// Set the prefix of every token uri.
+        /// Only manager
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::set_base_uri(uri.len() as u32))]
+        pub fn set_base_uri(origin: OriginFor<T>, uri: Vec<u8>) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_base_uri { uri: uri.clone() },
+            );
+
+            let bounded_uri: BoundedVec<u8, T::MaxUriLength> =
+                uri.clone().try_into().map_err(|_| Error::<T>::UriTooLong)?;
+            BaseUri::<T>::put(bounded_uri);
+
+            Self::deposit_event(Event::<T>::BaseUriChanged { uri });
+            Ok(())
+        }
+        /// Set the part of the token uri of `node` after the base uri,
+        /// `None` falls back to the hex form of `node`.
+        ///
+        /// Ensure: The caller has the permission of `node` or is its controller.
+        #[pallet::call_index(17)]
+        #[pallet::weight(T::WeightInfo::set_token_uri_suffix())]
+        pub fn set_token_uri_suffix(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            suffix: Option<Vec<u8>>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            if !Self::controls(&caller, node) {
+                Self::verify(&caller, node)?;
+            }
+            Self::check_frozen(node)?;
+
+            let bounded_suffix = suffix
+                .clone()
+                .map(BoundedVec::<u8, T::MaxUriLength>::try_from)
+                .transpose()
+                .map_err(|_| Error::<T>::UriTooLong)?;
+            TokenUriSuffixes::<T>::set(node, bounded_suffix);
+
+            Self::deposit_event(Event::<T>::TokenUriSuffixSet { node, suffix });
+            Ok(())
+        }
+        /// Set the cut of the sale price of the subdomain `node` which goes to the
+        /// beneficiary of `royalty` whenever it is sold, see `registrar::offer_sale`.
+        /// `None` falls back to the default royalty.
+        ///
+        /// Note: The royalty stays when `node` changes hands, its owner can't remove it.
+        ///
+        /// Ensure: The caller has the permission of the parent of `node`.
+        #[pallet::call_index(18)]
+        #[pallet::weight(T::WeightInfo::set_royalty())]
+        pub fn set_royalty(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            royalty: Option<RoyaltyOf<T>>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+
+            let parent = ParentOf::<T>::get(node).ok_or(Error::<T>::NotExist)?;
+            Self::verify(&caller, parent)?;
+            Self::check_frozen(node)?;
+            if let Some(royalty) = &royalty {
+                ensure!(
+                    royalty.basis_points <= MAX_BASIS_POINTS,
+                    Error::<T>::RoyaltyTooHigh
+                );
+            }
+
+            Royalties::<T>::set(node, royalty.clone());
+
+            Self::deposit_event(Event::<T>::RoyaltySet { node, royalty });
+            Ok(())
+        }
+        /// Set the royalty of the domains which have none of their own.
+        /// Only manager
+        #[pallet::call_index(19)]
+        #[pallet::weight(T::WeightInfo::set_default_royalty())]
+        pub fn set_default_royalty(
+            origin: OriginFor<T>,
+            royalty: Option<RoyaltyOf<T>>,
+        ) -> DispatchResult {
+            let manager = T::ManagerOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &manager,
+                &Call::<T>::set_default_royalty {
+                    royalty: royalty.clone(),
+                },
+            );
+
+            if let Some(royalty) = &royalty {
+                ensure!(
+                    royalty.basis_points <= MAX_BASIS_POINTS,
+                    Error::<T>::RoyaltyTooHigh
+                );
+            }
+
+            DefaultRoyalty::<T>::set(royalty.clone());
+
+            Self::deposit_event(Event::<T>::DefaultRoyaltySet { royalty });
+            Ok(())
+        }
+        /// Freeze `node` as `freeze` does, for `FreezeConfirmPeriod` unless governance
+        /// confirms it. `serve_records` tells whether the resolvers keep serving its records.
+        ///
+        /// The owner can appeal the freeze within `AppealPeriod`.
+        /// Only governance
+        #[pallet::call_index(20)]
+        #[pallet::weight(T::WeightInfo::emergency_freeze())]
+        pub fn emergency_freeze(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            serve_records: bool,
+        ) -> DispatchResult {
+            let who = T::GovernanceOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(
+                &who,
+                &Call::<T>::emergency_freeze {
+                    node,
+                    serve_records,
+                },
+            );
+
+            ensure!(T::Nft::owner(node).is_some(), Error::<T>::NotExist);
+            Self::check_frozen(node)?;
+            if FrozenNodes::<T>::contains_key(node) {
+                // an expired emergency freeze nobody released yet
+                Self::lift_freeze(node);
+            }
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let appeal_until = now + T::AppealPeriod::get();
+            let expires_at = now + T::FreezeConfirmPeriod::get();
+            FrozenNodes::<T>::insert(node, ());
+            EmergencyFreezes::<T>::insert(
+                node,
+                EmergencyFreeze {
+                    serve_records,
+                    appeal_until,
+                    expires_at,
+                    appeal: None,
+                },
+            );
+
+            Self::deposit_event(Event::<T>::EmergencyFrozen {
+                node,
+                serve_records,
+                appeal_until,
+                expires_at,
+            });
+            Ok(())
+        }
+        /// Appeal the emergency freeze of `node`, reserving `AppealBond` from the caller.
+        /// It opens a case of the `Arbitration`, governance can't confirm the freeze
+        /// until the case is ruled on.
+        ///
+        /// Ensure: The caller owns `node` and the appeal period is not over.
+        #[pallet::call_index(21)]
+        #[pallet::weight(T::WeightInfo::appeal_freeze())]
+        pub fn appeal_freeze(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            EmergencyFreezes::<T>::try_mutate(node, |maybe_freeze| -> DispatchResult {
+                let freeze = maybe_freeze
+                    .as_mut()
+                    .ok_or(Error::<T>::NotEmergencyFrozen)?;
+                ensure!(
+                    T::Nft::owner(node) == Some(who.clone()),
+                    Error::<T>::NotOwned
+                );
+                ensure!(
+                    frame_system::Pallet::<T>::block_number() < freeze.appeal_until,
+                    Error::<T>::AppealClosed
+                );
+                ensure!(freeze.appeal.is_none(), Error::<T>::AlreadyAppealed);
+
+                let bond = T::AppealBond::get();
+                T::Currency::reserve(&who, bond)?;
+                T::Arbitration::open_appeal(node, &who)?;
+                freeze.appeal = Some((who.clone(), bond));
+
+                Self::deposit_event(Event::<T>::FreezeAppealed {
+                    node,
+                    owner: who,
+                    bond,
+                });
+                Ok(())
+            })
+        }
+        /// Decide the emergency freeze of `node` before it expires: `uphold` keeps
+        /// it as a freeze of the manager and slashes the bond of the appeal,
+        /// otherwise it is lifted and the bond returned.
+        ///
+        /// Ensure: The appeal of the freeze, if any, was ruled on.
+        /// Only governance
+        #[pallet::call_index(22)]
+        #[pallet::weight(T::WeightInfo::confirm_freeze())]
+        pub fn confirm_freeze(
+            origin: OriginFor<T>,
+            node: DomainHash,
+            uphold: bool,
+        ) -> DispatchResult {
+            let who = T::GovernanceOrigin::ensure_origin(origin)?;
+            T::AuditLog::record_call::<Self, _>(&who, &Call::<T>::confirm_freeze { node, uphold });
+
+            let freeze = EmergencyFreezes::<T>::get(node).ok_or(Error::<T>::NotEmergencyFrozen)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() < freeze.expires_at,
+                Error::<T>::FreezeExpired
+            );
+            ensure!(
+                !T::Arbitration::appeal_pending(node),
+                Error::<T>::AppealPending
+            );
+
+            if uphold {
+                EmergencyFreezes::<T>::remove(node);
+                if let Some((owner, bond)) = freeze.appeal {
+                    let _ = T::Currency::slash_reserved(&owner, bond);
+                }
+
+                Self::deposit_event(Event::<T>::FreezeConfirmed { node });
             } else {
-                TokenApprovals::<T>::remove(node, to);
+                Self::lift_freeze(node);
             }
+            Ok(())
+        }
+        /// Lift the emergency freeze of `node` governance did not confirm in time.
+        #[pallet::call_index(23)]
+        #[pallet::weight(T::WeightInfo::release_freeze())]
+        pub fn release_freeze(origin: OriginFor<T>, node: DomainHash) -> DispatchResult {
+            let _who = ensure_signed(origin)?;
+
+            let freeze = EmergencyFreezes::<T>::get(node).ok_or(Error::<T>::NotEmergencyFrozen)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() >= freeze.expires_at,
+                Error::<T>::FreezeNotExpired
+            );
 
+            Self::lift_freeze(node);
             Ok(())
         }
     }
@@ -539,6 +1622,7 @@ pub trait WeightInfo {
     fn approval_for_all_false() -> Weight;
     fn set_resolver() -> Weight;
     fn burn() -> Weight;
+    fn burn_recursive(max_children: u32) -> Weight;
     fn set_official() -> Weight;
     fn approve(approved: bool) -> Weight {
         if approved {
@@ -549,21 +1633,44 @@ pub trait WeightInfo {
     }
     fn approve_true() -> Weight;
     fn approve_false() -> Weight;
+    fn transfer_all(limit: u32) -> Weight;
+    fn set_primary_name() -> Weight;
+    fn burn_fuses() -> Weight;
+    fn burn_child_fuses() -> Weight;
+    fn set_controller() -> Weight;
+    fn set_ttl() -> Weight;
+    fn clear_records() -> Weight;
+    fn freeze() -> Weight;
+    fn thaw() -> Weight;
+    fn emergency_freeze() -> Weight;
+    fn appeal_freeze() -> Weight;
+    fn confirm_freeze() -> Weight;
+    fn release_freeze() -> Weight;
+    fn set_token_metadata(len: u32) -> Weight;
+    fn set_base_uri(len: u32) -> Weight;
+    fn set_token_uri_suffix() -> Weight;
+    fn set_royalty() -> Weight;
+    fn set_default_royalty() -> Weight;
 }
 // TODO: replace litentry
 impl<T: pallet::Config> crate::traits::NFT<T::AccountId> for pallet::Pallet<T> {
-    type ClassId = T::ClassId;
+    type ClassId = crate::registry::ClassIdOf<T>;
 
-    type TokenId = T::TokenId;
+    type TokenId = DomainHash;
 
     type Balance = u128;
 
     fn balance(who: &T::AccountId) -> Self::Balance {
-        crate::nft::TokensByOwner::<T>::iter_prefix((who,)).count() as u128
+        OwnedTokenCount::<T>::get(who) as u128
     }
 
     fn owner(token: (Self::ClassId, Self::TokenId)) -> Option<T::AccountId> {
-        crate::nft::Pallet::<T>::tokens(token.0, token.1).map(|t| t.owner)
+        use crate::traits::NftBackend;
+
+        if token.0 != T::Nft::class_id() {
+            return None;
+        }
+        T::Nft::owner(token.1)
     }
     #[cfg_attr(
         not(feature = "runtime-benchmarks"),
@@ -574,8 +1681,9 @@ impl<T: pallet::Config> crate::traits::NFT<T::AccountId> for pallet::Pallet<T> {
         to: &T::AccountId,
         token: (Self::ClassId, Self::TokenId),
     ) -> DispatchResult {
-        use sp_runtime::traits::Zero;
-        ensure!(token.0 == T::ClassId::zero(), Error::<T>::NotExist);
+        use crate::traits::NftBackend;
+
+        ensure!(token.0 == T::Nft::class_id(), Error::<T>::NotExist);
 
         Self::do_transfer(from, to, token.1)
     }
@@ -583,6 +1691,7 @@ impl<T: pallet::Config> crate::traits::NFT<T::AccountId> for pallet::Pallet<T> {
 
 impl<T: pallet::Config> crate::traits::Registry for pallet::Pallet<T> {
     type AccountId = T::AccountId;
+    type ResolverId = T::ResolverId;
 
     #[cfg_attr(
         not(feature = "runtime-benchmarks"),
@@ -617,6 +1726,105 @@ impl<T: pallet::Config> crate::traits::Registry for pallet::Pallet<T> {
     fn transfer(from: &Self::AccountId, to: &Self::AccountId, node: DomainHash) -> DispatchResult {
         Self::do_transfer(from, to, node)
     }
+
+    #[frame_support::require_transactional]
+    fn transfer_batch(
+        from: &Self::AccountId,
+        to: &Self::AccountId,
+        nodes: &[DomainHash],
+    ) -> DispatchResult {
+        Self::do_transfer_batch(from, to, nodes)
+    }
+
+    #[frame_support::require_transactional]
+    fn transfer_sold(
+        from: &Self::AccountId,
+        to: &Self::AccountId,
+        node: DomainHash,
+        do_payments: impl FnOnce(&Self::AccountId, Option<&RoyaltyOf<T>>) -> DispatchResult,
+    ) -> DispatchResult {
+        use crate::traits::NftBackend;
+
+        let owner = T::Nft::owner(node).ok_or(Error::<T>::NotExist)?;
+        Self::do_transfer(from, to, node)?;
+
+        do_payments(&owner, Self::royalty_of(node).as_ref())
+    }
+
+    #[frame_support::require_transactional]
+    fn burn_unchecked(caller: &Self::AccountId, node: DomainHash) -> DispatchResult {
+        Self::burn_token(caller.clone(), node, false)
+    }
+
+    fn thaw_unchecked(node: DomainHash) {
+        if FrozenNodes::<T>::contains_key(node) {
+            Self::lift_freeze(node);
+        }
+    }
+
+    #[frame_support::require_transactional]
+    fn transfer_unchecked(to: &Self::AccountId, node: DomainHash) -> DispatchResult {
+        use crate::traits::NftBackend;
+
+        let owner = T::Nft::owner(node).ok_or(Error::<T>::NotExist)?;
+        T::Nft::transfer(&owner, to, node)?;
+        Self::transferred(node, owner, to);
+
+        Ok(())
+    }
+
+    fn set_resolver(node: DomainHash, resolver: Self::ResolverId) {
+        Resolver::<T>::insert(node, resolver.clone());
+
+        Self::deposit_event(Event::<T>::NewResolver { node, resolver });
+    }
+
+    fn root_of(node: DomainHash) -> DomainHash {
+        match RuntimeOrigin::<T>::get(node) {
+            Some(pns_types::DomainTracing::RuntimeOrigin(origin)) => origin,
+            _ => node,
+        }
+    }
+
+    #[frame_support::require_transactional]
+    fn mint_base_node(base_node: DomainHash) -> DispatchResult {
+        use crate::traits::NftBackend;
+
+        let official = Official::<T>::get().ok_or(Error::<T>::OfficialNotInitiated)?;
+        ensure!(
+            T::Nft::owner(base_node).is_none(),
+            Error::<T>::BaseNodeExists
+        );
+
+        // base nodes have no `RuntimeOrigin`, which keeps them from being burned.
+        T::Nft::mint(&official, base_node, Vec::with_capacity(0))?;
+        Self::index_owned(&official, base_node);
+
+        Self::deposit_event(Event::<T>::TokenMinted {
+            class_id: T::Nft::class_id(),
+            token_id: base_node,
+            node: base_node,
+            owner: official,
+        });
+        Ok(())
+    }
+
+    fn clear_approvals(node: DomainHash) {
+        let _ = TokenApprovals::<T>::clear_prefix(node, u32::MAX, None);
+        Controllers::<T>::remove(node);
+
+        Self::deposit_event(Event::<T>::TokenApprovalsCleared { node });
+    }
+
+    fn check_fuses(node: DomainHash, fuses: u32) -> DispatchResult {
+        Self::check_fuses(node, fuses)
+    }
+
+    fn owner_of(node: DomainHash) -> Option<Self::AccountId> {
+        use crate::traits::NftBackend;
+
+        T::Nft::owner(node)
+    }
 }
 
 impl<T: Config> crate::traits::Official for pallet::Pallet<T> {
@@ -644,6 +1852,10 @@ impl WeightInfo for () {
         Weight::zero()
     }
 
+    fn burn_recursive(_max_children: u32) -> Weight {
+        Weight::zero()
+    }
+
     fn set_official() -> Weight {
         Weight::zero()
     }
@@ -655,4 +1867,76 @@ impl WeightInfo for () {
     fn approve_false() -> Weight {
         Weight::zero()
     }
+
+    fn transfer_all(_limit: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn set_primary_name() -> Weight {
+        Weight::zero()
+    }
+
+    fn burn_fuses() -> Weight {
+        Weight::zero()
+    }
+
+    fn burn_child_fuses() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_controller() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_ttl() -> Weight {
+        Weight::zero()
+    }
+
+    fn clear_records() -> Weight {
+        Weight::zero()
+    }
+
+    fn freeze() -> Weight {
+        Weight::zero()
+    }
+
+    fn thaw() -> Weight {
+        Weight::zero()
+    }
+
+    fn emergency_freeze() -> Weight {
+        Weight::zero()
+    }
+
+    fn appeal_freeze() -> Weight {
+        Weight::zero()
+    }
+
+    fn confirm_freeze() -> Weight {
+        Weight::zero()
+    }
+
+    fn release_freeze() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_token_metadata(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn set_base_uri(_len: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn set_token_uri_suffix() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_royalty() -> Weight {
+        Weight::zero()
+    }
+
+    fn set_default_royalty() -> Weight {
+        Weight::zero()
+    }
 }
@@ -1,8 +1,9 @@
 use crate::*;
 use codec::Encode;
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_noop, assert_ok, traits::EnsureOrigin};
 use mock::*;
 use pns_resolvers::resolvers::{Address, TextKind};
+use redeem_code::LengthClass;
 use sp_runtime::testing::TestSignature;
 use traits::Label;
 
@@ -11,14 +12,16 @@ const DAYS: u64 = 24 * 60 * 60;
 #[test]
 fn register_test() {
     new_test_ext().execute_with(|| {
-        // now not supported chinese domain name
-        let name = "中文测试".as_bytes();
+        // mixes a cyrillic 'а' into a latin name
+        let name = "pаypalxxxxx".as_bytes();
         assert_noop!(
             Registrar::register(
                 RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
                 name.to_vec(),
                 RICH_ACCOUNT,
-                MinRegistrationDuration::get()
+                MinRegistrationDuration::get(),
+                None
             ),
             registrar::Error::<Test>::ParseLabelFailed
         );
@@ -27,9 +30,11 @@ fn register_test() {
         assert_noop!(
             Registrar::register(
                 RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
                 b"hello".to_vec(),
                 RICH_ACCOUNT,
-                MinRegistrationDuration::get()
+                MinRegistrationDuration::get(),
+                None
             ),
             registrar::Error::<Test>::LabelInvalid
         );
@@ -42,9 +47,11 @@ fn register_test() {
         assert_noop!(
             Registrar::register(
                 RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
                 name.to_vec(),
                 RICH_ACCOUNT,
-                MinRegistrationDuration::get() - DAYS
+                MinRegistrationDuration::get() - DAYS,
+                None
             ),
             registrar::Error::<Test>::RegistryDurationInvalid
         );
@@ -56,9 +63,11 @@ fn register_test() {
         // a right call
         assert_ok!(Registrar::register(
             RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
             name.to_vec(),
             RICH_ACCOUNT,
-            MinRegistrationDuration::get()
+            MinRegistrationDuration::get(),
+            None
         ));
 
         let now_free = Balances::free_balance(RICH_ACCOUNT);
@@ -93,9 +102,11 @@ fn register_test() {
         assert_noop!(
             Registrar::register(
                 RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
                 name.to_vec(),
                 MONEY_ACCOUNT,
-                MinRegistrationDuration::get()
+                MinRegistrationDuration::get(),
+                None
             ),
             registrar::Error::<Test>::Occupied
         );
@@ -103,9 +114,11 @@ fn register_test() {
         assert_noop!(
             Registrar::register(
                 RuntimeOrigin::signed(POOR_ACCOUNT),
+                DOT_BASENODE,
                 name2.to_vec(),
                 POOR_ACCOUNT,
-                MinRegistrationDuration::get()
+                MinRegistrationDuration::get(),
+                None
             ),
             pallet_balances::Error::<Test>::InsufficientBalance
         );
@@ -117,9 +130,11 @@ fn register_test() {
         assert_noop!(
             Registrar::register(
                 RuntimeOrigin::signed(POOR_ACCOUNT),
+                DOT_BASENODE,
                 name2.to_vec(),
                 POOR_ACCOUNT,
-                MinRegistrationDuration::get()
+                MinRegistrationDuration::get(),
+                None
             ),
             pallet_balances::Error::<Test>::InsufficientBalance
         );
@@ -128,16 +143,20 @@ fn register_test() {
 
         assert_ok!(Registrar::register(
             RuntimeOrigin::signed(POOR_ACCOUNT),
+            DOT_BASENODE,
             name2.to_vec(),
             POOR_ACCOUNT,
-            MinRegistrationDuration::get()
+            MinRegistrationDuration::get(),
+            None
         ));
 
         let renew_duration = 50 * DAYS;
         assert_ok!(Registrar::renew(
             RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
             name.to_vec(),
-            renew_duration
+            renew_duration,
+            None
         ));
 
         let old_expire = info.expire;
@@ -152,8 +171,10 @@ fn register_test() {
 
         assert_ok!(Registrar::renew(
             RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
             name.to_vec(),
-            renew_duration
+            renew_duration,
+            None
         ));
 
         let old_expire = info.expire;
@@ -235,7 +256,10 @@ fn redeem_code_test() {
         assert_ok!(RedeemCode::mint_redeem(
             RuntimeOrigin::signed(MANAGER_ACCOUNT),
             0,
-            10
+            10,
+            None,
+            1,
+            None
         ));
 
         let nouce = 0_u32;
@@ -243,7 +267,7 @@ fn redeem_code_test() {
         let label_node = label.node;
         let duration = MinRegistrationDuration::get();
 
-        let signature = (label_node, duration, nouce).encode();
+        let signature = (label_node, duration, nouce, POOR_ACCOUNT, 100_u64).encode();
 
         println!("{:?}", signature);
 
@@ -254,7 +278,8 @@ fn redeem_code_test() {
                 MinRegistrationDuration::get(),
                 0,
                 TestSignature(1, vec![1, 2, 3, 4]),
-                POOR_ACCOUNT
+                POOR_ACCOUNT,
+                100
             ),
             redeem_code::Error::<Test>::InvalidSignature
         );
@@ -266,7 +291,8 @@ fn redeem_code_test() {
                 MinRegistrationDuration::get(),
                 0,
                 TestSignature(1, signature.clone()),
-                POOR_ACCOUNT
+                POOR_ACCOUNT,
+                100
             ),
             redeem_code::Error::<Test>::InvalidSignature
         );
@@ -278,7 +304,8 @@ fn redeem_code_test() {
                 MinRegistrationDuration::get(),
                 0,
                 TestSignature(OFFICIAL_ACCOUNT, signature.clone()),
-                POOR_ACCOUNT
+                POOR_ACCOUNT,
+                100
             ),
             redeem_code::Error::<Test>::InvalidSignature
         );
@@ -289,7 +316,8 @@ fn redeem_code_test() {
             MinRegistrationDuration::get(),
             0,
             TestSignature(OFFICIAL_ACCOUNT, signature.clone()),
-            POOR_ACCOUNT
+            POOR_ACCOUNT,
+            100
         ));
 
         let test_node = label.encode_with_node(&DOT_BASENODE);
@@ -303,7 +331,8 @@ fn redeem_code_test() {
                 MinRegistrationDuration::get(),
                 0,
                 TestSignature(OFFICIAL_ACCOUNT, signature),
-                POOR_ACCOUNT
+                POOR_ACCOUNT,
+                100
             ),
             redeem_code::Error::<Test>::RedeemsHasBeenUsed
         );
@@ -311,7 +340,14 @@ fn redeem_code_test() {
         let nouce = 1_u32;
         let duration = MinRegistrationDuration::get();
 
-        let signature = (duration, nouce).encode();
+        let signature = (
+            duration,
+            nouce,
+            POOR_ACCOUNT,
+            100_u64,
+            LengthClass::AtLeast(10),
+        )
+            .encode();
 
         assert_noop!(
             RedeemCode::name_redeem_any(
@@ -320,7 +356,9 @@ fn redeem_code_test() {
                 MinRegistrationDuration::get(),
                 0,
                 TestSignature(OFFICIAL_ACCOUNT, vec![1, 2, 3, 4]),
-                POOR_ACCOUNT
+                POOR_ACCOUNT,
+                100,
+                LengthClass::AtLeast(10)
             ),
             redeem_code::Error::<Test>::RedeemsHasBeenUsed
         );
@@ -332,7 +370,9 @@ fn redeem_code_test() {
                 MinRegistrationDuration::get(),
                 1,
                 TestSignature(OFFICIAL_ACCOUNT, signature.clone()),
-                POOR_ACCOUNT
+                POOR_ACCOUNT,
+                100,
+                LengthClass::AtLeast(10)
             ),
             redeem_code::Error::<Test>::ParseLabelFailed
         );
@@ -344,16 +384,20 @@ fn redeem_code_test() {
                 MinRegistrationDuration::get(),
                 1,
                 TestSignature(OFFICIAL_ACCOUNT, signature.clone()),
-                POOR_ACCOUNT
+                POOR_ACCOUNT,
+                100,
+                LengthClass::AtLeast(10)
             ),
             redeem_code::Error::<Test>::LabelLenInvalid
         );
 
         assert_ok!(Registrar::register(
             RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
             b"cupnfishqqq".to_vec(),
             POOR_ACCOUNT,
-            MinRegistrationDuration::get()
+            MinRegistrationDuration::get(),
+            None
         ));
 
         assert_noop!(
@@ -363,7 +407,9 @@ fn redeem_code_test() {
                 MinRegistrationDuration::get(),
                 1,
                 TestSignature(OFFICIAL_ACCOUNT, signature.clone()),
-                POOR_ACCOUNT
+                POOR_ACCOUNT,
+                100,
+                LengthClass::AtLeast(10)
             ),
             registrar::Error::<Test>::Occupied
         );
@@ -374,7 +420,9 @@ fn redeem_code_test() {
             MinRegistrationDuration::get(),
             1,
             TestSignature(OFFICIAL_ACCOUNT, signature),
-            POOR_ACCOUNT
+            POOR_ACCOUNT,
+            100,
+            LengthClass::AtLeast(10)
         ));
 
         let test_node = Label::new_with_len("cupnfishxxx".as_bytes())
@@ -391,9 +439,11 @@ fn resolvers_test() {
     new_test_ext().execute_with(|| {
         assert_ok!(Registrar::register(
             RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
             b"cupnfishxxx".to_vec(),
             MONEY_ACCOUNT,
-            MinRegistrationDuration::get()
+            MinRegistrationDuration::get(),
+            None
         ));
 
         let node = Label::new_with_len("cupnfishxxx".as_bytes())
@@ -473,7 +523,9 @@ fn resolvers_test() {
 #[test]
 fn label_test() {
     // 中文 test
-    assert!(Label::new_with_len("中文域名暂不支持".as_bytes()).is_none());
+    assert!(Label::new_with_len("中文域名".as_bytes()).is_some());
+    assert!(Label::new_with_len("中文-域名".as_bytes()).is_none());
+    assert!(Label::new_with_len("xn--abc".as_bytes()).is_none());
 
     // white space test
     assert!(Label::new_with_len("hello world".as_bytes()).is_none());
@@ -502,3 +554,7048 @@ fn label_test() {
         DOT_BASENODE
     )
 }
+
+#[test]
+fn resolvers_lookup_one_test() {
+    use pns_types::ddns::codec_type::RecordType;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        let node = Label::new_with_len("cupnfishxxx".as_bytes())
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        assert_ok!(Resolvers::set_record(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RecordType::A,
+            vec![127, 0, 0, 1].into(),
+        ));
+        assert_ok!(Resolvers::set_record(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RecordType::TXT,
+            b"hello pns".to_vec().into(),
+        ));
+
+        let all = Resolvers::lookup(node);
+        assert_eq!(all.len(), 2);
+        for (kind, content) in all {
+            assert_eq!(Resolvers::lookup_one(node, kind), Some(content));
+        }
+
+        assert_eq!(Resolvers::lookup_one(node, RecordType::AAAA), None);
+    })
+}
+
+#[test]
+fn registration_limit_test() {
+    use frame_support::dispatch::DispatchInfo;
+    use sp_runtime::{
+        traits::SignedExtension,
+        transaction_validity::{InvalidTransaction, TransactionValidityError},
+    };
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let info = DispatchInfo::default();
+        let register_call = |name: &[u8]| {
+            RuntimeCall::Registrar(registrar::Call::register {
+                base_node: DOT_BASENODE,
+                name: name.to_vec(),
+                owner: RICH_ACCOUNT,
+                duration: MinRegistrationDuration::get(),
+                asset_id: None,
+            })
+        };
+        let limit = registrar::CheckRegistrationLimit::<Test>::new;
+
+        for name in [b"cupnfishxx1", b"cupnfishxx2"] {
+            let call = register_call(name);
+            assert_ok!(limit().validate(&RICH_ACCOUNT, &call, &info, 0));
+            assert_ok!(limit().pre_dispatch(&RICH_ACCOUNT, &call, &info, 0));
+        }
+
+        // the third registration in the same block is rejected.
+        let call = register_call(b"cupnfishxx3");
+        assert_eq!(
+            limit().validate(&RICH_ACCOUNT, &call, &info, 0),
+            Err(TransactionValidityError::Invalid(
+                InvalidTransaction::ExhaustsResources
+            ))
+        );
+        assert_eq!(
+            limit().pre_dispatch(&RICH_ACCOUNT, &call, &info, 0),
+            Err(TransactionValidityError::Invalid(
+                InvalidTransaction::ExhaustsResources
+            ))
+        );
+
+        // other accounts and other calls are not affected.
+        assert_ok!(limit().validate(&MONEY_ACCOUNT, &call, &info, 0));
+        let renew = RuntimeCall::Registrar(registrar::Call::renew {
+            base_node: DOT_BASENODE,
+            name: b"cupnfishxx1".to_vec(),
+            duration: MinRegistrationDuration::get(),
+            asset_id: None,
+        });
+        assert_ok!(limit().validate(&RICH_ACCOUNT, &renew, &info, 0));
+
+        // the counter starts over in the next block.
+        System::set_block_number(2);
+        assert_ok!(limit().pre_dispatch(&RICH_ACCOUNT, &call, &info, 0));
+
+        // a batch counts as many registrations as it has names.
+        let batch = |names: Vec<Vec<u8>>| {
+            RuntimeCall::Registrar(registrar::Call::register_batch {
+                base_node: DOT_BASENODE,
+                names: names.try_into().unwrap(),
+                owner: RICH_ACCOUNT,
+                duration: MinRegistrationDuration::get(),
+            })
+        };
+        let pair = batch(vec![b"cupnfishxx4".to_vec(), b"cupnfishxx5".to_vec()]);
+        assert_eq!(
+            limit().validate(&RICH_ACCOUNT, &pair, &info, 0),
+            Err(TransactionValidityError::Invalid(
+                InvalidTransaction::ExhaustsResources
+            ))
+        );
+        assert_ok!(limit().validate(&MONEY_ACCOUNT, &pair, &info, 0));
+        assert_ok!(limit().pre_dispatch(&MONEY_ACCOUNT, &pair, &info, 0));
+        assert_eq!(
+            limit().pre_dispatch(&MONEY_ACCOUNT, &call, &info, 0),
+            Err(TransactionValidityError::Invalid(
+                InvalidTransaction::ExhaustsResources
+            ))
+        );
+
+        // the counters of earlier blocks are ignored.
+        System::set_block_number(3);
+        assert_ok!(limit().validate(&MONEY_ACCOUNT, &pair, &info, 0));
+        assert_ok!(limit().pre_dispatch(&MONEY_ACCOUNT, &pair, &info, 0));
+    })
+}
+
+#[test]
+fn register_would_be_dusted_test() {
+    use frame_support::traits::Currency as _;
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx";
+        let duration = MinRegistrationDuration::get();
+        let total_price = PriceOracle::register_fee(name.len(), duration).unwrap()
+            + PriceOracle::deposit_fee(name.len()).unwrap();
+        let existential_deposit = ExistentialDeposit::get();
+
+        // can't afford it at all.
+        Balances::make_free_balance_be(&POOR_ACCOUNT, total_price - 1);
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                POOR_ACCOUNT,
+                duration,
+                None
+            ),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+
+        // can afford it, but would go below the existential deposit.
+        Balances::make_free_balance_be(&POOR_ACCOUNT, total_price + existential_deposit - 1);
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                POOR_ACCOUNT,
+                duration,
+                None
+            ),
+            registrar::Error::<Test>::WouldBeDusted
+        );
+
+        Balances::make_free_balance_be(&POOR_ACCOUNT, total_price + existential_deposit);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            POOR_ACCOUNT,
+            duration,
+            None
+        ));
+        assert_eq!(Balances::free_balance(POOR_ACCOUNT), existential_deposit);
+    })
+}
+
+#[test]
+fn reserved_for_test() {
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx";
+        let node = Label::new_with_len(name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        assert_noop!(
+            Registrar::reserve_for(RuntimeOrigin::signed(RICH_ACCOUNT), node, MONEY_ACCOUNT),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Registrar::reserve_for(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node,
+            MONEY_ACCOUNT
+        ));
+
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ),
+            registrar::Error::<Test>::Frozen
+        );
+
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node)));
+        assert!(!registrar::ReservedFor::<Test>::contains_key(node));
+
+        // a cleared hold no longer blocks anyone.
+        let name2 = b"cupnfishyyy";
+        let node2 = Label::new_with_len(name2)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::reserve_for(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node2,
+            MONEY_ACCOUNT
+        ));
+        assert_ok!(Registrar::clear_reserved_for(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node2
+        ));
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name2.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+    })
+}
+
+#[test]
+fn transfer_all_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let names: [&[u8]; 3] = [b"cupnfishxx1", b"cupnfishxx2", b"cupnfishxx3"];
+        let nodes = names.map(|name| {
+            assert_ok!(Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ));
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        });
+
+        assert_ok!(Registry::transfer_all(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            MONEY_ACCOUNT,
+            2,
+            None
+        ));
+        let next = match System::events().last().map(|record| &record.event) {
+            Some(RuntimeEvent::Registry(registry::Event::BatchTransferred {
+                transferred: 2,
+                skipped: 0,
+                next,
+                ..
+            })) => *next,
+            other => panic!("unexpected event: {other:?}"),
+        };
+        assert!(next.is_some());
+        assert_eq!(
+            nodes
+                .iter()
+                .filter(|node| Nft::is_owner(&MONEY_ACCOUNT, (0, **node)))
+                .count(),
+            2
+        );
+
+        assert_ok!(Registry::transfer_all(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            MONEY_ACCOUNT,
+            2,
+            next
+        ));
+        System::assert_last_event(RuntimeEvent::Registry(
+            registry::Event::BatchTransferred {
+                from: RICH_ACCOUNT,
+                to: MONEY_ACCOUNT,
+                transferred: 1,
+                skipped: 0,
+                next: None,
+            },
+        ));
+        for node in nodes {
+            assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node)));
+        }
+    })
+}
+
+#[test]
+fn default_resolver_test() {
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx";
+        let node = Label::new_with_len(name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_eq!(registry::Resolver::<Test>::get(node), DefaultResolver::get());
+
+        // owners can still pick their own resolver.
+        assert_ok!(Registry::set_resolver(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            2
+        ));
+        assert_eq!(registry::Resolver::<Test>::get(node), 2);
+    })
+}
+
+#[test]
+fn record_proof_test() {
+    use pns_resolvers::resolvers::Content;
+    use pns_types::ddns::codec_type::RecordType;
+    use sp_runtime::traits::BlakeTwo256;
+
+    let mut ext = new_test_ext();
+    let key = ext.execute_with(|| {
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        let node = Label::new_with_len("cupnfishxxx".as_bytes())
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        assert_ok!(Resolvers::set_record(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RecordType::A,
+            vec![127, 0, 0, 1].into(),
+        ));
+
+        Resolvers::record_key(node, RecordType::A)
+    });
+    ext.commit_all().unwrap();
+
+    let backend = ext.as_backend();
+    let root = *backend.root();
+    let proof = sp_state_machine::prove_read(backend, [&key]).unwrap();
+
+    let proven = sp_state_machine::read_proof_check::<BlakeTwo256, _>(root, proof, [&key]).unwrap();
+    assert_eq!(
+        proven.get(&key),
+        Some(&Some(Content(vec![127, 0, 0, 1]).encode()))
+    );
+}
+
+#[test]
+fn eip55_test() {
+    use pns_resolvers::resolvers::{parse_eip55, to_eip55};
+
+    let vectors: [&[u8]; 4] = [
+        b"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        b"0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        b"0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        b"0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+    for checksummed in vectors {
+        let address = parse_eip55(checksummed).unwrap();
+        assert_eq!(to_eip55(&address), checksummed.to_vec());
+
+        // addresses without checksum are accepted as they are.
+        assert_eq!(
+            parse_eip55(&checksummed.to_ascii_lowercase()),
+            Some(address)
+        );
+        assert_eq!(
+            parse_eip55(&checksummed[2..].to_ascii_uppercase()),
+            Some(address)
+        );
+    }
+
+    // a single flipped case breaks the checksum.
+    assert_eq!(
+        parse_eip55(b"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+        None
+    );
+    assert_eq!(parse_eip55(b"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA"), None);
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        let node = Label::new_with_len("cupnfishxxx".as_bytes())
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        assert_noop!(
+            Resolvers::set_ethereum_account(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                b"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD".to_vec(),
+            ),
+            pns_resolvers::resolvers::Error::<Test>::InvalidChecksum
+        );
+        assert_ok!(Resolvers::set_ethereum_account(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".to_vec(),
+        ));
+        assert_eq!(
+            Resolvers::ethereum_addresses(node),
+            vec![b"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_vec()]
+        );
+    })
+}
+
+#[test]
+fn renew_lock_test() {
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx";
+        let node = Label::new_with_len(name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        // anyone can renew by default.
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_noop!(
+            Registrar::set_renew_lock(RuntimeOrigin::signed(RICH_ACCOUNT), node, true),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::set_renew_lock(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            true
+        ));
+
+        assert_noop!(
+            Registrar::renew(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                MinRegistrationDuration::get(),
+                None
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        // operators of the owner are still allowed.
+        assert_ok!(Registry::approval_for_all(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            RICH_ACCOUNT,
+            true
+        ));
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_ok!(Registrar::set_renew_lock(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            false
+        ));
+        assert!(!registrar::RenewLocked::<Test>::contains_key(node));
+    })
+}
+
+#[test]
+fn stats_test() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Registrar::stats(), pns_types::PnsStats::default());
+
+        let names: [&[u8]; 2] = [b"cupnfishxx1", b"cupnfishxx2"];
+        for name in names {
+            assert_ok!(Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ));
+        }
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            names[0].to_vec(),
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_eq!(
+            Registrar::stats(),
+            pns_types::PnsStats {
+                registrations: 2,
+                renewals: 1,
+                active_domains: 2,
+            }
+        );
+
+        let node = Label::new_with_len(names[0])
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registry::burn(RuntimeOrigin::signed(RICH_ACCOUNT), node));
+        assert_eq!(Registrar::stats().active_domains, 1);
+        assert_eq!(Registrar::stats().registrations, 2);
+    })
+}
+
+#[test]
+fn default_ttl_test() {
+    use pns_resolvers::resolvers::DefaultTtls;
+    use pns_types::ddns::codec_type::RecordType;
+
+    new_test_ext().execute_with(|| {
+        let node = Label::new_with_len(b"cupnfishxxx")
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        // unconfigured types fall back to their defaults.
+        assert_eq!(Resolvers::record_ttl(node, RecordType::A), 300);
+        assert_eq!(Resolvers::record_ttl(node, RecordType::TXT), 86400);
+
+        assert_noop!(
+            Resolvers::set_default_ttl(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                RecordType::A,
+                Some(60)
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_ok!(Resolvers::set_default_ttl(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            RecordType::A,
+            Some(60)
+        ));
+        assert_eq!(Resolvers::record_ttl(node, RecordType::A), 60);
+        assert_eq!(Resolvers::record_ttl(node, RecordType::TXT), 86400);
+
+        assert_ok!(Resolvers::set_default_ttl(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            RecordType::A,
+            None
+        ));
+        assert!(!DefaultTtls::<Test>::contains_key(RecordType::A));
+        assert_eq!(Resolvers::record_ttl(node, RecordType::A), 300);
+    })
+}
+
+#[test]
+fn record_ttl_test() {
+    use pns_resolvers::resolvers::RecordTtls;
+    use pns_types::ddns::codec_type::RecordType;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        let node = Label::new_with_len(b"cupnfishxxx")
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        assert_ok!(Resolvers::set_record(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RecordType::A,
+            vec![127, 0, 0, 1].into(),
+        ));
+        assert_eq!(
+            Resolvers::lookup_one_with_ttl(node, RecordType::A),
+            Some((vec![127, 0, 0, 1], 300))
+        );
+
+        assert_noop!(
+            Resolvers::set_record_ttl(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                node,
+                RecordType::A,
+                Some(30)
+            ),
+            pns_resolvers::resolvers::Error::<Test>::InvalidPermission
+        );
+
+        assert_ok!(Resolvers::set_record_ttl(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RecordType::A,
+            Some(30)
+        ));
+        assert_eq!(
+            Resolvers::lookup_one_with_ttl(node, RecordType::A),
+            Some((vec![127, 0, 0, 1], 30))
+        );
+        // the ttl of a record wins over the default of its type.
+        assert_ok!(Resolvers::set_default_ttl(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            RecordType::A,
+            Some(60)
+        ));
+        assert_eq!(Resolvers::record_ttl(node, RecordType::A), 30);
+        assert_eq!(Resolvers::record_ttl(node, RecordType::AAAA), 300);
+        // other domains keep the default.
+        let other = Label::new_with_len(b"cupnfishyyy")
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_eq!(Resolvers::record_ttl(other, RecordType::A), 60);
+
+        assert_ok!(Resolvers::set_record_ttl(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RecordType::A,
+            None
+        ));
+        assert!(!RecordTtls::<Test>::contains_key(node, RecordType::A));
+        assert_eq!(Resolvers::record_ttl(node, RecordType::A), 60);
+
+        // clearing the profile drops the ttls as well.
+        assert_ok!(Resolvers::set_record_ttl(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RecordType::A,
+            Some(30)
+        ));
+        assert_ok!(Resolvers::clear_profile(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            0
+        ));
+        assert_eq!(RecordTtls::<Test>::iter_prefix(node).count(), 0);
+        assert_eq!(Resolvers::lookup_one_with_ttl(node, RecordType::A), None);
+    })
+}
+
+#[test]
+fn clear_profile_test() {
+    use pns_resolvers::resolvers::{Accounts, Records, Texts};
+    use pns_types::ddns::codec_type::RecordType;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        let node = Label::new_with_len(b"cupnfishxxx")
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        for address in [
+            Address::Id(POOR_ACCOUNT),
+            Address::Ethereum([4; 20]),
+            Address::Substrate([5; 32]),
+        ] {
+            assert_ok!(Resolvers::set_account(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                address,
+            ));
+        }
+        for kind in [TextKind::Email, TextKind::Url, TextKind::Ipfs] {
+            assert_ok!(Resolvers::set_text(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                kind,
+                b"cupnfish".to_vec().into(),
+            ));
+        }
+        for (kind, content) in [
+            (RecordType::A, vec![127, 0, 0, 1]),
+            (RecordType::TXT, b"hello pns".to_vec()),
+        ] {
+            assert_ok!(Resolvers::set_record(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                kind,
+                content.into(),
+            ));
+        }
+
+        assert_noop!(
+            Resolvers::clear_profile(RuntimeOrigin::signed(RICH_ACCOUNT), node, 3),
+            pns_resolvers::resolvers::Error::<Test>::InvalidPermission
+        );
+        assert_noop!(
+            Resolvers::clear_profile(RuntimeOrigin::signed(MONEY_ACCOUNT), node, 2),
+            pns_resolvers::resolvers::Error::<Test>::TooManyAccounts
+        );
+
+        assert_ok!(Resolvers::clear_profile(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            3
+        ));
+
+        assert_eq!(Accounts::<Test>::iter_prefix(node).count(), 0);
+        assert_eq!(Texts::<Test>::iter_prefix(node).count(), 0);
+        assert_eq!(Records::<Test>::iter_prefix(node).count(), 0);
+        assert!(Resolvers::lookup(node).is_empty());
+    })
+}
+
+#[test]
+fn availability_many_test() {
+    use pns_types::AvailabilityStatus;
+
+    new_test_ext().execute_with(|| {
+        let node = |name: &[u8]| {
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        };
+
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxx1".to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registrar::add_reserved(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node(b"cupnfishxx2")
+        ));
+        assert_ok!(Registrar::reserve_for(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node(b"cupnfishxx3"),
+            MONEY_ACCOUNT
+        ));
+
+        let names = vec![
+            b"cupnfishxx0".to_vec(),
+            b"cupnfishxx1".to_vec(),
+            b"cupnfishxx2".to_vec(),
+            b"cupnfishxx3".to_vec(),
+            b"hello".to_vec(),
+            "中文测试".as_bytes().to_vec(),
+        ];
+        assert_eq!(
+            Registrar::availability_many(DOT_BASENODE, names.clone()),
+            vec![
+                AvailabilityStatus::Available,
+                AvailabilityStatus::Registered,
+                AvailabilityStatus::Reserved,
+                AvailabilityStatus::Reserved,
+                AvailabilityStatus::Invalid,
+                AvailabilityStatus::Invalid,
+            ]
+        );
+
+        // names past their grace period and cooldown can be registered again.
+        Timestamp::set_timestamp(
+            Timestamp::now()
+                + MinRegistrationDuration::get()
+                + GracePeriod::get()
+                + ReRegistrationCooldown::get()
+                + 1,
+        );
+        assert_eq!(
+            Registrar::availability(DOT_BASENODE, b"cupnfishxx1"),
+            AvailabilityStatus::Available
+        );
+
+        let many = vec![b"cupnfishxx0".to_vec(); registrar::MAX_AVAILABILITY_QUERIES + 1];
+        assert_eq!(
+            Registrar::availability_many(DOT_BASENODE, many).len(),
+            registrar::MAX_AVAILABILITY_QUERIES
+        );
+    })
+}
+
+#[test]
+fn re_registration_cooldown_test() {
+    new_test_ext().execute_with(|| {
+        let names: [&[u8]; 2] = [b"cupnfishxx1", b"cupnfishxx2"];
+        for name in names {
+            assert_ok!(Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ));
+        }
+
+        let grace_end = Timestamp::now() + MinRegistrationDuration::get() + GracePeriod::get();
+        Timestamp::set_timestamp(grace_end + 1);
+
+        for name in names {
+            assert_noop!(
+                Registrar::register(
+                    RuntimeOrigin::signed(MONEY_ACCOUNT),
+                    DOT_BASENODE,
+                    name.to_vec(),
+                    MONEY_ACCOUNT,
+                    MinRegistrationDuration::get(),
+                    None
+                ),
+                registrar::Error::<Test>::InCooldown
+            );
+        }
+        assert_eq!(
+            Registrar::availability(DOT_BASENODE, names[1]),
+            pns_types::AvailabilityStatus::Registered
+        );
+
+        // the previous owner can take its name back.
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            names[0].to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        Timestamp::set_timestamp(grace_end + ReRegistrationCooldown::get() + 1);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            names[1].to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+    })
+}
+
+#[test]
+fn commit_reveal_test() {
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx".to_vec();
+        let secret = [7; 32];
+        let commitment = Registrar::make_commitment(DOT_BASENODE, &name, &RICH_ACCOUNT, &secret);
+        let reveal = |who: u64, secret: [u8; 32]| {
+            Registrar::reveal_register(
+                RuntimeOrigin::signed(who),
+                DOT_BASENODE,
+                name.clone(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                secret,
+                None,
+            )
+        };
+
+        assert_noop!(
+            reveal(RICH_ACCOUNT, secret),
+            registrar::Error::<Test>::CommitmentNotFound
+        );
+
+        assert_ok!(Registrar::commit(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            commitment
+        ));
+        assert_noop!(
+            Registrar::commit(RuntimeOrigin::signed(MONEY_ACCOUNT), commitment),
+            registrar::Error::<Test>::CommitmentExists
+        );
+        assert_noop!(
+            reveal(RICH_ACCOUNT, secret),
+            registrar::Error::<Test>::CommitmentTooNew
+        );
+
+        Timestamp::set_timestamp(Timestamp::now() + MinCommitmentAge::get());
+
+        // only the committer can reveal, with the same secret.
+        assert_noop!(
+            reveal(MONEY_ACCOUNT, secret),
+            registrar::Error::<Test>::CommitmentNotFound
+        );
+        assert_noop!(
+            reveal(RICH_ACCOUNT, [8; 32]),
+            registrar::Error::<Test>::CommitmentNotFound
+        );
+
+        assert_ok!(reveal(RICH_ACCOUNT, secret));
+        assert!(!registrar::Commitments::<Test>::contains_key(commitment));
+
+        let node = Label::new_with_len(&name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert!(registrar::RegistrarInfos::<Test>::contains_key(node));
+
+        // stale commitments can't be revealed.
+        let name = b"cupnfishxx2".to_vec();
+        let commitment = Registrar::make_commitment(DOT_BASENODE, &name, &RICH_ACCOUNT, &secret);
+        assert_ok!(Registrar::commit(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            commitment
+        ));
+        Timestamp::set_timestamp(Timestamp::now() + MaxCommitmentAge::get() + 1);
+        assert_noop!(
+            Registrar::reveal_register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name,
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                secret,
+                None,
+            ),
+            registrar::Error::<Test>::CommitmentTooOld
+        );
+    })
+}
+
+#[test]
+fn register_batch_test() {
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        let duration = MinRegistrationDuration::get();
+        let names = vec![
+            b"cupnfishxxx".to_vec(),
+            b"cupnfishxx2".to_vec(),
+            b"cupnfishxx3".to_vec(),
+        ];
+        let node = |name: &[u8]| {
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        };
+
+        assert_noop!(
+            Registrar::register_batch(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                Default::default(),
+                RICH_ACCOUNT,
+                duration
+            ),
+            registrar::Error::<Test>::InvalidBatchSize
+        );
+        let mut too_many = names.clone();
+        too_many.push(b"cupnfishxx4".to_vec());
+        assert!(frame_support::BoundedVec::<_, MaxBatchSize>::try_from(too_many).is_err());
+
+        let total_price = names
+            .iter()
+            .map(|name| {
+                PriceOracle::register_fee(name.len(), duration).unwrap()
+                    + PriceOracle::deposit_fee(name.len()).unwrap()
+            })
+            .sum::<Balance>();
+        let init_free = Balances::free_balance(RICH_ACCOUNT);
+
+        assert_ok!(Registrar::register_batch(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            names.clone().try_into().unwrap(),
+            MONEY_ACCOUNT,
+            duration
+        ));
+        assert_eq!(
+            Balances::free_balance(RICH_ACCOUNT),
+            init_free - total_price
+        );
+        for name in names.iter() {
+            assert!(registrar::RegistrarInfos::<Test>::contains_key(node(name)));
+            assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node(name))));
+        }
+
+        // one taken name fails the whole batch.
+        let init_free = Balances::free_balance(RICH_ACCOUNT);
+        assert!(Registrar::register_batch(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            vec![b"cupnfishxx5".to_vec(), names[0].clone()]
+                .try_into()
+                .unwrap(),
+            RICH_ACCOUNT,
+            duration
+        )
+        .is_err());
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), init_free);
+        assert!(!registrar::RegistrarInfos::<Test>::contains_key(node(
+            b"cupnfishxx5"
+        )));
+    })
+}
+
+#[test]
+fn renew_batch_test() {
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let duration = MinRegistrationDuration::get();
+        let names = vec![b"cupnfishxxx".to_vec(), b"cupnfishxx2".to_vec()];
+        assert_ok!(Registrar::register_batch(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            names.clone().try_into().unwrap(),
+            RICH_ACCOUNT,
+            duration
+        ));
+
+        assert_noop!(
+            Registrar::renew_batch(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                Default::default(),
+                duration
+            ),
+            registrar::Error::<Test>::InvalidBatchSize
+        );
+
+        let total_price = names
+            .iter()
+            .map(|name| PriceOracle::renew_fee(name.len(), duration).unwrap())
+            .sum::<Balance>();
+        let init_free = Balances::free_balance(MONEY_ACCOUNT);
+
+        assert_ok!(Registrar::renew_batch(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            names.clone().try_into().unwrap(),
+            duration
+        ));
+        assert_eq!(
+            Balances::free_balance(MONEY_ACCOUNT),
+            init_free - total_price
+        );
+        for name in names.iter() {
+            let node = Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE);
+            let expire = registrar::RegistrarInfos::<Test>::get(node).unwrap().expire;
+            assert_eq!(expire, Timestamp::now() + duration * 2);
+            System::assert_has_event(RuntimeEvent::Registrar(registrar::Event::NameRenewed {
+                name: name.clone(),
+                node,
+                duration,
+                expire,
+            }));
+        }
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::NamesRenewed {
+            nodes: names
+                .iter()
+                .map(|name| {
+                    Label::new_with_len(name)
+                        .unwrap()
+                        .0
+                        .encode_with_node(&DOT_BASENODE)
+                })
+                .collect(),
+            duration,
+            price: total_price,
+        }));
+
+        // an unregistered name fails the whole batch.
+        assert_noop!(
+            Registrar::renew_batch(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                vec![names[0].clone(), b"cupnfishxx3".to_vec()]
+                    .try_into()
+                    .unwrap(),
+                duration
+            ),
+            registrar::Error::<Test>::NotExistOrOccupied
+        );
+    })
+}
+
+#[test]
+fn register_with_config_test() {
+    use pns_resolvers::resolvers::InitialRecord;
+    use pns_types::ddns::codec_type::RecordType;
+
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx".to_vec();
+        let node = Label::new_with_len(&name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        let records = vec![
+            InitialRecord::Account(Address::Id(MONEY_ACCOUNT)),
+            InitialRecord::Text(TextKind::Email, b"cupnfish@qq.com".to_vec().into()),
+            InitialRecord::Record(RecordType::A, vec![127, 0, 0, 1].into()),
+            InitialRecord::Record(RecordType::AAAA, vec![0; 16].into()),
+        ];
+
+        let mut too_many = records.clone();
+        too_many.push(InitialRecord::Record(
+            RecordType::TXT,
+            b"hello pns".to_vec().into(),
+        ));
+        assert_noop!(
+            Registrar::register_with_config(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.clone(),
+                MONEY_ACCOUNT,
+                MinRegistrationDuration::get(),
+                too_many
+            ),
+            registrar::Error::<Test>::TooManyRecords
+        );
+
+        assert_ok!(Registrar::register_with_config(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name,
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            records
+        ));
+
+        assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node)));
+        assert!(pns_resolvers::resolvers::Accounts::<Test>::contains_key(
+            node,
+            Address::Id(MONEY_ACCOUNT)
+        ));
+        assert_eq!(
+            pns_resolvers::resolvers::Texts::<Test>::get(node, TextKind::Email).0,
+            b"cupnfish@qq.com".to_vec()
+        );
+        assert_eq!(
+            Resolvers::lookup_one(node, RecordType::A),
+            Some(vec![127, 0, 0, 1])
+        );
+        assert_eq!(
+            Resolvers::lookup_one(node, RecordType::AAAA),
+            Some(vec![0; 16])
+        );
+    })
+}
+
+#[test]
+fn auto_renew_test() {
+    use frame_support::traits::Hooks;
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"cupnfishxxx".to_vec();
+        let duration = MinRegistrationDuration::get();
+        let node = Label::new_with_len(&name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        let price = PriceOracle::renew_fee(name.len(), duration).unwrap();
+        let run_hook = || Registrar::on_idle(1, frame_support::weights::Weight::MAX);
+
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.clone(),
+            MONEY_ACCOUNT,
+            duration,
+            None
+        ));
+        assert_noop!(
+            Registrar::enable_auto_renew(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.clone(),
+                duration,
+                price
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::enable_auto_renew(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            name.clone(),
+            duration,
+            price
+        ));
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), price);
+        assert_noop!(
+            Registrar::top_up(RuntimeOrigin::signed(RICH_ACCOUNT), node, price),
+            registrar::Error::<Test>::NotOwned
+        );
+        assert_ok!(Registrar::top_up(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            price / 2
+        ));
+
+        let expire = registrar::RegistrarInfos::<Test>::get(node).unwrap().expire;
+        // not due yet.
+        run_hook();
+        assert_eq!(
+            registrar::RegistrarInfos::<Test>::get(node).unwrap().expire,
+            expire
+        );
+
+        Timestamp::set_timestamp(expire - AutoRenewWindow::get());
+        run_hook();
+        let renewed = registrar::RegistrarInfos::<Test>::get(node).unwrap().expire;
+        assert_eq!(renewed, expire + duration);
+        assert_eq!(
+            registrar::AutoRenewals::<Test>::get(node).unwrap().pot,
+            price / 2
+        );
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), price / 2);
+
+        // the pot can't pay for another renewal, it is returned.
+        Timestamp::set_timestamp(renewed - AutoRenewWindow::get());
+        run_hook();
+        assert_eq!(
+            registrar::RegistrarInfos::<Test>::get(node).unwrap().expire,
+            renewed
+        );
+        assert!(!registrar::AutoRenewals::<Test>::contains_key(node));
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), 0);
+        System::assert_last_event(RuntimeEvent::Registrar(
+            registrar::Event::AutoRenewCancelled {
+                node,
+                refunded: price / 2,
+            },
+        ));
+
+        assert_ok!(Registrar::enable_auto_renew(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            name,
+            duration,
+            price
+        ));
+        assert_ok!(Registrar::cancel_auto_renew(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node
+        ));
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), 0);
+    })
+}
+
+#[test]
+fn max_registration_duration_test() {
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx".to_vec();
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.clone(),
+                RICH_ACCOUNT,
+                MaxRegistrationDuration::get() + 1,
+                None
+            ),
+            registrar::Error::<Test>::RegistryDurationTooLong
+        );
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.clone(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        // renewals can't push the expiry further out either.
+        assert_noop!(
+            Registrar::renew(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.clone(),
+                MaxRegistrationDuration::get() - MinRegistrationDuration::get() + 1,
+                None
+            ),
+            registrar::Error::<Test>::RegistryDurationTooLong
+        );
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name,
+            MinRegistrationDuration::get(),
+            None
+        ));
+    })
+}
+
+#[test]
+fn expired_name_premium_test() {
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx".to_vec();
+        let duration = MinRegistrationDuration::get();
+        let base = PriceOracle::registration_fee(name.len()).unwrap();
+        let start = base * PremiumMultiplier::get() as Balance;
+
+        assert_eq!(PriceOracle::premium(name.len(), 0), Some(start));
+        assert_eq!(
+            PriceOracle::premium(name.len(), PremiumDuration::get() / 2),
+            Some(start / 2)
+        );
+        assert_eq!(
+            PriceOracle::premium(name.len(), PremiumDuration::get()),
+            Some(0)
+        );
+
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.clone(),
+            RICH_ACCOUNT,
+            duration,
+            None
+        ));
+
+        // right after the grace period the premium is almost untouched.
+        let grace_end = Timestamp::now() + duration + GracePeriod::get();
+        Timestamp::set_timestamp(grace_end + 1);
+        let price = PriceOracle::register_fee(name.len(), duration).unwrap()
+            + PriceOracle::premium(name.len(), 1).unwrap();
+        let init_free = Balances::free_balance(RICH_ACCOUNT);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.clone(),
+            RICH_ACCOUNT,
+            duration,
+            None
+        ));
+        // the deposit is paid again and the old one returned.
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), init_free - price);
+
+        // once it has decayed the base price is all that is left.
+        let grace_end = Timestamp::now() + duration + GracePeriod::get();
+        Timestamp::set_timestamp(grace_end + PremiumDuration::get());
+        let price = PriceOracle::register_fee(name.len(), duration).unwrap()
+            + PriceOracle::deposit_fee(name.len()).unwrap();
+        let init_free = Balances::free_balance(MONEY_ACCOUNT);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            name,
+            MONEY_ACCOUNT,
+            duration,
+            None
+        ));
+        assert_eq!(Balances::free_balance(MONEY_ACCOUNT), init_free - price);
+    })
+}
+
+#[test]
+fn grace_renewal_surcharge_test() {
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx".to_vec();
+        let duration = MinRegistrationDuration::get();
+        let price = PriceOracle::renew_fee(name.len(), duration).unwrap();
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.clone(),
+            RICH_ACCOUNT,
+            duration,
+            None
+        ));
+        let official = Balances::free_balance(OFFICIAL_ACCOUNT);
+
+        // on time renewals pay the plain price.
+        let init_free = Balances::free_balance(RICH_ACCOUNT);
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.clone(),
+            duration,
+            None
+        ));
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), init_free - price);
+
+        let node = Label::new_with_len(&name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        let expire = registrar::RegistrarInfos::<Test>::get(node).unwrap().expire;
+        Timestamp::set_timestamp(expire + 1);
+
+        let init_free = Balances::free_balance(RICH_ACCOUNT);
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name,
+            duration,
+            None
+        ));
+        let surcharge = GraceRenewalSurcharge::get() * price;
+        assert_eq!(
+            Balances::free_balance(RICH_ACCOUNT),
+            init_free - price - surcharge
+        );
+        assert_eq!(
+            Balances::free_balance(OFFICIAL_ACCOUNT),
+            official + price * 2 + surcharge
+        );
+    })
+}
+
+#[test]
+fn expiry_warning_test() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let duration = MinRegistrationDuration::get();
+        let names: [&[u8]; 3] = [b"cupnfishxx1", b"cupnfishxx2", b"cupnfishxx3"];
+        let node = |name: &[u8]| {
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        };
+        for name in names {
+            assert_ok!(Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                duration,
+                None
+            ));
+        }
+        let expire = registrar::RegistrarInfos::<Test>::get(node(names[0]))
+            .unwrap()
+            .expire;
+        let bucket = Registrar::expiry_bucket(expire);
+
+        // renewals move the domain to a later bucket.
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            names[2].to_vec(),
+            duration,
+            None
+        ));
+        assert!(!registrar::ExpiringAt::<Test>::contains_key(
+            bucket,
+            node(names[2])
+        ));
+        assert!(registrar::ExpiringAt::<Test>::contains_key(
+            Registrar::expiry_bucket(expire + duration),
+            node(names[2])
+        ));
+
+        let warnings = || {
+            System::events()
+                .into_iter()
+                .filter(|record| {
+                    matches!(
+                        record.event,
+                        RuntimeEvent::Registrar(registrar::Event::NameExpiringSoon { .. })
+                    )
+                })
+                .count()
+        };
+
+        Registrar::on_initialize(1);
+        assert_eq!(warnings(), 0);
+
+        Timestamp::set_timestamp(expire - ExpiryWarningPeriod::get());
+        Registrar::on_initialize(2);
+        assert_eq!(warnings(), 2);
+        System::assert_has_event(RuntimeEvent::Registrar(
+            registrar::Event::NameExpiringSoon {
+                node: node(names[0]),
+                expire,
+            },
+        ));
+        assert_eq!(
+            registrar::ExpiringAt::<Test>::iter_prefix(bucket).count(),
+            0
+        );
+
+        // each domain is only warned about once.
+        Registrar::on_initialize(3);
+        assert_eq!(warnings(), 2);
+    })
+}
+
+#[test]
+fn reap_expired_test() {
+    use frame_support::traits::Hooks;
+    use pns_types::ddns::codec_type::RecordType;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let duration = MinRegistrationDuration::get();
+        let node = |name: &[u8]| {
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        };
+        let (reaped, swept) = (b"cupnfishxx1".to_vec(), b"cupnfishxx2".to_vec());
+        assert_ok!(Registrar::register_with_config(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            reaped.clone(),
+            MONEY_ACCOUNT,
+            duration,
+            vec![pns_resolvers::resolvers::InitialRecord::Record(
+                RecordType::A,
+                vec![127, 0, 0, 1].into()
+            )]
+        ));
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            swept.clone(),
+            MONEY_ACCOUNT,
+            duration,
+            None
+        ));
+        let info = registrar::RegistrarInfos::<Test>::get(node(&reaped)).unwrap();
+        let grace_end = info.expire + GracePeriod::get();
+
+        Timestamp::set_timestamp(grace_end);
+        assert_noop!(
+            Registrar::reap_expired(RuntimeOrigin::signed(RICH_ACCOUNT), node(&reaped)),
+            registrar::Error::<Test>::Occupied
+        );
+        // the previous owner may still register it again.
+        Timestamp::set_timestamp(grace_end + 1);
+        assert_noop!(
+            Registrar::reap_expired(RuntimeOrigin::signed(RICH_ACCOUNT), node(&reaped)),
+            registrar::Error::<Test>::InCooldown
+        );
+
+        Timestamp::set_timestamp(grace_end + ReRegistrationCooldown::get() + 1);
+        let rich = Balances::free_balance(RICH_ACCOUNT);
+        let money = Balances::free_balance(MONEY_ACCOUNT);
+        assert_ok!(Registrar::reap_expired(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node(&reaped)
+        ));
+        let reward = ReapReward::get() * info.deposit;
+        assert!(reward > 0);
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), rich + reward);
+        assert_eq!(
+            Balances::free_balance(MONEY_ACCOUNT),
+            money + info.deposit - reward
+        );
+        assert!(!registrar::RegistrarInfos::<Test>::contains_key(node(
+            &reaped
+        )));
+        assert!(!Nft::is_owner(&MONEY_ACCOUNT, (0, node(&reaped))));
+        assert_eq!(Resolvers::lookup_one(node(&reaped), RecordType::A), None);
+        System::assert_has_event(RuntimeEvent::Registrar(registrar::Event::NameReaped {
+            node: node(&reaped),
+            reaper: RICH_ACCOUNT,
+            reward,
+        }));
+
+        // the sweeper reaps the other one over the next blocks, returning the whole deposit.
+        let deposit = registrar::RegistrarInfos::<Test>::get(node(&swept))
+            .unwrap()
+            .deposit;
+        let money = Balances::free_balance(MONEY_ACCOUNT);
+        for n in 0..registrar::RegistrarInfos::<Test>::iter().count() as u64 {
+            Registrar::on_idle(n, frame_support::weights::Weight::MAX);
+        }
+        assert!(!registrar::RegistrarInfos::<Test>::contains_key(node(
+            &swept
+        )));
+        assert!(!Nft::is_owner(&MONEY_ACCOUNT, (0, node(&swept))));
+        assert_eq!(Balances::free_balance(MONEY_ACCOUNT), money + deposit);
+    })
+}
+
+#[test]
+fn buy_capacity_test() {
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx".to_vec();
+        let node = Label::new_with_len(&name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name,
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_noop!(
+            Registrar::buy_capacity(RuntimeOrigin::signed(RICH_ACCOUNT), node, 5),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Registrar::buy_capacity(RuntimeOrigin::signed(MONEY_ACCOUNT), node, 0),
+            registrar::Error::<Test>::InvalidCapacity
+        );
+        assert_noop!(
+            Registrar::buy_capacity(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                MaxCapacity::get() - DefaultCapacity::get() + 1
+            ),
+            registrar::Error::<Test>::InvalidCapacity
+        );
+
+        let fee = PriceOracle::capacity_fee(5).unwrap();
+        assert!(fee > 0);
+        let balance = Balances::free_balance(MONEY_ACCOUNT);
+        assert_ok!(Registrar::buy_capacity(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            5
+        ));
+        assert_eq!(Balances::free_balance(MONEY_ACCOUNT), balance - fee);
+        let capacity = registrar::RegistrarInfos::<Test>::get(node)
+            .unwrap()
+            .capacity;
+        assert_eq!(capacity, DefaultCapacity::get() + 5);
+
+        for i in 0..capacity {
+            assert_ok!(Registrar::mint_subname(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                format!("sub{i}").into_bytes(),
+                MONEY_ACCOUNT
+            ));
+        }
+        assert_noop!(
+            Registrar::mint_subname(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                b"onemore".to_vec(),
+                MONEY_ACCOUNT
+            ),
+            registry::Error::<Test>::CapacityNotEnough
+        );
+    })
+}
+
+#[test]
+fn claim_reserved_test() {
+    new_test_ext().execute_with(|| {
+        let duration = MinRegistrationDuration::get();
+        let window = 24 * 60 * 60;
+        let node = |name: &[u8]| {
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        };
+        let (claimed, lapsed) = (b"cupnfishxx1".to_vec(), b"cupnfishxx2".to_vec());
+
+        assert_noop!(
+            Registrar::assign_reserved_claim(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                node(&claimed),
+                MONEY_ACCOUNT,
+                window
+            ),
+            registrar::Error::<Test>::NotReserved
+        );
+        for name in [&claimed, &lapsed] {
+            assert_ok!(Registrar::add_reserved(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                node(name)
+            ));
+            assert_noop!(
+                Registrar::assign_reserved_claim(
+                    RuntimeOrigin::signed(RICH_ACCOUNT),
+                    node(name),
+                    MONEY_ACCOUNT,
+                    window
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Registrar::assign_reserved_claim(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                node(name),
+                MONEY_ACCOUNT,
+                window
+            ));
+        }
+
+        // the name stays reserved for everyone else.
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                claimed.clone(),
+                RICH_ACCOUNT,
+                duration,
+                None
+            ),
+            registrar::Error::<Test>::Frozen
+        );
+        assert_noop!(
+            Registrar::claim_reserved(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                claimed.clone(),
+                duration
+            ),
+            registrar::Error::<Test>::NoReservedClaim
+        );
+
+        let balance = Balances::free_balance(MONEY_ACCOUNT);
+        assert_ok!(Registrar::claim_reserved(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            claimed.clone(),
+            duration
+        ));
+        assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node(&claimed))));
+        assert!(Balances::free_balance(MONEY_ACCOUNT) < balance);
+        assert!(!registrar::ReservedList::<Test>::contains_key(node(
+            &claimed
+        )));
+        assert!(!registrar::ReservedClaims::<Test>::contains_key(node(
+            &claimed
+        )));
+
+        Timestamp::set_timestamp(window + 1);
+        assert_noop!(
+            Registrar::claim_reserved(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                lapsed.clone(),
+                duration
+            ),
+            registrar::Error::<Test>::ReservedClaimExpired
+        );
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                lapsed,
+                MONEY_ACCOUNT,
+                duration,
+                None
+            ),
+            registrar::Error::<Test>::Frozen
+        );
+    })
+}
+
+#[test]
+fn reservation_rule_test() {
+    use registrar::ReservationRule;
+
+    new_test_ext().execute_with(|| {
+        let duration = MinRegistrationDuration::get();
+        let register = |name: &[u8]| {
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                duration,
+                None,
+            )
+        };
+        let prefix = ReservationRule::Prefix(b"polka".to_vec().try_into().unwrap());
+        let short = ReservationRule::Length { min: 3, max: 5 };
+
+        assert_noop!(
+            Registrar::add_reservation_rule(RuntimeOrigin::signed(RICH_ACCOUNT), prefix.clone()),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        for rule in [prefix.clone(), short.clone()] {
+            assert_ok!(Registrar::add_reservation_rule(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                rule
+            ));
+        }
+        assert_noop!(
+            Registrar::add_reservation_rule(RuntimeOrigin::signed(MANAGER_ACCOUNT), short.clone()),
+            registrar::Error::<Test>::ReservationRuleExists
+        );
+
+        assert_noop!(register(b"polkadotxxx"), registrar::Error::<Test>::Frozen);
+        assert_noop!(register(b"abcd"), registrar::Error::<Test>::Frozen);
+        assert_eq!(
+            Registrar::availability(DOT_BASENODE, b"polkadotxxx"),
+            pns_types::AvailabilityStatus::Reserved
+        );
+        assert_ok!(register(b"cupnfishxxx"));
+
+        assert_ok!(Registrar::remove_reservation_rule(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            prefix.clone()
+        ));
+        assert_noop!(
+            Registrar::remove_reservation_rule(RuntimeOrigin::signed(MANAGER_ACCOUNT), prefix),
+            registrar::Error::<Test>::ReservationRuleNotFound
+        );
+        assert_ok!(register(b"polkadotxxx"));
+        assert_noop!(register(b"abcd"), registrar::Error::<Test>::Frozen);
+
+        for max in 6..=7 {
+            assert_ok!(Registrar::add_reservation_rule(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                ReservationRule::Length { min: max, max }
+            ));
+        }
+        assert_noop!(
+            Registrar::add_reservation_rule(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                ReservationRule::Length { min: 8, max: 8 }
+            ),
+            registrar::Error::<Test>::TooManyReservationRules
+        );
+    })
+}
+
+#[test]
+fn multi_tld_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"cupnfishxx1".to_vec();
+        let ksm = Label::new_basenode(b"ksm").unwrap().node;
+        let config = pns_types::TldConfig {
+            grace_period: 30 * DAYS,
+            capacity: 5,
+            min_duration: 2 * MinRegistrationDuration::get(),
+            open: true,
+        };
+
+        assert_noop!(
+            Registrar::set_tld_config(RuntimeOrigin::signed(MANAGER_ACCOUNT), ksm, config.clone()),
+            registrar::Error::<Test>::UnknownBaseNode
+        );
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                ksm,
+                name.clone(),
+                RICH_ACCOUNT,
+                config.min_duration,
+                None
+            ),
+            registrar::Error::<Test>::UnknownBaseNode
+        );
+
+        assert_ok!(Registrar::create_tld(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            b"ksm".to_vec(),
+            config.clone()
+        ));
+
+        // the same label can be registered under both base nodes, each with its own settings.
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                ksm,
+                name.clone(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ),
+            registrar::Error::<Test>::RegistryDurationInvalid
+        );
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.clone(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            ksm,
+            name.clone(),
+            MONEY_ACCOUNT,
+            config.min_duration,
+            None
+        ));
+
+        let (label, _) = Label::new_with_len(&name).unwrap();
+        let dot_node = label.encode_with_node(&DOT_BASENODE);
+        let ksm_node = label.encode_with_node(&ksm);
+        assert_eq!(Registrar::tld_of(ksm_node), Some(config.clone()));
+        assert_eq!(
+            registrar::RegistrarInfos::<Test>::get(ksm_node)
+                .unwrap()
+                .capacity,
+            5
+        );
+        assert_eq!(
+            registrar::RegistrarInfos::<Test>::get(dot_node)
+                .unwrap()
+                .capacity,
+            DefaultCapacity::get()
+        );
+
+        // closing one base node leaves the others open.
+        let closed = pns_types::TldConfig {
+            open: false,
+            ..config
+        };
+        assert_ok!(Registrar::set_tld_config(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            ksm,
+            closed.clone()
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(
+            registrar::Event::TldConfigChanged {
+                base_node: ksm,
+                config: closed.clone(),
+            },
+        ));
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                ksm,
+                b"cupnfishxx2".to_vec(),
+                MONEY_ACCOUNT,
+                closed.min_duration,
+                None
+            ),
+            registrar::Error::<Test>::TldClosed
+        );
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxx2".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+    });
+}
+
+#[test]
+fn create_tld_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let ksm = Label::new_basenode(b"ksm").unwrap().node;
+        let config = pns_types::TldConfig {
+            grace_period: 30 * DAYS,
+            capacity: 5,
+            min_duration: MinRegistrationDuration::get(),
+            open: true,
+        };
+
+        assert_noop!(
+            Registrar::create_tld(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                b"ksm".to_vec(),
+                config.clone()
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Registrar::create_tld(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                b"dot".to_vec(),
+                config.clone()
+            ),
+            registrar::Error::<Test>::TldExists
+        );
+        assert_noop!(
+            Registrar::create_tld(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                b"k.s".to_vec(),
+                config.clone()
+            ),
+            registrar::Error::<Test>::ParseLabelFailed
+        );
+
+        assert_ok!(Registrar::create_tld(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            b"ksm".to_vec(),
+            config.clone()
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::TldCreated {
+            label: b"ksm".to_vec(),
+            base_node: ksm,
+            config: config.clone(),
+        }));
+        assert_eq!(
+            nft::Pallet::<Test>::tokens(0, ksm).map(|token| token.owner),
+            Some(OFFICIAL_ACCOUNT)
+        );
+        assert_eq!(registrar::BaseNodes::<Test>::get(ksm), Some(config.clone()));
+        assert_eq!(
+            registrar::RegistrarInfos::<Test>::get(ksm),
+            Some(pns_types::RegistrarInfo {
+                expire: u64::MAX,
+                capacity: 5,
+                deposit: 0,
+                register_fee: 0,
+            })
+        );
+        assert_eq!(registrar::ActiveDomains::<Test>::get(), 0);
+        assert_eq!(Registrar::tlds(), vec![b"ksm".to_vec()]);
+        assert_noop!(
+            Registrar::create_tld(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                b"ksm".to_vec(),
+                config
+            ),
+            registrar::Error::<Test>::TldExists
+        );
+
+        // base nodes can't be burned.
+        assert_noop!(
+            Registry::burn(RuntimeOrigin::signed(OFFICIAL_ACCOUNT), ksm),
+            registry::Error::<Test>::BanBurnBaseNode
+        );
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            ksm,
+            b"cupnfishxx1".to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+    });
+}
+
+#[test]
+fn whitelist_phase_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let duration = MinRegistrationDuration::get();
+        let register = |who: AccountId, name: &[u8]| {
+            Registrar::register(
+                RuntimeOrigin::signed(who),
+                DOT_BASENODE,
+                name.to_vec(),
+                who,
+                duration,
+                None,
+            )
+        };
+
+        assert_noop!(
+            Registrar::set_phase(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                registrar::RegistrationPhase::Closed
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Registrar::set_phase(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            registrar::RegistrationPhase::Closed
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::PhaseChanged {
+            phase: registrar::RegistrationPhase::Closed,
+        }));
+        assert_noop!(
+            register(RICH_ACCOUNT, b"cupnfishxx1"),
+            registrar::Error::<Test>::RegistrarClosed
+        );
+
+        assert_ok!(Registrar::set_phase(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            registrar::RegistrationPhase::Whitelist
+        ));
+        assert_noop!(
+            register(RICH_ACCOUNT, b"cupnfishxx1"),
+            registrar::Error::<Test>::NotWhitelisted
+        );
+        assert_ok!(Registrar::add_whitelisted(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            RICH_ACCOUNT
+        ));
+        assert_ok!(register(RICH_ACCOUNT, b"cupnfishxx1"));
+
+        // a voucher only works for the account it was signed for.
+        let voucher = TestSignature(OFFICIAL_ACCOUNT, Registrar::voucher_payload(&MONEY_ACCOUNT));
+        assert_noop!(
+            Registrar::redeem_voucher(RuntimeOrigin::signed(RICH_ACCOUNT), voucher.clone()),
+            registrar::Error::<Test>::InvalidVoucher
+        );
+        assert_noop!(
+            Registrar::redeem_voucher(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                TestSignature(MONEY_ACCOUNT, Registrar::voucher_payload(&MONEY_ACCOUNT))
+            ),
+            registrar::Error::<Test>::InvalidVoucher
+        );
+        assert_ok!(Registrar::redeem_voucher(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            voucher
+        ));
+        assert_ok!(register(MONEY_ACCOUNT, b"cupnfishxx2"));
+
+        assert_ok!(Registrar::remove_whitelisted(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            RICH_ACCOUNT
+        ));
+        assert_noop!(
+            register(RICH_ACCOUNT, b"cupnfishxx3"),
+            registrar::Error::<Test>::NotWhitelisted
+        );
+        // renewals are not limited by the phase.
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxx1".to_vec(),
+            duration,
+            None
+        ));
+
+        assert_ok!(Registrar::set_phase(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            registrar::RegistrationPhase::Open
+        ));
+        assert_ok!(register(RICH_ACCOUNT, b"cupnfishxx3"));
+    });
+}
+
+#[test]
+fn registration_quota_test() {
+    new_test_ext().execute_with(|| {
+        let register = |name: String| {
+            Registrar::register(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                name.into_bytes(),
+                MONEY_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None,
+            )
+        };
+
+        for i in 0..RegistrationQuota::get() {
+            assert_ok!(register(format!("cupnfishxq{i}")));
+        }
+        assert_noop!(
+            register(String::from("cupnfishxqq")),
+            registrar::Error::<Test>::QuotaExceeded
+        );
+        // the quota of other accounts is not touched.
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxqq".to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        Timestamp::set_timestamp(Timestamp::now() + RegistrationQuotaWindow::get());
+        assert_ok!(register(String::from("cupnfishxqz")));
+        assert_eq!(
+            registrar::RecentRegistrations::<Test>::get(MONEY_ACCOUNT).len(),
+            1
+        );
+    });
+}
+
+#[test]
+fn fee_split_test() {
+    use frame_support::traits::Currency as _;
+    use sp_runtime::Perbill;
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        FeeBurnShare::set(Perbill::from_percent(10));
+        FeeTreasuryShare::set(Perbill::from_percent(20));
+
+        let name = b"cupnfishxxx";
+        let fee = PriceOracle::register_fee(name.len(), MinRegistrationDuration::get()).unwrap();
+        let deposit = PriceOracle::deposit_fee(name.len()).unwrap();
+        let issuance = Balances::total_issuance();
+
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        let burned = Perbill::from_percent(10) * fee;
+        let to_treasury = Perbill::from_percent(20) * fee;
+        assert_eq!(Balances::free_balance(TREASURY_ACCOUNT), to_treasury);
+        // the deposit is never split.
+        assert_eq!(
+            Balances::total_balance(&OFFICIAL_ACCOUNT),
+            fee + deposit - burned - to_treasury
+        );
+        assert_eq!(Balances::total_issuance(), issuance - burned);
+    });
+}
+
+#[test]
+fn idn_label_test() {
+    use crate::traits::normalize_label;
+    use pns_types::AvailabilityStatus;
+
+    assert_eq!(
+        normalize_label("中文测试".as_bytes()).unwrap(),
+        b"xn--fiq228copfr97a".to_vec()
+    );
+    assert_eq!(
+        normalize_label("Bücher".as_bytes()).unwrap(),
+        b"xn--bcher-kva".to_vec()
+    );
+    assert_eq!(normalize_label(b"CupNfish").unwrap(), b"cupnfish".to_vec());
+    assert_eq!(
+        normalize_label("😀".as_bytes()).unwrap(),
+        b"xn--e28h".to_vec()
+    );
+    // japanese may mix kana and han, latin may not be mixed with cyrillic or greek.
+    assert!(normalize_label("日本語かなカナ".as_bytes()).is_some());
+    assert!(normalize_label("pаypal".as_bytes()).is_none());
+    assert!(normalize_label("αpple".as_bytes()).is_none());
+    // combining marks are not normalized, only precomposed letters are valid.
+    assert!(normalize_label("cafe\u{301}".as_bytes()).is_none());
+    // only the canonical punycode of a valid label.
+    assert!(normalize_label(b"xn--bcher-kvaz").is_none());
+    assert!(normalize_label(b"xn--BCHER-KVA").is_some());
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            "中文测试".as_bytes().to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        let (label, _) = Label::new_with_len(b"xn--fiq228copfr97a").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert!(registrar::RegistrarInfos::<Test>::contains_key(node));
+        assert_eq!(
+            Registrar::availability(DOT_BASENODE, b"xn--fiq228copfr97a"),
+            AvailabilityStatus::Registered
+        );
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                b"xn--fiq228copfr97a".to_vec(),
+                MONEY_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ),
+            registrar::Error::<Test>::Occupied
+        );
+    });
+}
+
+#[test]
+fn label_policy_test() {
+    new_test_ext().execute_with(|| {
+        let register = |name: &str| {
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.as_bytes().to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None,
+            )
+        };
+
+        assert_noop!(
+            register("cu-pnfishxx"),
+            registrar::Error::<Test>::ParseLabelFailed
+        );
+        StrictHyphens::set(false);
+        assert_ok!(register("cu-pnfishxx"));
+
+        Internationalized::set(false);
+        assert_noop!(
+            register("中文测试"),
+            registrar::Error::<Test>::ParseLabelFailed
+        );
+        assert_noop!(
+            register("xn--fiq228copfr97a"),
+            registrar::Error::<Test>::ParseLabelFailed
+        );
+        assert_eq!(
+            Registrar::availability(DOT_BASENODE, "中文测试".as_bytes()),
+            pns_types::AvailabilityStatus::Invalid
+        );
+
+        LabelMaxLen::set(10);
+        assert_noop!(
+            register("cupnfishxxx"),
+            registrar::Error::<Test>::ParseLabelFailed
+        );
+        assert_ok!(register("cupnfishxx"));
+    });
+}
+
+#[test]
+fn transfer_and_reset_test() {
+    use pns_types::ddns::codec_type::RecordType;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register_with_config(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            vec![pns_resolvers::resolvers::InitialRecord::Record(
+                RecordType::A,
+                vec![127, 0, 0, 1].into()
+            )]
+        ));
+        assert_ok!(Registry::approve(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            POOR_ACCOUNT,
+            node,
+            true
+        ));
+        assert_ok!(Registry::set_resolver(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            7
+        ));
+
+        // a plain transfer keeps the records, but not the approvals.
+        assert_ok!(Registrar::transfer(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            RICH_ACCOUNT,
+            node
+        ));
+        assert!(Resolvers::lookup_one(node, RecordType::A).is_some());
+        assert!(!registry::TokenApprovals::<Test>::contains_key(
+            node,
+            POOR_ACCOUNT
+        ));
+        assert_ok!(Registry::approve(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            POOR_ACCOUNT,
+            node,
+            true
+        ));
+
+        assert_noop!(
+            Registrar::transfer_and_reset(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                MONEY_ACCOUNT,
+                node
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::transfer_and_reset(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            MONEY_ACCOUNT,
+            node
+        ));
+        assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node)));
+        assert_eq!(Resolvers::lookup_one(node, RecordType::A), None);
+        assert_eq!(
+            registry::Resolver::<Test>::get(node),
+            DefaultResolver::get()
+        );
+        assert!(!registry::TokenApprovals::<Test>::contains_key(
+            node,
+            POOR_ACCOUNT
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::TransferReset {
+            node,
+            to: MONEY_ACCOUNT,
+        }));
+    });
+}
+
+#[test]
+fn transfer_offer_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_noop!(
+            Registrar::offer_transfer(RuntimeOrigin::signed(RICH_ACCOUNT), node, RICH_ACCOUNT),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::offer_transfer(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RICH_ACCOUNT
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::TransferOffered {
+            node,
+            from: MONEY_ACCOUNT,
+            to: RICH_ACCOUNT,
+            deadline: 1 + TransferOfferExpiry::get(),
+        }));
+        // nothing moves until the offer is accepted, and only by its recipient.
+        assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node)));
+        assert_noop!(
+            Registrar::accept_transfer(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            registrar::Error::<Test>::NoTransferOffer
+        );
+
+        System::set_block_number(2 + TransferOfferExpiry::get());
+        assert_noop!(
+            Registrar::accept_transfer(RuntimeOrigin::signed(RICH_ACCOUNT), node),
+            registrar::Error::<Test>::TransferOfferExpired
+        );
+
+        assert_ok!(Registrar::offer_transfer(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RICH_ACCOUNT
+        ));
+        assert_ok!(Registrar::accept_transfer(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node
+        ));
+        assert!(Nft::is_owner(&RICH_ACCOUNT, (0, node)));
+        assert!(!registrar::TransferOffers::<Test>::contains_key(node));
+        System::assert_last_event(RuntimeEvent::Registrar(
+            registrar::Event::TransferAccepted {
+                node,
+                from: MONEY_ACCOUNT,
+                to: RICH_ACCOUNT,
+            },
+        ));
+
+        // only the permission of the domain can withdraw an offer.
+        assert_ok!(Registrar::offer_transfer(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            POOR_ACCOUNT
+        ));
+        assert_noop!(
+            Registrar::cancel_transfer_offer(RuntimeOrigin::signed(MONEY_ACCOUNT), node),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::cancel_transfer_offer(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node
+        ));
+        assert_noop!(
+            Registrar::accept_transfer(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            registrar::Error::<Test>::NoTransferOffer
+        );
+    });
+}
+
+#[test]
+fn transfer_batch_test() {
+    new_test_ext().execute_with(|| {
+        let node = |name: &[u8]| {
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        };
+        let names = [b"cupnfishxx1", b"cupnfishxx2", b"cupnfishxx3"];
+        for name in names {
+            assert_ok!(Registrar::register(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                MONEY_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ));
+        }
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxx4".to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        // one domain of someone else fails the whole batch.
+        assert_noop!(
+            Registrar::transfer_batch(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                POOR_ACCOUNT,
+                vec![node(names[0]), node(b"cupnfishxx4")]
+                    .try_into()
+                    .unwrap()
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+
+        assert_ok!(Registrar::transfer_batch(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            POOR_ACCOUNT,
+            names
+                .iter()
+                .map(|name| node(*name))
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap()
+        ));
+        for name in names {
+            assert!(Nft::is_owner(&POOR_ACCOUNT, (0, node(name))));
+        }
+    });
+}
+
+#[test]
+fn transfer_lock_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registry::approval_for_all(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            POOR_ACCOUNT,
+            true
+        ));
+
+        let until = Timestamp::now() + DAYS;
+        // operators can't lock, only the owner.
+        assert_noop!(
+            Registrar::lock(RuntimeOrigin::signed(POOR_ACCOUNT), node, until),
+            registrar::Error::<Test>::NotOwned
+        );
+        assert_noop!(
+            Registrar::lock(RuntimeOrigin::signed(MONEY_ACCOUNT), node, Timestamp::now()),
+            registrar::Error::<Test>::LockEndInPast
+        );
+        assert_ok!(Registrar::lock(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            until
+        ));
+        assert_eq!(Registrar::transfer_lock(node), Some(until));
+
+        assert_noop!(
+            Registrar::transfer(RuntimeOrigin::signed(POOR_ACCOUNT), RICH_ACCOUNT, node),
+            registrar::Error::<Test>::Locked
+        );
+        assert_noop!(
+            Registry::approve(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                RICH_ACCOUNT,
+                node,
+                true
+            ),
+            registrar::Error::<Test>::Locked
+        );
+        assert_noop!(
+            Registrar::unlock(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            registrar::Error::<Test>::NotOwned
+        );
+
+        // the lock ends by itself.
+        Timestamp::set_timestamp(until);
+        assert_eq!(Registrar::transfer_lock(node), None);
+        assert_ok!(Registrar::transfer(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            RICH_ACCOUNT,
+            node
+        ));
+
+        // or early by the owner.
+        assert_ok!(Registrar::lock(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            until + DAYS
+        ));
+        assert_ok!(Registrar::unlock(RuntimeOrigin::signed(RICH_ACCOUNT), node));
+        assert_ok!(Registrar::transfer(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            MONEY_ACCOUNT,
+            node
+        ));
+    });
+}
+
+#[test]
+fn subname_lease_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        let (sub_label, _) = Label::new_with_len(b"shop").unwrap();
+        let subnode = sub_label.encode_with_node(&node);
+
+        assert_noop!(
+            Registrar::offer_subname_lease(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                node,
+                b"shop".to_vec(),
+                RICH_ACCOUNT,
+                DAYS,
+                100
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::offer_subname_lease(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"shop".to_vec(),
+            RICH_ACCOUNT,
+            DAYS,
+            100
+        ));
+        assert_noop!(
+            Registrar::accept_subname_lease(RuntimeOrigin::signed(POOR_ACCOUNT), subnode),
+            registrar::Error::<Test>::NoLeaseOffer
+        );
+
+        let rich_free = Balances::free_balance(RICH_ACCOUNT);
+        let money_free = Balances::free_balance(MONEY_ACCOUNT);
+        assert_ok!(Registrar::accept_subname_lease(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            subnode
+        ));
+        assert!(Nft::is_owner(&RICH_ACCOUNT, (0, subnode)));
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), rich_free - 100);
+        assert_eq!(Balances::free_balance(MONEY_ACCOUNT), money_free + 100);
+        let expire = Timestamp::now() + DAYS;
+        assert_eq!(
+            registrar::SubnameLeases::<Test>::get(subnode).map(|lease| lease.expire),
+            Some(expire)
+        );
+
+        // the holder extends its lease.
+        assert_ok!(Registrar::offer_subname_lease(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"shop".to_vec(),
+            RICH_ACCOUNT,
+            DAYS,
+            100
+        ));
+        assert_ok!(Registrar::accept_subname_lease(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            subnode
+        ));
+        let expire = expire + DAYS;
+        assert_eq!(
+            registrar::SubnameLeases::<Test>::get(subnode).map(|lease| lease.expire),
+            Some(expire)
+        );
+
+        assert_noop!(
+            Registrar::reclaim_subname(RuntimeOrigin::signed(MONEY_ACCOUNT), subnode),
+            registrar::Error::<Test>::LeaseNotExpired
+        );
+        Timestamp::set_timestamp(expire + 1);
+        assert_noop!(
+            Registrar::reclaim_subname(RuntimeOrigin::signed(RICH_ACCOUNT), subnode),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::reclaim_subname(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            subnode
+        ));
+        assert!(!Nft::is_owner(&RICH_ACCOUNT, (0, subnode)));
+        assert!(registrar::SubnameLeases::<Test>::get(subnode).is_none());
+
+        // the subdomain can be leased again.
+        assert_ok!(Registrar::offer_subname_lease(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"shop".to_vec(),
+            POOR_ACCOUNT,
+            DAYS,
+            0
+        ));
+        assert_ok!(Registrar::accept_subname_lease(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            subnode
+        ));
+        assert!(Nft::is_owner(&POOR_ACCOUNT, (0, subnode)));
+    });
+}
+
+#[test]
+fn subname_expiry_test() {
+    use traits::Registrar as _;
+
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registrar::mint_subname(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            RICH_ACCOUNT
+        ));
+        let (sub_label, _) = Label::new_with_len(b"team").unwrap();
+        let subnode = sub_label.encode_with_node(&node);
+        // without an own expiry it lives as long as its root.
+        assert_ok!(Registrar::check_expires_useable(subnode));
+
+        assert_noop!(
+            Registrar::set_subname_expiry(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                node,
+                b"team".to_vec(),
+                Some(Timestamp::now() + DAYS)
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::set_subname_expiry(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            Some(Timestamp::now() + DAYS)
+        ));
+        assert_ok!(Registrar::check_expires_useable(subnode));
+
+        Timestamp::set_timestamp(Timestamp::now() + DAYS);
+        assert_noop!(
+            Registrar::check_expires_useable(subnode),
+            registrar::Error::<Test>::SubnameExpired
+        );
+        assert_ok!(Registrar::check_expires_useable(node));
+        assert_noop!(
+            Registrar::mint_subname(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                subnode,
+                b"dev".to_vec(),
+                RICH_ACCOUNT
+            ),
+            registrar::Error::<Test>::SubnameExpired
+        );
+
+        assert_ok!(Registrar::set_subname_expiry(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            None
+        ));
+        assert_ok!(Registrar::check_expires_useable(subnode));
+
+        // leased subdomains expire with their lease.
+        assert_ok!(Registrar::offer_subname_lease(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"shop".to_vec(),
+            RICH_ACCOUNT,
+            DAYS,
+            0
+        ));
+        let (shop_label, _) = Label::new_with_len(b"shop").unwrap();
+        let shop = shop_label.encode_with_node(&node);
+        assert_ok!(Registrar::accept_subname_lease(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            shop
+        ));
+        assert_eq!(
+            registrar::SubnameExpiries::<Test>::get(shop),
+            Some(Timestamp::now() + DAYS)
+        );
+        assert_noop!(
+            Registrar::set_subname_expiry(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                b"shop".to_vec(),
+                None
+            ),
+            registrar::Error::<Test>::SubnameLeased
+        );
+    });
+}
+
+#[test]
+fn mint_subnames_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_noop!(
+            Registrar::mint_subnames(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                vec![],
+                RICH_ACCOUNT
+            ),
+            registrar::Error::<Test>::InvalidBatchSize
+        );
+        // all or nothing.
+        assert_noop!(
+            Registrar::mint_subnames(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                vec![b"alice".to_vec(), b"alice".to_vec()],
+                RICH_ACCOUNT
+            ),
+            registrar::Error::<Test>::NotExistOrOccupied
+        );
+
+        let labels = vec![b"alice".to_vec(), b"bob".to_vec(), b"carol".to_vec()];
+        assert_ok!(Registrar::mint_subnames(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            labels.clone(),
+            RICH_ACCOUNT
+        ));
+        for data in labels {
+            let (label, _) = Label::new_with_len(&data).unwrap();
+            let subnode = label.encode_with_node(&node);
+            assert!(Nft::is_owner(&RICH_ACCOUNT, (0, subnode)));
+            System::assert_has_event(RuntimeEvent::Registrar(
+                registrar::Event::SubnameRegistered {
+                    label: data,
+                    subnode,
+                    owner: RICH_ACCOUNT,
+                    node,
+                },
+            ));
+        }
+    });
+}
+
+#[test]
+fn lifecycle_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        let expire = registrar::RegistrarInfos::<Test>::get(node).unwrap().expire;
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            DAYS,
+            None
+        ));
+        assert_ok!(Registrar::transfer(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            RICH_ACCOUNT,
+            node
+        ));
+
+        Timestamp::set_timestamp(
+            expire + DAYS + GracePeriod::get() + ReRegistrationCooldown::get() + 1,
+        );
+        assert_ok!(Registrar::reap_expired(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node
+        ));
+
+        assert_eq!(
+            LifecycleLog::get(),
+            vec![
+                DomainLifecycle::Registered(node, MONEY_ACCOUNT, expire),
+                DomainLifecycle::Renewed(node, expire + DAYS),
+                DomainLifecycle::Transferred(node, MONEY_ACCOUNT, RICH_ACCOUNT),
+                DomainLifecycle::Expired(node, RICH_ACCOUNT),
+            ]
+        );
+    });
+}
+
+#[test]
+fn primary_name_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registry::approval_for_all(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            POOR_ACCOUNT,
+            true
+        ));
+
+        assert_noop!(
+            Registry::set_primary_name(RuntimeOrigin::signed(POOR_ACCOUNT), Some(node)),
+            registry::Error::<Test>::NotOwned
+        );
+        assert_ok!(Registry::set_primary_name(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            Some(node)
+        ));
+        assert_eq!(Registry::primary_name(&MONEY_ACCOUNT), Some(node));
+
+        // the primary name goes away with the domain.
+        assert_ok!(Registrar::transfer(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            RICH_ACCOUNT,
+            node
+        ));
+        assert_eq!(Registry::primary_name(&MONEY_ACCOUNT), None);
+
+        assert_ok!(Registry::set_primary_name(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            Some(node)
+        ));
+        assert_eq!(Registry::primary_name(&RICH_ACCOUNT), Some(node));
+        assert_ok!(Registry::set_primary_name(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            None
+        ));
+        assert_eq!(Registry::primary_name(&RICH_ACCOUNT), None);
+    });
+}
+
+#[test]
+fn auction_test() {
+    new_test_ext().execute_with(|| {
+        LabelMinLen::set(1);
+        let node = Label::from_normalized(b"ab").encode_with_node(&DOT_BASENODE);
+
+        // short names can only be auctioned.
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                b"ab".to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ),
+            registrar::Error::<Test>::LabelInvalid
+        );
+        assert_noop!(
+            Registrar::bid(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                b"cupnfishxxx".to_vec(),
+                MinAuctionBid::get()
+            ),
+            registrar::Error::<Test>::NotAuctionable
+        );
+        assert_noop!(
+            Registrar::bid(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                b"ab".to_vec(),
+                MinAuctionBid::get() - 1
+            ),
+            registrar::Error::<Test>::BidTooLow
+        );
+
+        assert_ok!(Registrar::bid(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"ab".to_vec(),
+            MinAuctionBid::get()
+        ));
+        let end = Timestamp::now() + AuctionPeriod::get();
+        assert_eq!(
+            registrar::Auctions::<Test>::get(node).map(|auction| auction.end),
+            Some(end)
+        );
+        assert_eq!(
+            Balances::reserved_balance(RICH_ACCOUNT),
+            MinAuctionBid::get()
+        );
+
+        assert_noop!(
+            Registrar::bid(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                b"ab".to_vec(),
+                MinAuctionBid::get()
+            ),
+            registrar::Error::<Test>::BidTooLow
+        );
+        assert_ok!(Registrar::bid(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"ab".to_vec(),
+            150
+        ));
+        // the outbid bidder gets its bid back.
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), 0);
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), 150);
+
+        assert_noop!(
+            Registrar::settle_auction(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            registrar::Error::<Test>::AuctionNotEnded
+        );
+        Timestamp::set_timestamp(end);
+        assert_noop!(
+            Registrar::bid(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                b"ab".to_vec(),
+                200
+            ),
+            registrar::Error::<Test>::AuctionEnded
+        );
+
+        let money_free = Balances::free_balance(MONEY_ACCOUNT);
+        assert_ok!(Registrar::settle_auction(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node
+        ));
+        assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node)));
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), 0);
+        assert_eq!(Balances::free_balance(MONEY_ACCOUNT), money_free);
+        assert_eq!(
+            registrar::RegistrarInfos::<Test>::get(node).map(|info| info.expire),
+            Some(end + MinRegistrationDuration::get())
+        );
+        assert!(registrar::Auctions::<Test>::get(node).is_none());
+    });
+}
+
+#[test]
+fn auction_of_renew_locked_name_test() {
+    new_test_ext().execute_with(|| {
+        LabelMinLen::set(1);
+        let node = Label::from_normalized(b"ab").encode_with_node(&DOT_BASENODE);
+        assert_ok!(PriceOracle::set_target_registrations(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            2
+        ));
+
+        assert_ok!(Registrar::bid(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"ab".to_vec(),
+            MinAuctionBid::get()
+        ));
+        Timestamp::set_timestamp(Timestamp::now() + AuctionPeriod::get());
+        assert_ok!(Registrar::settle_auction(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node
+        ));
+        assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node)));
+        assert_eq!(price_oracle::EpochRegistrations::<Test>::get(), 1);
+        assert_ok!(Registrar::set_renew_lock(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            true
+        ));
+
+        // the name expired and can be auctioned again.
+        let expire = registrar::RegistrarInfos::<Test>::get(node).unwrap().expire;
+        Timestamp::set_timestamp(expire + GracePeriod::get() + ReRegistrationCooldown::get() + 1);
+        assert_ok!(Registrar::bid(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"ab".to_vec(),
+            MinAuctionBid::get()
+        ));
+        Timestamp::set_timestamp(Timestamp::now() + AuctionPeriod::get());
+        assert_ok!(Registrar::settle_auction(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node
+        ));
+
+        assert!(Nft::is_owner(&RICH_ACCOUNT, (0, node)));
+        // the lock was a choice of the previous owner.
+        assert!(!registrar::RenewLocked::<Test>::contains_key(node));
+        assert_eq!(price_oracle::EpochRegistrations::<Test>::get(), 2);
+    });
+}
+
+#[test]
+fn fuses_test() {
+    use registry::fuses;
+
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registrar::mint_subname(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            RICH_ACCOUNT
+        ));
+        let (sub_label, _) = Label::new_with_len(b"team").unwrap();
+        let subnode = sub_label.encode_with_node(&node);
+        assert_ok!(Registry::approval_for_all(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            POOR_ACCOUNT,
+            true
+        ));
+
+        assert_noop!(
+            Registry::burn_fuses(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                node,
+                fuses::CANNOT_SET_RESOLVER
+            ),
+            registry::Error::<Test>::NotOwned
+        );
+        assert_noop!(
+            Registry::burn_fuses(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                fuses::PARENT_CANNOT_CONTROL
+            ),
+            registry::Error::<Test>::ParentFuse
+        );
+        assert_noop!(
+            Registry::burn_fuses(RuntimeOrigin::signed(MONEY_ACCOUNT), node, 1 << 7),
+            registry::Error::<Test>::UnknownFuse
+        );
+        assert_ok!(Registry::burn_fuses(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            fuses::CANNOT_SET_RESOLVER | fuses::CANNOT_CREATE_SUBDOMAIN
+        ));
+        assert_noop!(
+            Registry::set_resolver(RuntimeOrigin::signed(POOR_ACCOUNT), node, 2),
+            registry::Error::<Test>::FuseBurned
+        );
+        assert_noop!(
+            Registrar::mint_subname(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                b"dev".to_vec(),
+                RICH_ACCOUNT
+            ),
+            registry::Error::<Test>::FuseBurned
+        );
+
+        // the parent gives up the control of its subdomain.
+        assert_ok!(Registry::burn_child_fuses(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            fuses::PARENT_CANNOT_CONTROL | fuses::CANNOT_TRANSFER
+        ));
+        assert_noop!(
+            Registrar::set_subname_expiry(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                b"team".to_vec(),
+                Some(Timestamp::now() + DAYS)
+            ),
+            registry::Error::<Test>::FuseBurned
+        );
+        assert_noop!(
+            Registrar::transfer(RuntimeOrigin::signed(RICH_ACCOUNT), MONEY_ACCOUNT, subnode),
+            registry::Error::<Test>::FuseBurned
+        );
+        assert_eq!(
+            registry::Fuses::<Test>::get(subnode),
+            fuses::PARENT_CANNOT_CONTROL | fuses::CANNOT_TRANSFER
+        );
+    });
+}
+
+#[test]
+fn controller_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_noop!(
+            Registry::set_controller(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                node,
+                Some(RICH_ACCOUNT)
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registry::set_controller(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            Some(RICH_ACCOUNT)
+        ));
+
+        // the controller can't transfer or burn the domain,
+        assert_noop!(
+            Registrar::transfer(RuntimeOrigin::signed(RICH_ACCOUNT), RICH_ACCOUNT, node),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Registry::burn(RuntimeOrigin::signed(RICH_ACCOUNT), node),
+            registry::Error::<Test>::NoPermission
+        );
+        // but edits its records and creates subdomains.
+        assert_ok!(Resolvers::set_account(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            Address::Id(RICH_ACCOUNT),
+        ));
+        assert_ok!(Registrar::mint_subname(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            RICH_ACCOUNT
+        ));
+
+        assert_noop!(
+            Registry::set_controller(RuntimeOrigin::signed(RICH_ACCOUNT), node, None),
+            registry::Error::<Test>::NoPermission
+        );
+
+        assert_ok!(Registry::set_controller(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            None
+        ));
+        assert_noop!(
+            Resolvers::set_account(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                node,
+                Address::Id(RICH_ACCOUNT),
+            ),
+            pns_resolvers::resolvers::Error::<Test>::InvalidPermission
+        );
+    });
+}
+
+#[test]
+fn ttl_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_eq!(Registry::ttl(node), None);
+
+        assert_noop!(
+            Registry::set_ttl(RuntimeOrigin::signed(RICH_ACCOUNT), node, Some(300)),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registry::set_ttl(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            Some(300)
+        ));
+        assert_eq!(Registry::ttl(node), Some(300));
+        System::assert_has_event(RuntimeEvent::Registry(registry::Event::TtlChanged {
+            node,
+            ttl: Some(300),
+        }));
+
+        // the controller may change it too.
+        assert_ok!(Registry::set_controller(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            Some(RICH_ACCOUNT)
+        ));
+        assert_ok!(Registry::set_ttl(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            Some(60)
+        ));
+        assert_eq!(Registry::ttl(node), Some(60));
+
+        assert_ok!(Registry::set_ttl(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            None
+        ));
+        assert_eq!(Registry::ttl(node), None);
+
+        // it is gone with the domain.
+        assert_ok!(Registry::set_ttl(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            Some(300)
+        ));
+        assert_ok!(Registry::burn(RuntimeOrigin::signed(MONEY_ACCOUNT), node));
+        assert_eq!(Registry::ttl(node), None);
+    });
+}
+
+#[test]
+fn clear_records_test() {
+    use pns_resolvers::resolvers::Content;
+    use pns_types::ddns::codec_type::RecordType;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Resolvers::set_record(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RecordType::A,
+            Content(vec![127, 0, 0, 1])
+        ));
+        assert_ok!(Resolvers::set_account(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            Address::Ethereum([7; 20]),
+        ));
+        assert_eq!(Registry::record_version(node), 0);
+
+        assert_noop!(
+            Registry::clear_records(RuntimeOrigin::signed(RICH_ACCOUNT), node),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registry::clear_records(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node
+        ));
+        assert_eq!(Registry::record_version(node), 1);
+        System::assert_has_event(RuntimeEvent::Registry(registry::Event::RecordsCleared {
+            node,
+            version: 1,
+        }));
+
+        assert!(Resolvers::lookup(node).is_empty());
+        assert_eq!(Resolvers::lookup_one(node, RecordType::A), None);
+        assert!(Resolvers::ethereum_addresses(node).is_empty());
+
+        // new records are written with the new version.
+        assert_ok!(Resolvers::set_record(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            RecordType::A,
+            Content(vec![10, 0, 0, 1])
+        ));
+        assert_eq!(
+            Resolvers::lookup_one(node, RecordType::A),
+            Some(vec![10, 0, 0, 1])
+        );
+    });
+}
+
+#[test]
+fn domains_of_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registrar::mint_subname(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            MONEY_ACCOUNT
+        ));
+        let (sub_label, _) = Label::new_with_len(b"team").unwrap();
+        let subnode = sub_label.encode_with_node(&node);
+
+        let mut domains = Registry::domains_of(&MONEY_ACCOUNT);
+        domains.sort();
+        let mut expected = vec![node, subnode];
+        expected.sort();
+        assert_eq!(domains, expected);
+
+        assert_ok!(Registrar::transfer(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            RICH_ACCOUNT,
+            subnode
+        ));
+        assert_eq!(Registry::domains_of(&MONEY_ACCOUNT), vec![node]);
+        assert_eq!(Registry::domains_of(&RICH_ACCOUNT), vec![subnode]);
+
+        assert_ok!(Registry::burn(RuntimeOrigin::signed(RICH_ACCOUNT), subnode));
+        assert!(Registry::domains_of(&RICH_ACCOUNT).is_empty());
+    });
+}
+
+#[test]
+fn subnodes_of_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_eq!(Registry::subnodes_of(node, None), (vec![], None));
+
+        let subnodes = [b"team".to_vec(), b"blog".to_vec(), b"shop".to_vec()]
+            .into_iter()
+            .map(|name| {
+                assert_ok!(Registrar::mint_subname(
+                    RuntimeOrigin::signed(MONEY_ACCOUNT),
+                    node,
+                    name.clone(),
+                    MONEY_ACCOUNT
+                ));
+                Label::new_with_len(&name)
+                    .unwrap()
+                    .0
+                    .encode_with_node(&node)
+            })
+            .collect::<Vec<_>>();
+
+        let (page, next) = Registry::subnodes_of(node, None);
+        assert_eq!(next, None);
+        let mut sorted = page.clone();
+        sorted.sort();
+        let mut expected = subnodes.clone();
+        expected.sort();
+        assert_eq!(sorted, expected);
+        // continues after the cursor.
+        assert_eq!(Registry::subnodes_of(node, Some(page[0])).0, page[1..]);
+
+        assert_ok!(Registry::burn(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            subnodes[0]
+        ));
+        assert_eq!(Registry::subnodes_of(node, None).0.len(), 2);
+        assert!(!Registry::subnodes_of(node, None).0.contains(&subnodes[0]));
+    });
+}
+
+#[test]
+fn approvals_cleared_on_burn_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registry::approve(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            POOR_ACCOUNT,
+            node,
+            true
+        ));
+
+        assert_ok!(Registry::burn(RuntimeOrigin::signed(MONEY_ACCOUNT), node));
+        assert!(!registry::TokenApprovals::<Test>::contains_key(
+            node,
+            POOR_ACCOUNT
+        ));
+    });
+}
+
+#[test]
+fn freeze_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_noop!(
+            Registry::freeze(RuntimeOrigin::signed(MONEY_ACCOUNT), node),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Registry::freeze(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node
+        ));
+        System::assert_has_event(RuntimeEvent::Registry(registry::Event::Frozen { node }));
+
+        assert_noop!(
+            Registrar::transfer(RuntimeOrigin::signed(MONEY_ACCOUNT), RICH_ACCOUNT, node),
+            registry::Error::<Test>::Frozen
+        );
+        assert_noop!(
+            Registry::burn(RuntimeOrigin::signed(MONEY_ACCOUNT), node),
+            registry::Error::<Test>::Frozen
+        );
+        assert_noop!(
+            Registrar::mint_subname(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                b"team".to_vec(),
+                MONEY_ACCOUNT
+            ),
+            registry::Error::<Test>::Frozen
+        );
+        assert_noop!(
+            Registry::set_resolver(RuntimeOrigin::signed(MONEY_ACCOUNT), node, 7),
+            registry::Error::<Test>::Frozen
+        );
+        assert_noop!(
+            Resolvers::set_account(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                Address::Id(MONEY_ACCOUNT),
+            ),
+            pns_resolvers::resolvers::Error::<Test>::InvalidPermission
+        );
+
+        assert_ok!(Registry::thaw(RuntimeOrigin::signed(MANAGER_ACCOUNT), node));
+        System::assert_has_event(RuntimeEvent::Registry(registry::Event::Thawed { node }));
+        assert_ok!(Registrar::transfer(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            RICH_ACCOUNT,
+            node
+        ));
+    });
+}
+
+#[test]
+fn burn_recursive_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registrar::mint_subname(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            RICH_ACCOUNT
+        ));
+        let team = Label::new_with_len(b"team")
+            .unwrap()
+            .0
+            .encode_with_node(&node);
+        assert_ok!(Registrar::mint_subname(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            team,
+            b"dev".to_vec(),
+            RICH_ACCOUNT
+        ));
+        let dev = Label::new_with_len(b"dev")
+            .unwrap()
+            .0
+            .encode_with_node(&team);
+
+        assert_noop!(
+            Registry::burn(RuntimeOrigin::signed(MONEY_ACCOUNT), node),
+            registry::Error::<Test>::SubnodeNotClear
+        );
+        assert_noop!(
+            Registry::burn_recursive(RuntimeOrigin::signed(RICH_ACCOUNT), node, 10),
+            registry::Error::<Test>::NoPermission
+        );
+
+        // the deepest subdomain goes first.
+        assert_ok!(Registry::burn_recursive(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            1
+        ));
+        assert!(!Nft::is_owner(&RICH_ACCOUNT, (0, dev)));
+        assert!(Nft::is_owner(&RICH_ACCOUNT, (0, team)));
+        System::assert_last_event(RuntimeEvent::Registry(registry::Event::SubtreeBurned {
+            node,
+            burned: 1,
+            node_burned: false,
+        }));
+
+        assert_ok!(Registry::burn_recursive(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            10
+        ));
+        assert!(!Nft::is_owner(&RICH_ACCOUNT, (0, team)));
+        assert!(!Nft::is_owner(&MONEY_ACCOUNT, (0, node)));
+        System::assert_last_event(RuntimeEvent::Registry(registry::Event::SubtreeBurned {
+            node,
+            burned: 1,
+            node_burned: true,
+        }));
+    });
+}
+
+#[test]
+fn token_metadata_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_noop!(
+            Registry::set_token_metadata(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                node,
+                b"ipfs://a".to_vec()
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Registry::set_token_metadata(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                node,
+                vec![7; MaxMetadata::get() as usize + 1]
+            ),
+            nft::Error::<Test>::MaxMetadataExceeded
+        );
+        assert_ok!(Registry::set_token_metadata(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"ipfs://a".to_vec()
+        ));
+        assert_eq!(
+            Nft::tokens(0, node).unwrap().metadata.to_vec(),
+            b"ipfs://a".to_vec()
+        );
+    });
+}
+
+#[test]
+fn soulbound_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert!(!Registry::is_soulbound(node));
+
+        assert_ok!(Registry::burn_fuses(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            registry::fuses::CANNOT_TRANSFER
+        ));
+        assert!(Registry::is_soulbound(node));
+
+        assert_noop!(
+            Registrar::transfer(RuntimeOrigin::signed(MONEY_ACCOUNT), RICH_ACCOUNT, node),
+            registry::Error::<Test>::FuseBurned
+        );
+        assert_noop!(
+            Registrar::offer_transfer(RuntimeOrigin::signed(MONEY_ACCOUNT), node, RICH_ACCOUNT),
+            registry::Error::<Test>::FuseBurned
+        );
+
+        // only burning the name ends it.
+        assert_ok!(Registry::burn(RuntimeOrigin::signed(MONEY_ACCOUNT), node));
+        assert!(!Registry::is_soulbound(node));
+    });
+}
+
+#[test]
+fn approval_events_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_ok!(Registry::approve(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            POOR_ACCOUNT,
+            node,
+            true
+        ));
+        System::assert_last_event(RuntimeEvent::Registry(registry::Event::TokenApproved {
+            node,
+            owner: MONEY_ACCOUNT,
+            approved: POOR_ACCOUNT,
+        }));
+
+        assert_ok!(Registry::approve(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            POOR_ACCOUNT,
+            node,
+            false
+        ));
+        System::assert_last_event(RuntimeEvent::Registry(
+            registry::Event::TokenApprovalRevoked {
+                node,
+                owner: MONEY_ACCOUNT,
+                approved: POOR_ACCOUNT,
+            },
+        ));
+
+        assert_ok!(Registrar::transfer_and_reset(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            RICH_ACCOUNT,
+            node
+        ));
+        System::assert_has_event(RuntimeEvent::Registry(
+            registry::Event::TokenApprovalsCleared { node },
+        ));
+    });
+}
+
+#[test]
+fn token_uri_test() {
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_eq!(Registry::token_uri(0, node), None);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        // the hex form of the node without a base uri or suffix.
+        let uri = Registry::token_uri(0, node).unwrap();
+        assert_eq!(uri.len(), 66);
+        assert!(uri.starts_with(b"0x"));
+        assert_eq!(Registry::token_uri(1, node), None);
+
+        assert_noop!(
+            Registry::set_base_uri(RuntimeOrigin::signed(MONEY_ACCOUNT), b"ipfs://".to_vec()),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Registry::set_base_uri(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                vec![b'a'; MaxMetadata::get() as usize + 1]
+            ),
+            registry::Error::<Test>::UriTooLong
+        );
+        assert_ok!(Registry::set_base_uri(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            b"ipfs://".to_vec()
+        ));
+        assert!(Registry::token_uri(0, node)
+            .unwrap()
+            .starts_with(b"ipfs://0x"));
+
+        assert_noop!(
+            Registry::set_token_uri_suffix(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                node,
+                Some(b"a.json".to_vec())
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registry::set_token_uri_suffix(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            Some(b"a.json".to_vec())
+        ));
+        assert_eq!(
+            Registry::token_uri(0, node),
+            Some(b"ipfs://a.json".to_vec())
+        );
+    });
+}
+
+#[test]
+fn children_counter_test() {
+    use crate::traits::NftBackend;
+
+    new_test_ext().execute_with(|| {
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registrar::mint_subname(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            RICH_ACCOUNT
+        ));
+        let team = Label::new_with_len(b"team")
+            .unwrap()
+            .0
+            .encode_with_node(&node);
+        assert_ok!(Registrar::mint_subname(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            team,
+            b"dev".to_vec(),
+            RICH_ACCOUNT
+        ));
+        let dev = Label::new_with_len(b"dev")
+            .unwrap()
+            .0
+            .encode_with_node(&team);
+
+        assert_eq!(<Nft as NftBackend<u64>>::owner(dev), Some(RICH_ACCOUNT));
+        // the nested subdomain is counted by both its parent and the registered domain.
+        assert_eq!(registry::Children::<Test>::get(node), 2);
+        assert_eq!(registry::Children::<Test>::get(team), 1);
+
+        assert_ok!(Registry::burn(RuntimeOrigin::signed(RICH_ACCOUNT), dev));
+        assert_eq!(<Nft as NftBackend<u64>>::owner(dev), None);
+        assert_eq!(registry::Children::<Test>::get(node), 1);
+        assert_eq!(registry::Children::<Test>::get(team), 0);
+    });
+}
+
+#[test]
+fn royalty_test() {
+    use crate::registry::Royalty;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (label, _) = Label::new_with_len(b"cupnfishxxx").unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            MONEY_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registrar::mint_subname(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            b"team".to_vec(),
+            MONEY_ACCOUNT
+        ));
+        let team = Label::new_with_len(b"team")
+            .unwrap()
+            .0
+            .encode_with_node(&node);
+        let royalty = Royalty {
+            beneficiary: POOR_ACCOUNT,
+            basis_points: 1_000,
+        };
+
+        // only the parent decides the royalty of a subdomain.
+        assert_noop!(
+            Registry::set_royalty(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                team,
+                Some(royalty.clone())
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            Registry::set_royalty(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                team,
+                Some(Royalty {
+                    beneficiary: POOR_ACCOUNT,
+                    basis_points: 10_001,
+                })
+            ),
+            registry::Error::<Test>::RoyaltyTooHigh
+        );
+        assert_ok!(Registry::set_royalty(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            team,
+            Some(royalty.clone())
+        ));
+        assert_eq!(Registry::royalty_of(team), Some(royalty));
+        assert_eq!(Registry::royalty_of(node), None);
+
+        assert_ok!(Registrar::offer_sale(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            team,
+            RICH_ACCOUNT,
+            1_000
+        ));
+        let poor_free = Balances::free_balance(POOR_ACCOUNT);
+        let money_free = Balances::free_balance(MONEY_ACCOUNT);
+        let rich_free = Balances::free_balance(RICH_ACCOUNT);
+        assert_ok!(Registrar::accept_transfer(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            team
+        ));
+        assert!(Nft::is_owner(&RICH_ACCOUNT, (0, team)));
+        assert_eq!(Balances::free_balance(POOR_ACCOUNT), poor_free + 100);
+        assert_eq!(Balances::free_balance(MONEY_ACCOUNT), money_free + 900);
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), rich_free - 1_000);
+        System::assert_has_event(RuntimeEvent::Registrar(registrar::Event::RoyaltyPaid {
+            node: team,
+            beneficiary: POOR_ACCOUNT,
+            amount: 100,
+        }));
+
+        // domains without a royalty of their own fall back to the default one.
+        let default_royalty = Royalty {
+            beneficiary: MANAGER_ACCOUNT,
+            basis_points: 500,
+        };
+        assert_noop!(
+            Registry::set_default_royalty(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                Some(default_royalty.clone())
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Registry::set_default_royalty(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            Some(default_royalty.clone())
+        ));
+        assert_eq!(Registry::royalty_of(node), Some(default_royalty));
+    });
+}
+
+#[test]
+fn nft_transfer_batch_test() {
+    new_test_ext().execute_with(|| {
+        let node = |name: &[u8]| {
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        };
+        for (name, owner) in [
+            (b"cupnfishxx1", MONEY_ACCOUNT),
+            (b"cupnfishxx2", MONEY_ACCOUNT),
+            (b"cupnfishxx3", RICH_ACCOUNT),
+        ] {
+            assert_ok!(Registrar::register(
+                RuntimeOrigin::signed(owner),
+                DOT_BASENODE,
+                name.to_vec(),
+                owner,
+                MinRegistrationDuration::get(),
+                None
+            ));
+        }
+        let (first, second) = (node(b"cupnfishxx1"), node(b"cupnfishxx2"));
+
+        assert_noop!(
+            Nft::transfer_batch(
+                &MONEY_ACCOUNT,
+                &POOR_ACCOUNT,
+                &[(0, first), (0, node(b"cupnfishxx3"))]
+            ),
+            nft::Error::<Test>::NoPermission
+        );
+        assert_ok!(Nft::transfer_batch(
+            &MONEY_ACCOUNT,
+            &POOR_ACCOUNT,
+            &[(0, first), (0, second)]
+        ));
+        assert!(Nft::is_owner(&POOR_ACCOUNT, (0, first)));
+        assert!(Nft::is_owner(&POOR_ACCOUNT, (0, second)));
+        assert!(!Nft::is_owner(&MONEY_ACCOUNT, (0, first)));
+
+        // through the registry, a domain listed twice fails the whole batch.
+        assert_noop!(
+            Registrar::transfer_batch(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                MONEY_ACCOUNT,
+                vec![first, second, first].try_into().unwrap()
+            ),
+            registry::Error::<Test>::NoPermission
+        );
+        assert_ok!(Registrar::transfer_batch(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            MONEY_ACCOUNT,
+            vec![first, second].try_into().unwrap()
+        ));
+        assert_eq!(
+            registry::OwnedNodes::<Test>::iter_key_prefix(MONEY_ACCOUNT).count(),
+            2
+        );
+    });
+}
+
+#[test]
+fn owned_token_count_test() {
+    use crate::traits::NFT;
+
+    new_test_ext().execute_with(|| {
+        let node = |name: &[u8]| {
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        };
+        let names = [b"cupnfishxx1", b"cupnfishxx2"];
+        for name in names {
+            assert_ok!(Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ));
+        }
+        assert_eq!(Registry::balance_of(&RICH_ACCOUNT), 2);
+        assert_eq!(<Registry as NFT<u64>>::balance(&RICH_ACCOUNT), 2);
+
+        assert_ok!(Registrar::transfer(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            MONEY_ACCOUNT,
+            node(names[0])
+        ));
+        assert_eq!(Registry::balance_of(&RICH_ACCOUNT), 1);
+        assert_eq!(Registry::balance_of(&MONEY_ACCOUNT), 1);
+
+        assert_ok!(Registry::burn(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node(names[1])
+        ));
+        assert_eq!(Registry::balance_of(&RICH_ACCOUNT), 0);
+        assert!(!registry::OwnedTokenCount::<Test>::contains_key(
+            RICH_ACCOUNT
+        ));
+    });
+}
+
+#[test]
+fn tokens_of_owner_test() {
+    new_test_ext().execute_with(|| {
+        let names = [b"cupnfishxx1", b"cupnfishxx2", b"cupnfishxx3"];
+        for name in names {
+            assert_ok!(Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ));
+        }
+
+        let (mut tokens, next) = Nft::tokens_of_owner(&RICH_ACCOUNT, None, 2);
+        assert_eq!(tokens.len(), 2);
+        assert!(next.is_some());
+        let (rest, next) = Nft::tokens_of_owner(&RICH_ACCOUNT, next, 2);
+        assert_eq!(rest.len(), 1);
+        assert_eq!(next, None);
+
+        tokens.extend(rest);
+        for name in names {
+            let node = Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE);
+            assert!(tokens.contains(&(0, node)));
+        }
+        assert_eq!(Nft::tokens_of_owner(&POOR_ACCOUNT, None, 2), (vec![], None));
+    });
+}
+
+
+#[test]
+fn nft_storage_info_test() {
+    use frame_support::traits::StorageInfoTrait;
+
+    // every storage item is bounded, so its proof size can be weighed.
+    for info in <Nft as StorageInfoTrait>::storage_info() {
+        assert!(
+            info.max_size.is_some(),
+            "{:?} is unbounded",
+            info.storage_name
+        );
+    }
+}
+
+#[test]
+fn price_oracle_ocw_test() {
+    use codec::Decode;
+    use frame_support::traits::Hooks;
+    use sp_core::offchain::{testing, OffchainWorkerExt, TransactionPoolExt};
+
+    let (offchain, offchain_state) = testing::TestOffchainExt::new();
+    let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+    let mut ext = new_test_ext();
+    ext.register_extension(OffchainWorkerExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+
+    let urls = [
+        "http://a.oracle/rate",
+        "http://b.oracle/rate",
+        "http://c.oracle/rate",
+    ];
+    for (url, rate) in urls.iter().zip([
+        b"1050000000000\n".to_vec(),
+        b"1000000000000".to_vec(),
+        b"2000000000000".to_vec(),
+    ]) {
+        offchain_state
+            .write()
+            .expect_request(testing::PendingRequest {
+                method: "GET".into(),
+                uri: url.to_string(),
+                response: Some(rate),
+                sent: true,
+                ..Default::default()
+            });
+    }
+
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        let endpoints = urls
+            .iter()
+            .map(|url| url.as_bytes().to_vec().try_into().unwrap())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        assert_noop!(
+            PriceOracle::set_oracle_endpoints(RuntimeOrigin::signed(POOR_ACCOUNT), endpoints),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        let endpoints = urls
+            .iter()
+            .map(|url| url.as_bytes().to_vec().try_into().unwrap())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        assert_ok!(PriceOracle::set_oracle_endpoints(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            endpoints
+        ));
+
+        // the median of the three answers is submitted
+        PriceOracle::offchain_worker(1);
+        let tx = pool_state.write().transactions.pop().unwrap();
+        let tx = UncheckedExtrinsic::decode(&mut &tx[..]).unwrap();
+        assert!(tx.signature.is_none());
+        assert_eq!(
+            tx.function,
+            RuntimeCall::PriceOracle(price_oracle::Call::submit_exchange_rate {
+                rate: 1_050_000_000_000
+            })
+        );
+
+        assert_noop!(
+            PriceOracle::submit_exchange_rate(RuntimeOrigin::signed(MANAGER_ACCOUNT), BASE),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            PriceOracle::submit_exchange_rate(RuntimeOrigin::none(), 0),
+            price_oracle::Error::<Test>::ZeroRate
+        );
+        assert_noop!(
+            PriceOracle::submit_exchange_rate(RuntimeOrigin::none(), 2 * BASE),
+            price_oracle::Error::<Test>::RateOutOfBounds
+        );
+        assert_ok!(PriceOracle::submit_exchange_rate(
+            RuntimeOrigin::none(),
+            1_050_000_000_000
+        ));
+        assert_eq!(price_oracle::ExchangeRate::<Test>::get(), 1_050_000_000_000);
+
+        // nothing more until the interval has passed
+        System::set_block_number(10);
+        assert!(!PriceOracle::oracle_due(10));
+        assert_noop!(
+            PriceOracle::submit_exchange_rate(RuntimeOrigin::none(), BASE),
+            price_oracle::Error::<Test>::OracleNotDue
+        );
+        PriceOracle::offchain_worker(10);
+        assert!(pool_state.read().transactions.is_empty());
+
+        System::set_block_number(11);
+        assert_ok!(PriceOracle::submit_exchange_rate(
+            RuntimeOrigin::none(),
+            BASE
+        ));
+    });
+}
+
+#[test]
+fn feed_exchange_rate_test() {
+    use traits::{ExchangeRate, FeedExchangeRate};
+    type Rate = FeedExchangeRate<TestFeed, Timestamp, MaxFeedAge, TestRate>;
+
+    new_test_ext().execute_with(|| {
+        let fallback = TestRate::get_exchange_rate();
+        // no rate fed yet
+        assert_eq!(Rate::get_exchange_rate(), fallback);
+
+        let now = Timestamp::now();
+        FeedRate::set(Some((BASE, now)));
+        assert_eq!(Rate::get_exchange_rate(), BASE);
+
+        Timestamp::set_timestamp(now + MaxFeedAge::get());
+        assert_eq!(Rate::get_exchange_rate(), BASE);
+
+        // stale
+        Timestamp::set_timestamp(now + MaxFeedAge::get() + 1);
+        assert_eq!(Rate::get_exchange_rate(), fallback);
+
+        FeedRate::set(Some((0, Timestamp::now())));
+        assert_eq!(Rate::get_exchange_rate(), fallback);
+        FeedRate::set(None);
+    });
+}
+
+#[test]
+fn demand_pricing_test() {
+    use frame_support::traits::Hooks;
+    use sp_runtime::{FixedPointNumber, FixedU128};
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"hello-world";
+        let flat_fee = PriceOracle::registration_fee(name.len()).unwrap();
+
+        // flat prices count nothing
+        PriceOracle::note_registration();
+        assert_eq!(price_oracle::EpochRegistrations::<Test>::get(), 0);
+
+        assert_noop!(
+            PriceOracle::set_target_registrations(RuntimeOrigin::signed(POOR_ACCOUNT), 2),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(PriceOracle::set_target_registrations(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            2
+        ));
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        PriceOracle::note_registration();
+        PriceOracle::note_registration();
+        PriceOracle::note_registration();
+        assert_eq!(price_oracle::EpochRegistrations::<Test>::get(), 4);
+
+        // twice the target moves the price up by the whole adjustment
+        PriceOracle::on_initialize(DemandEpoch::get());
+        assert_eq!(price_oracle::EpochRegistrations::<Test>::get(), 0);
+        assert_eq!(
+            price_oracle::DemandMultiplier::<Test>::get(),
+            FixedU128::saturating_from_rational(11, 10)
+        );
+        assert_eq!(
+            PriceOracle::registration_fee(name.len()).unwrap(),
+            flat_fee * 11 / 10
+        );
+
+        // half the target moves it down by half of it
+        PriceOracle::note_registration();
+        PriceOracle::on_initialize(2 * DemandEpoch::get());
+        assert_eq!(
+            price_oracle::DemandMultiplier::<Test>::get(),
+            FixedU128::saturating_from_rational(10450, 10000)
+        );
+
+        // never below the bound
+        for epoch in 3..20 {
+            PriceOracle::on_initialize(epoch * DemandEpoch::get());
+        }
+        assert_eq!(
+            price_oracle::DemandMultiplier::<Test>::get(),
+            MinDemandMultiplier::get()
+        );
+
+        // back to flat prices
+        assert_ok!(PriceOracle::set_target_registrations(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0
+        ));
+        assert_eq!(PriceOracle::registration_fee(name.len()).unwrap(), flat_fee);
+    });
+}
+
+#[test]
+fn price_tiers_test() {
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        let rate = <TestRate as traits::ExchangeRate>::get_exchange_rate();
+        assert_eq!(PriceOracle::registration_fee(1), Some(11 * rate));
+        assert_eq!(PriceOracle::registration_fee(40), Some(rate));
+
+        // three coarse tiers, longer names pay the last one
+        assert_ok!(PriceOracle::set_base_price(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            vec![30, 20, 10].try_into().unwrap()
+        ));
+        assert_eq!(PriceOracle::registration_fee(0), Some(30 * rate));
+        assert_eq!(PriceOracle::registration_fee(2), Some(20 * rate));
+        assert_eq!(PriceOracle::registration_fee(11), Some(10 * rate));
+
+        // without any tier nothing can be priced
+        assert_ok!(PriceOracle::set_base_price(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            Default::default()
+        ));
+        assert_eq!(PriceOracle::registration_fee(5), None);
+
+        // the fixed arrays of eleven tiers are migrated
+        frame_support::storage::unhashed::put(
+            &price_oracle::RentPrice::<Test>::hashed_key(),
+            &[5_u128; 11],
+        );
+        migration::Initialize::<Test>::migrate_price_tiers();
+        assert_eq!(price_oracle::RentPrice::<Test>::get().to_vec(), vec![5; 11]);
+    });
+}
+
+#[test]
+fn promotion_test() {
+    use frame_support::traits::Hooks;
+    use price_oracle::Promotion;
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let duration = MinRegistrationDuration::get();
+        let full_register = PriceOracle::register_fee(5, duration).unwrap();
+        let full_renew = PriceOracle::renew_fee(5, duration).unwrap();
+
+        assert_noop!(
+            PriceOracle::set_promotion(RuntimeOrigin::signed(POOR_ACCOUNT), 10, 20, 2_500, 4),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            PriceOracle::set_promotion(RuntimeOrigin::signed(MANAGER_ACCOUNT), 20, 10, 2_500, 4),
+            price_oracle::Error::<Test>::InvalidPromotion
+        );
+        assert_noop!(
+            PriceOracle::set_promotion(RuntimeOrigin::signed(MANAGER_ACCOUNT), 10, 20, 10_001, 4),
+            price_oracle::Error::<Test>::DiscountTooHigh
+        );
+        assert_ok!(PriceOracle::set_promotion(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            10,
+            20,
+            2_500,
+            4
+        ));
+        let promotion = Promotion {
+            start: 10,
+            end: 20,
+            discount_bps: 2_500,
+            min_len: 4,
+        };
+        System::assert_last_event(RuntimeEvent::PriceOracle(
+            price_oracle::Event::PromotionScheduled(promotion.clone()),
+        ));
+
+        // not started yet
+        assert_eq!(PriceOracle::register_fee(5, duration), Some(full_register));
+
+        System::set_block_number(10);
+        PriceOracle::on_initialize(10);
+        System::assert_last_event(RuntimeEvent::PriceOracle(
+            price_oracle::Event::PromotionStarted(promotion.clone()),
+        ));
+        assert_eq!(
+            PriceOracle::register_fee(5, duration),
+            Some(full_register * 3 / 4)
+        );
+        assert_eq!(
+            PriceOracle::renew_fee(5, duration),
+            Some(full_renew * 3 / 4)
+        );
+        // too short for the promotion
+        assert_eq!(
+            PriceOracle::register_fee(3, duration),
+            Some(
+                PriceOracle::registration_fee(3).unwrap()
+                    + PriceOracle::renew_fee(3, duration).unwrap()
+            )
+        );
+
+        System::set_block_number(20);
+        PriceOracle::on_initialize(20);
+        System::assert_last_event(RuntimeEvent::PriceOracle(
+            price_oracle::Event::PromotionEnded(promotion),
+        ));
+        assert_eq!(price_oracle::CurrentPromotion::<Test>::get(), None);
+        assert_eq!(PriceOracle::register_fee(5, duration), Some(full_register));
+    });
+}
+
+#[test]
+fn fee_quote_test() {
+    new_test_ext().execute_with(|| {
+        let name = b"hello-world";
+        let duration = MinRegistrationDuration::get();
+        assert_eq!(
+            Registrar::register_fee(DOT_BASENODE, b"hello", duration),
+            None
+        );
+        assert_eq!(Registrar::renew_fee(DOT_BASENODE, name, duration), None);
+
+        let register_fee = Registrar::register_fee(DOT_BASENODE, name, duration).unwrap();
+        let deposit = Registrar::deposit_fee(name).unwrap();
+        let init_free = Balances::free_balance(RICH_ACCOUNT);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            duration,
+            None
+        ));
+        assert_eq!(
+            init_free - Balances::free_balance(RICH_ACCOUNT),
+            register_fee + deposit
+        );
+
+        let renew_fee = Registrar::renew_fee(DOT_BASENODE, name, duration).unwrap();
+        let init_free = Balances::free_balance(RICH_ACCOUNT);
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            duration,
+            None
+        ));
+        assert_eq!(init_free - Balances::free_balance(RICH_ACCOUNT), renew_fee);
+
+        // the quote follows the grace period surcharge
+        Timestamp::set_timestamp(Timestamp::now() + 3 * duration);
+        let grace_fee = Registrar::renew_fee(DOT_BASENODE, name, duration).unwrap();
+        assert_eq!(
+            grace_fee,
+            renew_fee + GraceRenewalSurcharge::get() * renew_fee
+        );
+    });
+}
+
+#[test]
+fn asset_payment_test() {
+    use traits::PriceOracle as _;
+    const USDT: u32 = 7;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"hello-world";
+        let duration = MinRegistrationDuration::get();
+        assert_ok!(Assets::force_create(
+            RuntimeOrigin::root(),
+            USDT.into(),
+            OFFICIAL_ACCOUNT,
+            true,
+            1
+        ));
+        assert_ok!(Assets::mint(
+            RuntimeOrigin::signed(OFFICIAL_ACCOUNT),
+            USDT.into(),
+            RICH_ACCOUNT,
+            1_000_000 * BASE
+        ));
+
+        // not accepted yet
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                duration,
+                Some(USDT)
+            ),
+            registrar::Error::<Test>::AssetNotAccepted
+        );
+
+        assert_noop!(
+            PriceOracle::set_asset_rate(RuntimeOrigin::signed(POOR_ACCOUNT), USDT, Some(1_000_000)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(PriceOracle::set_asset_rate(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            USDT,
+            Some(1_000_000)
+        ));
+
+        // the fee is paid in the asset, the deposit in the native token
+        let fee = Registrar::register_fee(DOT_BASENODE, name, duration).unwrap();
+        let amount = PriceOracle::asset_amount(USDT, fee).unwrap();
+        assert_eq!(
+            amount,
+            fee * 1_000_000 / <TestRate as traits::ExchangeRate>::get_exchange_rate()
+        );
+        let deposit = Registrar::deposit_fee(name).unwrap();
+        let init_free = Balances::free_balance(RICH_ACCOUNT);
+        let init_assets = Assets::balance(USDT, RICH_ACCOUNT);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            duration,
+            Some(USDT)
+        ));
+        assert_eq!(init_free - Balances::free_balance(RICH_ACCOUNT), deposit);
+        assert_eq!(init_assets - Assets::balance(USDT, RICH_ACCOUNT), amount);
+        System::assert_has_event(RuntimeEvent::Registrar(registrar::Event::FeePaidInAsset {
+            payer: RICH_ACCOUNT,
+            asset: USDT,
+            amount,
+        }));
+
+        let price = Registrar::renew_fee(DOT_BASENODE, name, duration).unwrap();
+        let init_free = Balances::free_balance(RICH_ACCOUNT);
+        let init_assets = Assets::balance(USDT, RICH_ACCOUNT);
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            duration,
+            Some(USDT)
+        ));
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), init_free);
+        assert_eq!(
+            init_assets - Assets::balance(USDT, RICH_ACCOUNT),
+            PriceOracle::asset_amount(USDT, price).unwrap()
+        );
+
+        // no longer accepted
+        assert_ok!(PriceOracle::set_asset_rate(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            USDT,
+            None
+        ));
+        assert_noop!(
+            Registrar::renew(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                duration,
+                Some(USDT)
+            ),
+            registrar::Error::<Test>::AssetNotAccepted
+        );
+    });
+}
+
+#[test]
+fn exchange_rate_guard_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_eq!(price_oracle::ExchangeRate::<Test>::get(), BASE);
+
+        // too far in either direction
+        assert_noop!(
+            PriceOracle::set_exchange_rate(RuntimeOrigin::signed(MANAGER_ACCOUNT), 0),
+            price_oracle::Error::<Test>::ZeroRate
+        );
+        assert_noop!(
+            PriceOracle::set_exchange_rate(RuntimeOrigin::signed(MANAGER_ACCOUNT), BASE * 2),
+            price_oracle::Error::<Test>::RateOutOfBounds
+        );
+        assert_noop!(
+            PriceOracle::set_exchange_rate(RuntimeOrigin::signed(MANAGER_ACCOUNT), BASE / 2),
+            price_oracle::Error::<Test>::RateOutOfBounds
+        );
+        assert_ok!(PriceOracle::set_exchange_rate(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            BASE + BASE / 10
+        ));
+
+        // once per cooldown
+        System::set_block_number(10);
+        assert_noop!(
+            PriceOracle::set_exchange_rate(RuntimeOrigin::signed(MANAGER_ACCOUNT), BASE),
+            price_oracle::Error::<Test>::RateChangeTooSoon
+        );
+        System::set_block_number(11);
+        assert_ok!(PriceOracle::set_exchange_rate(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            BASE
+        ));
+
+        // root is not bound
+        assert_noop!(
+            PriceOracle::force_set_exchange_rate(RuntimeOrigin::signed(MANAGER_ACCOUNT), BASE * 2),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(PriceOracle::force_set_exchange_rate(
+            RuntimeOrigin::root(),
+            BASE * 2
+        ));
+        assert_eq!(price_oracle::ExchangeRate::<Test>::get(), BASE * 2);
+        System::assert_last_event(RuntimeEvent::PriceOracle(
+            price_oracle::Event::ExchangeRateForced(BASE * 2),
+        ));
+    });
+}
+
+#[test]
+fn scheduled_price_update_test() {
+    use frame_support::traits::Hooks;
+    use price_oracle::{BasePrice, DepositPrice, PendingPriceUpdate, PriceUpdate, RentPrice};
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let rent_prices = RentPrice::<Test>::get();
+        let base_prices: price_oracle::PricesOf<Test> = vec![BASE * 2; 11].try_into().unwrap();
+        let deposit_prices: price_oracle::PricesOf<Test> = vec![BASE; 11].try_into().unwrap();
+        let update = PriceUpdate {
+            base_prices: Some(base_prices.clone()),
+            rent_prices: None,
+            deposit_prices: Some(deposit_prices.clone()),
+        };
+
+        assert_noop!(
+            PriceOracle::schedule_price_update(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                update.clone(),
+                10
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        // less than `MinPriceNotice` ahead
+        assert_noop!(
+            PriceOracle::schedule_price_update(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                update.clone(),
+                5
+            ),
+            price_oracle::Error::<Test>::PriceNoticeTooShort
+        );
+        assert_ok!(PriceOracle::schedule_price_update(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            update.clone(),
+            10
+        ));
+        System::assert_last_event(RuntimeEvent::PriceOracle(
+            price_oracle::Event::PriceUpdateScheduled(update.clone(), 10),
+        ));
+
+        // still the current prices until the effective block
+        System::set_block_number(9);
+        PriceOracle::on_initialize(9);
+        assert_ne!(BasePrice::<Test>::get(), base_prices);
+        assert_eq!(PendingPriceUpdate::<Test>::get(), Some((update, 10)));
+
+        System::set_block_number(10);
+        PriceOracle::on_initialize(10);
+        assert_eq!(BasePrice::<Test>::get(), base_prices);
+        assert_eq!(RentPrice::<Test>::get(), rent_prices);
+        assert_eq!(DepositPrice::<Test>::get(), deposit_prices);
+        assert_eq!(PendingPriceUpdate::<Test>::get(), None);
+        System::assert_has_event(RuntimeEvent::PriceOracle(
+            price_oracle::Event::BasePriceChanged(base_prices),
+        ));
+        System::assert_last_event(RuntimeEvent::PriceOracle(
+            price_oracle::Event::DepositPriceChanged(deposit_prices),
+        ));
+    });
+}
+
+#[test]
+fn duration_discount_test() {
+    use price_oracle::DurationDiscount;
+    use sp_runtime::Perbill;
+    use traits::PriceOracle as _;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let year: Moment = 365 * 24 * 60 * 60;
+        let full = |duration| PriceOracle::renew_fee(5, duration).unwrap();
+        let (one_year, two_years, five_years, ten_years) =
+            (full(year), full(2 * year), full(5 * year), full(10 * year));
+
+        let unsorted: price_oracle::DurationDiscountsOf<Test> = vec![
+            DurationDiscount {
+                min_duration: 5 * year,
+                discount_bps: 1_500,
+            },
+            DurationDiscount {
+                min_duration: 2 * year,
+                discount_bps: 500,
+            },
+        ]
+        .try_into()
+        .unwrap();
+        assert_noop!(
+            PriceOracle::set_duration_discounts(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                unsorted.clone()
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            PriceOracle::set_duration_discounts(RuntimeOrigin::signed(MANAGER_ACCOUNT), unsorted),
+            price_oracle::Error::<Test>::UnsortedDurationDiscounts
+        );
+        let too_high: price_oracle::DurationDiscountsOf<Test> = vec![DurationDiscount {
+            min_duration: 2 * year,
+            discount_bps: 10_001,
+        }]
+        .try_into()
+        .unwrap();
+        assert_noop!(
+            PriceOracle::set_duration_discounts(RuntimeOrigin::signed(MANAGER_ACCOUNT), too_high),
+            price_oracle::Error::<Test>::DiscountTooHigh
+        );
+
+        let discounts: price_oracle::DurationDiscountsOf<Test> = vec![
+            DurationDiscount {
+                min_duration: 2 * year,
+                discount_bps: 500,
+            },
+            DurationDiscount {
+                min_duration: 5 * year,
+                discount_bps: 1_500,
+            },
+        ]
+        .try_into()
+        .unwrap();
+        assert_ok!(PriceOracle::set_duration_discounts(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            discounts.clone()
+        ));
+        System::assert_last_event(RuntimeEvent::PriceOracle(
+            price_oracle::Event::DurationDiscountsChanged(discounts),
+        ));
+
+        assert_eq!(full(year), one_year);
+        assert_eq!(
+            full(2 * year),
+            two_years - Perbill::from_percent(5) * two_years
+        );
+        assert_eq!(
+            full(5 * year),
+            five_years - Perbill::from_percent(15) * five_years
+        );
+        // the longest reached discount applies
+        assert_eq!(
+            full(10 * year),
+            ten_years - Perbill::from_percent(15) * ten_years
+        );
+        // five years at once are cheaper than five annual renewals
+        assert!(full(5 * year) < one_year * 5);
+    });
+}
+
+#[test]
+fn redeem_code_beneficiary_test() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            10,
+            None,
+            1,
+            None
+        ));
+        let (label, _) = Label::new_with_len("cupnfish".as_bytes()).unwrap();
+        let duration = MinRegistrationDuration::get();
+        let code = TestSignature(
+            OFFICIAL_ACCOUNT,
+            (label.node, duration, 0_u32, POOR_ACCOUNT, 10_u64).encode(),
+        );
+
+        // an interceptor can't redeem the code for itself
+        assert_noop!(
+            RedeemCode::name_redeem(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                b"cupnfish".to_vec(),
+                duration,
+                0,
+                code.clone(),
+                RICH_ACCOUNT,
+                10
+            ),
+            redeem_code::Error::<Test>::InvalidSignature
+        );
+        // nor extend its deadline
+        assert_noop!(
+            RedeemCode::name_redeem(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                b"cupnfish".to_vec(),
+                duration,
+                0,
+                code.clone(),
+                POOR_ACCOUNT,
+                20
+            ),
+            redeem_code::Error::<Test>::InvalidSignature
+        );
+
+        System::set_block_number(11);
+        assert_noop!(
+            RedeemCode::name_redeem(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                b"cupnfish".to_vec(),
+                duration,
+                0,
+                code.clone(),
+                POOR_ACCOUNT,
+                10
+            ),
+            redeem_code::Error::<Test>::RedeemCodeExpired
+        );
+
+        System::set_block_number(10);
+        // anyone may submit it for the signed owner
+        assert_ok!(RedeemCode::name_redeem(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            b"cupnfish".to_vec(),
+            duration,
+            0,
+            code,
+            POOR_ACCOUNT,
+            10
+        ));
+        assert!(Nft::is_owner(
+            &POOR_ACCOUNT,
+            (0, label.encode_with_node(&DOT_BASENODE))
+        ));
+    });
+}
+
+#[test]
+fn redeem_batch_test() {
+    use sp_core::H256;
+
+    new_test_ext().execute_with(|| {
+        let duration = MinRegistrationDuration::get();
+        let hash = |pair: (H256, H256)| H256(sp_io::hashing::blake2_256(&pair.encode()));
+        let leaves = [
+            RedeemCode::leaf_of(0, duration, &POOR_ACCOUNT),
+            RedeemCode::leaf_of(1, duration, &RICH_ACCOUNT),
+            RedeemCode::leaf_of(2, duration, &POOR_ACCOUNT),
+            H256::zero(),
+        ];
+        let left = hash((leaves[0], leaves[1]));
+        let right = hash((leaves[2], leaves[3]));
+        let root = hash((left, right));
+
+        assert_noop!(
+            RedeemCode::commit_redeem_batch(RuntimeOrigin::signed(POOR_ACCOUNT), root, 3, 10),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            RedeemCode::commit_redeem_batch(RuntimeOrigin::signed(MANAGER_ACCOUNT), root, 0, 10),
+            redeem_code::Error::<Test>::InvalidBatchSize
+        );
+        assert_ok!(RedeemCode::commit_redeem_batch(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            root,
+            3,
+            10
+        ));
+
+        let proof = |nodes: Vec<H256>| -> frame_support::BoundedVec<H256, MaxBatchDepth> {
+            nodes.try_into().unwrap()
+        };
+        assert_noop!(
+            RedeemCode::name_redeem_batch(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                b"cupnfishxxx".to_vec(),
+                duration,
+                1,
+                2,
+                proof(vec![leaves[3], left]),
+                POOR_ACCOUNT
+            ),
+            redeem_code::Error::<Test>::UnknownBatch
+        );
+        // the code of index 2 belongs to `POOR_ACCOUNT`
+        assert_noop!(
+            RedeemCode::name_redeem_batch(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                b"cupnfishxxx".to_vec(),
+                duration,
+                0,
+                2,
+                proof(vec![leaves[3], left]),
+                RICH_ACCOUNT
+            ),
+            redeem_code::Error::<Test>::InvalidProof
+        );
+        // the zero padding leaf is not a code
+        assert_noop!(
+            RedeemCode::name_redeem_batch(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                b"cupnfishxxx".to_vec(),
+                duration,
+                0,
+                3,
+                proof(vec![leaves[2], left]),
+                POOR_ACCOUNT
+            ),
+            redeem_code::Error::<Test>::InvalidProof
+        );
+        assert_ok!(RedeemCode::name_redeem_batch(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            b"cupnfishxxx".to_vec(),
+            duration,
+            0,
+            2,
+            proof(vec![leaves[3], left]),
+            POOR_ACCOUNT
+        ));
+        let test_node = Label::new_with_len("cupnfishxxx".as_bytes())
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert!(Nft::is_owner(&POOR_ACCOUNT, (0, test_node)));
+
+        assert_noop!(
+            RedeemCode::name_redeem_batch(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                b"cupnfishyyy".to_vec(),
+                duration,
+                0,
+                2,
+                proof(vec![leaves[3], left]),
+                POOR_ACCOUNT
+            ),
+            redeem_code::Error::<Test>::RedeemsHasBeenUsed
+        );
+
+        System::set_block_number(11);
+        assert_noop!(
+            RedeemCode::name_redeem_batch(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                b"cupnfishyyy".to_vec(),
+                duration,
+                0,
+                1,
+                proof(vec![leaves[0], right]),
+                RICH_ACCOUNT
+            ),
+            redeem_code::Error::<Test>::RedeemCodeExpired
+        );
+    });
+}
+
+#[test]
+fn expiring_redeem_code_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            4,
+            Some(10),
+            1,
+            None
+        ));
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            5,
+            9,
+            None,
+            1,
+            None
+        ));
+        System::assert_last_event(RuntimeEvent::RedeemCode(
+            redeem_code::Event::RedeemsMinted {
+                start: 5,
+                end: 9,
+                expires_at: None,
+                max_uses: 1,
+                campaign: None,
+            },
+        ));
+
+        let duration = MinRegistrationDuration::get();
+        let code = |nouce: u32| {
+            TestSignature(
+                OFFICIAL_ACCOUNT,
+                (
+                    duration,
+                    nouce,
+                    POOR_ACCOUNT,
+                    100_u64,
+                    LengthClass::AtLeast(10),
+                )
+                    .encode(),
+            )
+        };
+        let redeem = |name: &[u8], nouce: u32| {
+            RedeemCode::name_redeem_any(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                name.to_vec(),
+                duration,
+                nouce,
+                code(nouce),
+                POOR_ACCOUNT,
+                100,
+                LengthClass::AtLeast(10),
+            )
+        };
+
+        // nothing has expired yet
+        assert_ok!(RedeemCode::sweep_redeems(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            0,
+            9
+        ));
+        System::assert_last_event(RuntimeEvent::RedeemCode(redeem_code::Event::RedeemsSwept {
+            count: 0,
+        }));
+        assert_ok!(redeem(b"cupnfishxxx", 0));
+
+        System::set_block_number(11);
+        assert_noop!(
+            redeem(b"cupnfishyyy", 1),
+            redeem_code::Error::<Test>::RedeemCodeExpired
+        );
+        assert_ok!(redeem(b"cupnfishyyy", 5));
+
+        assert_ok!(RedeemCode::sweep_redeems(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            0,
+            9
+        ));
+        System::assert_last_event(RuntimeEvent::RedeemCode(redeem_code::Event::RedeemsSwept {
+            count: 4,
+        }));
+        assert!((0..5).all(|nouce| !redeem_code::Redeems::<Test>::contains_key(nouce)));
+        assert!((6..10).all(|nouce| redeem_code::Redeems::<Test>::contains_key(nouce)));
+        assert_noop!(
+            redeem(b"cupnfishzzz", 1),
+            redeem_code::Error::<Test>::RedeemsHasBeenUsed
+        );
+    });
+}
+
+#[test]
+fn length_class_redeem_code_test() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            10,
+            None,
+            1,
+            None
+        ));
+        let duration = MinRegistrationDuration::get();
+        let short = LengthClass::Exactly(3);
+        let code = TestSignature(
+            OFFICIAL_ACCOUNT,
+            (duration, 0_u32, POOR_ACCOUNT, 100_u64, short).encode(),
+        );
+        let redeem = |name: &[u8], length: LengthClass| {
+            RedeemCode::name_redeem_any(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                name.to_vec(),
+                duration,
+                0,
+                code.clone(),
+                POOR_ACCOUNT,
+                100,
+                length,
+            )
+        };
+
+        // a short name voucher can't be used for a long name
+        assert_noop!(
+            redeem(b"cupnfishxxx", short),
+            redeem_code::Error::<Test>::LabelLenInvalid
+        );
+        // nor turned into a long name voucher
+        assert_noop!(
+            redeem(b"cupnfishxxx", LengthClass::AtLeast(3)),
+            redeem_code::Error::<Test>::InvalidSignature
+        );
+        assert_ok!(redeem(b"pns", short));
+
+        let test_node = Label::new_with_len("pns".as_bytes())
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert!(Nft::is_owner(&POOR_ACCOUNT, (0, test_node)));
+    });
+}
+
+#[test]
+fn revoke_redeem_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            10,
+            None,
+            1,
+            None
+        ));
+
+        assert_noop!(
+            RedeemCode::revoke_redeem(RuntimeOrigin::signed(POOR_ACCOUNT), 0, 4),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            RedeemCode::revoke_redeem(RuntimeOrigin::signed(MANAGER_ACCOUNT), 4, 0),
+            redeem_code::Error::<Test>::RangeInvaild
+        );
+        assert_ok!(RedeemCode::revoke_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            4
+        ));
+        System::assert_last_event(RuntimeEvent::RedeemCode(
+            redeem_code::Event::RedeemsRevoked { start: 0, end: 4 },
+        ));
+        assert!((0..5).all(|nouce| !redeem_code::Redeems::<Test>::contains_key(nouce)));
+        assert!((5..=10).all(|nouce| redeem_code::Redeems::<Test>::contains_key(nouce)));
+
+        let duration = MinRegistrationDuration::get();
+        let code = TestSignature(
+            OFFICIAL_ACCOUNT,
+            (
+                duration,
+                0_u32,
+                POOR_ACCOUNT,
+                100_u64,
+                LengthClass::AtLeast(10),
+            )
+                .encode(),
+        );
+        assert_noop!(
+            RedeemCode::name_redeem_any(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                b"cupnfishxxx".to_vec(),
+                duration,
+                0,
+                code,
+                POOR_ACCOUNT,
+                100,
+                LengthClass::AtLeast(10)
+            ),
+            redeem_code::Error::<Test>::RedeemsHasBeenUsed
+        );
+    });
+}
+
+#[test]
+fn multi_use_redeem_code_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            RedeemCode::mint_redeem(RuntimeOrigin::signed(MANAGER_ACCOUNT), 0, 1, None, 0, None),
+            redeem_code::Error::<Test>::InvalidMaxUses
+        );
+        assert_noop!(
+            RedeemCode::mint_redeem(RuntimeOrigin::signed(MANAGER_ACCOUNT), 0, 1, None, 101, None),
+            redeem_code::Error::<Test>::InvalidMaxUses
+        );
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            1,
+            None,
+            2,
+            None
+        ));
+
+        let duration = MinRegistrationDuration::get();
+        let length = LengthClass::AtLeast(10);
+        let redeem = |name: &[u8], owner: u64| {
+            RedeemCode::name_redeem_any(
+                RuntimeOrigin::signed(owner),
+                name.to_vec(),
+                duration,
+                0,
+                TestSignature(
+                    OFFICIAL_ACCOUNT,
+                    (duration, 0_u32, owner, 100_u64, length).encode(),
+                ),
+                owner,
+                100,
+                length,
+            )
+        };
+
+        assert_ok!(redeem(b"cupnfishxxx", POOR_ACCOUNT));
+        assert!(matches!(
+            System::events().last().unwrap().event,
+            RuntimeEvent::RedeemCode(redeem_code::Event::RedeemCodeUsed { remaining: 1, .. })
+        ));
+        // each account redeems a code once
+        assert_noop!(
+            redeem(b"cupnfishyyy", POOR_ACCOUNT),
+            redeem_code::Error::<Test>::AlreadyRedeemed
+        );
+
+        assert_ok!(redeem(b"cupnfishyyy", RICH_ACCOUNT));
+        assert!(matches!(
+            System::events().last().unwrap().event,
+            RuntimeEvent::RedeemCode(redeem_code::Event::RedeemCodeUsed { remaining: 0, .. })
+        ));
+        assert!(!redeem_code::Redeems::<Test>::contains_key(0));
+        assert!(!redeem_code::RedeemedBy::<Test>::contains_key(
+            0,
+            POOR_ACCOUNT
+        ));
+        assert_noop!(
+            redeem(b"cupnfishzzz", MONEY_ACCOUNT),
+            redeem_code::Error::<Test>::RedeemsHasBeenUsed
+        );
+    });
+}
+
+#[test]
+fn name_redeem_exact_test() {
+    new_test_ext().execute_with(|| {
+        let ksm = Label::new_basenode(b"ksm").unwrap().node;
+        assert_ok!(Registrar::create_tld(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            b"ksm".to_vec(),
+            pns_types::TldConfig {
+                grace_period: 30 * DAYS,
+                capacity: 5,
+                min_duration: MinRegistrationDuration::get(),
+                open: true,
+            }
+        ));
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            10,
+            None,
+            1,
+            None
+        ));
+
+        let duration = MinRegistrationDuration::get();
+        let (label, _) = Label::new_with_len(b"pns").unwrap();
+        let node = label.encode_with_node(&ksm);
+        let code = TestSignature(
+            OFFICIAL_ACCOUNT,
+            (node, duration, 0_u32, POOR_ACCOUNT, 100_u64).encode(),
+        );
+        let redeem = |base_node, name: &[u8]| {
+            RedeemCode::name_redeem_exact(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                base_node,
+                name.to_vec(),
+                duration,
+                0,
+                code.clone(),
+                POOR_ACCOUNT,
+                100,
+            )
+        };
+
+        // only the signed name under the signed base node
+        assert_noop!(
+            redeem(ksm, b"cupnfishxxx"),
+            redeem_code::Error::<Test>::InvalidSignature
+        );
+        assert_noop!(
+            redeem(DOT_BASENODE, b"pns"),
+            redeem_code::Error::<Test>::InvalidSignature
+        );
+        // nor through `name_redeem` under the default base node
+        assert_noop!(
+            RedeemCode::name_redeem(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                b"pns".to_vec(),
+                duration,
+                0,
+                code.clone(),
+                POOR_ACCOUNT,
+                100
+            ),
+            redeem_code::Error::<Test>::InvalidSignature
+        );
+
+        assert_ok!(redeem(ksm, b"pns"));
+        assert!(Nft::is_owner(&POOR_ACCOUNT, (0, node)));
+        assert!(!Nft::is_owner(
+            &POOR_ACCOUNT,
+            (0, label.encode_with_node(&DOT_BASENODE))
+        ));
+    });
+}
+
+#[test]
+fn renew_redeem_test() {
+    new_test_ext().execute_with(|| {
+        let duration = MinRegistrationDuration::get();
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"cupnfishxxx".to_vec(),
+            RICH_ACCOUNT,
+            duration,
+            None
+        ));
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            10,
+            None,
+            1,
+            None
+        ));
+
+        let node = Label::new_with_len(b"cupnfishxxx")
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        let expire = registrar::RegistrarInfos::<Test>::get(node).unwrap().expire;
+        let code = TestSignature(
+            OFFICIAL_ACCOUNT,
+            (node, 30 * DAYS, 0_u32, POOR_ACCOUNT, 10_u64).encode(),
+        );
+
+        // the code is for another duration
+        assert_noop!(
+            RedeemCode::renew_redeem(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                b"cupnfishxxx".to_vec(),
+                60 * DAYS,
+                0,
+                code.clone(),
+                POOR_ACCOUNT,
+                10
+            ),
+            redeem_code::Error::<Test>::InvalidSignature
+        );
+        // an interceptor can't redeem the code for itself
+        assert_noop!(
+            RedeemCode::renew_redeem(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                b"cupnfishxxx".to_vec(),
+                30 * DAYS,
+                0,
+                code.clone(),
+                RICH_ACCOUNT,
+                10
+            ),
+            redeem_code::Error::<Test>::InvalidSignature
+        );
+
+        System::set_block_number(11);
+        assert_noop!(
+            RedeemCode::renew_redeem(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                b"cupnfishxxx".to_vec(),
+                30 * DAYS,
+                0,
+                code.clone(),
+                POOR_ACCOUNT,
+                10
+            ),
+            redeem_code::Error::<Test>::RedeemCodeExpired
+        );
+
+        System::set_block_number(10);
+
+        let free = Balances::free_balance(POOR_ACCOUNT);
+        assert_ok!(RedeemCode::renew_redeem(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            b"cupnfishxxx".to_vec(),
+            30 * DAYS,
+            0,
+            code.clone(),
+            POOR_ACCOUNT,
+            10
+        ));
+        assert_eq!(
+            registrar::RegistrarInfos::<Test>::get(node).unwrap().expire,
+            expire + 30 * DAYS
+        );
+        assert_eq!(Balances::free_balance(POOR_ACCOUNT), free);
+
+        assert_noop!(
+            RedeemCode::renew_redeem(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                b"cupnfishxxx".to_vec(),
+                30 * DAYS,
+                0,
+                code,
+                POOR_ACCOUNT,
+                10
+            ),
+            redeem_code::Error::<Test>::RedeemsHasBeenUsed
+        );
+    });
+}
+
+#[test]
+fn redeem_campaign_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name: redeem_code::CampaignNameOf<Test> = b"conference".to_vec().try_into().unwrap();
+        assert_noop!(
+            RedeemCode::create_campaign(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                name.clone(),
+                Some(1),
+                None
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(RedeemCode::create_campaign(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            name.clone(),
+            Some(1),
+            None
+        ));
+        System::assert_last_event(RuntimeEvent::RedeemCode(
+            redeem_code::Event::CampaignCreated { campaign: 0, name },
+        ));
+        assert_noop!(
+            RedeemCode::mint_redeem(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                0,
+                2,
+                None,
+                1,
+                Some(1)
+            ),
+            redeem_code::Error::<Test>::UnknownCampaign
+        );
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            2,
+            None,
+            1,
+            Some(0)
+        ));
+
+        let duration = MinRegistrationDuration::get();
+        let length = LengthClass::AtLeast(10);
+        let redeem = |name: &[u8], nouce: u32| {
+            RedeemCode::name_redeem_any(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                name.to_vec(),
+                duration,
+                nouce,
+                TestSignature(
+                    OFFICIAL_ACCOUNT,
+                    (duration, nouce, POOR_ACCOUNT, 100_u64, length).encode(),
+                ),
+                POOR_ACCOUNT,
+                100,
+                length,
+            )
+        };
+
+        assert_ok!(RedeemCode::pause_campaign(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            true
+        ));
+        assert_noop!(
+            redeem(b"cupnfishxxx", 0),
+            redeem_code::Error::<Test>::CampaignNotActive
+        );
+        assert_ok!(RedeemCode::pause_campaign(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            false
+        ));
+        assert_ok!(redeem(b"cupnfishxxx", 0));
+        let campaign = redeem_code::Campaigns::<Test>::get(0).unwrap();
+        assert_eq!(
+            (campaign.redemptions, campaign.total_duration),
+            (1, duration)
+        );
+        // a single registration in the budget
+        assert_noop!(
+            redeem(b"cupnfishyyy", 1),
+            redeem_code::Error::<Test>::CampaignBudgetExceeded
+        );
+
+        assert_ok!(RedeemCode::close_campaign(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0
+        ));
+        System::assert_last_event(RuntimeEvent::RedeemCode(
+            redeem_code::Event::CampaignClosed {
+                campaign: 0,
+                redemptions: 1,
+                total_duration: duration,
+            },
+        ));
+        assert_noop!(
+            RedeemCode::pause_campaign(RuntimeOrigin::signed(MANAGER_ACCOUNT), 0, false),
+            redeem_code::Error::<Test>::CampaignClosed
+        );
+        assert_noop!(
+            RedeemCode::mint_redeem(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                3,
+                5,
+                None,
+                1,
+                Some(0)
+            ),
+            redeem_code::Error::<Test>::CampaignClosed
+        );
+        assert_noop!(
+            redeem(b"cupnfishyyy", 1),
+            redeem_code::Error::<Test>::CampaignNotActive
+        );
+    });
+}
+
+#[test]
+fn spent_redeem_code_audit_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        assert_ok!(RedeemCode::mint_redeem(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            0,
+            10,
+            None,
+            1,
+            None
+        ));
+
+        let duration = MinRegistrationDuration::get();
+        let length = LengthClass::AtLeast(10);
+        let code = TestSignature(
+            OFFICIAL_ACCOUNT,
+            (duration, 7_u32, POOR_ACCOUNT, 100_u64, length).encode(),
+        );
+        assert_ok!(RedeemCode::name_redeem_any(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            b"cupnfishxxx".to_vec(),
+            duration,
+            7,
+            code.clone(),
+            POOR_ACCOUNT,
+            100,
+            length
+        ));
+
+        let node = Label::new_with_len(b"cupnfishxxx")
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        System::assert_last_event(RuntimeEvent::RedeemCode(
+            redeem_code::Event::RedeemCodeUsed {
+                code,
+                nouce: 7,
+                node,
+                to: POOR_ACCOUNT,
+                remaining: 0,
+            },
+        ));
+        assert_eq!(
+            redeem_code::SpentCodes::<Test>::get(7, POOR_ACCOUNT),
+            Some(redeem_code::SpentCode { node, at: 3 })
+        );
+        assert_eq!(redeem_code::SpentCodes::<Test>::get(7, RICH_ACCOUNT), None);
+    });
+}
+
+#[test]
+fn origin_role_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let prices = price_oracle::BasePrice::<Test>::get();
+        let node = Label::new_with_len(b"cupnfishxx")
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+
+        // only the managers give out roles
+        assert_noop!(
+            ManagerOrigin::set_role(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                POOR_ACCOUNT,
+                origin::Role::PriceAdmin,
+                true
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            PriceOracle::set_base_price(RuntimeOrigin::signed(POOR_ACCOUNT), prices.clone()),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(ManagerOrigin::set_role(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            POOR_ACCOUNT,
+            origin::Role::PriceAdmin,
+            true
+        ));
+        System::assert_last_event(RuntimeEvent::ManagerOrigin(origin::Event::RoleGranted(
+            POOR_ACCOUNT,
+            origin::Role::PriceAdmin,
+        )));
+
+        // the price admin sets the prices, but nothing else
+        assert_ok!(PriceOracle::set_base_price(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            prices
+        ));
+        assert_noop!(
+            Registry::freeze(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Registrar::add_reserved(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            ManagerOrigin::set_registrar_open(RuntimeOrigin::signed(POOR_ACCOUNT), false),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        // the reserve admin only manages the reserved names
+        assert_ok!(ManagerOrigin::set_role(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            RICH_ACCOUNT,
+            origin::Role::ReserveAdmin,
+            true
+        ));
+        assert_ok!(Registrar::add_reserved(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node
+        ));
+        assert_noop!(
+            Registry::freeze(RuntimeOrigin::signed(RICH_ACCOUNT), node),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_ok!(ManagerOrigin::set_role(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            POOR_ACCOUNT,
+            origin::Role::PriceAdmin,
+            false
+        ));
+        System::assert_last_event(RuntimeEvent::ManagerOrigin(origin::Event::RoleRevoked(
+            POOR_ACCOUNT,
+            origin::Role::PriceAdmin,
+        )));
+        assert_noop!(
+            PriceOracle::set_base_price(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                price_oracle::BasePrice::<Test>::get()
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn manager_handover_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_noop!(
+            ManagerOrigin::propose_origin(RuntimeOrigin::signed(RICH_ACCOUNT), RICH_ACCOUNT),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        // a wrong address gains nothing until it accepts
+        assert_ok!(ManagerOrigin::propose_origin(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            POOR_ACCOUNT
+        ));
+        System::assert_last_event(RuntimeEvent::ManagerOrigin(origin::Event::OriginProposed(
+            MANAGER_ACCOUNT,
+            POOR_ACCOUNT,
+        )));
+        assert!(!origin::Origins::<Test>::contains_key(POOR_ACCOUNT));
+        assert_noop!(
+            ManagerOrigin::accept_origin(RuntimeOrigin::signed(RICH_ACCOUNT)),
+            origin::Error::<Test>::NotProposed
+        );
+
+        assert_ok!(ManagerOrigin::propose_origin(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            RICH_ACCOUNT
+        ));
+        assert_ok!(ManagerOrigin::accept_origin(RuntimeOrigin::signed(
+            RICH_ACCOUNT
+        )));
+        System::assert_last_event(RuntimeEvent::ManagerOrigin(origin::Event::OriginAccepted(
+            MANAGER_ACCOUNT,
+            RICH_ACCOUNT,
+        )));
+        assert!(origin::Origins::<Test>::contains_key(RICH_ACCOUNT));
+        assert!(!origin::Origins::<Test>::contains_key(MANAGER_ACCOUNT));
+        assert_noop!(
+            ManagerOrigin::set_registrar_open(RuntimeOrigin::signed(MANAGER_ACCOUNT), false),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(ManagerOrigin::set_registrar_open(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            false
+        ));
+
+        // the stale proposal died with the seat it would have taken over
+        assert_noop!(
+            ManagerOrigin::accept_origin(RuntimeOrigin::signed(POOR_ACCOUNT)),
+            origin::Error::<Test>::ProposerNotManager
+        );
+    });
+}
+
+#[test]
+fn council_origin_test() {
+    new_test_ext().execute_with(|| {
+        let prices = price_oracle::BasePrice::<Test>::get();
+        let council = |yes: u32, members: u32| -> RuntimeOrigin {
+            pallet_collective::RawOrigin::<AccountId, CouncilCollective>::Members(yes, members)
+                .into()
+        };
+
+        // a tied vote is not a majority
+        assert_noop!(
+            PriceOracle::set_base_price(council(2, 4), prices.clone()),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(PriceOracle::set_base_price(council(3, 4), prices.clone()));
+        assert_eq!(
+            <<Test as price_oracle::Config>::ManagerOrigin as EnsureOrigin<_>>::ensure_origin(
+                council(3, 4)
+            ),
+            Ok(COUNCIL_ACCOUNT)
+        );
+
+        // the price admins keep their powers next to the council
+        assert_ok!(PriceOracle::set_base_price(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            prices
+        ));
+    });
+}
+
+#[test]
+fn pause_operation_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"cupnfishxx";
+        let node = Label::new_with_len(name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_noop!(
+            ManagerOrigin::set_paused(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                traits::Operation::Transfer,
+                true
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(ManagerOrigin::set_paused(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            traits::Operation::Transfer,
+            true
+        ));
+        System::assert_last_event(RuntimeEvent::ManagerOrigin(origin::Event::PauseChanged(
+            traits::Operation::Transfer,
+            true,
+        )));
+        assert_ok!(ManagerOrigin::set_paused(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            traits::Operation::ResolverWrites,
+            true
+        ));
+
+        // only the paused operations stop
+        assert_noop!(
+            Registrar::transfer(RuntimeOrigin::signed(RICH_ACCOUNT), POOR_ACCOUNT, node),
+            registrar::Error::<Test>::OperationPaused
+        );
+        assert_noop!(
+            Resolvers::set_account(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                node,
+                Address::Id(POOR_ACCOUNT)
+            ),
+            pns_resolvers::resolvers::Error::<Test>::WritesPaused
+        );
+        assert_ok!(Registrar::renew(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            MinRegistrationDuration::get(),
+            None
+        ));
+
+        assert_ok!(ManagerOrigin::set_paused(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            traits::Operation::Transfer,
+            false
+        ));
+        System::assert_last_event(RuntimeEvent::ManagerOrigin(origin::Event::PauseChanged(
+            traits::Operation::Transfer,
+            false,
+        )));
+        assert_ok!(Registrar::transfer(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            POOR_ACCOUNT,
+            node
+        ));
+        assert_eq!(
+            origin::PausedOperations::<Test>::get(),
+            traits::Operation::ResolverWrites.bit()
+        );
+    });
+}
+
+#[test]
+fn audit_log_test() {
+    new_test_ext().execute_with(|| {
+        use frame_support::traits::PalletInfoAccess;
+        System::set_block_number(3);
+        let node = Label::new_with_len(b"cupnfishxx")
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        let prices = price_oracle::BasePrice::<Test>::get();
+
+        // failed calls leave no trace
+        assert_noop!(
+            Registrar::add_reserved(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert!(ManagerOrigin::audit_log().is_empty());
+
+        assert_ok!(ManagerOrigin::set_registrar_open(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            true
+        ));
+        assert_ok!(Registrar::add_reserved(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node
+        ));
+        assert_ok!(PriceOracle::set_base_price(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            prices
+        ));
+        assert_ok!(Registrar::remove_reserved(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node
+        ));
+        System::set_block_number(4);
+        assert_ok!(ManagerOrigin::set_paused(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            traits::Operation::Register,
+            false
+        ));
+
+        // only the last `MaxAuditEntries` are kept, oldest first
+        assert_eq!(origin::AuditCount::<Test>::get(), 5);
+        let log = ManagerOrigin::audit_log();
+        assert_eq!(log.len(), 4);
+        assert_eq!(
+            log[0],
+            pns_types::AuditEntry {
+                pallet: Registrar::index() as u8,
+                call: 0,
+                args_hash: sp_io::hashing::blake2_256(&node.encode()).into(),
+                who: MANAGER_ACCOUNT,
+                at: 3,
+            }
+        );
+        assert_eq!(log[1].pallet, PriceOracle::index() as u8);
+        assert_eq!((log[2].pallet, log[2].call), (Registrar::index() as u8, 1));
+        assert_eq!(
+            (log[3].pallet, log[3].call, log[3].at),
+            (ManagerOrigin::index() as u8, 6, 4)
+        );
+    });
+}
+
+#[test]
+fn reserved_batch_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let node = |name: &[u8]| {
+            Label::new_with_len(name)
+                .unwrap()
+                .0
+                .encode_with_node(&DOT_BASENODE)
+        };
+        let nodes = [
+            node(b"cupnfishxx1"),
+            node(b"cupnfishxx2"),
+            node(b"cupnfishxx3"),
+        ];
+        let council: RuntimeOrigin =
+            pallet_collective::RawOrigin::<AccountId, CouncilCollective>::Members(3, 4).into();
+
+        assert_noop!(
+            Registrar::add_reserved_batch(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                vec![].try_into().unwrap()
+            ),
+            registrar::Error::<Test>::InvalidBatchSize
+        );
+        // up to `ReserveBatchThreshold` names the manager key is enough
+        assert_ok!(Registrar::add_reserved_batch(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            nodes[..2].to_vec().try_into().unwrap()
+        ));
+        System::assert_has_event(RuntimeEvent::Registrar(registrar::Event::NameReserved {
+            node: nodes[0],
+        }));
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::NameReserved {
+            node: nodes[1],
+        }));
+
+        // larger batches need root or the council
+        assert_noop!(
+            Registrar::add_reserved_batch(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                nodes.to_vec().try_into().unwrap()
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Registrar::add_reserved_batch(
+            council,
+            nodes.to_vec().try_into().unwrap()
+        ));
+        assert!(nodes
+            .iter()
+            .all(|node| registrar::ReservedList::<Test>::contains_key(node)));
+
+        assert_noop!(
+            Registrar::remove_reserved_batch(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                nodes.to_vec().try_into().unwrap()
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Registrar::remove_reserved_batch(
+            RuntimeOrigin::root(),
+            nodes.to_vec().try_into().unwrap()
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::NameUnReserved {
+            node: nodes[2],
+        }));
+        assert!(!nodes
+            .iter()
+            .any(|node| registrar::ReservedList::<Test>::contains_key(node)));
+    });
+}
+
+#[test]
+fn emergency_freeze_test() {
+    use pns_types::ddns::codec_type::RecordType;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"cupnfishxx";
+        let node = Label::new_with_len(name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Resolvers::set_record(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            RecordType::A,
+            vec![127, 0, 0, 1].into()
+        ));
+
+        // a single manager key can't do it
+        assert_noop!(
+            Registry::emergency_freeze(RuntimeOrigin::signed(MANAGER_ACCOUNT), node, false),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Registry::emergency_freeze(
+            RuntimeOrigin::root(),
+            node,
+            false
+        ));
+        System::assert_last_event(RuntimeEvent::Registry(registry::Event::EmergencyFrozen {
+            node,
+            serve_records: false,
+            appeal_until: 6,
+            expires_at: 11,
+        }));
+        assert_noop!(
+            Registrar::transfer(RuntimeOrigin::signed(RICH_ACCOUNT), POOR_ACCOUNT, node),
+            registry::Error::<Test>::Frozen
+        );
+        assert!(Resolvers::lookup(node).is_empty());
+
+        // only the owner appeals, once, within the appeal period
+        assert_noop!(
+            Registry::appeal_freeze(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            registry::Error::<Test>::NotOwned
+        );
+        let reserved = Balances::reserved_balance(RICH_ACCOUNT);
+        assert_ok!(Registry::appeal_freeze(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node
+        ));
+        assert_eq!(
+            Balances::reserved_balance(RICH_ACCOUNT),
+            reserved + AppealBond::get()
+        );
+        assert_eq!(dispute::Appeals::<Test>::get(node), Some(RICH_ACCOUNT));
+        assert_noop!(
+            Registry::appeal_freeze(RuntimeOrigin::signed(RICH_ACCOUNT), node),
+            registry::Error::<Test>::AlreadyAppealed
+        );
+
+        // not confirmed in time, the freeze is over and the bond comes back
+        assert_noop!(
+            Registry::release_freeze(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            registry::Error::<Test>::FreezeNotExpired
+        );
+        System::set_block_number(11);
+        assert_eq!(
+            Resolvers::lookup(node),
+            vec![(RecordType::A, vec![127, 0, 0, 1])]
+        );
+        assert_noop!(
+            Registry::confirm_freeze(RuntimeOrigin::root(), node, true),
+            registry::Error::<Test>::FreezeExpired
+        );
+        assert_ok!(Registry::release_freeze(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node
+        ));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), reserved);
+        assert!(!registry::FrozenNodes::<Test>::contains_key(node));
+        assert!(!dispute::Appeals::<Test>::contains_key(node));
+
+        // an upheld freeze stays and costs the appeal its bond
+        assert_ok!(Registry::emergency_freeze(
+            RuntimeOrigin::root(),
+            node,
+            true
+        ));
+        assert_eq!(Resolvers::lookup(node).len(), 1);
+        assert_noop!(
+            Registry::appeal_freeze(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            registry::Error::<Test>::NotOwned
+        );
+        assert_ok!(Registry::appeal_freeze(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node
+        ));
+        assert_noop!(
+            Registry::confirm_freeze(RuntimeOrigin::root(), node, true),
+            registry::Error::<Test>::AppealPending
+        );
+        assert_ok!(Dispute::rule_appeal(RuntimeOrigin::root(), node, true));
+        let free = Balances::free_balance(RICH_ACCOUNT);
+        assert_ok!(Registry::confirm_freeze(RuntimeOrigin::root(), node, true));
+        System::assert_last_event(RuntimeEvent::Registry(registry::Event::FreezeConfirmed {
+            node,
+        }));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), reserved);
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), free);
+        System::set_block_number(30);
+        assert_noop!(
+            Registrar::transfer(RuntimeOrigin::signed(RICH_ACCOUNT), POOR_ACCOUNT, node),
+            registry::Error::<Test>::Frozen
+        );
+    });
+}
+
+#[test]
+fn slash_deposit_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"cupnfishxx";
+        let node = Label::new_with_len(name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        let deposit = registrar::RegistrarInfos::<Test>::get(node)
+            .unwrap()
+            .deposit;
+        let half = sp_runtime::Perbill::from_percent(50);
+
+        assert_noop!(
+            Registrar::slash_deposit(RuntimeOrigin::signed(MANAGER_ACCOUNT), node, half, false),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        let treasury = Balances::free_balance(TREASURY_ACCOUNT);
+        assert_ok!(Registrar::slash_deposit(
+            RuntimeOrigin::root(),
+            node,
+            half,
+            false
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::DepositSlashed {
+            node,
+            owner: RICH_ACCOUNT,
+            slashed: half * deposit,
+            remaining: deposit - half * deposit,
+        }));
+        assert_eq!(
+            Balances::free_balance(TREASURY_ACCOUNT),
+            treasury + half * deposit
+        );
+        assert_eq!(
+            registrar::RegistrarInfos::<Test>::get(node)
+                .unwrap()
+                .deposit,
+            deposit - half * deposit
+        );
+
+        // burning a frozen name returns what is left of the deposit
+        assert_ok!(Registry::freeze(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node
+        ));
+        let free = Balances::free_balance(RICH_ACCOUNT);
+        assert_ok!(Registrar::slash_deposit(
+            RuntimeOrigin::root(),
+            node,
+            sp_runtime::Perbill::zero(),
+            true
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(
+            registrar::Event::NameBurnedForAbuse {
+                node,
+                owner: RICH_ACCOUNT,
+            },
+        ));
+        assert_eq!(
+            Balances::free_balance(RICH_ACCOUNT),
+            free + deposit - half * deposit
+        );
+        assert!(registrar::RegistrarInfos::<Test>::get(node).is_none());
+        assert!(!registry::FrozenNodes::<Test>::contains_key(node));
+        assert_eq!(Registrar::stats().active_domains, 0);
+    });
+}
+
+#[test]
+fn dispute_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"cupnfishxx";
+        let node = Label::new_with_len(name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        let claim = dispute::EvidenceOf::<Test>::truncate_from(b"ipfs://claim".to_vec());
+        let response = dispute::EvidenceOf::<Test>::truncate_from(b"ipfs://response".to_vec());
+
+        assert_noop!(
+            Dispute::open_dispute(RuntimeOrigin::signed(RICH_ACCOUNT), node, claim.clone()),
+            dispute::Error::<Test>::OwnDomain
+        );
+        assert_ok!(Dispute::open_dispute(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            node,
+            claim.clone()
+        ));
+        assert_eq!(
+            Balances::reserved_balance(MONEY_ACCOUNT),
+            ChallengeBond::get()
+        );
+        assert_noop!(
+            Dispute::open_dispute(RuntimeOrigin::signed(MONEY_ACCOUNT), node, claim.clone()),
+            dispute::Error::<Test>::AlreadyDisputed
+        );
+
+        // the holder answered, the arbitration can rule right away
+        assert_noop!(
+            Dispute::rule(RuntimeOrigin::root(), node, dispute::Ruling::Transfer),
+            dispute::Error::<Test>::AwaitingResponse
+        );
+        assert_noop!(
+            Dispute::respond(RuntimeOrigin::signed(MONEY_ACCOUNT), node, response.clone()),
+            dispute::Error::<Test>::NotHolder
+        );
+        assert_ok!(Dispute::respond(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            response
+        ));
+        assert_noop!(
+            Dispute::rule(
+                RuntimeOrigin::signed(MANAGER_ACCOUNT),
+                node,
+                dispute::Ruling::Transfer
+            ),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        // a frozen domain is transferred all the same
+        assert_ok!(Registry::freeze(
+            RuntimeOrigin::signed(MANAGER_ACCOUNT),
+            node
+        ));
+        let rich = Balances::free_balance(RICH_ACCOUNT);
+        let money = Balances::free_balance(MONEY_ACCOUNT);
+        assert_ok!(Dispute::rule(
+            RuntimeOrigin::root(),
+            node,
+            dispute::Ruling::Transfer
+        ));
+        System::assert_last_event(RuntimeEvent::Dispute(dispute::Event::DisputeRuled {
+            node,
+            ruling: dispute::Ruling::Transfer,
+            winner: MONEY_ACCOUNT,
+            loser: RICH_ACCOUNT,
+        }));
+        assert!(registry::OwnedNodes::<Test>::contains_key(
+            MONEY_ACCOUNT,
+            node
+        ));
+        assert!(!registry::FrozenNodes::<Test>::contains_key(node));
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), rich);
+        assert_eq!(
+            Balances::free_balance(MONEY_ACCOUNT),
+            money + ChallengeBond::get() + ResponseBond::get()
+        );
+        assert!(dispute::Disputes::<Test>::get(node).is_none());
+
+        // nobody answered in time, the challenger loses its bond to the holder
+        let reserved = Balances::reserved_balance(RICH_ACCOUNT);
+        assert_ok!(Dispute::open_dispute(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            claim.clone()
+        ));
+        System::set_block_number(1 + ResponsePeriod::get());
+        let rich = Balances::free_balance(RICH_ACCOUNT);
+        let money = Balances::free_balance(MONEY_ACCOUNT);
+        assert_ok!(Dispute::rule(
+            RuntimeOrigin::root(),
+            node,
+            dispute::Ruling::Retain
+        ));
+        assert!(registry::OwnedNodes::<Test>::contains_key(
+            MONEY_ACCOUNT,
+            node
+        ));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), reserved);
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), rich);
+        assert_eq!(
+            Balances::free_balance(MONEY_ACCOUNT),
+            money + ChallengeBond::get()
+        );
+
+        // withdrawing returns the bond
+        assert_ok!(Dispute::open_dispute(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            claim
+        ));
+        assert_noop!(
+            Dispute::withdraw_dispute(RuntimeOrigin::signed(MONEY_ACCOUNT), node),
+            dispute::Error::<Test>::NotChallenger
+        );
+        assert_ok!(Dispute::withdraw_dispute(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node
+        ));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), reserved);
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), rich);
+    });
+}
+
+#[test]
+fn candle_auction_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        LabelMinLen::set(1);
+        let node = Label::from_normalized(b"ab").encode_with_node(&DOT_BASENODE);
+
+        assert_ok!(Registrar::bid(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"ab".to_vec(),
+            MinAuctionBid::get()
+        ));
+        let end = Timestamp::now() + AuctionPeriod::get();
+
+        // the bid leading the first sample may still win, it stays reserved.
+        Timestamp::set_timestamp(end - AuctionEndingPeriod::get() + 2 * AuctionSampleLength::get());
+        assert_ok!(Registrar::bid(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"ab".to_vec(),
+            150
+        ));
+        assert_eq!(
+            Balances::reserved_balance(RICH_ACCOUNT),
+            MinAuctionBid::get()
+        );
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), 150);
+        assert_eq!(
+            registrar::AuctionSamples::<Test>::iter_prefix(node).count(),
+            2
+        );
+
+        // both bids may still win, there is no room for a third bidder.
+        Timestamp::set_timestamp(end - AuctionEndingPeriod::get() + 3 * AuctionSampleLength::get());
+        assert_noop!(
+            Registrar::bid(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                DOT_BASENODE,
+                b"ab".to_vec(),
+                200
+            ),
+            registrar::Error::<Test>::TooManyBidders
+        );
+
+        // two bids can win, the auction is closed before it is drawn.
+        Timestamp::set_timestamp(end);
+        assert_ok!(Registrar::settle_auction(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::AuctionClosed {
+            node,
+            closed_at: 1,
+        }));
+        assert_noop!(
+            Registrar::settle_auction(RuntimeOrigin::signed(POOR_ACCOUNT), node),
+            registrar::Error::<Test>::RandomnessNotReady
+        );
+        assert_noop!(
+            Registrar::claim_bid(RuntimeOrigin::signed(POOR_ACCOUNT), node, MONEY_ACCOUNT),
+            registrar::Error::<Test>::AuctionNotSettled
+        );
+
+        // the second sample is drawn, which the first bid still led.
+        System::set_block_number(2);
+        RandomSeed::set(1);
+        let rich_free = Balances::free_balance(RICH_ACCOUNT);
+        let money_free = Balances::free_balance(MONEY_ACCOUNT);
+        assert_ok!(Registrar::settle_auction(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(registrar::Event::AuctionSettled {
+            node,
+            winner: RICH_ACCOUNT,
+            amount: MinAuctionBid::get(),
+        }));
+        assert!(Nft::is_owner(&RICH_ACCOUNT, (0, node)));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), 0);
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), rich_free);
+        assert!(registrar::AuctionClosings::<Test>::get(node).is_none());
+        // the bid which lost is returned once claimed.
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), 150);
+        assert_ok!(Registrar::claim_bid(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node,
+            MONEY_ACCOUNT
+        ));
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), 0);
+        assert_eq!(Balances::free_balance(MONEY_ACCOUNT), money_free + 150);
+        assert_noop!(
+            Registrar::claim_bid(RuntimeOrigin::signed(POOR_ACCOUNT), node, MONEY_ACCOUNT),
+            registrar::Error::<Test>::NoBid
+        );
+        assert!(!registrar::AuctionBidders::<Test>::contains_key(node));
+        assert_eq!(
+            registrar::AuctionSamples::<Test>::iter_prefix(node).count(),
+            0
+        );
+    });
+}
+
+#[test]
+fn commitment_deposit_test() {
+    new_test_ext().execute_with(|| {
+        let name = b"cupnfishxxx".to_vec();
+        let secret = [7; 32];
+        let commitment = Registrar::make_commitment(DOT_BASENODE, &name, &RICH_ACCOUNT, &secret);
+        // the commitment covers the base node.
+        assert_ne!(
+            commitment,
+            Registrar::make_commitment(sp_core::H256::zero(), &name, &RICH_ACCOUNT, &secret)
+        );
+
+        let reserved = Balances::reserved_balance(RICH_ACCOUNT);
+        assert_ok!(Registrar::commit(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            commitment
+        ));
+        assert_eq!(
+            Balances::reserved_balance(RICH_ACCOUNT),
+            reserved + CommitmentDeposit::get()
+        );
+        assert_noop!(
+            Registrar::remove_commitment(RuntimeOrigin::signed(MONEY_ACCOUNT), commitment),
+            registrar::Error::<Test>::CommitmentNotExpired
+        );
+
+        // only names committed to can be registered.
+        RequireCommitment::set(true);
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                name.clone(),
+                MONEY_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ),
+            registrar::Error::<Test>::CommitmentRequired
+        );
+        assert_noop!(
+            Registrar::register_batch(
+                RuntimeOrigin::signed(MONEY_ACCOUNT),
+                DOT_BASENODE,
+                vec![name.clone()].try_into().unwrap(),
+                MONEY_ACCOUNT,
+                MinRegistrationDuration::get()
+            ),
+            registrar::Error::<Test>::CommitmentRequired
+        );
+
+        Timestamp::set_timestamp(Timestamp::now() + MinCommitmentAge::get());
+        assert_ok!(Registrar::reveal_register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name,
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            secret,
+            None
+        ));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), reserved);
+
+        // anyone can remove a stale commitment, the deposit goes back to its committer.
+        System::set_block_number(1);
+        let commitment =
+            Registrar::make_commitment(DOT_BASENODE, b"cupnfishxx2", &RICH_ACCOUNT, &secret);
+        assert_ok!(Registrar::commit(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            commitment
+        ));
+        Timestamp::set_timestamp(Timestamp::now() + MaxCommitmentAge::get() + 1);
+        assert_ok!(Registrar::remove_commitment(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            commitment
+        ));
+        System::assert_last_event(RuntimeEvent::Registrar(
+            registrar::Event::CommitmentRemoved {
+                who: RICH_ACCOUNT,
+                commitment,
+            },
+        ));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), reserved);
+        assert!(!registrar::Commitments::<Test>::contains_key(commitment));
+    });
+}
+
+#[test]
+fn candle_auction_default_policy_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        DefaultLabelPolicy::set(true);
+        let node = Label::from_normalized(b"ab").encode_with_node(&DOT_BASENODE);
+
+        // too short to be registered, but auctioned.
+        assert_noop!(
+            Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                b"ab".to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ),
+            registrar::Error::<Test>::ParseLabelFailed
+        );
+        assert_ok!(Registrar::bid(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"ab".to_vec(),
+            100
+        ));
+        let end = Timestamp::now() + AuctionPeriod::get();
+
+        // outbid in the sample it raised its bid in, the first bid may still win.
+        Timestamp::set_timestamp(end - AuctionEndingPeriod::get() + 2 * AuctionSampleLength::get());
+        assert_ok!(Registrar::bid(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            b"ab".to_vec(),
+            200
+        ));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), 200);
+        assert_ok!(Registrar::bid(
+            RuntimeOrigin::signed(MONEY_ACCOUNT),
+            DOT_BASENODE,
+            b"ab".to_vec(),
+            300
+        ));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), 100);
+        assert_eq!(
+            registrar::AuctionReserves::<Test>::get(node, RICH_ACCOUNT),
+            100
+        );
+
+        Timestamp::set_timestamp(end);
+        assert_ok!(Registrar::settle_auction(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node
+        ));
+        System::set_block_number(2);
+        RandomSeed::set(2);
+        assert_ok!(Registrar::settle_auction(
+            RuntimeOrigin::signed(POOR_ACCOUNT),
+            node
+        ));
+        assert!(Nft::is_owner(&MONEY_ACCOUNT, (0, node)));
+        assert_eq!(Balances::reserved_balance(MONEY_ACCOUNT), 0);
+
+        // no new auction until the bids of the last one are claimed.
+        assert_noop!(
+            Registrar::bid(
+                RuntimeOrigin::signed(POOR_ACCOUNT),
+                DOT_BASENODE,
+                b"ab".to_vec(),
+                100
+            ),
+            registrar::Error::<Test>::UnclaimedBids
+        );
+        assert_ok!(Registrar::claim_bid(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node,
+            RICH_ACCOUNT
+        ));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), 0);
+    });
+}
+
+#[test]
+fn candle_sample_test() {
+    use auction::{AuctionStatus, Candle};
+
+    // the ending period is not a multiple of the sample length.
+    let candle = Candle::<u64> {
+        ending_period: 90,
+        sample_length: 20,
+    };
+    assert_eq!(candle.samples(), 4);
+    assert_eq!(candle.status(1000, 900), AuctionStatus::StartingPeriod);
+    assert_eq!(candle.status(1000, 915), AuctionStatus::EndingPeriod(0, 5));
+    assert_eq!(candle.status(1000, 975), AuctionStatus::EndingPeriod(3, 5));
+    // the remainder belongs to the last sample.
+    assert_eq!(candle.status(1000, 995), AuctionStatus::EndingPeriod(3, 25));
+    assert_eq!(candle.status(1000, 1000), AuctionStatus::Ended);
+    assert_eq!(candle.draw(&7_u32.to_le_bytes()), 3);
+}
+
+#[test]
+fn transfer_all_max_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        for name in [b"cupnfishxx1", b"cupnfishxx2", b"cupnfishxx3"] {
+            assert_ok!(Registrar::register(
+                RuntimeOrigin::signed(RICH_ACCOUNT),
+                DOT_BASENODE,
+                name.to_vec(),
+                RICH_ACCOUNT,
+                MinRegistrationDuration::get(),
+                None
+            ));
+        }
+
+        // the limit is lowered to `MaxTransferAll`.
+        assert_ok!(Registry::transfer_all(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            MONEY_ACCOUNT,
+            u32::MAX,
+            None
+        ));
+        match System::events().last().map(|record| &record.event) {
+            Some(RuntimeEvent::Registry(registry::Event::BatchTransferred {
+                transferred: 2,
+                skipped: 0,
+                next: Some(_),
+                ..
+            })) => {}
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert_eq!(registry::OwnedTokenCount::<Test>::get(MONEY_ACCOUNT), 2);
+    })
+}
+
+#[test]
+fn expiry_warning_catch_up_test() {
+    use frame_support::traits::Hooks;
+    use traits::Registrar as _;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let first = Registrar::expiry_bucket(Timestamp::now());
+
+        // a hook which fell behind catches up over several blocks.
+        MaxExpiryBucketsPerBlock::set(2);
+        Registrar::on_initialize(1);
+        assert_eq!(registrar::NextExpiryBucket::<Test>::get(), Some(first + 2));
+        MaxExpiryBucketsPerBlock::set(64);
+        Registrar::on_initialize(2);
+        let next = Registrar::expiry_bucket(Timestamp::now() + ExpiryWarningPeriod::get()) + 1;
+        assert_eq!(registrar::NextExpiryBucket::<Test>::get(), Some(next));
+
+        // a domain expiring in a bucket which was already warned about goes in the next one.
+        let name = b"cupnfishxxx";
+        let (label, _) = Label::new_with_len(name).unwrap();
+        let node = label.encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::for_redeem_code(
+            name.to_vec(),
+            MONEY_ACCOUNT,
+            ExpiryBucket::get(),
+            label
+        ));
+        let expire = registrar::RegistrarInfos::<Test>::get(node).unwrap().expire;
+        assert!(Registrar::expiry_bucket(expire) < next);
+        assert!(registrar::ExpiringAt::<Test>::contains_key(next, node));
+
+        Timestamp::set_timestamp(Timestamp::now() + ExpiryBucket::get());
+        Registrar::on_initialize(3);
+        System::assert_has_event(RuntimeEvent::Registrar(
+            registrar::Event::NameExpiringSoon { node, expire },
+        ));
+    })
+}
+
+#[test]
+fn appeal_ruling_test() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"cupnfishxx";
+        let node = Label::new_with_len(name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        assert_ok!(Registry::emergency_freeze(
+            RuntimeOrigin::root(),
+            node,
+            false
+        ));
+        assert_noop!(
+            Dispute::rule_appeal(RuntimeOrigin::root(), node, false),
+            dispute::Error::<Test>::NotAppealed
+        );
+
+        let reserved = Balances::reserved_balance(RICH_ACCOUNT);
+        assert_ok!(Registry::appeal_freeze(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            node
+        ));
+        System::assert_has_event(RuntimeEvent::Dispute(dispute::Event::AppealOpened {
+            node,
+            appellant: RICH_ACCOUNT,
+        }));
+
+        // only the arbitration rules, overturning the freeze returns the bond
+        assert_noop!(
+            Dispute::rule_appeal(RuntimeOrigin::signed(MANAGER_ACCOUNT), node, false),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Dispute::rule_appeal(RuntimeOrigin::root(), node, false));
+        System::assert_last_event(RuntimeEvent::Dispute(dispute::Event::AppealRuled {
+            node,
+            appellant: RICH_ACCOUNT,
+            upheld: false,
+        }));
+        assert_eq!(Balances::reserved_balance(RICH_ACCOUNT), reserved);
+        assert!(!registry::FrozenNodes::<Test>::contains_key(node));
+        assert!(!registry::EmergencyFreezes::<Test>::contains_key(node));
+        assert_noop!(
+            Registry::confirm_freeze(RuntimeOrigin::root(), node, true),
+            registry::Error::<Test>::NotEmergencyFrozen
+        );
+    });
+}
+
+#[test]
+fn slash_deposit_refund_test() {
+    use frame_support::traits::Currency as _;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let name = b"cupnfishxx";
+        let node = Label::new_with_len(name)
+            .unwrap()
+            .0
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(Registrar::register(
+            RuntimeOrigin::signed(RICH_ACCOUNT),
+            DOT_BASENODE,
+            name.to_vec(),
+            RICH_ACCOUNT,
+            MinRegistrationDuration::get(),
+            None
+        ));
+        let deposit = registrar::RegistrarInfos::<Test>::get(node)
+            .unwrap()
+            .deposit;
+
+        // the deposit can't be returned, nothing is burned
+        let official = Balances::free_balance(OFFICIAL_ACCOUNT);
+        Balances::make_free_balance_be(&OFFICIAL_ACCOUNT, deposit - 1);
+        assert_noop!(
+            Registrar::slash_deposit(
+                RuntimeOrigin::root(),
+                node,
+                sp_runtime::Perbill::zero(),
+                true
+            ),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+        assert!(registrar::RegistrarInfos::<Test>::contains_key(node));
+        assert!(Nft::is_owner(&RICH_ACCOUNT, (0, node)));
+
+        Balances::make_free_balance_be(&OFFICIAL_ACCOUNT, official);
+        let free = Balances::free_balance(RICH_ACCOUNT);
+        assert_ok!(Registrar::slash_deposit(
+            RuntimeOrigin::root(),
+            node,
+            sp_runtime::Perbill::zero(),
+            true
+        ));
+        assert_eq!(Balances::free_balance(RICH_ACCOUNT), free + deposit);
+        assert_eq!(Balances::free_balance(OFFICIAL_ACCOUNT), official - deposit);
+        assert!(registrar::RegistrarInfos::<Test>::get(node).is_none());
+    });
+}
+
+mod nft_adapter {
+    use crate::{mock_nfts::*, nft_adapter::METADATA_KEY, registry, traits::Label};
+    use frame_support::{
+        assert_noop, assert_ok,
+        storage::with_storage_layer,
+        traits::tokens::{nonfungibles, nonfungibles_v2},
+    };
+    use pns_types::DomainHash;
+    use sp_runtime::{DispatchError, TokenError};
+
+    /// Mints `dot` to the official account and `alice.dot` to `ALICE` through the registry.
+    fn mint_alice() -> DomainHash {
+        let node = Label::new(b"alice")
+            .unwrap()
+            .encode_with_node(&DOT_BASENODE);
+        assert_ok!(with_storage_layer(|| -> Result<(), DispatchError> {
+            <Registry as crate::traits::Registry>::mint_base_node(DOT_BASENODE)?;
+            <Registry as crate::traits::Registry>::mint_subname(
+                &OFFICIAL_ACCOUNT,
+                DOT_BASENODE,
+                node,
+                ALICE,
+                10,
+                |_| Ok(()),
+            )
+        }));
+        node
+    }
+
+    #[test]
+    fn nfts_adapter_test() {
+        new_test_ext().execute_with(|| {
+            UseNftsV2::set(true);
+            let owner = |node| {
+                <Nfts as nonfungibles_v2::Inspect<AccountId>>::owner(&PnsCollection::get(), &node)
+            };
+
+            let node = mint_alice();
+            assert_eq!(owner(DOT_BASENODE), Some(OFFICIAL_ACCOUNT));
+            assert_eq!(owner(node), Some(ALICE));
+
+            assert_ok!(Registry::set_token_metadata(
+                RuntimeOrigin::signed(ALICE),
+                node,
+                b"avatar".to_vec()
+            ));
+            assert_eq!(
+                <Nfts as nonfungibles_v2::Inspect<AccountId>>::system_attribute(
+                    &PnsCollection::get(),
+                    &node,
+                    METADATA_KEY
+                ),
+                Some(b"avatar".to_vec())
+            );
+
+            assert_ok!(Registry::transfer_all(
+                RuntimeOrigin::signed(ALICE),
+                BOB,
+                2,
+                None
+            ));
+            assert_eq!(owner(node), Some(BOB));
+            assert_eq!(Registry::domains_of(&BOB), vec![node]);
+
+            assert_noop!(
+                Registry::burn(RuntimeOrigin::signed(ALICE), node),
+                registry::Error::<Test>::NoPermission
+            );
+            assert_ok!(Registry::burn(RuntimeOrigin::signed(BOB), node));
+            assert_eq!(owner(node), None);
+            assert!(Registry::domains_of(&BOB).is_empty());
+        });
+    }
+
+    #[test]
+    fn uniques_adapter_test() {
+        new_test_ext().execute_with(|| {
+            UseNftsV2::set(false);
+            let owner = |node| {
+                <Uniques as nonfungibles::Inspect<AccountId>>::owner(&PnsCollection::get(), &node)
+            };
+
+            let node = mint_alice();
+            assert_eq!(owner(DOT_BASENODE), Some(OFFICIAL_ACCOUNT));
+            assert_eq!(owner(node), Some(ALICE));
+
+            // `pallet-uniques` has no attributes in `nonfungibles::Mutate`.
+            assert_noop!(
+                Registry::set_token_metadata(
+                    RuntimeOrigin::signed(ALICE),
+                    node,
+                    b"avatar".to_vec()
+                ),
+                TokenError::Unsupported
+            );
+
+            assert_ok!(Registry::transfer_all(
+                RuntimeOrigin::signed(ALICE),
+                BOB,
+                2,
+                None
+            ));
+            assert_eq!(owner(node), Some(BOB));
+            assert_eq!(Registry::domains_of(&BOB), vec![node]);
+
+            assert_noop!(
+                Registry::burn(RuntimeOrigin::signed(ALICE), node),
+                registry::Error::<Test>::NoPermission
+            );
+            assert_ok!(Registry::burn(RuntimeOrigin::signed(BOB), node));
+            assert_eq!(owner(node), None);
+            assert!(Registry::domains_of(&BOB).is_empty());
+        });
+    }
+}
@@ -1,11 +1,14 @@
 use codec::{Encode, FullCodec};
-use core::fmt::Debug;
-use frame_support::traits::Currency;
+use core::{fmt::Debug, marker::PhantomData};
+use frame_support::{
+    traits::{Currency, Get, PalletInfoAccess, Time},
+    Parameter,
+};
 use pns_types::DomainHash;
 
-use sp_io::hashing::keccak_256;
+use sp_io::hashing::{blake2_256, keccak_256};
 use sp_runtime::{
-    traits::{AtLeast32BitUnsigned, MaybeSerializeDeserialize},
+    traits::{AtLeast32BitUnsigned, MaybeSerializeDeserialize, Member, Saturating, Zero},
     DispatchError, DispatchResult,
 };
 use sp_std::vec::Vec;
@@ -16,15 +19,45 @@ pub trait Registrar {
     type Moment;
     fn check_expires_registrable(node: DomainHash) -> DispatchResult;
     fn check_expires_renewable(node: DomainHash) -> DispatchResult;
+    /// Fails if `node` or the registered domain it belongs to expired,
+    /// subdomains may expire before their root, see `registrar::set_subname_expiry`.
     fn check_expires_useable(node: DomainHash) -> DispatchResult;
+    /// Fails if the owner of `node` locked it, see `registrar::lock`.
+    fn check_unlocked(node: DomainHash) -> DispatchResult;
+    /// Forget the registration of `node`, its deposit goes back to `owner`.
+    ///
+    /// Fails and keeps the registration if the deposit can't be returned.
     fn clear_registrar_info(node: DomainHash, owner: &Self::AccountId) -> DispatchResult;
+    /// Forget the expiry and lease of the subdomain `node`, it is being burned.
+    fn clear_subname_info(node: DomainHash);
+    /// Called by the registry after `node` changed hands.
+    fn on_transferred(node: DomainHash, from: &Self::AccountId, to: &Self::AccountId);
     fn for_redeem_code(
         name: Vec<u8>,
         to: Self::AccountId,
         duration: Self::Moment,
         label: Label,
     ) -> DispatchResult;
+    /// Like `for_redeem_code`, under `base_node` instead of the default one.
+    fn for_redeem_code_in(
+        base_node: DomainHash,
+        name: Vec<u8>,
+        to: Self::AccountId,
+        duration: Self::Moment,
+        label: Label,
+    ) -> DispatchResult;
+    /// Renews `name` under the default base node for free, on behalf of `caller`.
+    fn for_renew_code(
+        caller: &Self::AccountId,
+        name: Vec<u8>,
+        duration: Self::Moment,
+    ) -> DispatchResult;
+    /// The default base node, the one redeem codes are issued for.
     fn basenode() -> DomainHash;
+    /// Every base node domains can be registered under.
+    fn base_nodes() -> Vec<DomainHash>;
+    /// Label of `name` and the length of its normalized form, following the registrar's label policy.
+    fn label_of(name: &[u8]) -> Option<(Label, usize)>;
     // fn for_auction_set_expires(
     // 	node: DomainHash,
     // 	deposit: Self::Balance,
@@ -35,6 +68,7 @@ pub trait Registrar {
 /// 登记表
 pub trait Registry: NFT<Self::AccountId> {
     type AccountId;
+    type ResolverId;
 
     fn mint_subname(
         node_owner: &Self::AccountId,
@@ -46,6 +80,58 @@ pub trait Registry: NFT<Self::AccountId> {
     ) -> DispatchResult;
     fn available(caller: &Self::AccountId, node: DomainHash) -> DispatchResult;
     fn transfer(from: &Self::AccountId, to: &Self::AccountId, node: DomainHash) -> DispatchResult;
+    /// Same as `transfer` for several `nodes`, if any of them can't be transferred none of them are.
+    fn transfer_batch(
+        from: &Self::AccountId,
+        to: &Self::AccountId,
+        nodes: &[DomainHash],
+    ) -> DispatchResult;
+    /// Same as `transfer` for `node` sold to `to`, `do_payments` gets the owner
+    /// and the royalty of `node` to pay the price with, see `registry::set_royalty`.
+    fn transfer_sold(
+        from: &Self::AccountId,
+        to: &Self::AccountId,
+        node: DomainHash,
+        // `[owner, maybe_royalty]`
+        do_payments: impl FnOnce(
+            &Self::AccountId,
+            Option<&crate::registry::Royalty<Self::AccountId>>,
+        ) -> DispatchResult,
+    ) -> DispatchResult;
+    /// Burn `node` on behalf of `caller` without any permission check,
+    /// its registrar info is cleared as well.
+    fn burn_unchecked(caller: &Self::AccountId, node: DomainHash) -> DispatchResult;
+    /// Lift any freeze of `node` without any permission check, e.g. before burning it.
+    fn thaw_unchecked(node: DomainHash);
+    /// Move `node` from its owner to `to` without any permission check,
+    /// neither its fuses, locks nor freezes are checked.
+    fn transfer_unchecked(to: &Self::AccountId, node: DomainHash) -> DispatchResult;
+    /// Set the resolver of `node` without any permission check.
+    fn set_resolver(node: DomainHash, resolver: Self::ResolverId);
+    /// The registered domain `node` is a subdomain of, `node` itself otherwise.
+    fn root_of(node: DomainHash) -> DomainHash;
+    /// Mint a new base node to the official account.
+    fn mint_base_node(base_node: DomainHash) -> DispatchResult;
+    /// Remove every token approval and the controller of `node`.
+    fn clear_approvals(node: DomainHash);
+    /// The owner of `node`, `None` if it does not exist.
+    fn owner_of(node: DomainHash) -> Option<Self::AccountId>;
+    /// Fails if any of `fuses` of `node` is burned, see `registry::fuses`.
+    fn check_fuses(node: DomainHash, fuses: u32) -> DispatchResult;
+}
+
+/// Lets other pallets react to registered domain names without forking the registrar,
+/// several of them can be configured as a tuple.
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+pub trait OnDomainLifecycle<AccountId, Moment: Copy> {
+    /// `node` was registered to `owner` until `expire`.
+    fn on_registered(node: DomainHash, owner: &AccountId, expire: Moment);
+    /// `node` was renewed until `expire`.
+    fn on_renewed(node: DomainHash, expire: Moment);
+    /// `node` was transferred from `from` to `to`, subdomains included.
+    fn on_transferred(node: DomainHash, from: &AccountId, to: &AccountId);
+    /// `node` of `owner` expired and is reaped or registered by someone else.
+    fn on_expired(node: DomainHash, owner: &AccountId);
 }
 
 // 客户
@@ -57,6 +143,7 @@ pub trait Customer<AccountId> {
 pub trait PriceOracle {
     type Moment;
     type Balance;
+    type AssetId;
     /// Returns the price to register or renew a name.
     /// * `name`: The name being registered or renewed.
     /// * `expires`: When the name presently expires (0 if this is a new registration).
@@ -66,6 +153,15 @@ pub trait PriceOracle {
     fn register_fee(name_len: usize, duration: Self::Moment) -> Option<Self::Balance>;
     fn deposit_fee(name_len: usize) -> Option<Self::Balance>;
     fn registration_fee(name_len: usize) -> Option<Self::Balance>;
+    /// Extra price of registering a name again `expired_for` after its grace period ended,
+    /// it decays to zero so freshly expired names can't be sniped at the base price.
+    fn premium(name_len: usize, expired_for: Self::Moment) -> Option<Self::Balance>;
+    /// Price of `extra_slots` more subdomains for a name.
+    fn capacity_fee(extra_slots: u32) -> Option<Self::Balance>;
+    /// Called after a name was registered, for prices following the demand.
+    fn note_registration() {}
+    /// `amount` of the native token converted to `asset`, `None` if fees can't be paid in `asset`.
+    fn asset_amount(asset: Self::AssetId, amount: Self::Balance) -> Option<Self::Balance>;
 }
 
 /// Abstraction over a non-fungible token system.
@@ -100,6 +196,44 @@ pub trait NFT<AccountId> {
     ) -> DispatchResult;
 }
 
+/// The nft system the domains are tokens of, all of them in a single class.
+///
+/// Implemented by the bundled [`crate::nft`] pallet, see [`crate::nft_adapter`]
+/// for runtimes which already run a standard nft pallet.
+pub trait NftBackend<AccountId> {
+    /// The NFT class identifier.
+    type ClassId: Parameter + Member + Default + Copy;
+
+    /// The class the domains are minted in.
+    fn class_id() -> Self::ClassId;
+
+    /// The owner of `node`, `None` if it is not minted.
+    fn owner(node: DomainHash) -> Option<AccountId>;
+
+    /// Mint `node` to `owner`.
+    fn mint(owner: &AccountId, node: DomainHash, metadata: Vec<u8>) -> DispatchResult;
+
+    /// Move `node` from its owner `from` to `to`, permissions are up to the registry.
+    fn transfer(from: &AccountId, to: &AccountId, node: DomainHash) -> DispatchResult;
+
+    /// Move several `nodes` of `from` to `to`, none of them if any fails.
+    fn transfer_batch(from: &AccountId, to: &AccountId, nodes: &[DomainHash]) -> DispatchResult {
+        for node in nodes {
+            Self::transfer(from, to, *node)?;
+        }
+        Ok(())
+    }
+
+    /// Destroy `node` of `owner`.
+    fn burn(owner: &AccountId, node: DomainHash) -> DispatchResult;
+
+    /// Replace the metadata of `node`.
+    fn set_metadata(node: DomainHash, metadata: Vec<u8>) -> DispatchResult;
+
+    /// Hand the class over to `owner`, the new official account.
+    fn set_class_owner(owner: &AccountId) -> DispatchResult;
+}
+
 pub struct Label {
     pub node: DomainHash,
 }
@@ -109,15 +243,15 @@ pub const MIN_REGISTRABLE_LEN: usize = 3;
 
 impl Label {
     pub fn new(data: &[u8]) -> Option<Self> {
-        check_label(data)?;
+        let data = normalize_label(data)?;
 
-        let node = DomainHash::from(keccak_256(data));
+        let node = DomainHash::from(keccak_256(&data));
         Some(Self { node })
     }
     pub fn new_basenode(data: &[u8]) -> Option<Self> {
-        check_label(data)?;
+        let data = normalize_label(data)?;
 
-        let node = DomainHash::from(keccak_256(data));
+        let node = DomainHash::from(keccak_256(&data));
 
         let encoded = &(DomainHash::default(), node).encode();
         let hash_encoded = keccak_256(encoded);
@@ -140,10 +274,18 @@ impl Label {
             node: self.encode_with_baselabel(&node.node),
         })
     }
+    /// Label of an already normalized `data`, see [`LabelPolicy`].
+    pub fn from_normalized(data: &[u8]) -> Self {
+        Self {
+            node: DomainHash::from(keccak_256(data)),
+        }
+    }
+
+    /// The length is the one of the normalized label, see [`normalize_label`].
     pub fn new_with_len(data: &[u8]) -> Option<(Self, usize)> {
-        check_label(data)?;
+        let data = normalize_label(data)?;
 
-        let node = DomainHash::from(keccak_256(data));
+        let node = DomainHash::from(keccak_256(&data));
         Some((Self { node }, data.len()))
     }
 
@@ -170,46 +312,172 @@ impl Label {
         DomainHash::from(keccak_256(encoded))
     }
 }
-// TODO: (暂不支持中文域名)
 // 域名不区分大小写和简繁体。
 // 域名的合法长度为1~63个字符（域名主体，不包括后缀）。
 // 英文域名合法字符为a-z、0-9、短划线（-）。
 // （ 说明 短划线（-）不能出现在开头和结尾以及在第三和第四字符位置。）
-// 中文域名除英文域名合法字符外，必须含有至少一个汉字（简体或繁体），计算中文域名字符长度以转换后的punycode码为准。
-// 不支持xn—开头的请求参数（punycode码），请以中文域名作为请求参数。
+// 国际化域名（中文、emoji等）除英文域名合法字符外，必须含有至少一个非ASCII字符，计算域名字符长度以转换后的punycode码为准。
+// xn--开头的请求参数（punycode码）必须是某个合法国际化域名转换后的结果，两者视为同一个域名。
+// 国际化域名的规则见 `crate::idn`。
 pub fn check_label(label: &[u8]) -> Option<()> {
-    let label = core::str::from_utf8(label)
-        .map(|label| label.to_ascii_lowercase())
-        .ok()?;
-
-    if !(LABEL_MIN_LEN..=LABEL_MAX_LEN).contains(&label.len()) {
-        return None;
-    }
-
-    let label_chars = label.chars().collect::<Vec<_>>();
-
-    match label_chars.as_slice() {
-        [first, middle @ .., last]
-            if first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric() =>
-        {
-            for (i, &c) in middle.iter().enumerate() {
-                match c {
-                    c if c.is_ascii_alphanumeric() => continue,
-                    c if c == '-' => {
-                        if i == 1 || i == 2 {
-                            return None;
+    normalize_label(label).map(|_| ())
+}
+
+/// The form `label` is hashed and stored with, `None` if it is not a valid label.
+///
+/// ASCII labels are lower cased, internationalized ones are converted to their
+/// `xn--` punycode form, the one DNS resolvers query for.
+pub fn normalize_label(label: &[u8]) -> Option<Vec<u8>> {
+    LabelRules::DEFAULT.normalize(label)
+}
+
+/// What [`LabelRules::normalize`] checks a label against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LabelRules {
+    /// Length bounds of the normalized label, `max_len` is capped at [`LABEL_MAX_LEN`].
+    pub min_len: usize,
+    pub max_len: usize,
+    /// Rejects hyphens at the 3rd and 4th character.
+    pub strict_hyphens: bool,
+    /// Accepts internationalized labels, see `crate::idn`.
+    pub internationalized: bool,
+}
+
+impl LabelRules {
+    /// The rules of [`check_label`].
+    pub const DEFAULT: Self = Self {
+        min_len: LABEL_MIN_LEN,
+        max_len: LABEL_MAX_LEN,
+        strict_hyphens: true,
+        internationalized: true,
+    };
+
+    /// Same as [`normalize_label`], with these rules.
+    pub fn normalize(&self, label: &[u8]) -> Option<Vec<u8>> {
+        let label = core::str::from_utf8(label).ok()?;
+        let normalized = if label.is_ascii() {
+            let label = label.to_ascii_lowercase();
+            if label.starts_with(crate::idn::ACE_PREFIX) {
+                if !self.internationalized {
+                    return None;
+                }
+                crate::idn::to_unicode(&label, self.strict_hyphens)?;
+            } else {
+                self.check_ascii(&label)?;
+            }
+            label.into_bytes()
+        } else if self.internationalized {
+            crate::idn::to_ascii(label, self.strict_hyphens)?
+        } else {
+            return None;
+        };
+
+        (self.min_len..=self.max_len.min(LABEL_MAX_LEN))
+            .contains(&normalized.len())
+            .then_some(normalized)
+    }
+
+    fn check_ascii(&self, label: &str) -> Option<()> {
+        let label_chars = label.chars().collect::<Vec<_>>();
+
+        match label_chars.as_slice() {
+            [single] if single.is_ascii_alphanumeric() => {}
+            [first, middle @ .., last]
+                if first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric() =>
+            {
+                for (i, &c) in middle.iter().enumerate() {
+                    match c {
+                        c if c.is_ascii_alphanumeric() => continue,
+                        c if c == '-' => {
+                            if self.strict_hyphens && (i == 1 || i == 2) {
+                                return None;
+                            }
+                            continue;
                         }
-                        continue;
+                        _ => return None,
                     }
-                    _ => return None,
                 }
             }
+            _ => return None,
+        }
+
+        Some(())
+    }
+}
+
+/// Decides which labels can be registered and the form they are stored with,
+/// see `registrar::Config::LabelPolicy`.
+pub trait LabelPolicy {
+    /// The normalized form of `label`, `None` if it is not allowed.
+    fn normalize(label: &[u8]) -> Option<Vec<u8>>;
+
+    /// The normalized form of a `label` put up for auction, see `registrar::bid`.
+    /// Those are shorter than [`MIN_REGISTRABLE_LEN`].
+    fn normalize_auctionable(label: &[u8]) -> Option<Vec<u8>> {
+        Self::normalize(label)
+    }
+}
+
+/// The rules of [`check_label`], which only auctions relax to any length.
+impl LabelPolicy for () {
+    fn normalize(label: &[u8]) -> Option<Vec<u8>> {
+        normalize_label(label)
+    }
+
+    fn normalize_auctionable(label: &[u8]) -> Option<Vec<u8>> {
+        LabelRules {
+            min_len: 1,
+            ..LabelRules::DEFAULT
         }
-        _ => return None,
+        .normalize(label)
     }
+}
 
-    Some(())
+/// [`LabelRules`] as a [`LabelPolicy`], for runtimes which only need to adjust them.
+pub struct RulesLabelPolicy<MinLen, MaxLen, StrictHyphens, Internationalized>(
+    PhantomData<(MinLen, MaxLen, StrictHyphens, Internationalized)>,
+);
+
+impl<MinLen, MaxLen, StrictHyphens, Internationalized>
+    RulesLabelPolicy<MinLen, MaxLen, StrictHyphens, Internationalized>
+where
+    MinLen: Get<u32>,
+    MaxLen: Get<u32>,
+    StrictHyphens: Get<bool>,
+    Internationalized: Get<bool>,
+{
+    fn rules() -> LabelRules {
+        LabelRules {
+            min_len: MinLen::get() as usize,
+            max_len: MaxLen::get() as usize,
+            strict_hyphens: StrictHyphens::get(),
+            internationalized: Internationalized::get(),
+        }
+    }
 }
+
+/// `MinLen` only applies to registrations, auctions accept any length.
+impl<MinLen, MaxLen, StrictHyphens, Internationalized> LabelPolicy
+    for RulesLabelPolicy<MinLen, MaxLen, StrictHyphens, Internationalized>
+where
+    MinLen: Get<u32>,
+    MaxLen: Get<u32>,
+    StrictHyphens: Get<bool>,
+    Internationalized: Get<bool>,
+{
+    fn normalize(label: &[u8]) -> Option<Vec<u8>> {
+        Self::rules().normalize(label)
+    }
+
+    fn normalize_auctionable(label: &[u8]) -> Option<Vec<u8>> {
+        LabelRules {
+            min_len: 1,
+            ..Self::rules()
+        }
+        .normalize(label)
+    }
+}
+
 pub trait Available {
     fn is_anctionable(&self) -> bool;
     fn is_registrable(&self) -> bool;
@@ -217,7 +485,7 @@ pub trait Available {
 
 impl Available for usize {
     fn is_anctionable(&self) -> bool {
-        *self > LABEL_MIN_LEN && *self < MIN_REGISTRABLE_LEN
+        (1..MIN_REGISTRABLE_LEN).contains(self)
     }
 
     fn is_registrable(&self) -> bool {
@@ -231,6 +499,59 @@ pub trait ExchangeRate {
     fn get_exchange_rate() -> Self::Balance;
 }
 
+/// An external price feed, such as `orml-oracle`, read by [`FeedExchangeRate`].
+///
+/// ```ignore
+/// pub struct OrmlFeed;
+///
+/// impl PriceFeed for OrmlFeed {
+///     type Balance = Balance;
+///     type Moment = Moment;
+///
+///     fn latest_rate() -> Option<(Balance, Moment)> {
+///         <Oracle as DataProviderExtended<_, _>>::get_no_op(&NativeToken::get())
+///             .map(|value| (value.value.saturating_mul_int(UNIT), value.timestamp))
+///     }
+/// }
+/// ```
+pub trait PriceFeed {
+    type Balance;
+    type Moment;
+    /// The latest 1 USD to balance rate and when it was fed.
+    fn latest_rate() -> Option<(Self::Balance, Self::Moment)>;
+}
+
+/// [`ExchangeRate`] read from `Feed`, falling back to `Fallback` when the feed
+/// has no rate, a zero rate or one older than `MaxAge` according to `Clock`.
+///
+/// `Fallback` is usually `price_oracle::Pallet`, whose rate is only used while
+/// the feed is stale.
+pub struct FeedExchangeRate<Feed, Clock, MaxAge, Fallback>(
+    PhantomData<(Feed, Clock, MaxAge, Fallback)>,
+);
+
+impl<Feed, Clock, MaxAge, Fallback> ExchangeRate for FeedExchangeRate<Feed, Clock, MaxAge, Fallback>
+where
+    Clock: Time,
+    Feed: PriceFeed<Moment = Clock::Moment>,
+    Feed::Balance: Zero,
+    MaxAge: Get<Clock::Moment>,
+    Fallback: ExchangeRate<Balance = Feed::Balance>,
+{
+    type Balance = Feed::Balance;
+
+    fn get_exchange_rate() -> Self::Balance {
+        match Feed::latest_rate() {
+            Some((rate, fed_at))
+                if !rate.is_zero() && Clock::now().saturating_sub(fed_at) <= MaxAge::get() =>
+            {
+                rate
+            }
+            _ => Fallback::get_exchange_rate(),
+        }
+    }
+}
+
 pub trait Official {
     type AccountId;
 
@@ -239,4 +560,93 @@ pub trait Official {
 
 pub trait IsRegistrarOpen {
     fn is_open() -> bool;
+    /// Whether `op` is not paused on its own, `is_open` still closes all of them.
+    fn is_enabled(op: Operation) -> bool;
+}
+
+/// Keeps the privileged calls for auditing, see `origin::AuditEntries`.
+///
+/// `origin::Pallet` keeps them on chain, `()` drops them.
+pub trait AuditLog<AccountId> {
+    /// Records that `who` dispatched the call `call` of the pallet at index `pallet`.
+    fn record(who: &AccountId, pallet: u8, call: u8, args_hash: sp_core::H256);
+
+    /// Records the dispatched `call` of the pallet `P`, encoded as its index and arguments.
+    fn record_call<P: PalletInfoAccess, C: Encode>(who: &AccountId, call: &C) {
+        let encoded = call.encode();
+        Self::record(
+            who,
+            P::index() as u8,
+            encoded[0],
+            blake2_256(&encoded[1..]).into(),
+        )
+    }
+}
+
+impl<AccountId> AuditLog<AccountId> for () {
+    fn record(_: &AccountId, _: u8, _: u8, _: sp_core::H256) {}
+}
+
+/// Where the appeals of emergency freezes are ruled on, see `dispute::rule_appeal`.
+pub trait Arbitration<AccountId> {
+    /// Opens the case of `appellant` against the emergency freeze of `node`.
+    fn open_appeal(node: DomainHash, appellant: &AccountId) -> DispatchResult;
+    /// Whether the appeal of `node` still waits for its ruling.
+    fn appeal_pending(node: DomainHash) -> bool;
+    /// Closes the appeal of `node` without a ruling, its freeze is lifted.
+    fn drop_appeal(node: DomainHash);
+}
+
+/// Appeals are left to governance alone.
+impl<AccountId> Arbitration<AccountId> for () {
+    fn open_appeal(_: DomainHash, _: &AccountId) -> DispatchResult {
+        Ok(())
+    }
+
+    fn appeal_pending(_: DomainHash) -> bool {
+        false
+    }
+
+    fn drop_appeal(_: DomainHash) {}
+}
+
+/// The operations which can be paused independently, see `origin::set_paused`.
+#[derive(
+    Encode,
+    codec::Decode,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+    scale_info::TypeInfo,
+    codec::MaxEncodedLen,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum Operation {
+    Register,
+    Renew,
+    Transfer,
+    MintSubname,
+    ResolverWrites,
+    Redeem,
+}
+
+impl Operation {
+    /// The bit of `self` in the paused operations.
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// Writes the records a domain is registered with, see `register_with_config`,
+/// and clears them once it is reaped.
+pub trait ResolverRecords {
+    type Record: frame_support::Parameter;
+
+    /// Write `records` of `node` without any permission check.
+    fn set_records(node: DomainHash, records: Vec<Self::Record>) -> DispatchResult;
+
+    /// Remove every record of `node`.
+    fn clear_records(node: DomainHash);
 }
@@ -4,7 +4,6 @@
 use crate::resolvers::{Address, Call, Config, Content, Pallet, TextKind};
 use frame_benchmarking::account;
 use frame_benchmarking::benchmarks;
-use frame_support::traits::Get;
 use frame_system::RawOrigin;
 use pns_types::DomainHash;
 use sp_runtime::traits::StaticLookup;
@@ -33,18 +32,24 @@ where
 {
     let owner = create_caller::<T, T::Currency>(888);
     let owner_clone = owner.clone();
+    let base_node = pns_registrar::registrar::DefaultBaseNode::<T>::get();
+    let min_duration = pns_registrar::registrar::BaseNodes::<T>::get(base_node)
+        .ok_or("no default base node")?
+        .min_duration;
     pns_registrar::registrar::Pallet::<T>::register(
         RawOrigin::Signed(owner).into(),
+        base_node,
         b"cupnfishuuu".to_vec(),
         account_to_source::<T>(owner_clone.clone()),
-        T::MinRegistrationDuration::get(),
+        min_duration,
+        None,
     )?;
     Ok((
         owner_clone,
         pns_registrar::traits::Label::new("cupnfishuuu".as_bytes())
             .unwrap()
             .0
-            .encode_with_node(&T::BaseNode::get()),
+            .encode_with_node(&base_node),
     ))
 }
 
@@ -5,6 +5,12 @@ This module provides functionality for domain name resolution. Most of these int
 ### Module functions
 - `set_account` - sets the account resolve, which requires the domain to be available relative to that user (ownership of the domain, the domain is not expired)
 - `set_text` - set text parsing, same requirements as above
+- `set_default_ttl` - sets the ttl records of a type are served with, requires manager privileges
+- `set_ethereum_account` - sets an ethereum address from its hex form, verifying the EIP-55 checksum if there is one
+- `clear_profile` - removes every account, text and record of a domain at once, same requirements as `set_account`
+
+Accounts, texts and records are stored under [`records_key`] of a domain, which changes with
+its record version, so the registry can invalidate all of them without touching each entry.
 !*/
 
 use codec::{Encode, MaxEncodedLen};
@@ -15,7 +21,7 @@ pub use pallet::*;
 pub mod pallet {
     use super::*;
     use codec::EncodeLike;
-    use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
+    use frame_support::{dispatch::DispatchResult, pallet_prelude::*, traits::EnsureOrigin};
     use frame_system::pallet_prelude::*;
     use pns_types::ddns::codec_type::RecordType;
     use scale_info::TypeInfo;
@@ -37,6 +43,8 @@ pub mod pallet {
 
         type RegistryChecker: RegistryChecker<AccountId = Self::AccountId>;
 
+        type ManagerOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
         type Public: TypeInfo
             + Decode
             + Encode
@@ -76,7 +84,7 @@ pub mod pallet {
         Ethereum([u8; 20]),
         Id(Id),
     }
-    /// account_id mapping
+    /// account_id mapping, keyed by [`records_key`]
     #[pallet::storage]
     pub type Accounts<T: Config> = StorageDoubleMap<
         _,
@@ -109,7 +117,15 @@ pub mod pallet {
         Github,
         Ipfs,
     }
-    /// text mapping
+    /// A record a domain can be registered with.
+    #[derive(Encode, Decode, Clone, Eq, PartialEq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+    pub enum InitialRecord<Id> {
+        Account(Address<Id>),
+        Text(TextKind, Content),
+        /// e.g. `A` or `AAAA`
+        Record(RecordType, Content),
+    }
+    /// text mapping, keyed by [`records_key`]
     #[pallet::storage]
     pub type Texts<T: Config> = StorageDoubleMap<
         _,
@@ -121,7 +137,7 @@ pub mod pallet {
         ValueQuery,
     >;
 
-    /// ddns record
+    /// ddns record, keyed by [`records_key`]
     #[pallet::storage]
     pub type Records<T: Config> = StorageDoubleMap<
         _,
@@ -133,6 +149,19 @@ pub mod pallet {
         ValueQuery,
     >;
 
+    /// `record_type` -> default ttl in seconds
+    ///
+    /// Types without an entry fall back to [`fallback_ttl`].
+    #[pallet::storage]
+    pub type DefaultTtls<T: Config> = StorageMap<_, Twox64Concat, RecordType, u32>;
+
+    /// ttl in seconds of a single record, keyed by [`records_key`]
+    ///
+    /// Records without an entry are served with the default ttl of their type.
+    #[pallet::storage]
+    pub type RecordTtls<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, pns_types::DomainHash, Twox64Concat, RecordType, u32>;
+
     #[pallet::genesis_config]
     pub struct GenesisConfig<T: Config> {
         /// vec![ `node` , `address` ]
@@ -181,6 +210,18 @@ pub mod pallet {
             kind: RecordType,
             content: Content,
         },
+        DefaultTtlChanged {
+            kind: RecordType,
+            ttl: Option<u32>,
+        },
+        RecordTtlChanged {
+            node: pns_types::DomainHash,
+            kind: RecordType,
+            ttl: Option<u32>,
+        },
+        ProfileCleared {
+            node: pns_types::DomainHash,
+        },
     }
 
     #[pallet::error]
@@ -191,6 +232,12 @@ pub mod pallet {
         InvalidPermission,
         /// Not supported address index.
         NotSupportedIndex,
+        /// The ethereum address is not valid hex, or its EIP-55 checksum does not match.
+        InvalidChecksum,
+        /// The domain has more accounts than `max_accounts` allows to clear.
+        TooManyAccounts,
+        /// Writing records is paused by the managers for now.
+        WritesPaused,
     }
 
     #[pallet::call]
@@ -204,12 +251,16 @@ pub mod pallet {
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
+            ensure!(
+                T::RegistryChecker::records_writable(),
+                Error::<T>::WritesPaused
+            );
             ensure!(
                 T::RegistryChecker::check_node_useable(node, &who),
                 Error::<T>::InvalidPermission
             );
 
-            Accounts::<T>::insert(node, &address, ());
+            Accounts::<T>::insert(records_key::<T>(node), &address, ());
 
             Self::deposit_event(Event::<T>::AddressChanged { node, address });
 
@@ -225,12 +276,16 @@ pub mod pallet {
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
+            ensure!(
+                T::RegistryChecker::records_writable(),
+                Error::<T>::WritesPaused
+            );
             ensure!(
                 T::RegistryChecker::check_node_useable(node, &who),
                 Error::<T>::InvalidPermission
             );
 
-            Records::<T>::insert(node, &record_type, &content);
+            Records::<T>::insert(records_key::<T>(node), &record_type, &content);
 
             Self::deposit_event(Event::<T>::RecordsChanged {
                 node,
@@ -250,12 +305,16 @@ pub mod pallet {
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
+            ensure!(
+                T::RegistryChecker::records_writable(),
+                Error::<T>::WritesPaused
+            );
             ensure!(
                 T::RegistryChecker::check_node_useable(node, &who),
                 Error::<T>::InvalidPermission
             );
 
-            Texts::<T>::insert(node, &kind, &content);
+            Texts::<T>::insert(records_key::<T>(node), &kind, &content);
 
             Self::deposit_event(Event::<T>::TextsChanged {
                 node,
@@ -263,6 +322,121 @@ pub mod pallet {
                 content,
             });
 
+            Ok(())
+        }
+        /// Set an ethereum address given in hex (`0x` prefix optional).
+        ///
+        /// A mixed-case address must carry a valid EIP-55 checksum,
+        /// all lower or upper case addresses are taken as they are.
+        #[pallet::call_index(4)]
+        #[pallet::weight(T::WeightInfo::set_account())]
+        pub fn set_ethereum_account(
+            origin: OriginFor<T>,
+            node: pns_types::DomainHash,
+            address: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                T::RegistryChecker::records_writable(),
+                Error::<T>::WritesPaused
+            );
+            ensure!(
+                T::RegistryChecker::check_node_useable(node, &who),
+                Error::<T>::InvalidPermission
+            );
+
+            let address =
+                Address::Ethereum(parse_eip55(&address).ok_or(Error::<T>::InvalidChecksum)?);
+
+            Accounts::<T>::insert(records_key::<T>(node), &address, ());
+
+            Self::deposit_event(Event::<T>::AddressChanged { node, address });
+
+            Ok(())
+        }
+        /// Set the default ttl of a record type, `None` restores the fallback.
+        /// Only manager
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::set_default_ttl())]
+        pub fn set_default_ttl(
+            origin: OriginFor<T>,
+            kind: RecordType,
+            ttl: Option<u32>,
+        ) -> DispatchResult {
+            let _who = T::ManagerOrigin::ensure_origin(origin)?;
+
+            DefaultTtls::<T>::set(kind, ttl);
+
+            Self::deposit_event(Event::<T>::DefaultTtlChanged { kind, ttl });
+
+            Ok(())
+        }
+        /// Remove every account, text and record of `node`.
+        ///
+        /// Texts and records are bounded by their kinds, accounts are not,
+        /// so the caller declares how many accounts may be removed.
+        /// Nothing is removed if the domain has more than `max_accounts`.
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::clear_profile(*max_accounts))]
+        pub fn clear_profile(
+            origin: OriginFor<T>,
+            node: pns_types::DomainHash,
+            max_accounts: u32,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                T::RegistryChecker::records_writable(),
+                Error::<T>::WritesPaused
+            );
+            ensure!(
+                T::RegistryChecker::check_node_useable(node, &who),
+                Error::<T>::InvalidPermission
+            );
+
+            let key = records_key::<T>(node);
+            ensure!(
+                Accounts::<T>::iter_key_prefix(key)
+                    .nth(max_accounts as usize)
+                    .is_none(),
+                Error::<T>::TooManyAccounts
+            );
+
+            let _ = Accounts::<T>::clear_prefix(key, max_accounts, None);
+            let _ = Texts::<T>::clear_prefix(key, u32::MAX, None);
+            let _ = Records::<T>::clear_prefix(key, u32::MAX, None);
+            let _ = RecordTtls::<T>::clear_prefix(key, u32::MAX, None);
+
+            Self::deposit_event(Event::<T>::ProfileCleared { node });
+
+            Ok(())
+        }
+        /// Set the ttl the record of `kind` of `node` is served with,
+        /// `None` restores the default of its type.
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::WeightInfo::set_record_ttl())]
+        pub fn set_record_ttl(
+            origin: OriginFor<T>,
+            node: pns_types::DomainHash,
+            kind: RecordType,
+            ttl: Option<u32>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                T::RegistryChecker::records_writable(),
+                Error::<T>::WritesPaused
+            );
+            ensure!(
+                T::RegistryChecker::check_node_useable(node, &who),
+                Error::<T>::InvalidPermission
+            );
+
+            RecordTtls::<T>::set(records_key::<T>(node), kind, ttl);
+
+            Self::deposit_event(Event::<T>::RecordTtlChanged { node, kind, ttl });
+
             Ok(())
         }
     }
@@ -278,11 +452,33 @@ pub trait WeightInfo {
     fn set_record(content_len: u32) -> Weight;
 
     fn set_account() -> Weight;
+
+    fn set_default_ttl() -> Weight;
+
+    fn clear_profile(max_accounts: u32) -> Weight;
+
+    fn set_record_ttl() -> Weight;
 }
 
 pub trait RegistryChecker {
     type AccountId;
     fn check_node_useable(node: pns_types::DomainHash, owner: &Self::AccountId) -> bool;
+    /// Version of the records of `node`, records written with an older one are not served anymore.
+    fn record_version(node: pns_types::DomainHash) -> u32;
+    /// Whether records can be written at all, `false` while the writes are paused.
+    fn records_writable() -> bool;
+    /// Whether the records of `node` are served, `false` e.g. while it is frozen in an emergency.
+    fn records_served(node: pns_types::DomainHash) -> bool;
+}
+
+/// Key the accounts, texts and records of `node` are stored under.
+///
+/// Version `0` is `node` itself, so records written before versioning stay valid.
+pub fn records_key<T: Config>(node: DomainHash) -> DomainHash {
+    match T::RegistryChecker::record_version(node) {
+        0 => node,
+        version => DomainHash::from(sp_io::hashing::blake2_256(&(node, version).encode())),
+    }
 }
 
 #[derive(
@@ -322,12 +518,184 @@ impl WeightInfo for () {
     fn set_account() -> Weight {
         Weight::zero()
     }
+
+    fn set_default_ttl() -> Weight {
+        Weight::zero()
+    }
+
+    fn clear_profile(_max_accounts: u32) -> Weight {
+        Weight::zero()
+    }
+
+    fn set_record_ttl() -> Weight {
+        Weight::zero()
+    }
 }
 
 impl<C: Config> Pallet<C> {
     pub fn lookup(id: DomainHash) -> Vec<(RecordType, Vec<u8>)> {
-        Records::<C>::iter_prefix(id)
+        if !C::RegistryChecker::records_served(id) {
+            return Vec::new();
+        }
+        Records::<C>::iter_prefix(records_key::<C>(id))
             .map(|(k2, v)| (k2, v.0))
             .collect::<Vec<(RecordType, Vec<u8>)>>()
     }
+
+    /// Ttl in seconds a record of `id` is served with.
+    ///
+    /// Records without a ttl of their own get the default of their type.
+    pub fn record_ttl(id: DomainHash, kind: RecordType) -> u32 {
+        RecordTtls::<C>::get(records_key::<C>(id), kind)
+            .or_else(|| DefaultTtls::<C>::get(kind))
+            .unwrap_or_else(|| fallback_ttl(kind))
+    }
+
+    /// Ethereum addresses of `id` in their EIP-55 checksummed form.
+    pub fn ethereum_addresses(id: DomainHash) -> Vec<Vec<u8>> {
+        if !C::RegistryChecker::records_served(id) {
+            return Vec::new();
+        }
+        Accounts::<C>::iter_key_prefix(records_key::<C>(id))
+            .filter_map(|address| match address {
+                Address::Ethereum(address) => Some(to_eip55(&address)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Storage key of a single record, used to build a storage proof of it
+    /// (see `sc_client_api::ProofProvider::read_proof`) for light clients.
+    ///
+    /// The proven storage value is the SCALE encoded record content.
+    pub fn record_key(id: DomainHash, kind: RecordType) -> Vec<u8> {
+        Records::<C>::hashed_key_for(records_key::<C>(id), kind)
+    }
+
+    /// Write `records` of `id` without any permission check,
+    /// used to set up a domain while it is registered.
+    pub fn set_initial_records(id: DomainHash, records: Vec<InitialRecord<C::AccountId>>) {
+        let key = records_key::<C>(id);
+        for record in records {
+            match record {
+                InitialRecord::Account(address) => {
+                    Accounts::<C>::insert(key, &address, ());
+                    Self::deposit_event(Event::<C>::AddressChanged { node: id, address });
+                }
+                InitialRecord::Text(kind, content) => {
+                    Texts::<C>::insert(key, &kind, &content);
+                    Self::deposit_event(Event::<C>::TextsChanged {
+                        node: id,
+                        kind,
+                        content,
+                    });
+                }
+                InitialRecord::Record(kind, content) => {
+                    Records::<C>::insert(key, kind, &content);
+                    Self::deposit_event(Event::<C>::RecordsChanged {
+                        node: id,
+                        kind,
+                        content,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Remove every record of `id` without any permission check,
+    /// used when an expired domain is burned.
+    pub fn clear_records(id: DomainHash) {
+        let key = records_key::<C>(id);
+        let _ = Accounts::<C>::clear_prefix(key, u32::MAX, None);
+        let _ = Texts::<C>::clear_prefix(key, u32::MAX, None);
+        let _ = Records::<C>::clear_prefix(key, u32::MAX, None);
+        let _ = RecordTtls::<C>::clear_prefix(key, u32::MAX, None);
+
+        Self::deposit_event(Event::<C>::ProfileCleared { node: id });
+    }
+
+    /// Gets a single record of `node` without iterating the other record types.
+    pub fn lookup_one(id: DomainHash, kind: RecordType) -> Option<Vec<u8>> {
+        if !C::RegistryChecker::records_served(id) {
+            return None;
+        }
+        Records::<C>::try_get(records_key::<C>(id), kind)
+            .ok()
+            .map(|content| content.0)
+    }
+
+    /// [`Self::lookup_one`] together with the [`Self::record_ttl`] it is served with.
+    pub fn lookup_one_with_ttl(id: DomainHash, kind: RecordType) -> Option<(Vec<u8>, u32)> {
+        Self::lookup_one(id, kind).map(|content| (content, Self::record_ttl(id, kind)))
+    }
+}
+
+/// Ttl of a record type the manager has not configured.
+///
+/// Addresses are kept short so failovers propagate quickly,
+/// records which rarely change are cached for a day.
+pub fn fallback_ttl(kind: RecordType) -> u32 {
+    match kind {
+        RecordType::A | RecordType::AAAA | RecordType::ANAME => 300,
+        RecordType::TXT
+        | RecordType::NS
+        | RecordType::MX
+        | RecordType::SOA
+        | RecordType::CAA
+        | RecordType::SRV => 86400,
+        _ => 3600,
+    }
+}
+
+/// Renders an ethereum address as EIP-55 mixed-case checksummed hex, with `0x` prefix.
+pub fn to_eip55(address: &[u8; 20]) -> Vec<u8> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    let lower = address
+        .iter()
+        .flat_map(|byte| [HEX[(byte >> 4) as usize], HEX[(byte & 0x0f) as usize]])
+        .collect::<Vec<u8>>();
+    let hash = sp_io::hashing::keccak_256(&lower);
+
+    let mut res = Vec::with_capacity(42);
+    res.extend_from_slice(b"0x");
+    for (i, c) in lower.into_iter().enumerate() {
+        let nibble = if i % 2 == 0 {
+            hash[i / 2] >> 4
+        } else {
+            hash[i / 2] & 0x0f
+        };
+        if nibble >= 8 {
+            res.push(c.to_ascii_uppercase());
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+/// Parses a hex ethereum address, `0x` prefix optional.
+///
+/// Returns `None` for invalid hex, or for a mixed-case address whose EIP-55 checksum
+/// does not match. All lower or upper case addresses carry no checksum.
+pub fn parse_eip55(data: &[u8]) -> Option<[u8; 20]> {
+    let hex = data.strip_prefix(b"0x").unwrap_or(data);
+    if hex.len() != 40 {
+        return None;
+    }
+
+    let mut address = [0u8; 20];
+    for (i, pair) in hex.chunks(2).enumerate() {
+        let high = (pair[0] as char).to_digit(16)?;
+        let low = (pair[1] as char).to_digit(16)?;
+        address[i] = (high << 4 | low) as u8;
+    }
+
+    let has_lower = hex.iter().any(u8::is_ascii_lowercase);
+    let has_upper = hex.iter().any(u8::is_ascii_uppercase);
+    if has_lower && has_upper && to_eip55(&address)[2..] != *hex {
+        return None;
+    }
+
+    Some(address)
 }
@@ -3,7 +3,10 @@
 #![allow(clippy::unnecessary_mut_passed)]
 
 use codec::{Decode, Encode};
-use pns_types::{ddns::codec_type::RecordType, DomainHash, RegistrarInfo};
+use pns_types::{
+    ddns::codec_type::RecordType, AuditEntry, AvailabilityStatus, DomainHash, PnsStats,
+    RegistrarInfo,
+};
 use sp_runtime::traits::MaybeSerialize;
 
 sp_api::decl_runtime_apis! {
@@ -15,8 +18,53 @@ sp_api::decl_runtime_apis! {
     {
         fn get_info(id: DomainHash) -> Option<RegistrarInfo<Duration, Balance>>;
         fn all() -> sp_std::vec::Vec<(DomainHash,RegistrarInfo<Duration, Balance>)>;
+        fn stats() -> PnsStats;
+        /// Labels of the top level domains created on chain after genesis.
+        fn tlds() -> sp_std::vec::Vec<sp_std::vec::Vec<u8>>;
+        /// Availability of each of `names` under `base_node` in input order, at most 64 names are checked.
+        fn availability_many(base_node: DomainHash, names: sp_std::vec::Vec<sp_std::vec::Vec<u8>>) -> sp_std::vec::Vec<AvailabilityStatus>;
+        /// Fee of registering `name` under `base_node` for `duration`, premium included, without the deposit.
+        fn register_fee(base_node: DomainHash, name: sp_std::vec::Vec<u8>, duration: Duration) -> Option<Balance>;
+        /// Fee of renewing the registered `name` under `base_node` for `duration`, grace period surcharge included.
+        fn renew_fee(base_node: DomainHash, name: sp_std::vec::Vec<u8>, duration: Duration) -> Option<Balance>;
+        /// Deposit reserved when registering `name`, returned when it is registered again by someone else.
+        fn deposit_fee(name: sp_std::vec::Vec<u8>) -> Option<Balance>;
         fn lookup(id: DomainHash) -> sp_std::vec::Vec<(RecordType, sp_std::vec::Vec<u8>)>;
+        fn lookup_one(id: DomainHash, kind: RecordType) -> Option<sp_std::vec::Vec<u8>>;
+        /// Ttl in seconds the record of `kind` of `id` should be served with, see also `ttl`.
+        fn record_ttl(id: DomainHash, kind: RecordType) -> u32;
+        /// Ttl in seconds set for all records of `id` by its owner, it takes precedence over `record_ttl`.
+        fn ttl(id: DomainHash) -> Option<u32>;
+        /// `lookup_one` together with the ttl the record is served with, `ttl` taking precedence over `record_ttl`.
+        fn lookup_one_with_ttl(id: DomainHash, kind: RecordType) -> Option<(sp_std::vec::Vec<u8>, u32)>;
+        /// Ethereum addresses of `id`, rendered as EIP-55 checksummed hex.
+        fn ethereum_addresses(id: DomainHash) -> sp_std::vec::Vec<sp_std::vec::Vec<u8>>;
+        /// The storage proof itself can only be built by the node, see `pns_ddns::ServerDeps::record_proof`.
+        fn record_key(id: DomainHash, kind: RecordType) -> sp_std::vec::Vec<u8>;
         fn check_node_useable(node: DomainHash, owner: &AccountId) -> bool;
+        /// End of the transfer lock of `node`, `None` if it is not locked.
+        fn transfer_lock(node: DomainHash) -> Option<Duration>;
+        /// Whether `node` is bound to its owner until it is burned.
+        fn is_soulbound(node: DomainHash) -> bool;
+        /// The domain `who` is displayed with, `None` if it has none or does not own it anymore.
+        fn primary_name(who: AccountId) -> Option<DomainHash>;
+        /// Every domain `owner` owns, in no particular order.
+        fn domains_of(owner: AccountId) -> sp_std::vec::Vec<DomainHash>;
+        /// Uri of the metadata of a token of the nft module, PNS domains are of class `0`.
+        fn token_uri(class: u32, token: DomainHash) -> Option<sp_std::vec::Vec<u8>>;
+        /// A page of at most 64 direct children of `node` after `start_after`, with the cursor of the next page.
+        fn subnodes_of(node: DomainHash, start_after: Option<DomainHash>) -> (sp_std::vec::Vec<DomainHash>, Option<DomainHash>);
+        /// A page of at most `limit` (up to 256) tokens of the nft module `owner` holds after `start_key`, with the cursor of the next page.
+        fn tokens_of_owner(owner: AccountId, start_key: Option<(u32, DomainHash)>, limit: u32) -> (sp_std::vec::Vec<(u32, DomainHash)>, Option<(u32, DomainHash)>);
         // fn set_record(who: AccountId,code: Signature,id: DomainHash,tp: RecordType,content: sp_std::vec::Vec<u8>) -> bool;
     }
+
+    /// Privileged calls recorded by the origin module of `pns-registrar`.
+    pub trait PnsAuditApi<AccountId, BlockNumber>
+    where AccountId: Decode + Encode,
+    BlockNumber: Decode + Encode,
+    {
+        /// The most recent privileged calls, oldest first.
+        fn audit_log() -> sp_std::vec::Vec<AuditEntry<AccountId, BlockNumber>>;
+    }
 }
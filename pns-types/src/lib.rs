@@ -22,6 +22,20 @@ pub struct RegistrarInfo<Moment, Balance> {
     pub register_fee: Balance,
 }
 
+/// 顶级域名配置
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug, Clone, TypeInfo, MaxEncodedLen)]
+pub struct TldConfig<Moment> {
+    /// How long a domain can still be renewed after it expired.
+    pub grace_period: Moment,
+    /// Subdomain capacity of newly registered domains.
+    pub capacity: u32,
+    /// The shortest duration a domain can be registered for.
+    pub min_duration: Moment,
+    /// Whether domains can be registered right now.
+    pub open: bool,
+}
+
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug, Clone, TypeInfo, MaxEncodedLen)]
 pub enum DomainTracing {
@@ -31,9 +45,50 @@ pub enum DomainTracing {
 
 /// 域名记录
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug, Clone, Default, TypeInfo)]
+#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug, Clone, Default, TypeInfo, MaxEncodedLen)]
 pub struct Record {
     pub children: u32,
 }
 
+/// 注册统计
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug, Clone, Default, TypeInfo)]
+pub struct PnsStats {
+    /// Registrations ever made, including re-registrations of expired names.
+    pub registrations: u64,
+    /// Renewals ever made.
+    pub renewals: u64,
+    /// Registered domains which have not been burned.
+    pub active_domains: u64,
+}
+
+/// A privileged call recorded by the origin module.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug, Clone, TypeInfo, MaxEncodedLen)]
+pub struct AuditEntry<AccountId, BlockNumber> {
+    /// Index of the pallet in the runtime.
+    pub pallet: u8,
+    /// Index of the call in the pallet.
+    pub call: u8,
+    /// Blake2-256 hash of the encoded arguments of the call.
+    pub args_hash: sp_core::H256,
+    /// The manager, or the account a role or collective acted as.
+    pub who: AccountId,
+    pub at: BlockNumber,
+}
+
+/// 域名可注册状态
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug, Clone, Copy, TypeInfo, MaxEncodedLen)]
+pub enum AvailabilityStatus {
+    /// Can be registered now, including names past their grace period.
+    Available,
+    /// Registered and not yet past its grace period.
+    Registered,
+    /// Reserved by the manager, possibly for a single account.
+    Reserved,
+    /// Not a valid or registrable label.
+    Invalid,
+}
+
 pub type DomainHash = sp_core::H256;